@@ -0,0 +1,100 @@
+// Shows the status of the most recent build while serving a site.
+//
+// The dev server streams the build status over a WebSocket. A badge is shown
+// while a build is in progress and briefly after it finishes, and the errors
+// are shown over the page when a build fails.
+(function () {
+  var SOCKET_PATH = "/__razorbill/build-status";
+  var RECONNECT_DELAY_MS = 1000;
+  var BADGE_TIMEOUT_MS = 2000;
+
+  var root = document.createElement("razorbill-build-overlay");
+  var shadow = root.attachShadow({ mode: "open" });
+  shadow.innerHTML =
+    "<style>" +
+    ":host { all: initial; }" +
+    ".badge { position: fixed; right: 16px; bottom: 16px; z-index: 2147483647; padding: 6px 12px; border-radius: 6px; background: #1f2937; color: #f9fafb; font: 13px/1.4 system-ui, sans-serif; box-shadow: 0 2px 8px rgba(0, 0, 0, 0.3); }" +
+    ".errors { position: fixed; inset: 0; z-index: 2147483647; overflow: auto; padding: 32px; background: rgba(17, 24, 39, 0.95); color: #fecaca; font: 14px/1.5 ui-monospace, monospace; }" +
+    ".errors h1 { margin: 0 0 16px; color: #f87171; font: bold 18px/1.4 system-ui, sans-serif; }" +
+    ".errors pre { margin: 0 0 16px; white-space: pre-wrap; }" +
+    ".errors button { position: absolute; top: 24px; right: 32px; border: 0; background: none; color: #f9fafb; font-size: 24px; cursor: pointer; }" +
+    "[hidden] { display: none; }" +
+    "</style>" +
+    '<div class="badge" hidden></div>' +
+    '<div class="errors" hidden><button title="Dismiss">&times;</button><h1>Build failed</h1><div class="messages"></div></div>';
+
+  var badge = shadow.querySelector(".badge");
+  var errors = shadow.querySelector(".errors");
+  var messages = shadow.querySelector(".messages");
+  var badgeTimeout;
+
+  shadow.querySelector("button").addEventListener("click", function () {
+    errors.hidden = true;
+  });
+
+  function showBadge(text, timeout) {
+    clearTimeout(badgeTimeout);
+    badge.textContent = text;
+    badge.hidden = false;
+
+    if (timeout) {
+      badgeTimeout = setTimeout(function () {
+        badge.hidden = true;
+      }, timeout);
+    }
+  }
+
+  // The first status on each connection is that of a build that finished
+  // before the page was loaded, so we only mention it if it failed.
+  function update(status, isInitial) {
+    var lastBuild = status.last_build;
+
+    if (status.building) {
+      showBadge("Building…");
+      return;
+    }
+
+    if (!lastBuild || !lastBuild.finished_at) {
+      return;
+    }
+
+    messages.textContent = "";
+    lastBuild.errors.forEach(function (error) {
+      var pre = document.createElement("pre");
+      pre.textContent = error;
+      messages.appendChild(pre);
+    });
+    errors.hidden = lastBuild.errors.length === 0;
+
+    if (lastBuild.errors.length === 0 && !isInitial) {
+      showBadge("Built in " + lastBuild.duration_ms + "ms", BADGE_TIMEOUT_MS);
+    } else if (lastBuild.errors.length > 0) {
+      badge.hidden = true;
+    }
+  }
+
+  function connect() {
+    var protocol = location.protocol === "https:" ? "wss:" : "ws:";
+    var socket = new WebSocket(protocol + "//" + location.host + SOCKET_PATH);
+    var isInitial = true;
+
+    socket.addEventListener("message", function (event) {
+      update(JSON.parse(event.data), isInitial);
+      isInitial = false;
+    });
+    socket.addEventListener("close", function () {
+      setTimeout(connect, RECONNECT_DELAY_MS);
+    });
+  }
+
+  function mount() {
+    document.body.appendChild(root);
+    connect();
+  }
+
+  if (document.body) {
+    mount();
+  } else {
+    document.addEventListener("DOMContentLoaded", mount);
+  }
+})();
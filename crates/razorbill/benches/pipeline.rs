@@ -0,0 +1,302 @@
+//! Benchmarks for the site pipeline.
+//!
+//! Run with `cargo bench -p razorbill --features bench`.
+//!
+//! The number of allocations made while rendering each fixture is printed
+//! after the timings.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use auk::*;
+use auk_markdown::MarkdownComponents;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use razorbill::markdown::{builtin_shortcodes, markdown_with_shortcodes, Shortcode};
+use razorbill::Site;
+use serde::Deserialize;
+
+/// The global allocator, wrapped to count allocations.
+struct CountingAllocator;
+
+/// The number of allocations (including reallocations) made so far.
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// A set of generated content to benchmark against.
+struct Fixture {
+    name: &'static str,
+    pages: usize,
+    paragraphs: usize,
+    shortcodes_per_paragraph: usize,
+}
+
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "small_pages",
+        pages: 1_000,
+        paragraphs: 3,
+        shortcodes_per_paragraph: 0,
+    },
+    Fixture {
+        name: "many_pages",
+        pages: 5_000,
+        paragraphs: 3,
+        shortcodes_per_paragraph: 0,
+    },
+    Fixture {
+        name: "large_pages",
+        pages: 100,
+        paragraphs: 200,
+        shortcodes_per_paragraph: 0,
+    },
+    Fixture {
+        name: "heavy_shortcodes",
+        pages: 100,
+        paragraphs: 50,
+        shortcodes_per_paragraph: 4,
+    },
+];
+
+impl Fixture {
+    fn page_body(&self, page: usize) -> String {
+        let mut body = String::new();
+
+        for paragraph in 0..self.paragraphs {
+            if paragraph % 10 == 0 {
+                writeln!(body, "## Heading {paragraph}\n").unwrap();
+            }
+
+            writeln!(
+                body,
+                "This is paragraph {paragraph} of page {page}, with some *emphasis*, \
+                 some **strong text**, `inline code`, and [a link](@/posts/page-0.md).\n"
+            )
+            .unwrap();
+
+            for index in 0..self.shortcodes_per_paragraph {
+                if index % 2 == 0 {
+                    writeln!(
+                        body,
+                        "{{{{ callout(kind=\"note\", text=\"Callout {index}\") }}}}\n"
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(body, "{{{{ youtube(\"8o3i10OuMFQ\", start={index}) }}}}\n").unwrap();
+                }
+            }
+
+            if paragraph % 25 == 0 {
+                writeln!(
+                    body,
+                    "```rust\nfn main() {{\n    println!(\"{page}\");\n}}\n```\n"
+                )
+                .unwrap();
+            }
+        }
+
+        body
+    }
+
+    /// Writes the fixture's content to a temporary directory and returns its root.
+    fn write(&self) -> PathBuf {
+        let root = std::env::temp_dir().join("razorbill-bench").join(self.name);
+        let posts_path = root.join("content").join("posts");
+
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&posts_path).unwrap();
+
+        write_file(
+            &root.join("content/_index.md"),
+            "+++\ntitle = \"Home\"\n+++\n",
+        );
+        write_file(
+            &posts_path.join("_index.md"),
+            "+++\ntitle = \"Posts\"\nsort_by = \"date\"\n+++\n",
+        );
+
+        for page in 0..self.pages {
+            let front_matter = format!(
+                "+++\ntitle = \"Page {page}\"\ndate = \"2024-01-{:02}\"\n+++\n\n",
+                page % 28 + 1
+            );
+
+            write_file(
+                &posts_path.join(format!("page-{page}.md")),
+                &(front_matter + &self.page_body(page)),
+            );
+        }
+
+        root
+    }
+}
+
+fn write_file(path: &Path, content: &str) {
+    fs::write(path, content).unwrap();
+}
+
+/// The Markdown components, left as the defaults.
+struct BenchMarkdownComponents;
+
+impl MarkdownComponents for BenchMarkdownComponents {}
+
+#[derive(Deserialize)]
+struct CalloutArgs {
+    kind: String,
+    text: String,
+}
+
+fn shortcodes() -> HashMap<String, Shortcode> {
+    let mut shortcodes = builtin_shortcodes();
+    shortcodes.insert(
+        "callout".to_string(),
+        Shortcode::new(|args: CalloutArgs| {
+            div()
+                .class(format!("callout callout-{}", args.kind))
+                .child(p().child(args.text))
+                .into()
+        }),
+    );
+
+    shortcodes
+}
+
+fn site(root: &Path) -> Site {
+    shortcodes()
+        .into_iter()
+        .fold(
+            Site::builder()
+                .root(root)
+                .base_url("https://bench.example.com")
+                .templates(
+                    |ctx| {
+                        html().child(
+                            body().child(h1().child(ctx.section.title.clone().unwrap_or_default())),
+                        )
+                    },
+                    |ctx| {
+                        html().child(
+                            body().child(h1().child(ctx.section.title.clone().unwrap_or_default())),
+                        )
+                    },
+                    |ctx| {
+                        html().child(
+                            body()
+                                .child(h1().child(ctx.page.title.clone().unwrap_or_default()))
+                                .child(div().children(ctx.page.content.iter().cloned())),
+                        )
+                    },
+                ),
+            |builder, (name, shortcode)| builder.add_shortcode(name, shortcode),
+        )
+        .build()
+}
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load");
+    group.sample_size(10);
+
+    for fixture in FIXTURES {
+        let root = fixture.write();
+
+        group.bench_function(fixture.name, |b| {
+            b.iter_batched(
+                || site(&root),
+                |mut site| site.load().unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_markdown(c: &mut Criterion) {
+    let mut group = c.benchmark_group("markdown_with_shortcodes");
+    let components: Box<dyn MarkdownComponents> = Box::new(BenchMarkdownComponents);
+    let shortcodes = shortcodes();
+
+    for fixture in FIXTURES {
+        let text = fixture.page_body(0);
+
+        group.bench_function(fixture.name, |b| {
+            b.iter(|| markdown_with_shortcodes(&text, &components, &shortcodes))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    group.sample_size(10);
+
+    for fixture in FIXTURES {
+        let root = fixture.write();
+
+        group.bench_function(fixture.name, |b| {
+            b.iter_batched(
+                || {
+                    let mut site = site(&root);
+                    site.load().unwrap();
+                    site
+                },
+                |mut site| site.render_in_memory().unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+/// Reports the number of allocations made while rendering each fixture.
+///
+/// Criterion only measures time, so the counts are printed alongside its
+/// results.
+fn report_render_allocations(_c: &mut Criterion) {
+    for fixture in FIXTURES {
+        let root = fixture.write();
+
+        let mut site = site(&root);
+        site.load().unwrap();
+
+        let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+        site.render_in_memory().unwrap();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+
+        println!("render/{}: {allocations} allocations", fixture.name);
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_load,
+    bench_markdown,
+    bench_render,
+    report_render_allocations
+);
+criterion_main!(benches);
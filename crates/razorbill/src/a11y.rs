@@ -0,0 +1,301 @@
+//! An audit of rendered pages for common accessibility problems.
+//!
+//! The audit is enabled with
+//! [`SiteBuilder::accessibility_audit`](crate::SiteBuilder::accessibility_audit),
+//! and its findings are included in the [`BuildReport`](crate::BuildReport):
+//!
+//! ```
+//! use razorbill::a11y::{A11yRule, AccessibilityAudit};
+//! use razorbill::lint::Severity;
+//! use razorbill::Site;
+//!
+//! let builder = Site::builder().accessibility_audit(
+//!     AccessibilityAudit::new()
+//!         .severity(A11yRule::ImageAlt, Severity::Error)
+//!         .disable(A11yRule::HeadingOrder),
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use auk::{Element, HtmlElement};
+
+use crate::html_validation::HtmlProblem;
+use crate::lint::Severity;
+
+/// A check performed by the [`AccessibilityAudit`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum A11yRule {
+    /// Images must have `alt` text (which may be empty for decorative images).
+    ImageAlt,
+    /// Headings must not skip levels (e.g., an `<h4>` directly after an `<h2>`).
+    HeadingOrder,
+    /// Links must have text that describes them, either as their content or
+    /// with an `aria-label`.
+    LinkName,
+    /// The `<html>` element must declare the language of the page.
+    HtmlLang,
+}
+
+impl A11yRule {
+    pub const ALL: [Self; 4] = [
+        Self::ImageAlt,
+        Self::HeadingOrder,
+        Self::LinkName,
+        Self::HtmlLang,
+    ];
+
+    /// Returns the name that identifies the rule's diagnostics (e.g., `a11y-image-alt`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ImageAlt => "a11y-image-alt",
+            Self::HeadingOrder => "a11y-heading-order",
+            Self::LinkName => "a11y-link-name",
+            Self::HtmlLang => "a11y-html-lang",
+        }
+    }
+}
+
+impl fmt::Display for A11yRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The configuration of the accessibility audit.
+///
+/// All of the rules are reported as warnings, unless configured otherwise.
+#[derive(Debug, Clone)]
+pub struct AccessibilityAudit {
+    /// The severity of each rule, or `None` if it is disabled.
+    severities: HashMap<A11yRule, Option<Severity>>,
+}
+
+impl Default for AccessibilityAudit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccessibilityAudit {
+    /// Returns a new [`AccessibilityAudit`] with all of the rules enabled.
+    pub fn new() -> Self {
+        Self {
+            severities: A11yRule::ALL
+                .into_iter()
+                .map(|rule| (rule, Some(Severity::Warning)))
+                .collect(),
+        }
+    }
+
+    /// Sets the severity that the given rule's problems are reported with.
+    pub fn severity(mut self, rule: A11yRule, severity: Severity) -> Self {
+        self.severities.insert(rule, Some(severity));
+        self
+    }
+
+    /// Disables the given rule.
+    pub fn disable(mut self, rule: A11yRule) -> Self {
+        self.severities.insert(rule, None);
+        self
+    }
+
+    /// Audits the given document, returning the problems found by the enabled
+    /// rules in document order.
+    pub(crate) fn audit(&self, document: &HtmlElement) -> Vec<HtmlProblem> {
+        let mut auditor = Auditor {
+            audit: self,
+            previous_heading_level: None,
+            problems: Vec::new(),
+        };
+
+        if document.tag_name == "html" && !document.attrs.contains_key("lang") {
+            auditor.report(
+                A11yRule::HtmlLang,
+                "<html> has no lang attribute".to_string(),
+            );
+        }
+
+        auditor.visit(document);
+        auditor.problems
+    }
+}
+
+struct Auditor<'a> {
+    audit: &'a AccessibilityAudit,
+    previous_heading_level: Option<u8>,
+    problems: Vec<HtmlProblem>,
+}
+
+impl<'a> Auditor<'a> {
+    fn report(&mut self, rule: A11yRule, message: String) {
+        let Some(Some(severity)) = self.audit.severities.get(&rule) else {
+            return;
+        };
+
+        self.problems.push(HtmlProblem {
+            severity: *severity,
+            rule: rule.name(),
+            message,
+        });
+    }
+
+    fn visit(&mut self, element: &HtmlElement) {
+        match element.tag_name.as_str() {
+            "img" if !element.attrs.contains_key("alt") => {
+                let src = element.attrs.get("src").map_or("", String::as_str);
+                self.report(
+                    A11yRule::ImageAlt,
+                    format!("<img src=\"{src}\"> has no alt text"),
+                );
+            }
+            "a" if accessible_name(element).trim().is_empty() => {
+                let href = element.attrs.get("href").map_or("", String::as_str);
+                self.report(
+                    A11yRule::LinkName,
+                    format!("link to \"{href}\" has no accessible text"),
+                );
+            }
+            tag_name => {
+                if let Some(level) = heading_level(tag_name) {
+                    if let Some(previous_level) = self.previous_heading_level {
+                        if level > previous_level + 1 {
+                            self.report(
+                                A11yRule::HeadingOrder,
+                                format!(
+                                    "<{tag_name}> \"{}\" skips a level after <h{previous_level}>",
+                                    text_content(element).trim()
+                                ),
+                            );
+                        }
+                    }
+
+                    self.previous_heading_level = Some(level);
+                }
+            }
+        }
+
+        for child in &element.children {
+            if let Element::Html(child) = child {
+                self.visit(child);
+            }
+        }
+    }
+}
+
+/// Returns the level of the heading with the given tag name (e.g., `2` for
+/// `<h2>`), if it is a heading.
+fn heading_level(tag_name: &str) -> Option<u8> {
+    match tag_name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+/// Returns the text that assistive technologies announce for the given element.
+fn accessible_name(element: &HtmlElement) -> String {
+    ["aria-label", "title"]
+        .into_iter()
+        .filter_map(|name| element.attrs.get(name))
+        .find(|value| !value.trim().is_empty())
+        .cloned()
+        .unwrap_or_else(|| text_content(element))
+}
+
+/// Returns the text within the given element, including the `alt` text of
+/// any images.
+fn text_content(element: &HtmlElement) -> String {
+    let mut text = String::new();
+
+    if element.tag_name == "img" {
+        text.extend(element.attrs.get("alt").map(String::as_str));
+    }
+
+    for child in &element.children {
+        match child {
+            Element::Text(child) => text.push_str(&child.text),
+            Element::Html(child) => text.push_str(&text_content(child)),
+        }
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use auk::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn document() -> HtmlElement {
+        html().child(
+            body()
+                .child(h1().child("Title"))
+                .child(h3().child("Skipped"))
+                .child(h2().child("Back"))
+                .child(img().src("/cover.png"))
+                .child(img().src("/divider.png").attr("alt", ""))
+                .child(a().href("/about/").child("About"))
+                .child(
+                    a().href("/rss/")
+                        .child(img().src("/rss.png").attr("alt", "Feed")),
+                )
+                .child(a().href("/search/").attr("aria-label", "Search"))
+                .child(a().href("/empty/").child(span().child(" "))),
+        )
+    }
+
+    #[test]
+    fn test_accessibility_audit() {
+        assert_eq!(
+            AccessibilityAudit::new().audit(&document()),
+            vec![
+                HtmlProblem {
+                    severity: Severity::Warning,
+                    rule: "a11y-html-lang",
+                    message: "<html> has no lang attribute".to_string(),
+                },
+                HtmlProblem {
+                    severity: Severity::Warning,
+                    rule: "a11y-heading-order",
+                    message: "<h3> \"Skipped\" skips a level after <h1>".to_string(),
+                },
+                HtmlProblem {
+                    severity: Severity::Warning,
+                    rule: "a11y-image-alt",
+                    message: "<img src=\"/cover.png\"> has no alt text".to_string(),
+                },
+                HtmlProblem {
+                    severity: Severity::Warning,
+                    rule: "a11y-link-name",
+                    message: "link to \"/empty/\" has no accessible text".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_accessibility_audit_severity() {
+        let audit = AccessibilityAudit::new()
+            .severity(A11yRule::ImageAlt, Severity::Error)
+            .disable(A11yRule::HtmlLang)
+            .disable(A11yRule::HeadingOrder)
+            .disable(A11yRule::LinkName);
+
+        assert_eq!(
+            audit.audit(&document()),
+            vec![HtmlProblem {
+                severity: Severity::Error,
+                rule: "a11y-image-alt",
+                message: "<img src=\"/cover.png\"> has no alt text".to_string(),
+            }]
+        );
+    }
+}
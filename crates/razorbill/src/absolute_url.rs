@@ -0,0 +1,130 @@
+//! Rewriting relative URLs to absolute ones.
+//!
+//! Relative URLs only work when the HTML is viewed on the site itself. HTML
+//! that is read elsewhere—like the content of feed entries, or the output of a
+//! custom exporter—needs its URLs to be absolute.
+
+use std::convert::Infallible;
+
+use auk::visitor::{noop_visit_element, MutVisitor};
+use auk::HtmlElement;
+use url::{ParseError, Url};
+
+use crate::permalink::Permalink;
+
+/// A visitor that rewrites relative `href` and `src` attributes to absolute
+/// URLs, resolved against the URL of the page containing them.
+///
+/// ```
+/// use auk::visitor::MutVisitor;
+/// use auk::*;
+/// use razorbill::absolute_url::AbsoluteUrlRewriter;
+///
+/// let mut element = a().href("../other-post/");
+///
+/// AbsoluteUrlRewriter::new("https://example.com/posts/hello/")
+///     .unwrap()
+///     .visit(&mut element)
+///     .unwrap();
+///
+/// assert_eq!(
+///     element.attrs.get("href").map(String::as_str),
+///     Some("https://example.com/posts/other-post/")
+/// );
+/// ```
+pub struct AbsoluteUrlRewriter {
+    base_url: Url,
+}
+
+impl AbsoluteUrlRewriter {
+    /// Returns a new [`AbsoluteUrlRewriter`] that resolves URLs against the
+    /// given page URL.
+    pub fn new(page_url: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            base_url: Url::parse(page_url)?,
+        })
+    }
+
+    pub(crate) fn from_permalink(permalink: &Permalink) -> Self {
+        Self {
+            base_url: permalink.as_url().clone(),
+        }
+    }
+
+    /// Rewrites the given URL to an absolute URL, if it is relative.
+    ///
+    /// URLs that are already absolute, including ones with other schemes
+    /// (e.g., `mailto:`), are left alone.
+    pub fn rewrite(&self, value: &mut String) {
+        if value.is_empty() || Url::parse(value) != Err(ParseError::RelativeUrlWithoutBase) {
+            return;
+        }
+
+        if let Ok(url) = self.base_url.join(value) {
+            *value = url.into();
+        }
+    }
+}
+
+impl MutVisitor for AbsoluteUrlRewriter {
+    type Error = Infallible;
+
+    fn visit(&mut self, element: &mut HtmlElement) -> Result<(), Self::Error> {
+        noop_visit_element(self, element)
+    }
+
+    fn visit_attr(&mut self, name: &str, value: &mut String) -> Result<(), Self::Error> {
+        if name == "href" || name == "src" {
+            self.rewrite(value);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::testing::MarkdownHarness;
+
+    use super::*;
+
+    #[test]
+    fn test_absolute_url_rewriter() {
+        let rewrite = |value: &str| {
+            let mut value = value.to_string();
+            AbsoluteUrlRewriter::new("https://example.com/posts/hello/")
+                .unwrap()
+                .rewrite(&mut value);
+            value
+        };
+
+        assert_eq!(rewrite("/about/"), "https://example.com/about/");
+        assert_eq!(
+            rewrite("cover.png"),
+            "https://example.com/posts/hello/cover.png"
+        );
+        assert_eq!(rewrite("../other/"), "https://example.com/posts/other/");
+        assert_eq!(
+            rewrite("#section"),
+            "https://example.com/posts/hello/#section"
+        );
+        assert_eq!(
+            rewrite("//cdn.example.com/a.js"),
+            "https://cdn.example.com/a.js"
+        );
+        assert_eq!(rewrite("https://other.com/"), "https://other.com/");
+        assert_eq!(rewrite("mailto:me@example.com"), "mailto:me@example.com");
+        assert_eq!(rewrite(""), "");
+
+        let rendered = MarkdownHarness::new().render_with_transform(
+            "[About](/about/) and [the cover](cover.png)",
+            &mut AbsoluteUrlRewriter::new("https://example.com/posts/hello/").unwrap(),
+        );
+        assert_eq!(
+            rendered,
+            r#"<p><a href="https://example.com/about/">About</a> and <a href="https://example.com/posts/hello/cover.png">the cover</a></p>"#
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Analytics snippets that are injected into every rendered page.
+
+use auk::*;
+
+/// An analytics provider whose snippet is added to each page of the site.
+///
+/// Snippets are only included in builds; they are omitted when serving the
+/// site locally, so that development doesn't skew the numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Analytics {
+    /// [Plausible](https://plausible.io).
+    Plausible {
+        /// The domain of the site, as registered with Plausible.
+        domain: String,
+        /// The URL of the Plausible script, for self-hosted instances.
+        script_src: Option<String>,
+    },
+    /// [GoatCounter](https://www.goatcounter.com).
+    GoatCounter {
+        /// The URL of the GoatCounter endpoint (e.g., `https://mysite.goatcounter.com/count`).
+        endpoint: String,
+    },
+    /// [Google Analytics](https://marketingplatform.google.com/about/analytics/).
+    GoogleAnalytics {
+        /// The measurement ID of the Google Analytics property (e.g., `G-XXXXXXXXXX`).
+        measurement_id: String,
+    },
+}
+
+impl Analytics {
+    pub fn plausible(domain: impl Into<String>) -> Self {
+        Self::Plausible {
+            domain: domain.into(),
+            script_src: None,
+        }
+    }
+
+    /// Returns a [`Analytics::GoatCounter`] for the site with the given GoatCounter code.
+    pub fn goat_counter(code: impl AsRef<str>) -> Self {
+        Self::GoatCounter {
+            endpoint: format!("https://{}.goatcounter.com/count", code.as_ref()),
+        }
+    }
+
+    pub fn google_analytics(measurement_id: impl Into<String>) -> Self {
+        Self::GoogleAnalytics {
+            measurement_id: measurement_id.into(),
+        }
+    }
+
+    /// Returns the elements to add to the `<head>` of each page.
+    pub(crate) fn head_elements(&self) -> Vec<Element> {
+        match self {
+            Self::Plausible { domain, script_src } => vec![script()
+                .attr("defer", "")
+                .attr("data-domain", domain)
+                .src(
+                    script_src
+                        .as_deref()
+                        .unwrap_or("https://plausible.io/js/script.js"),
+                )
+                .into()],
+            Self::GoatCounter { .. } => Vec::new(),
+            Self::GoogleAnalytics { measurement_id } => vec![
+                script()
+                    .attr("async", "")
+                    .src(format!(
+                        "https://www.googletagmanager.com/gtag/js?id={measurement_id}"
+                    ))
+                    .into(),
+                script()
+                    .child(format!(
+                        "window.dataLayer = window.dataLayer || []; function gtag(){{dataLayer.push(arguments);}} gtag('js', new Date()); gtag('config', '{measurement_id}');"
+                    ))
+                    .into(),
+            ],
+        }
+    }
+
+    /// Returns the elements to add to the end of the `<body>` of each page.
+    pub(crate) fn body_elements(&self) -> Vec<Element> {
+        match self {
+            Self::Plausible { .. } | Self::GoogleAnalytics { .. } => Vec::new(),
+            Self::GoatCounter { endpoint } => vec![script()
+                .attr("data-goatcounter", endpoint)
+                .attr("async", "")
+                .src("https://gc.zgo.at/count.js")
+                .into()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::render_elements_to_string;
+
+    use super::*;
+
+    #[test]
+    fn test_analytics_snippets() {
+        let plausible = Analytics::plausible("example.com");
+        let head = render_elements_to_string(plausible.head_elements());
+        assert!(head.contains(r#"data-domain="example.com""#));
+        assert!(head.contains(r#"src="https://plausible.io/js/script.js""#));
+        assert!(plausible.body_elements().is_empty());
+
+        let goat_counter = Analytics::goat_counter("example");
+        let body = render_elements_to_string(goat_counter.body_elements());
+        assert!(goat_counter.head_elements().is_empty());
+        assert!(body.contains(r#"data-goatcounter="https://example.goatcounter.com/count""#));
+
+        let google_analytics = Analytics::google_analytics("G-12345");
+        let head = render_elements_to_string(google_analytics.head_elements());
+        assert!(head.contains(r#"src="https://www.googletagmanager.com/gtag/js?id=G-12345""#));
+        assert!(head.contains("gtag('config', 'G-12345');"));
+    }
+}
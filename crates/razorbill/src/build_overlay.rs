@@ -0,0 +1,93 @@
+//! The overlay shown in the browser while serving a site, with the status of
+//! the most recent build.
+//!
+//! The status is streamed to the overlay over a WebSocket, so that it can show
+//! that a build is in progress and the errors from a build that failed.
+
+use futures_util::{SinkExt, StreamExt};
+use hyper::body::Incoming;
+use hyper::upgrade::Upgraded;
+use hyper::{Request, Response};
+use hyper_util::rt::TokioIo;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::WebSocketStream;
+
+use crate::live_reload::{accept_websocket, is_websocket_upgrade};
+use crate::serve::{BuildStatus, ResponseBody};
+
+pub(crate) const BUILD_OVERLAY_JS: &str = include_str!("../assets/build-overlay.js");
+
+/// The path that the overlay script is served from.
+pub(crate) const BUILD_OVERLAY_JS_PATH: &str = "/__razorbill/build-overlay.js";
+
+/// The path that the overlay connects to for build status updates.
+pub(crate) const BUILD_STATUS_SOCKET_PATH: &str = "/__razorbill/build-status";
+
+/// Broadcasts the build status to all of the connected overlays.
+#[derive(Clone)]
+pub(crate) struct BuildStatusBroadcaster {
+    tx: broadcast::Sender<String>,
+}
+
+impl BuildStatusBroadcaster {
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(16);
+
+        Self { tx }
+    }
+
+    /// Sends the given build status to all of the connected overlays.
+    pub fn send(&self, status: &BuildStatus) {
+        // Sending only fails when there are no overlays connected, which is fine.
+        let _ = self.tx.send(status.to_json().to_string());
+    }
+
+    /// Returns whether the request is an overlay trying to connect.
+    pub fn is_connect_request(req: &Request<Incoming>) -> bool {
+        req.uri().path() == BUILD_STATUS_SOCKET_PATH && is_websocket_upgrade(req.headers())
+    }
+
+    /// Accepts the connection from an overlay, upgrading it to a WebSocket.
+    ///
+    /// The overlay is sent the given status as soon as it connects.
+    pub fn connect(&self, req: Request<Incoming>, status: &BuildStatus) -> Response<ResponseBody> {
+        let current = status.to_json().to_string();
+        let statuses = self.tx.subscribe();
+
+        accept_websocket(req, "build overlay", |socket| {
+            handle_client(socket, current, statuses)
+        })
+    }
+}
+
+/// Sends the build status to a connected overlay whenever it changes.
+async fn handle_client(
+    mut socket: WebSocketStream<TokioIo<Upgraded>>,
+    current: String,
+    mut statuses: broadcast::Receiver<String>,
+) -> Result<(), tungstenite::Error> {
+    socket.send(Message::Text(current)).await?;
+
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                match message {
+                    None => return Ok(()),
+                    Some(message) => {
+                        if let Message::Close(_) = message? {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+            status = statuses.recv() => {
+                match status {
+                    Ok(status) => socket.send(Message::Text(status)).await?,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    }
+}
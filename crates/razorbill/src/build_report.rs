@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::content::{ScheduledPage, UnknownTaxonomy};
+use crate::lint::Diagnostic;
+
+/// A summary of a single render of a site.
+///
+/// The report for the most recent render is passed to any
+/// [`after_render`](crate::SiteBuilder::after_render) hooks and is available
+/// via [`Site::report`](crate::Site::report).
+#[derive(Debug, Clone, Default)]
+pub struct BuildReport {
+    /// The number of sections in the site.
+    pub section_count: usize,
+    /// The number of pages in the site.
+    pub page_count: usize,
+    /// How long it took to render the site.
+    pub render_duration: Duration,
+    /// The pages that declared terms for taxonomies that aren't registered
+    /// with the site.
+    pub unknown_taxonomies: Vec<UnknownTaxonomy>,
+    /// The pages that are dated in the future, ordered by when they go live.
+    ///
+    /// These pages are only published when building with
+    /// [`SiteBuilder::publish_future`](crate::SiteBuilder::publish_future).
+    pub scheduled_pages: Vec<ScheduledPage>,
+    /// The problems found by the site's [content rules](crate::lint::ContentRule).
+    pub diagnostics: Vec<Diagnostic>,
+    /// The time spent rendering each page, from slowest to fastest.
+    ///
+    /// This is only collected when building with [`BuildOptions::timings`](crate::BuildOptions::timings).
+    pub page_timings: Vec<PageTiming>,
+    /// The files copied from the static directory.
+    pub static_files: StaticFilesReport,
+    /// The `@/` links that don't point to any content, or to an anchor that
+    /// doesn't exist, ordered by link.
+    pub broken_links: Vec<BrokenLink>,
+    /// The relative `src` attributes in content that point to co-located
+    /// assets that aren't in the output, ordered by `src`.
+    pub missing_assets: Vec<MissingAsset>,
+    /// The menu items that link to content that doesn't exist.
+    ///
+    /// These items are left out of their menus.
+    pub unknown_menu_items: Vec<UnknownMenuItem>,
+}
+
+/// A [`MenuItem`](crate::menu::MenuItem) that links to content that doesn't
+/// exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownMenuItem {
+    /// The name of the menu containing the item.
+    pub menu: String,
+    /// The path to the content file the item links to.
+    pub path: PathBuf,
+}
+
+/// An `@/` link that doesn't point to any content, or to an anchor that
+/// doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BrokenLink {
+    /// The link, as written (e.g., `@/blog/missing.md`).
+    pub link: String,
+    /// The permalink of the page containing the link.
+    pub permalink: String,
+}
+
+/// A relative `src` in content that points to a co-located asset that isn't in
+/// the output.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MissingAsset {
+    /// The `src`, as written (e.g., `cat.png`).
+    pub src: String,
+    /// The permalink of the page containing the `src`.
+    pub permalink: String,
+    /// The path the asset was expected at, next to the page's content file.
+    pub source_path: PathBuf,
+    /// Whether the asset exists.
+    ///
+    /// Assets that exist are only copied to the output when content is passed
+    /// through (see [`SiteBuilder::passthrough_content`](crate::SiteBuilder::passthrough_content)).
+    pub exists: bool,
+}
+
+/// The number of files from the static directory handled by a render.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StaticFilesReport {
+    /// The files that were copied to the output.
+    pub copied: usize,
+    /// The files that were left as-is, because they hadn't changed since they
+    /// were last copied.
+    pub unchanged: usize,
+    /// The files that were skipped by the [`StaticFiles`](crate::static_files::StaticFiles) filters.
+    pub excluded: usize,
+}
+
+/// The time spent rendering a single page.
+#[derive(Debug, Clone, Default)]
+pub struct PageTiming {
+    /// The path to the page's content file, relative to the content directory.
+    pub path: PathBuf,
+    /// The time spent rendering the page's Markdown, excluding shortcodes.
+    pub markdown: Duration,
+    /// The time spent rendering the shortcodes in the page's Markdown.
+    pub shortcodes: Duration,
+    /// The time spent in the page's template.
+    pub template: Duration,
+    /// The time spent serializing the rendered page to HTML.
+    pub serialization: Duration,
+}
+
+impl PageTiming {
+    pub fn total(&self) -> Duration {
+        self.markdown + self.shortcodes + self.template + self.serialization
+    }
+}
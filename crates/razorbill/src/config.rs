@@ -0,0 +1,133 @@
+//! Loading site settings from a [Zola](https://www.getzola.org)-style `config.toml`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::content::Taxonomy;
+
+/// The settings read from a `config.toml`.
+///
+/// Keys that razorbill doesn't support are ignored, so that existing Zola
+/// configuration files can be used as-is.
+#[derive(Debug, Deserialize)]
+pub struct ConfigFile {
+    pub base_url: String,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub taxonomies: Vec<TaxonomyConfig>,
+    /// The name of the timezone that dates are displayed in (e.g., `America/New_York`).
+    pub timezone: Option<String>,
+    /// The language that content is written in, unless it specifies otherwise.
+    pub default_language: Option<String>,
+    /// The default format for dates, using [`chrono`'s syntax](https://docs.rs/chrono/latest/chrono/format/strftime/index.html).
+    pub date_format: Option<String>,
+    /// User-defined settings, made available to templates.
+    #[serde(default)]
+    pub extra: toml::Table,
+}
+
+/// A taxonomy declared in a `config.toml`.
+#[derive(Debug, Deserialize)]
+pub struct TaxonomyConfig {
+    pub name: String,
+    /// The path that the taxonomy is rendered under, if it differs from its name.
+    pub path: Option<String>,
+    /// Whether to render the page that lists all of the taxonomy's terms.
+    pub render_list: Option<bool>,
+    /// Whether to render a page for each of the taxonomy's terms.
+    pub render_terms: Option<bool>,
+}
+
+impl From<TaxonomyConfig> for Taxonomy {
+    fn from(taxonomy: TaxonomyConfig) -> Self {
+        Taxonomy {
+            name: taxonomy.name,
+            path: taxonomy.path,
+            render_list: taxonomy.render_list.unwrap_or(true),
+            render_terms: taxonomy.render_terms.unwrap_or(true),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LoadConfigError {
+    #[error("failed to read config '{filepath}': {err}")]
+    Io {
+        err: std::io::Error,
+        filepath: PathBuf,
+    },
+
+    #[error("invalid config in '{filepath}': {err}")]
+    InvalidConfig {
+        err: toml::de::Error,
+        filepath: PathBuf,
+    },
+
+    #[error("unknown timezone '{timezone}' in '{filepath}'")]
+    UnknownTimezone { timezone: String, filepath: PathBuf },
+}
+
+impl ConfigFile {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, LoadConfigError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|err| LoadConfigError::Io {
+            err,
+            filepath: path.to_owned(),
+        })?;
+
+        toml::from_str(&contents).map_err(|err| LoadConfigError::InvalidConfig {
+            err,
+            filepath: path.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_zola_config() {
+        let config: ConfigFile = toml::from_str(indoc! {r#"
+            base_url = "https://example.com"
+            title = "My Blog"
+            compile_sass = true
+
+            taxonomies = [
+                { name = "tags", feed = true },
+                { name = "categories" },
+            ]
+
+            [markdown]
+            highlight_code = true
+
+            [extra]
+            author = "Jane Doe"
+        "#})
+        .unwrap();
+
+        assert_eq!(config.base_url, "https://example.com");
+        assert_eq!(config.title.as_deref(), Some("My Blog"));
+        assert_eq!(
+            config
+                .taxonomies
+                .iter()
+                .map(|taxonomy| taxonomy.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["tags", "categories"]
+        );
+        assert_eq!(
+            config
+                .extra
+                .get("author")
+                .and_then(|author| author.as_str()),
+            Some("Jane Doe")
+        );
+    }
+}
@@ -1,19 +1,33 @@
 mod aggregator;
+mod assets;
 mod collections;
+mod content_ref;
+mod dated_pages;
+mod document;
 mod file_info;
 mod front_matter;
 mod page;
 mod reading_metrics;
+mod scheduling;
 mod section;
+mod series;
 mod sorting;
+mod stats;
 mod taxonomy;
 
 pub use aggregator::*;
+pub use assets::*;
 pub use collections::*;
+pub use content_ref::*;
+pub use dated_pages::*;
+pub use document::*;
 pub use file_info::*;
 pub use front_matter::*;
 pub use page::*;
 pub use reading_metrics::*;
+pub use scheduling::*;
 pub use section::*;
+pub use series::*;
 pub use sorting::*;
+pub use stats::*;
 pub use taxonomy::*;
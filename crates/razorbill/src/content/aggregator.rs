@@ -1,13 +1,18 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use crate::content::{sort_pages_by, Page, Pages, Section, Sections, SortBy, Taxonomy};
+use crate::content::{
+    compare_sections_by_weight, merge_tables, sort_pages_by, CascadeFrontMatter, Page, Pages,
+    Section, Sections, SortBy, Taxonomy, TermNormalization, AUTHORS_TAXONOMY,
+};
+use crate::SiteConfig;
 
 pub struct ContentAggregator {
     content_path: PathBuf,
     sections: Sections,
     pages: Pages,
     taxonomies: HashMap<String, HashMap<String, Vec<PathBuf>>>,
+    term_normalization: TermNormalization,
 }
 
 impl ContentAggregator {
@@ -24,9 +29,16 @@ impl ContentAggregator {
             sections: Sections::default(),
             pages: Pages::default(),
             taxonomies,
+            term_normalization: TermNormalization::default(),
         }
     }
 
+    /// Sets how taxonomy terms are normalized when grouping pages by term.
+    pub fn term_normalization(mut self, term_normalization: TermNormalization) -> Self {
+        self.term_normalization = term_normalization;
+        self
+    }
+
     /// Adds the given [`Section`] to the aggregate.
     pub fn add_section(&mut self, section: Section) {
         self.sections.insert(section.file.path.clone(), section);
@@ -34,18 +46,56 @@ impl ContentAggregator {
 
     /// Adds the given [`Page`] to the aggregate.
     pub fn add_page(&mut self, page: Page) {
-        for (taxonomy_name, terms) in &page.meta.taxonomies {
-            let Some(pages_by_term) = self.taxonomies.get_mut(taxonomy_name) else {
+        self.pages.insert(page.file.path.clone(), page);
+    }
+
+    /// Returns the pages and sections that have been added to the aggregate,
+    /// so that they can be modified before they are aggregated.
+    pub fn content_mut(&mut self) -> (&mut Pages, &mut Sections) {
+        (&mut self.pages, &mut self.sections)
+    }
+
+    /// Adds an implicit [`Section`] for each directory containing pages that
+    /// doesn't have an `_index.md`.
+    ///
+    /// Implicit sections inherit their templates and sorting from their
+    /// nearest ancestor section.
+    pub fn add_implicit_sections(&mut self, config: &SiteConfig) {
+        // Directories are visited from the outside in, so that implicit
+        // sections can inherit from the implicit sections above them.
+        let directories = self
+            .pages
+            .values()
+            .flat_map(|page| page.file.parent.ancestors())
+            .filter(|directory| directory.starts_with(&self.content_path))
+            .map(Path::to_owned)
+            .collect::<BTreeSet<_>>();
+
+        for directory in directories {
+            let index_path = directory.join("_index.md");
+
+            // An `_index.md` that exists on disk but isn't in the aggregate
+            // belongs to a draft section, which shouldn't be resurrected.
+            if self.sections.contains_key(&index_path) || index_path.exists() {
                 continue;
-            };
+            }
 
-            for term in terms {
-                let pages = pages_by_term.entry(term.clone()).or_default();
-                pages.push(page.file.path.clone());
+            let mut section = Section::implicit(config, &self.content_path, &directory);
+
+            let nearest_ancestor = directory
+                .ancestors()
+                .skip(1)
+                .take_while(|ancestor| ancestor.starts_with(&self.content_path))
+                .find_map(|ancestor| self.sections.get(&ancestor.join("_index.md")));
+            if let Some(ancestor) = nearest_ancestor {
+                section.meta.template = ancestor.meta.template.clone();
+                section.meta.page_template = ancestor.meta.page_template.clone();
+                section.meta.sort_by = ancestor.meta.sort_by;
+                section.meta.sort_order = ancestor.meta.sort_order;
             }
-        }
 
-        self.pages.insert(page.file.path.clone(), page);
+            self.add_section(section);
+        }
     }
 
     /// Aggregates and returns all of the sections, pages, and taxonomies in the aggregate.
@@ -58,6 +108,31 @@ impl ContentAggregator {
     ) {
         let ancestors = self.build_ancestors();
 
+        let mut subsections: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        for path in self.sections.keys() {
+            let Some(parent) = ancestors.get(path).and_then(|ancestors| ancestors.last()) else {
+                continue;
+            };
+
+            if self.sections.contains_key(parent) {
+                subsections
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(path.clone());
+            }
+        }
+
+        for (parent, mut children) in subsections {
+            children
+                .sort_by(|a, b| compare_sections_by_weight(&self.sections[a], &self.sections[b]));
+
+            self.sections.get_mut(&parent).unwrap().subsections = children;
+        }
+
+        for (path, section) in self.sections.iter_mut() {
+            section.ancestors = ancestors.get(path).cloned().unwrap_or_default();
+        }
+
         for (path, page) in self.pages.iter_mut() {
             let mut parent_section_path = page.file.parent.join("_index.md");
 
@@ -72,16 +147,6 @@ impl ContentAggregator {
                     .unwrap_or_default();
                 page.ancestors.push(parent_section.file.path.clone());
 
-                if page.meta.template.is_none() {
-                    for ancestor in page.ancestors.iter().rev() {
-                        let section = self.sections.get(ancestor).unwrap();
-                        if let Some(template) = section.meta.page_template.as_ref() {
-                            page.meta.template = Some(template.clone());
-                            break;
-                        }
-                    }
-                }
-
                 if !is_transparent {
                     break;
                 }
@@ -91,19 +156,94 @@ impl ContentAggregator {
                     None => break,
                 }
             }
+
+            // Front matter is cascaded from the nearest ancestor outwards, so
+            // that nearer sections take precedence.
+            let ancestors = page.ancestors.clone();
+            for ancestor in ancestors.iter().rev() {
+                let section = self.sections.get(ancestor).unwrap();
+
+                if page.meta.template.is_none() {
+                    page.meta.template = section
+                        .meta
+                        .cascade
+                        .template
+                        .clone()
+                        .or_else(|| section.meta.page_template.clone());
+                }
+
+                if page.meta.lang.is_none() {
+                    page.meta.lang = section.meta.lang.clone();
+                }
+
+                apply_cascade(page, &section.meta.cascade);
+            }
+
+            if self.taxonomies.contains_key(AUTHORS_TAXONOMY) {
+                let page_authors = page
+                    .meta
+                    .taxonomies
+                    .entry(AUTHORS_TAXONOMY.to_string())
+                    .or_default();
+
+                for author in &page.meta.authors {
+                    if !page_authors.contains(author) {
+                        page_authors.push(author.clone());
+                    }
+                }
+            }
         }
 
-        for (_path, section) in self.sections.iter_mut() {
+        self.normalize_terms();
+
+        // Taxonomies are indexed after cascading, so that they include any
+        // terms cascaded from sections.
+        for (path, page) in self.pages.iter() {
+            for (taxonomy_name, terms) in &page.meta.taxonomies {
+                let Some(pages_by_term) = self.taxonomies.get_mut(taxonomy_name) else {
+                    continue;
+                };
+
+                for term in terms {
+                    let pages = pages_by_term.entry(term.clone()).or_default();
+                    pages.push(path.clone());
+                }
+            }
+        }
+
+        let mut siblings = HashMap::new();
+
+        for (section_path, section) in self.sections.iter_mut() {
             let pages = section
                 .pages
                 .iter()
                 .map(|path| &self.pages[path])
                 .collect::<Vec<_>>();
 
-            let (sorted_pages, unsorted_pages) = match section.meta.sort_by.into() {
-                Some(sort_by) => sort_pages_by(sort_by, pages),
-                None => continue,
+            let Some(sort_by) = Option::<SortBy>::from(section.meta.sort_by) else {
+                continue;
             };
+            let sort_order = section
+                .meta
+                .sort_order
+                .unwrap_or_else(|| sort_by.default_order());
+
+            let (sorted_pages, unsorted_pages) = sort_pages_by(sort_by, sort_order, pages);
+
+            // Pages are only linked to their siblings in their own section, not
+            // any transparent ancestors they may also be listed in.
+            for (index, page_path) in sorted_pages.iter().enumerate() {
+                if self.pages[page_path].file.parent.join("_index.md") != *section_path {
+                    continue;
+                }
+
+                let previous = index
+                    .checked_sub(1)
+                    .map(|index| sorted_pages[index].clone());
+                let next = sorted_pages.get(index + 1).cloned();
+
+                siblings.insert(page_path.clone(), (previous, next));
+            }
 
             let mut reordered_pages = sorted_pages;
             reordered_pages.extend(unsorted_pages);
@@ -111,6 +251,26 @@ impl ContentAggregator {
             section.pages = reordered_pages;
         }
 
+        for (page_path, (previous, next)) in siblings {
+            let page = self.pages.get_mut(&page_path).unwrap();
+            page.previous = previous;
+            page.next = next;
+        }
+
+        let all_pages = self
+            .sections
+            .keys()
+            .map(|path| {
+                let mut all_pages = Vec::new();
+                collect_all_pages(&self.sections, path, &mut all_pages, &mut HashSet::new());
+                (path.clone(), all_pages)
+            })
+            .collect::<Vec<_>>();
+
+        for (path, all_pages) in all_pages {
+            self.sections.get_mut(&path).unwrap().all_pages = all_pages;
+        }
+
         for (_taxonomy, pages_by_term) in self.taxonomies.iter_mut() {
             for (_term, page_paths) in pages_by_term {
                 let pages = page_paths
@@ -118,7 +278,8 @@ impl ContentAggregator {
                     .map(|page| self.pages.get(page).unwrap())
                     .collect::<Vec<_>>();
 
-                let (sorted_pages, unsorted_pages) = sort_pages_by(SortBy::Date, pages);
+                let (sorted_pages, unsorted_pages) =
+                    sort_pages_by(SortBy::Date, SortBy::Date.default_order(), pages);
 
                 let mut reordered_pages = sorted_pages;
                 reordered_pages.extend(unsorted_pages);
@@ -130,6 +291,67 @@ impl ContentAggregator {
         (self.sections, self.pages, self.taxonomies)
     }
 
+    /// Rewrites the taxonomy terms on each page to their canonical spelling, so
+    /// that terms that normalize to the same value are merged.
+    fn normalize_terms(&mut self) {
+        if self.term_normalization == TermNormalization::default() {
+            return;
+        }
+
+        // Pages are visited in a stable order, so that ties between spellings
+        // are always broken the same way.
+        let mut page_paths = self.pages.keys().cloned().collect::<Vec<_>>();
+        page_paths.sort();
+
+        let mut spellings: HashMap<(String, String), Vec<(String, usize)>> = HashMap::new();
+        for path in &page_paths {
+            for (taxonomy, terms) in &self.pages[path].meta.taxonomies {
+                for term in terms {
+                    let spelling = self.term_normalization.spelling(term);
+                    let key = (taxonomy.clone(), self.term_normalization.normalize(term));
+
+                    let counts = spellings.entry(key).or_default();
+                    match counts.iter_mut().find(|(existing, _)| existing == spelling) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((spelling.to_owned(), 1)),
+                    }
+                }
+            }
+        }
+
+        let canonical_terms = spellings
+            .into_iter()
+            .map(|(key, counts)| {
+                // `max_by_key` returns the last maximum, so we search in reverse
+                // to have ties go to the first spelling.
+                let (spelling, _count) = counts
+                    .into_iter()
+                    .rev()
+                    .max_by_key(|(_, count)| *count)
+                    .unwrap();
+
+                (key, spelling)
+            })
+            .collect::<HashMap<_, _>>();
+
+        for page in self.pages.values_mut() {
+            for (taxonomy, terms) in page.meta.taxonomies.iter_mut() {
+                let mut canonical_page_terms = Vec::with_capacity(terms.len());
+
+                for term in terms.iter() {
+                    let key = (taxonomy.clone(), self.term_normalization.normalize(term));
+                    let canonical_term = &canonical_terms[&key];
+
+                    if !canonical_page_terms.contains(canonical_term) {
+                        canonical_page_terms.push(canonical_term.clone());
+                    }
+                }
+
+                *terms = canonical_page_terms;
+            }
+        }
+    }
+
     fn build_ancestors(&self) -> HashMap<PathBuf, Vec<PathBuf>> {
         let mut ancestors = HashMap::new();
 
@@ -159,77 +381,78 @@ impl ContentAggregator {
     }
 }
 
+/// Collects the pages in the given section and all of the sections nested
+/// beneath it.
+///
+/// Pages in transparent sections are already listed in their parent sections,
+/// so pages that have already been seen are skipped.
+fn collect_all_pages(
+    sections: &Sections,
+    section_path: &Path,
+    all_pages: &mut Vec<PathBuf>,
+    seen: &mut HashSet<PathBuf>,
+) {
+    let Some(section) = sections.get(section_path) else {
+        return;
+    };
+
+    for page in &section.pages {
+        if seen.insert(page.clone()) {
+            all_pages.push(page.clone());
+        }
+    }
+
+    for subsection in &section.subsections {
+        collect_all_pages(sections, subsection, all_pages, seen);
+    }
+}
+
+/// Merges the cascaded taxonomy terms and extra values into the given page.
+///
+/// Values already present on the page are kept.
+fn apply_cascade(page: &mut Page, cascade: &CascadeFrontMatter) {
+    for (taxonomy, terms) in &cascade.taxonomies {
+        let page_terms = page.meta.taxonomies.entry(taxonomy.clone()).or_default();
+
+        for term in terms {
+            if !page_terms.contains(term) {
+                page_terms.push(term.clone());
+            }
+        }
+    }
+
+    merge_tables(&mut page.meta.extra, &cascade.extra);
+}
+
 #[cfg(test)]
 mod tests {
-    use auk_markdown::TableOfContents;
+    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    use crate::content::{
-        FileInfo, MaybeSortBy, PageFrontMatter, PagePath, ReadTime, SectionFrontMatter,
-        SectionPath, SortBy, WordCount, AVERAGE_ADULT_WPM,
-    };
-    use crate::permalink::Permalink;
+    use crate::content::{MaybeSortBy, PageFrontMatter, SectionFrontMatter, SortBy};
+    use crate::test_site::{make_page, make_section};
     use crate::SiteConfig;
 
     use super::*;
 
-    fn make_section(filepath: &str, sort_by: MaybeSortBy) -> Section {
-        let config = SiteConfig {
-            base_url: "https://example.com".to_string(),
-            title: None,
-            taxonomies: Vec::new(),
-            reading_speed: AVERAGE_ADULT_WPM,
-        };
-
-        let root_path = PathBuf::new();
-        let file = FileInfo::new(&root_path, filepath);
-        let path = SectionPath::from_file_path(root_path, &file.path).unwrap();
-
-        Section {
-            meta: SectionFrontMatter {
+    fn sorted_section(filepath: &str, sort_by: MaybeSortBy) -> Section {
+        make_section(
+            filepath,
+            SectionFrontMatter {
                 sort_by,
                 ..Default::default()
             },
-            permalink: Permalink::from_path(&config, path.0.as_str()),
-            path,
-            file,
-            raw_content: String::new(),
-            content: Vec::new(),
-            table_of_contents: TableOfContents::default(),
-            word_count: WordCount(0),
-            read_time: ReadTime(0),
-            pages: Vec::new(),
-        }
+        )
     }
 
-    fn make_page(filepath: &str, date: &str) -> Page {
-        let config = SiteConfig {
-            base_url: "https://example.com".to_string(),
-            title: None,
-            taxonomies: Vec::new(),
-            reading_speed: AVERAGE_ADULT_WPM,
-        };
-
-        let root_path = PathBuf::new();
-        let file = FileInfo::new(&root_path, filepath);
-        let path = PagePath::from_file_path(root_path, &file.path).unwrap();
-
-        Page {
-            meta: PageFrontMatter {
+    fn dated_page(filepath: &str, date: &str) -> Page {
+        make_page(
+            filepath,
+            PageFrontMatter {
                 date: Some(date.to_string()),
                 ..Default::default()
             },
-            permalink: Permalink::from_path(&config, path.0.as_str()),
-            path,
-            file,
-            ancestors: Vec::new(),
-            slug: String::new(),
-            raw_content: String::new(),
-            content: Vec::new(),
-            table_of_contents: TableOfContents::default(),
-            word_count: WordCount(0),
-            read_time: ReadTime(0),
-        }
+        )
     }
 
     #[test]
@@ -247,18 +470,33 @@ mod tests {
         ];
 
         for (filepath, sort_by) in sections {
-            aggregator.add_section(make_section(filepath, sort_by))
+            aggregator.add_section(sorted_section(filepath, sort_by))
         }
 
         for (filepath, date) in pages {
-            aggregator.add_page(make_page(filepath, date));
+            aggregator.add_page(dated_page(filepath, date));
         }
 
         let (sections, pages, _taxonomies) = aggregator.aggregate();
 
+        let root_section = sections.get(&PathBuf::from("content/_index.md")).unwrap();
+        assert_eq!(root_section.pages, Vec::<PathBuf>::new());
+        assert_eq!(
+            root_section.all_pages,
+            vec![
+                PathBuf::from("content/blog/2024-01-01-happy-new-year.md"),
+                PathBuf::from("content/blog/2023-12-31-year-in-review.md"),
+                PathBuf::from("content/blog/2023-07-01-hello-world.md"),
+            ]
+        );
+
         let blog_section = sections
             .get(&PathBuf::from("content/blog/_index.md"))
             .unwrap();
+        assert_eq!(
+            blog_section.ancestors,
+            vec![PathBuf::from("content/_index.md")]
+        );
         assert_eq!(
             blog_section.pages,
             vec![
@@ -278,5 +516,247 @@ mod tests {
                 PathBuf::from("content/blog/_index.md")
             ]
         );
+
+        let year_in_review_page = pages
+            .get(&PathBuf::from("content/blog/2023-12-31-year-in-review.md"))
+            .unwrap();
+        assert_eq!(
+            year_in_review_page.previous,
+            Some(PathBuf::from("content/blog/2024-01-01-happy-new-year.md"))
+        );
+        assert_eq!(
+            year_in_review_page.next,
+            Some(PathBuf::from("content/blog/2023-07-01-hello-world.md"))
+        );
+        assert_eq!(hello_world_page.next, None);
+    }
+
+    #[test]
+    fn test_aggregate_cascade() {
+        let mut aggregator =
+            ContentAggregator::new(PathBuf::from("content"), vec![Taxonomy::new("tags")]);
+
+        let mut root_section = sorted_section("content/_index.md", MaybeSortBy::None);
+        root_section.meta.cascade = toml::from_str(indoc! {r#"
+            template = "default"
+
+            [extra]
+            author = "Jane Doe"
+            comments = true
+        "#})
+        .unwrap();
+        aggregator.add_section(root_section);
+
+        let mut blog_section = sorted_section("content/blog/_index.md", MaybeSortBy::None);
+        blog_section.meta.cascade = toml::from_str(indoc! {r#"
+            template = "post"
+
+            [taxonomies]
+            tags = ["blog"]
+
+            [extra]
+            comments = false
+        "#})
+        .unwrap();
+        aggregator.add_section(blog_section);
+
+        let mut page = dated_page("content/blog/2024-01-01-happy-new-year.md", "2024-01-01");
+        page.meta.taxonomies =
+            HashMap::from_iter([("tags".to_string(), vec!["new-year".to_string()])]);
+        page.meta.extra = toml::from_str(r#"author = "John Doe""#).unwrap();
+        aggregator.add_page(page);
+
+        let (_sections, pages, taxonomies) = aggregator.aggregate();
+
+        let page = pages
+            .get(&PathBuf::from("content/blog/2024-01-01-happy-new-year.md"))
+            .unwrap();
+        assert_eq!(page.meta.template.as_deref(), Some("post"));
+        assert_eq!(
+            page.meta.taxonomies["tags"],
+            vec!["new-year".to_string(), "blog".to_string()]
+        );
+        assert_eq!(
+            page.meta.extra,
+            toml::from_str::<toml::Table>(indoc! {r#"
+                author = "John Doe"
+                comments = false
+            "#})
+            .unwrap()
+        );
+        assert_eq!(
+            taxonomies["tags"]["blog"],
+            vec![PathBuf::from("content/blog/2024-01-01-happy-new-year.md")]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_implicit_sections() {
+        let config = SiteConfig::for_tests("https://example.com");
+
+        let mut aggregator = ContentAggregator::new(PathBuf::from("content"), Vec::new());
+
+        let mut root_section = sorted_section("content/_index.md", MaybeSortBy::None);
+        root_section.meta.page_template = Some("post".to_string());
+        aggregator.add_section(root_section);
+        aggregator.add_section(sorted_section(
+            "content/blog/_index.md",
+            MaybeSortBy::SortBy(SortBy::Date),
+        ));
+
+        let pages = vec![
+            ("content/blog/2023/2023-07-01-hello-world.md", "2023-07-01"),
+            (
+                "content/blog/2023/2023-12-31-year-in-review.md",
+                "2023-12-31",
+            ),
+            ("content/notes/first-note.md", "2024-01-01"),
+        ];
+
+        for (filepath, date) in pages {
+            aggregator.add_page(dated_page(filepath, date));
+        }
+
+        aggregator.add_implicit_sections(&config);
+
+        let (sections, pages, _taxonomies) = aggregator.aggregate();
+
+        let year_section = sections
+            .get(&PathBuf::from("content/blog/2023/_index.md"))
+            .unwrap();
+        assert_eq!(year_section.meta.title.as_deref(), Some("2023"));
+        assert_eq!(
+            year_section.permalink.as_str(),
+            "https://example.com/blog/2023/"
+        );
+        assert_eq!(
+            year_section.pages,
+            vec![
+                PathBuf::from("content/blog/2023/2023-12-31-year-in-review.md"),
+                PathBuf::from("content/blog/2023/2023-07-01-hello-world.md"),
+            ]
+        );
+
+        let blog_section = sections
+            .get(&PathBuf::from("content/blog/_index.md"))
+            .unwrap();
+        assert_eq!(
+            blog_section.subsections,
+            vec![PathBuf::from("content/blog/2023/_index.md")]
+        );
+
+        let note_page = pages
+            .get(&PathBuf::from("content/notes/first-note.md"))
+            .unwrap();
+        assert_eq!(
+            note_page.ancestors,
+            vec![
+                PathBuf::from("content/_index.md"),
+                PathBuf::from("content/notes/_index.md")
+            ]
+        );
+        assert_eq!(note_page.meta.template.as_deref(), Some("post"));
+    }
+
+    #[test]
+    fn test_aggregate_authors() {
+        let mut aggregator = ContentAggregator::new(
+            PathBuf::from("content"),
+            vec![Taxonomy::new(AUTHORS_TAXONOMY)],
+        );
+
+        let pages = vec![
+            ("content/a.md", "2024-01-01", vec!["jane-doe"]),
+            ("content/b.md", "2024-01-02", vec!["jane-doe", "john-doe"]),
+        ];
+
+        for (filepath, date, authors) in pages {
+            let mut page = dated_page(filepath, date);
+            page.meta.authors = authors.into_iter().map(ToOwned::to_owned).collect();
+
+            aggregator.add_page(page);
+        }
+
+        let (_sections, _pages, taxonomies) = aggregator.aggregate();
+
+        assert_eq!(
+            taxonomies[AUTHORS_TAXONOMY]["jane-doe"],
+            vec![PathBuf::from("content/b.md"), PathBuf::from("content/a.md")]
+        );
+        assert_eq!(
+            taxonomies[AUTHORS_TAXONOMY]["john-doe"],
+            vec![PathBuf::from("content/b.md")]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_normalized_terms() {
+        let mut aggregator =
+            ContentAggregator::new(PathBuf::from("content"), vec![Taxonomy::new("tags")])
+                .term_normalization(TermNormalization::all());
+
+        let pages = vec![
+            ("content/a.md", "2024-01-01", vec!["rust", "cafe\u{301}"]),
+            ("content/b.md", "2024-01-02", vec!["Rust", "café"]),
+            ("content/c.md", "2024-01-03", vec![" Rust ", "RUST"]),
+        ];
+
+        for (filepath, date, terms) in pages {
+            let mut page = dated_page(filepath, date);
+            page.meta.taxonomies = HashMap::from_iter([(
+                "tags".to_string(),
+                terms.into_iter().map(ToOwned::to_owned).collect(),
+            )]);
+
+            aggregator.add_page(page);
+        }
+
+        let (_sections, pages, taxonomies) = aggregator.aggregate();
+
+        let mut terms = taxonomies["tags"].keys().cloned().collect::<Vec<_>>();
+        terms.sort();
+        assert_eq!(terms, vec!["Rust".to_string(), "cafe\u{301}".to_string()]);
+        assert_eq!(
+            taxonomies["tags"]["Rust"],
+            vec![
+                PathBuf::from("content/c.md"),
+                PathBuf::from("content/b.md"),
+                PathBuf::from("content/a.md"),
+            ]
+        );
+
+        let page = pages.get(&PathBuf::from("content/c.md")).unwrap();
+        assert_eq!(page.meta.taxonomies["tags"], vec!["Rust".to_string()]);
+    }
+
+    #[test]
+    fn test_aggregate_subsections_ordered_by_weight() {
+        let mut aggregator = ContentAggregator::new(PathBuf::from("content"), Vec::new());
+
+        let sections = vec![
+            ("content/_index.md", None),
+            ("content/blog/_index.md", Some(2)),
+            ("content/about/_index.md", None),
+            ("content/projects/_index.md", Some(1)),
+        ];
+
+        for (filepath, weight) in sections {
+            let mut section = sorted_section(filepath, MaybeSortBy::None);
+            section.meta.weight = weight;
+
+            aggregator.add_section(section);
+        }
+
+        let (sections, _pages, _taxonomies) = aggregator.aggregate();
+
+        let root_section = sections.get(&PathBuf::from("content/_index.md")).unwrap();
+        assert_eq!(
+            root_section.subsections,
+            vec![
+                PathBuf::from("content/projects/_index.md"),
+                PathBuf::from("content/blog/_index.md"),
+                PathBuf::from("content/about/_index.md"),
+            ]
+        );
     }
 }
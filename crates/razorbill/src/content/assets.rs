@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+
+use crate::content::{Pages, Sections};
+use crate::permalink::Permalink;
+use crate::SiteConfig;
+
+/// A file in the content directory that isn't content (e.g., `posts/data.csv`),
+/// which is copied to the output as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentAsset {
+    /// The path to the file in the content directory.
+    pub file_path: PathBuf,
+    pub permalink: Permalink,
+}
+
+/// Returns whether the file with the given name is passed through to the
+/// output, rather than being read as content.
+pub(crate) fn is_passthrough_file(filename: &str) -> bool {
+    !filename.ends_with(".md") && !filename.starts_with('.')
+}
+
+/// Resolves the URL of the asset at the given path.
+///
+/// Assets within a page's bundle (e.g., `content/blog/hello-world/cover.png`
+/// for `content/blog/hello-world.md`) are placed beneath the page, and the
+/// path to the page is returned along with the asset. Otherwise, assets are
+/// placed beneath the section containing them.
+///
+/// Returns `None` if the asset isn't published.
+pub(crate) fn resolve_content_asset(
+    config: &SiteConfig,
+    content_path: &Path,
+    sections: &Sections,
+    pages: &Pages,
+    file_path: &Path,
+) -> Option<(ContentAsset, Option<PathBuf>)> {
+    for bundle_path in file_path.ancestors().skip(1) {
+        if bundle_path == content_path || !bundle_path.starts_with(content_path) {
+            break;
+        }
+
+        let page_path = bundle_path.with_extension("md");
+        let Some(page) = pages.get(&page_path) else {
+            // The assets of pages that aren't published (e.g., drafts) aren't
+            // published either.
+            if page_path.is_file() {
+                return None;
+            }
+
+            continue;
+        };
+
+        let relative_path = file_path.strip_prefix(bundle_path).ok()?;
+        let permalink = page.permalink.join(&relative_url(relative_path))?;
+
+        return Some((
+            ContentAsset {
+                file_path: file_path.to_owned(),
+                permalink,
+            },
+            Some(page_path),
+        ));
+    }
+
+    let parent = file_path.parent()?;
+    let parent_permalink = match sections.get(&parent.join("_index.md")) {
+        Some(section) => section.permalink.clone(),
+        None => {
+            let relative_parent = parent.strip_prefix(content_path).ok()?;
+            Permalink::from_path(config, &relative_url(relative_parent))
+        }
+    };
+    let permalink = parent_permalink.join(&relative_url(Path::new(file_path.file_name()?)))?;
+
+    Some((
+        ContentAsset {
+            file_path: file_path.to_owned(),
+            permalink,
+        },
+        None,
+    ))
+}
+
+/// Returns the given relative path as a URL path, with `/` as the separator.
+fn relative_url(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,171 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::content::{PagePath, SectionPath};
+use crate::permalink::Permalink;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseContentPathError {
+    #[error("content path '{0}' must start with '/'")]
+    MissingLeadingSlash(String),
+
+    #[error("content path '{0}' has an empty segment")]
+    EmptySegment(String),
+
+    #[error("page path '{0}' refers to a section")]
+    NotAPage(String),
+
+    #[error("section path '{0}' must end with '/_index'")]
+    NotASection(String),
+}
+
+/// Checks that the given content path starts with a `/` and has no empty
+/// segments, returning its segments.
+pub(crate) fn parse_segments(path: &str) -> Result<Vec<&str>, ParseContentPathError> {
+    let Some(rest) = path.strip_prefix('/') else {
+        return Err(ParseContentPathError::MissingLeadingSlash(path.to_string()));
+    };
+
+    let segments = rest.split('/').collect::<Vec<_>>();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(ParseContentPathError::EmptySegment(path.to_string()));
+    }
+
+    Ok(segments)
+}
+
+/// A reference to a section or page, used to look it up from a template.
+#[derive(Debug, Clone)]
+pub enum ContentRef {
+    /// The path to the content file, where paths starting with `@/` are
+    /// relative to the content directory (e.g., `@/blog/_index.md`).
+    File(PathBuf),
+    Page(PagePath),
+    Section(SectionPath),
+    /// The permalink of the section or page.
+    Permalink(Permalink),
+}
+
+impl From<&str> for ContentRef {
+    fn from(path: &str) -> Self {
+        Self::File(PathBuf::from(path))
+    }
+}
+
+impl From<String> for ContentRef {
+    fn from(path: String) -> Self {
+        Self::File(PathBuf::from(path))
+    }
+}
+
+impl From<&String> for ContentRef {
+    fn from(path: &String) -> Self {
+        Self::File(PathBuf::from(path))
+    }
+}
+
+impl From<&Path> for ContentRef {
+    fn from(path: &Path) -> Self {
+        Self::File(path.to_owned())
+    }
+}
+
+impl From<PathBuf> for ContentRef {
+    fn from(path: PathBuf) -> Self {
+        Self::File(path)
+    }
+}
+
+impl From<&PathBuf> for ContentRef {
+    fn from(path: &PathBuf) -> Self {
+        Self::File(path.clone())
+    }
+}
+
+impl From<PagePath> for ContentRef {
+    fn from(path: PagePath) -> Self {
+        Self::Page(path)
+    }
+}
+
+impl From<&PagePath> for ContentRef {
+    fn from(path: &PagePath) -> Self {
+        Self::Page(path.clone())
+    }
+}
+
+impl From<SectionPath> for ContentRef {
+    fn from(path: SectionPath) -> Self {
+        Self::Section(path)
+    }
+}
+
+impl From<&SectionPath> for ContentRef {
+    fn from(path: &SectionPath) -> Self {
+        Self::Section(path.clone())
+    }
+}
+
+impl From<Permalink> for ContentRef {
+    fn from(permalink: Permalink) -> Self {
+        Self::Permalink(permalink)
+    }
+}
+
+impl From<&Permalink> for ContentRef {
+    fn from(permalink: &Permalink) -> Self {
+        Self::Permalink(permalink.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_page_path() {
+        assert_eq!(
+            "/blog/hello-world".parse::<PagePath>(),
+            Ok(PagePath("/blog/hello-world".to_string()))
+        );
+        assert_eq!(
+            "blog/hello-world".parse::<PagePath>(),
+            Err(ParseContentPathError::MissingLeadingSlash(
+                "blog/hello-world".to_string()
+            ))
+        );
+        assert_eq!(
+            "/blog//hello-world".parse::<PagePath>(),
+            Err(ParseContentPathError::EmptySegment(
+                "/blog//hello-world".to_string()
+            ))
+        );
+        assert_eq!(
+            "/blog/_index".parse::<PagePath>(),
+            Err(ParseContentPathError::NotAPage("/blog/_index".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_section_path() {
+        assert_eq!(
+            "/_index".parse::<SectionPath>(),
+            Ok(SectionPath("/_index".to_string()))
+        );
+        assert_eq!(
+            "/blog/_index".parse::<SectionPath>(),
+            Ok(SectionPath("/blog/_index".to_string()))
+        );
+        assert_eq!(
+            "/blog/".parse::<SectionPath>(),
+            Err(ParseContentPathError::EmptySegment("/blog/".to_string()))
+        );
+        assert_eq!(
+            "/blog".parse::<SectionPath>(),
+            Err(ParseContentPathError::NotASection("/blog".to_string()))
+        );
+    }
+}
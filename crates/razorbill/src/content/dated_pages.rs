@@ -0,0 +1,58 @@
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+use crate::content::Pages;
+use crate::date::parse_date;
+
+/// Returns the paths to the pages that have a valid date, from newest to oldest.
+pub fn dated_pages(pages: &Pages) -> Vec<PathBuf> {
+    let mut dated_pages = pages
+        .values()
+        .filter_map(|page| {
+            let date = page.meta.date.as_deref()?;
+            Some((parse_date(date)?, date, &page.file.path))
+        })
+        .collect::<Vec<_>>();
+
+    dated_pages.sort_by_key(|(date, raw_date, path)| (Reverse(*date), Reverse(*raw_date), *path));
+
+    dated_pages
+        .into_iter()
+        .map(|(_, _, path)| path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_site::TestSite;
+
+    use super::*;
+
+    #[test]
+    fn test_dated_pages() {
+        let test_site = TestSite::new("dated_pages");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("older.md", "+++\ndate = 2023-12-31\n+++\n")
+            .write("newer.md", "+++\ndate = 2024-03-15\n+++\n")
+            .write("same-day.md", "+++\ndate = 2024-03-15\n+++\n")
+            .write(
+                "same-day-later.md",
+                "+++\ndate = 2024-03-15T08:00:00Z\n+++\n",
+            )
+            .write("undated.md", "+++\n+++\n");
+        let site = test_site.load();
+
+        // Pages with the same date are ordered by their full date, and then by
+        // path.
+        assert_eq!(
+            dated_pages(&site.pages),
+            ["same-day-later.md", "newer.md", "same-day.md", "older.md"]
+                .into_iter()
+                .map(|path| test_site.content_path().join(path))
+                .collect::<Vec<_>>()
+        );
+    }
+}
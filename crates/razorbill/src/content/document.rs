@@ -0,0 +1,232 @@
+use std::path::Path;
+use std::{fmt, fs, io};
+
+use thiserror::Error;
+
+use crate::content::front_matter::toml_regex;
+
+const DELIMITER: &str = "+++";
+
+/// The order that well-known front matter keys are written in.
+///
+/// Any other keys are written after these, in alphabetical order, with tables
+/// coming last.
+const KEY_ORDER: &[&str] = &[
+    "title",
+    "description",
+    "slug",
+    "date",
+    "updated",
+    "weight",
+    "draft",
+    "template",
+    "page_template",
+    "series",
+    "authors",
+    "sort_by",
+    "sort_order",
+    "transparent",
+    "generate_feed",
+    "pdf",
+    "aliases",
+    "taxonomies",
+    "cascade",
+    "extra",
+];
+
+#[derive(Error, Debug)]
+pub enum ContentDocumentError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("no front matter found")]
+    MissingFrontMatter,
+
+    #[error("failed to parse front matter: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("failed to serialize front matter: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// A content file whose front matter can be rewritten programmatically.
+///
+/// Everything after the front matter is preserved byte-for-byte, and the front
+/// matter is always written out with its keys in a normalized order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentDocument {
+    pub front_matter: toml::Table,
+    /// Everything after the closing `+++`.
+    body: String,
+}
+
+impl ContentDocument {
+    /// Returns a new [`ContentDocument`] with the given front matter and Markdown content.
+    pub fn new(front_matter: toml::Table, content: &str) -> Self {
+        Self {
+            front_matter,
+            body: format!("\n{content}"),
+        }
+    }
+
+    /// Parses a [`ContentDocument`] from the given text.
+    pub fn parse(text: &str) -> Result<Self, ContentDocumentError> {
+        let captures = toml_regex()
+            .captures(text)
+            .ok_or(ContentDocumentError::MissingFrontMatter)?;
+        let front_matter = captures.get(1).unwrap();
+
+        Ok(Self {
+            front_matter: toml::from_str(front_matter.as_str())?,
+            body: text[front_matter.end() + DELIMITER.len()..].to_string(),
+        })
+    }
+
+    /// Reads a [`ContentDocument`] from the file at the given path.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ContentDocumentError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Writes this [`ContentDocument`] to the file at the given path.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ContentDocumentError> {
+        fs::write(path, self.render()?)?;
+
+        Ok(())
+    }
+
+    /// Returns everything that follows the front matter.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Sets the value of the front matter field with the given key.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<toml::Value>) {
+        self.front_matter.insert(key.into(), value.into());
+    }
+
+    /// Removes the front matter field with the given key.
+    pub fn remove(&mut self, key: &str) -> Option<toml::Value> {
+        self.front_matter.remove(key)
+    }
+
+    /// Adds the given alias, if it isn't already present.
+    pub fn add_alias(&mut self, alias: &str) {
+        push_unique(
+            self.front_matter
+                .entry("aliases")
+                .or_insert_with(|| toml::Value::Array(Vec::new())),
+            alias,
+        );
+    }
+
+    /// Adds the given term to the given taxonomy, if it isn't already present.
+    pub fn add_taxonomy_term(&mut self, taxonomy: &str, term: &str) {
+        let taxonomies = self
+            .front_matter
+            .entry("taxonomies")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+
+        if let toml::Value::Table(taxonomies) = taxonomies {
+            push_unique(
+                taxonomies
+                    .entry(taxonomy)
+                    .or_insert_with(|| toml::Value::Array(Vec::new())),
+                term,
+            );
+        }
+    }
+
+    /// Renders this [`ContentDocument`] back to text.
+    pub fn render(&self) -> Result<String, ContentDocumentError> {
+        Ok(format!(
+            "{DELIMITER}\n{front_matter}{DELIMITER}{body}",
+            front_matter = toml::to_string(&self.normalized_front_matter())?,
+            body = self.body
+        ))
+    }
+
+    fn normalized_front_matter(&self) -> toml::Table {
+        let mut entries = self.front_matter.clone().into_iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, value)| {
+            (
+                value.is_table(),
+                KEY_ORDER
+                    .iter()
+                    .position(|known_key| *known_key == key.as_str())
+                    .unwrap_or(KEY_ORDER.len()),
+                key.clone(),
+            )
+        });
+
+        entries.into_iter().collect()
+    }
+}
+
+impl fmt::Display for ContentDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rendered = self.render().map_err(|_| fmt::Error)?;
+
+        write!(f, "{rendered}")
+    }
+}
+
+fn push_unique(value: &mut toml::Value, item: &str) {
+    let toml::Value::Array(items) = value else {
+        return;
+    };
+
+    if !items.iter().any(|existing| existing.as_str() == Some(item)) {
+        items.push(toml::Value::String(item.to_owned()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_rewrite_preserves_content() {
+        let text = indoc! {r#"
+            +++
+            draft = false
+            title = "Hello, world!"
+
+            [extra]
+            mood = "happy"
+            +++
+
+            This is the *content*.
+            +++
+        "#};
+
+        let mut document = ContentDocument::parse(text).unwrap();
+        document.set("updated", "2024-01-02");
+        document.add_taxonomy_term("tags", "rust");
+        document.add_taxonomy_term("tags", "rust");
+        document.add_alias("/old-hello-world/");
+
+        assert_eq!(
+            document.render().unwrap(),
+            indoc! {r#"
+                +++
+                title = "Hello, world!"
+                updated = "2024-01-02"
+                draft = false
+                aliases = ["/old-hello-world/"]
+
+                [taxonomies]
+                tags = ["rust"]
+
+                [extra]
+                mood = "happy"
+                +++
+
+                This is the *content*.
+                +++
+            "#}
+        );
+    }
+}
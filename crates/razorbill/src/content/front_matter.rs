@@ -2,16 +2,35 @@ use std::sync::OnceLock;
 
 use regex::Regex;
 use serde::{Deserialize, Deserializer};
+use thiserror::Error;
 
 static TOML_REGEX: OnceLock<Regex> = OnceLock::new();
 
-fn toml_regex() -> &'static Regex {
+pub(crate) fn toml_regex() -> &'static Regex {
     &TOML_REGEX.get_or_init(|| {
         let pattern = r"^[[:space:]]*\+\+\+(\r?\n(?s).*?(?-s))\+\+\+[[:space:]]*(?:$|(?:\r?\n((?s).*(?-s))$))";
         Regex::new(pattern).expect("failed to compile regex for TOML front matter")
     })
 }
 
+/// An error encountered while parsing front matter.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum FrontMatterError {
+    #[error("missing front matter")]
+    Missing,
+
+    /// The front matter is malformed, or doesn't match what is expected (e.g.,
+    /// a field has the wrong type).
+    #[error("{message} (line {line}, column {column})")]
+    Invalid {
+        message: String,
+        /// The line in the file where the problem is, starting from 1.
+        line: usize,
+        /// The column in the line where the problem is, starting from 1.
+        column: usize,
+    },
+}
+
 #[derive(Debug)]
 pub struct RawTomlFrontMatter<'a>(&'a str);
 
@@ -24,20 +43,43 @@ impl RawTomlFrontMatter<'_> {
     }
 }
 
-pub fn parse_front_matter<'a, T>(content: &'a str) -> Option<(T, &'a str)>
+pub fn parse_front_matter<'a, T>(content: &'a str) -> Result<(T, &'a str), FrontMatterError>
 where
     T: serde::de::DeserializeOwned,
 {
-    if let Some(captures) = toml_regex().captures(content) {
-        let front_matter = RawTomlFrontMatter(captures.get(1).unwrap().as_str());
-        let content = captures.get(2).map_or("", |m| m.as_str());
+    let captures = toml_regex()
+        .captures(content)
+        .ok_or(FrontMatterError::Missing)?;
 
-        let front_matter: T = front_matter.deserialize().unwrap();
+    let raw_front_matter = captures.get(1).unwrap();
+    let front_matter = RawTomlFrontMatter(raw_front_matter.as_str());
+    let body = captures.get(2).map_or("", |m| m.as_str());
 
-        Some((front_matter, content))
-    } else {
-        None
-    }
+    let front_matter: T = front_matter.deserialize().map_err(|err| {
+        // The span is relative to the front matter, so we offset it to point
+        // into the whole file.
+        let offset = raw_front_matter.start() + err.span().map_or(0, |span| span.start);
+        let (line, column) = line_and_column(content, offset);
+
+        FrontMatterError::Invalid {
+            message: err.message().trim().to_string(),
+            line,
+            column,
+        }
+    })?;
+
+    Ok((front_matter, body))
+}
+
+/// Returns the line and column (both starting from 1) of the given byte offset
+/// in the text.
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let preceding = &text[..offset.min(text.len())];
+    let line = preceding.matches('\n').count() + 1;
+    let line_start = preceding.rfind('\n').map_or(0, |index| index + 1);
+    let column = preceding[line_start..].chars().count() + 1;
+
+    (line, column)
 }
 
 pub fn from_toml_datetime<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
@@ -62,3 +104,58 @@ where
         },
     }
 }
+
+/// Merges the values from `defaults` into `table`, recursing into nested tables.
+pub(crate) fn merge_tables(table: &mut toml::Table, defaults: &toml::Table) {
+    for (key, default) in defaults {
+        match (table.get_mut(key), default) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(defaults)) => {
+                merge_tables(table, defaults)
+            }
+            (Some(_), _) => {}
+            (None, default) => {
+                table.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct TestFrontMatter {
+        #[allow(dead_code)]
+        title: String,
+    }
+
+    #[test]
+    fn test_parse_front_matter_errors() {
+        let error = parse_front_matter::<TestFrontMatter>("Hello, world!").unwrap_err();
+        assert_eq!(error, FrontMatterError::Missing);
+
+        let text = indoc! {"
+            +++
+            title = 42
+            +++
+
+            Hello, world!
+        "};
+        let error = parse_front_matter::<TestFrontMatter>(text).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                FrontMatterError::Invalid {
+                    line: 2,
+                    column: 9,
+                    ..
+                }
+            ),
+            "unexpected error: {error:?}"
+        );
+    }
+}
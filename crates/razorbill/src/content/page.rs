@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
 use std::{fmt, fs};
 
 use auk::Element;
@@ -8,8 +10,11 @@ use serde::Deserialize;
 use thiserror::Error;
 
 use crate::content::{
-    from_toml_datetime, parse_front_matter, FileInfo, ReadTime, ReadingMetrics, WordCount,
+    from_toml_datetime, parse_front_matter, parse_segments, ContentAsset, FileInfo,
+    FrontMatterError, ParseContentPathError, ReadTime, ReadingMetrics, WordCount,
 };
+use crate::date::parse_datetime;
+use crate::markdown::Heading;
 use crate::permalink::Permalink;
 use crate::SiteConfig;
 
@@ -20,15 +25,34 @@ pub struct Page {
     pub path: PagePath,
     pub permalink: Permalink,
     pub ancestors: Vec<PathBuf>,
+    /// The page before this one in its parent section's sort order.
+    pub previous: Option<PathBuf>,
+    /// The page after this one in its parent section's sort order.
+    pub next: Option<PathBuf>,
     pub slug: String,
     pub raw_content: String,
-    pub content: Vec<Element>,
+    /// The number of lines in the file before the `raw_content` (i.e., the
+    /// front matter), for mapping lines in the content to lines in the file.
+    pub content_line_offset: usize,
+    /// The rendered content, shared with the render context so that templates
+    /// can hold onto it without copying the whole element tree.
+    pub content: Arc<Vec<Element>>,
     pub table_of_contents: TableOfContents,
+    /// The headings in the rendered content, nested by level.
+    pub headings: Vec<Heading>,
     pub word_count: WordCount,
     pub read_time: ReadTime,
+    /// The URL of the page's generated social card image, if social cards are enabled.
+    pub social_image: Option<Permalink>,
+    /// The URL of the page's PDF, if it is exported to PDF.
+    pub pdf_url: Option<Permalink>,
+    /// The files in the page's bundle that are passed through to the output.
+    pub assets: Vec<ContentAsset>,
 }
 
-#[derive(Debug)]
+/// The path that identifies a page, relative to the content directory and
+/// without an extension (e.g., `/blog/hello-world`).
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct PagePath(pub(crate) String);
 
 impl fmt::Display for PagePath {
@@ -37,7 +61,24 @@ impl fmt::Display for PagePath {
     }
 }
 
+impl FromStr for PagePath {
+    type Err = ParseContentPathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let segments = parse_segments(path)?;
+        if segments.last() == Some(&"_index") {
+            return Err(ParseContentPathError::NotAPage(path.to_string()));
+        }
+
+        Ok(Self(path.to_string()))
+    }
+}
+
 impl PagePath {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
     pub fn from_file_path(
         root_path: impl AsRef<Path>,
         file_path: impl AsRef<Path>,
@@ -58,18 +99,40 @@ impl PagePath {
 #[derive(Debug, Default, Deserialize)]
 pub struct PageFrontMatter {
     pub title: Option<String>,
+    /// A short summary of the page, for use in feeds and `<meta>` tags.
+    pub description: Option<String>,
     pub slug: Option<String>,
+    /// The language the page is written in (e.g., `fr`).
+    ///
+    /// Defaults to the language of the nearest section that has one, and
+    /// then to the site's default language.
+    pub lang: Option<String>,
     #[serde(default, deserialize_with = "from_toml_datetime")]
     pub date: Option<String>,
     #[serde(default, deserialize_with = "from_toml_datetime")]
     pub updated: Option<String>,
+    /// The weight of the page, for use when sorting by weight.
+    pub weight: Option<usize>,
     #[serde(default)]
     pub draft: bool,
     #[serde(default)]
     pub aliases: Vec<String>,
     pub template: Option<String>,
+    /// The name of the series the page belongs to.
+    pub series: Option<String>,
+    /// The IDs of the authors of the page.
+    #[serde(default)]
+    pub authors: Vec<String>,
     #[serde(default)]
     pub taxonomies: HashMap<String, Vec<String>>,
+    /// Whether the page should be exported to PDF.
+    #[serde(default)]
+    pub pdf: bool,
+    /// Whether the page should be kept out of search engines.
+    ///
+    /// Pages with `no_index` are left out of the sitemap and feeds.
+    #[serde(default)]
+    pub no_index: bool,
 
     #[serde(default)]
     pub extra: toml::Table,
@@ -83,8 +146,14 @@ pub enum ParsePageError {
         filepath: PathBuf,
     },
 
-    #[error("invalid front matter in '{filepath}'")]
-    InvalidFrontMatter { filepath: PathBuf },
+    #[error("invalid front matter in '{filepath}': {err}")]
+    InvalidFrontMatter {
+        err: FrontMatterError,
+        filepath: PathBuf,
+    },
+
+    #[error("invalid date '{date}' in '{filepath}'")]
+    InvalidDate { date: String, filepath: PathBuf },
 }
 
 impl Page {
@@ -110,12 +179,23 @@ impl Page {
     ) -> Result<Self, ParsePageError> {
         let root_path = root_path.as_ref();
         let (front_matter, content) =
-            parse_front_matter::<PageFrontMatter>(text).ok_or_else(|| {
+            parse_front_matter::<PageFrontMatter>(text).map_err(|err| {
                 ParsePageError::InvalidFrontMatter {
+                    err,
                     filepath: filepath.to_owned(),
                 }
             })?;
 
+        let dates = [&front_matter.date, &front_matter.updated];
+        for date in dates.into_iter().flatten() {
+            if parse_datetime(date, config.timezone).is_none() {
+                return Err(ParsePageError::InvalidDate {
+                    date: date.clone(),
+                    filepath: filepath.to_owned(),
+                });
+            }
+        }
+
         let file = FileInfo::new(root_path, filepath);
         let slug = front_matter
             .slug
@@ -125,6 +205,9 @@ impl Page {
         let path = PagePath::from_file_path(root_path, &file.path).unwrap();
 
         let reading_metrics = ReadingMetrics::for_content(&content, config.reading_speed);
+        let content_line_offset = text
+            .strip_suffix(content)
+            .map_or(0, |front_matter| front_matter.matches('\n').count());
 
         Ok(Self {
             meta: front_matter,
@@ -132,12 +215,27 @@ impl Page {
             permalink: Permalink::from_path(config, path.0.as_str()),
             path,
             ancestors: Vec::new(),
+            previous: None,
+            next: None,
             slug,
             raw_content: content.to_string(),
-            content: Vec::new(),
+            content_line_offset,
+            content: Arc::default(),
             table_of_contents: TableOfContents::default(),
+            headings: Vec::new(),
             word_count: reading_metrics.word_count,
             read_time: reading_metrics.read_time,
+            social_image: None,
+            pdf_url: None,
+            assets: Vec::new(),
         })
     }
+
+    /// Returns the path to the directory that holds the assets co-located with
+    /// this page.
+    ///
+    /// For a page at `content/blog/hello-world.md` this is `content/blog/hello-world`.
+    pub fn bundle_path(&self) -> PathBuf {
+        self.file.path.with_extension("")
+    }
 }
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::content::Page;
+use crate::date::parse_datetime;
+
+/// A page that is dated in the future, and so isn't published until its date
+/// has passed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledPage {
+    /// The path to the page.
+    pub page: PathBuf,
+    /// When the page goes live.
+    pub publish_at: DateTime<Tz>,
+}
+
+/// Returns the [`ScheduledPage`] for the given page, if it is dated after `now`.
+pub(crate) fn scheduled_page(
+    page: &Page,
+    timezone: Tz,
+    now: DateTime<Utc>,
+) -> Option<ScheduledPage> {
+    let publish_at = parse_datetime(page.meta.date.as_deref()?, timezone)?;
+
+    (publish_at > now).then(|| ScheduledPage {
+        page: page.file.path.clone(),
+        publish_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::TimeZone;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::SiteConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_scheduled_page() {
+        let config = SiteConfig::for_tests("https://example.com");
+
+        let parse_page = |date: &str| {
+            let text = format!("+++\ntitle = \"Upcoming\"\ndate = {date}\n+++\n");
+            Page::parse(&config, &text, "content", Path::new("content/upcoming.md")).unwrap()
+        };
+
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let new_york = chrono_tz::America::New_York;
+
+        assert_eq!(
+            scheduled_page(&parse_page("2024-03-02"), new_york, now),
+            Some(ScheduledPage {
+                page: PathBuf::from("content/upcoming.md"),
+                publish_at: new_york.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap(),
+            })
+        );
+        assert_eq!(
+            scheduled_page(&parse_page("2024-03-01T11:00:00Z"), new_york, now),
+            None
+        );
+        assert_eq!(
+            scheduled_page(&parse_page("2024-02-29"), new_york, now),
+            None
+        );
+
+        let undated = Page::parse(
+            &config,
+            indoc! {r#"
+                +++
+                title = "Undated"
+                +++
+            "#},
+            "content",
+            Path::new("content/undated.md"),
+        )
+        .unwrap();
+        assert_eq!(scheduled_page(&undated, new_york, now), None);
+    }
+}
@@ -1,4 +1,7 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
 use std::{fmt, fs, io};
 
 use auk::Element;
@@ -7,8 +10,10 @@ use serde::Deserialize;
 use thiserror::Error;
 
 use crate::content::{
-    parse_front_matter, FileInfo, MaybeSortBy, ReadTime, ReadingMetrics, WordCount,
+    parse_front_matter, parse_segments, FileInfo, FrontMatterError, MaybeSortBy,
+    ParseContentPathError, ReadTime, ReadingMetrics, SortOrder, WordCount,
 };
+use crate::markdown::Heading;
 use crate::permalink::Permalink;
 use crate::SiteConfig;
 
@@ -19,14 +24,29 @@ pub struct Section {
     pub path: SectionPath,
     pub permalink: Permalink,
     pub raw_content: String,
-    pub content: Vec<Element>,
+    /// The rendered content, shared with the render context so that templates
+    /// can hold onto it without copying the whole element tree.
+    pub content: Arc<Vec<Element>>,
     pub table_of_contents: TableOfContents,
+    /// The headings in the rendered content, nested by level.
+    pub headings: Vec<Heading>,
     pub word_count: WordCount,
     pub read_time: ReadTime,
     pub pages: Vec<PathBuf>,
+    /// The sections containing this one, ordered from the root section down.
+    pub ancestors: Vec<PathBuf>,
+    /// The sections nested directly beneath this one, ordered by weight.
+    pub subsections: Vec<PathBuf>,
+    /// The pages in this section and all of the sections nested beneath it.
+    ///
+    /// The section's own pages come first, followed by those of each of its
+    /// subsections in turn.
+    pub all_pages: Vec<PathBuf>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// The path that identifies a section, relative to the content directory and
+/// without an extension (e.g., `/blog/_index`, or `/_index` for the root section).
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct SectionPath(pub(crate) String);
 
 impl fmt::Display for SectionPath {
@@ -35,7 +55,24 @@ impl fmt::Display for SectionPath {
     }
 }
 
+impl FromStr for SectionPath {
+    type Err = ParseContentPathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let segments = parse_segments(path)?;
+        if segments.last() != Some(&"_index") {
+            return Err(ParseContentPathError::NotASection(path.to_string()));
+        }
+
+        Ok(Self(path.to_string()))
+    }
+}
+
 impl SectionPath {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
     pub fn from_file_path(
         root_path: impl AsRef<Path>,
         file_path: impl AsRef<Path>,
@@ -56,21 +93,63 @@ impl SectionPath {
 #[derive(Debug, Default, Deserialize)]
 pub struct SectionFrontMatter {
     pub title: Option<String>,
+    /// A short summary of the section, for use in feeds and `<meta>` tags.
+    pub description: Option<String>,
     pub template: Option<String>,
     pub page_template: Option<String>,
 
+    /// The language the section and its pages are written in (e.g., `fr`).
+    pub lang: Option<String>,
+
+    /// The weight of the section, for ordering it amongst its sibling sections.
+    pub weight: Option<usize>,
+
     #[serde(default)]
     pub sort_by: MaybeSortBy,
 
+    /// The order to sort pages in.
+    ///
+    /// Defaults to the natural order for the `sort_by` strategy.
+    pub sort_order: Option<SortOrder>,
+
     #[serde(default)]
     pub transparent: bool,
 
+    /// Whether an Atom feed should be generated for the section's pages.
+    #[serde(default)]
+    pub generate_feed: bool,
+
     #[serde(default)]
     pub draft: bool,
 
     #[serde(default)]
     pub aliases: Vec<String>,
 
+    /// Whether the section should be kept out of search engines.
+    ///
+    /// Sections with `no_index` are left out of the sitemap. This doesn't
+    /// apply to the pages within the section.
+    #[serde(default)]
+    pub no_index: bool,
+
+    /// The front matter that is applied to all of the pages within the section.
+    #[serde(default)]
+    pub cascade: CascadeFrontMatter,
+
+    #[serde(default)]
+    pub extra: toml::Table,
+}
+
+/// Front matter that cascades from a section to the pages within it.
+///
+/// Values set on a page take precedence over cascaded ones, and values cascaded
+/// from nearer sections take precedence over those from further up.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct CascadeFrontMatter {
+    pub template: Option<String>,
+    /// The taxonomy terms added to each page.
+    #[serde(default)]
+    pub taxonomies: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub extra: toml::Table,
 }
@@ -83,8 +162,11 @@ pub enum ParseSectionError {
         index_path: PathBuf,
     },
 
-    #[error("invalid front matter in '{filepath}'")]
-    InvalidFrontMatter { filepath: PathBuf },
+    #[error("invalid front matter in '{filepath}': {err}")]
+    InvalidFrontMatter {
+        err: FrontMatterError,
+        filepath: PathBuf,
+    },
 }
 
 impl Section {
@@ -120,8 +202,9 @@ impl Section {
     ) -> Result<Self, ParseSectionError> {
         let root_path = root_path.as_ref();
         let (front_matter, content) =
-            parse_front_matter::<SectionFrontMatter>(text).ok_or_else(|| {
+            parse_front_matter::<SectionFrontMatter>(text).map_err(|err| {
                 ParseSectionError::InvalidFrontMatter {
+                    err,
                     filepath: filepath.to_owned(),
                 }
             })?;
@@ -137,11 +220,59 @@ impl Section {
             permalink: Permalink::from_path(config, path.0.as_str()),
             path,
             raw_content: content.to_string(),
-            content: Vec::new(),
+            content: Arc::default(),
             table_of_contents: TableOfContents::default(),
+            headings: Vec::new(),
             word_count: reading_metrics.word_count,
             read_time: reading_metrics.read_time,
             pages: Vec::new(),
+            ancestors: Vec::new(),
+            subsections: Vec::new(),
+            all_pages: Vec::new(),
         })
     }
+
+    /// Returns an implicit section for the given directory, which doesn't have
+    /// an `_index.md` of its own.
+    ///
+    /// The section has no content, and is titled after the directory.
+    pub fn implicit(
+        config: &SiteConfig,
+        root_path: impl AsRef<Path>,
+        path: impl AsRef<Path>,
+    ) -> Self {
+        let root_path = root_path.as_ref();
+        let path = path.as_ref();
+
+        let file = FileInfo::new(root_path, path.join("_index.md"));
+        let section_path = SectionPath::from_file_path(root_path, &file.path).unwrap();
+
+        Self {
+            meta: SectionFrontMatter {
+                title: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                ..Default::default()
+            },
+            file,
+            permalink: Permalink::from_path(config, section_path.0.as_str()),
+            path: section_path,
+            raw_content: String::new(),
+            content: Arc::default(),
+            table_of_contents: TableOfContents::default(),
+            headings: Vec::new(),
+            word_count: WordCount(0),
+            read_time: ReadTime(0),
+            pages: Vec::new(),
+            ancestors: Vec::new(),
+            subsections: Vec::new(),
+            all_pages: Vec::new(),
+        }
+    }
+
+    /// Returns the path to the directory that holds the assets co-located with
+    /// this section.
+    pub fn bundle_path(&self) -> PathBuf {
+        self.file.parent.clone()
+    }
 }
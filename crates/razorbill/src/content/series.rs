@@ -0,0 +1,92 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::content::{Page, Pages};
+
+/// Groups the pages into series, based on the `series` in their front matter.
+///
+/// The pages in each series are ordered by weight, falling back to their date
+/// (oldest first) so that the series reads in the order it was written.
+pub fn group_series(pages: &Pages) -> HashMap<String, Vec<PathBuf>> {
+    let mut series: HashMap<String, Vec<&Page>> = HashMap::new();
+
+    for page in pages.values() {
+        if let Some(name) = page.meta.series.as_ref() {
+            series.entry(name.clone()).or_default().push(page);
+        }
+    }
+
+    series
+        .into_iter()
+        .map(|(name, mut pages)| {
+            pages.sort_by(|a, b| compare_series_entries(a, b));
+
+            let pages = pages
+                .into_iter()
+                .map(|page| page.file.path.clone())
+                .collect();
+
+            (name, pages)
+        })
+        .collect()
+}
+
+fn compare_series_entries(a: &Page, b: &Page) -> Ordering {
+    let by_weight = match (a.meta.weight, b.meta.weight) {
+        (Some(a_weight), Some(b_weight)) => a_weight.cmp(&b_weight),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+
+    by_weight
+        .then_with(|| a.meta.date.cmp(&b.meta.date))
+        .then_with(|| a.path.0.cmp(&b.path.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::content::PageFrontMatter;
+    use crate::test_site::make_page;
+
+    use super::*;
+
+    fn page_in_series(filepath: &str, series: Option<&str>, date: &str) -> Page {
+        make_page(
+            filepath,
+            PageFrontMatter {
+                date: Some(date.to_string()),
+                series: series.map(ToString::to_string),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_group_series() {
+        let mut pages = Pages::default();
+        for page in [
+            page_in_series("content/rust-2.md", Some("rust"), "2024-02-01"),
+            page_in_series("content/rust-1.md", Some("rust"), "2024-01-01"),
+            page_in_series("content/standalone.md", None, "2024-01-15"),
+            page_in_series("content/rust-3.md", Some("rust"), "2024-03-01"),
+        ] {
+            pages.insert(page.file.path.clone(), page);
+        }
+
+        let series = group_series(&pages);
+
+        assert_eq!(series.len(), 1);
+        assert_eq!(
+            series["rust"],
+            vec![
+                PathBuf::from("content/rust-1.md"),
+                PathBuf::from("content/rust-2.md"),
+                PathBuf::from("content/rust-3.md"),
+            ]
+        );
+    }
+}
@@ -3,13 +3,53 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use crate::content::Page;
+use crate::content::{Page, Section};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SortBy {
-    /// Sort by date, in descending order (newest to oldest).
+    /// Sort by date.
+    ///
+    /// Sorted in descending order (newest to oldest) by default.
     Date,
+
+    /// Sort by the date the content was last updated, falling back to the date
+    /// for content that has never been updated.
+    ///
+    /// Sorted in descending order (most recently updated first) by default.
+    UpdateDate,
+
+    /// Sort by title.
+    ///
+    /// Sorted in ascending order by default.
+    Title,
+
+    /// Sort by weight.
+    ///
+    /// Sorted in ascending order (lightest to heaviest) by default.
+    Weight,
+
+    /// Sort by permalink.
+    ///
+    /// Sorted in ascending order by default.
+    Permalink,
+}
+
+impl SortBy {
+    /// Returns the [`SortOrder`] used for this [`SortBy`] when one is not specified.
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            Self::Date | Self::UpdateDate => SortOrder::Descending,
+            Self::Title | Self::Weight | Self::Permalink => SortOrder::Ascending,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Ascending,
+    Descending,
 }
 
 #[derive(
@@ -33,10 +73,31 @@ impl From<MaybeSortBy> for Option<SortBy> {
     }
 }
 
-pub fn sort_pages_by(sort_by: SortBy, pages: Vec<&Page>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+/// Compares two sections by their weight, in ascending order.
+///
+/// Sections without a weight are ordered after those with one.
+pub fn compare_sections_by_weight(a: &Section, b: &Section) -> Ordering {
+    match (a.meta.weight, b.meta.weight) {
+        (Some(a_weight), Some(b_weight)) => a_weight.cmp(&b_weight),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+    .then_with(|| a.path.0.cmp(&b.path.0))
+}
+
+pub fn sort_pages_by(
+    sort_by: SortBy,
+    sort_order: SortOrder,
+    pages: Vec<&Page>,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
     let (mut sortable, not_sortable): (Vec<&Page>, Vec<_>) =
         pages.iter().partition(|page| match sort_by {
             SortBy::Date => page.meta.date.is_some(),
+            SortBy::UpdateDate => page.meta.updated.is_some() || page.meta.date.is_some(),
+            SortBy::Title => page.meta.title.is_some(),
+            SortBy::Weight => page.meta.weight.is_some(),
+            SortBy::Permalink => true,
         });
 
     sortable.sort_unstable_by(|a, b| {
@@ -45,8 +106,27 @@ pub fn sort_pages_by(sort_by: SortBy, pages: Vec<&Page>) -> (Vec<PathBuf>, Vec<P
                 let a_date = a.meta.date.as_ref().unwrap();
                 let b_date = b.meta.date.as_ref().unwrap();
 
-                b_date.cmp(&a_date)
+                a_date.cmp(&b_date)
+            }
+            SortBy::UpdateDate => {
+                let a_date = a.meta.updated.as_ref().or(a.meta.date.as_ref()).unwrap();
+                let b_date = b.meta.updated.as_ref().or(b.meta.date.as_ref()).unwrap();
+
+                a_date.cmp(&b_date)
+            }
+            SortBy::Title => {
+                let a_title = a.meta.title.as_ref().unwrap().to_lowercase();
+                let b_title = b.meta.title.as_ref().unwrap().to_lowercase();
+
+                a_title.cmp(&b_title)
             }
+            SortBy::Weight => a.meta.weight.unwrap().cmp(&b.meta.weight.unwrap()),
+            SortBy::Permalink => a.permalink.cmp(&b.permalink),
+        };
+
+        let ord = match sort_order {
+            SortOrder::Ascending => ord,
+            SortOrder::Descending => ord.reverse(),
         };
 
         match ord {
@@ -63,3 +143,164 @@ pub fn sort_pages_by(sort_by: SortBy, pages: Vec<&Page>) -> (Vec<PathBuf>, Vec<P
             .collect(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::content::PageFrontMatter;
+    use crate::test_site::make_page;
+
+    use super::*;
+
+    fn sort(sort_by: SortBy, sort_order: SortOrder, pages: &[Page]) -> (Vec<&str>, Vec<&str>) {
+        let (sorted, unsorted) = sort_pages_by(sort_by, sort_order, pages.iter().collect());
+        let file_stem = |path: &PathBuf| {
+            pages
+                .iter()
+                .find(|page| page.file.path == *path)
+                .and_then(|page| page.file.path.file_stem())
+                .and_then(|stem| stem.to_str())
+                .unwrap()
+        };
+
+        (
+            sorted.iter().map(file_stem).collect(),
+            unsorted.iter().map(file_stem).collect(),
+        )
+    }
+
+    fn titled(filepath: &str, title: Option<&str>) -> Page {
+        make_page(
+            filepath,
+            PageFrontMatter {
+                title: title.map(ToString::to_string),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn dated(filepath: &str, date: Option<&str>, updated: Option<&str>) -> Page {
+        make_page(
+            filepath,
+            PageFrontMatter {
+                date: date.map(ToString::to_string),
+                updated: updated.map(ToString::to_string),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_sort_pages_by_date() {
+        let pages = [
+            dated("content/b.md", Some("2024-01-01"), None),
+            dated("content/undated.md", None, Some("2024-06-01")),
+            dated("content/c.md", Some("2024-03-01"), None),
+            dated("content/a.md", Some("2024-01-01"), None),
+        ];
+
+        // Pages with the same date are ordered by path.
+        assert_eq!(
+            sort(SortBy::Date, SortBy::Date.default_order(), &pages),
+            (vec!["c", "a", "b"], vec!["undated"])
+        );
+        assert_eq!(
+            sort(SortBy::Date, SortOrder::Ascending, &pages),
+            (vec!["a", "b", "c"], vec!["undated"])
+        );
+    }
+
+    #[test]
+    fn test_sort_pages_by_update_date() {
+        let pages = [
+            dated("content/a.md", Some("2024-01-01"), Some("2024-05-01")),
+            dated("content/b.md", Some("2024-03-01"), None),
+            dated("content/c.md", None, Some("2024-04-01")),
+            dated("content/undated.md", None, None),
+        ];
+
+        assert_eq!(
+            sort(
+                SortBy::UpdateDate,
+                SortBy::UpdateDate.default_order(),
+                &pages
+            ),
+            (vec!["a", "c", "b"], vec!["undated"])
+        );
+    }
+
+    #[test]
+    fn test_sort_pages_by_title() {
+        let pages = [
+            titled("content/b.md", Some("banana")),
+            titled("content/untitled.md", None),
+            titled("content/a.md", Some("Apple")),
+            titled("content/c.md", Some("cherry")),
+        ];
+
+        // Titles are compared case-insensitively.
+        assert_eq!(
+            sort(SortBy::Title, SortBy::Title.default_order(), &pages),
+            (vec!["a", "b", "c"], vec!["untitled"])
+        );
+        assert_eq!(
+            sort(SortBy::Title, SortOrder::Descending, &pages),
+            (vec!["c", "b", "a"], vec!["untitled"])
+        );
+    }
+
+    #[test]
+    fn test_sort_pages_by_weight() {
+        let weighted = |filepath: &str, weight: Option<usize>| {
+            make_page(
+                filepath,
+                PageFrontMatter {
+                    weight,
+                    ..Default::default()
+                },
+            )
+        };
+        let pages = [
+            weighted("content/heavy.md", Some(3)),
+            weighted("content/unweighted-1.md", None),
+            weighted("content/light.md", Some(1)),
+            weighted("content/unweighted-2.md", None),
+            weighted("content/medium.md", Some(2)),
+        ];
+
+        // Pages without a weight keep the order they were given in.
+        assert_eq!(
+            sort(SortBy::Weight, SortBy::Weight.default_order(), &pages),
+            (
+                vec!["light", "medium", "heavy"],
+                vec!["unweighted-1", "unweighted-2"]
+            )
+        );
+        assert_eq!(
+            sort(SortBy::Weight, SortOrder::Descending, &pages),
+            (
+                vec!["heavy", "medium", "light"],
+                vec!["unweighted-1", "unweighted-2"]
+            )
+        );
+    }
+
+    #[test]
+    fn test_sort_pages_by_permalink() {
+        let pages = [
+            titled("content/zebra.md", None),
+            titled("content/blog/post.md", None),
+            titled("content/about.md", None),
+        ];
+
+        assert_eq!(
+            sort(SortBy::Permalink, SortBy::Permalink.default_order(), &pages),
+            (vec!["about", "post", "zebra"], vec![])
+        );
+        assert_eq!(
+            sort(SortBy::Permalink, SortOrder::Descending, &pages),
+            (vec!["zebra", "post", "about"], vec![])
+        );
+    }
+}
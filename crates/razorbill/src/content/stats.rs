@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use chrono::Datelike;
+
+use crate::content::{Pages, ReadTime, Sections, WordCount};
+use crate::date::parse_date;
+
+/// Aggregate statistics about the content of a site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteStats {
+    /// The total number of pages.
+    pub page_count: usize,
+    /// The total number of words across all pages.
+    pub word_count: WordCount,
+    /// The total time it would take to read every page.
+    pub read_time: ReadTime,
+    /// The number of pages in each section, keyed by the path to the section.
+    pub pages_per_section: BTreeMap<PathBuf, usize>,
+    /// The number of pages with each taxonomy term, keyed by taxonomy and then by term.
+    pub pages_per_taxonomy_term: BTreeMap<String, BTreeMap<String, usize>>,
+    /// The number of dated pages published in each year.
+    pub pages_per_year: BTreeMap<i32, usize>,
+}
+
+impl Default for SiteStats {
+    fn default() -> Self {
+        Self {
+            page_count: 0,
+            word_count: WordCount(0),
+            read_time: ReadTime(0),
+            pages_per_section: BTreeMap::new(),
+            pages_per_taxonomy_term: BTreeMap::new(),
+            pages_per_year: BTreeMap::new(),
+        }
+    }
+}
+
+impl SiteStats {
+    /// Computes the statistics for the given content.
+    pub fn compute(
+        pages: &Pages,
+        sections: &Sections,
+        taxonomies: &HashMap<String, HashMap<String, Vec<PathBuf>>>,
+    ) -> Self {
+        let mut pages_per_year = BTreeMap::new();
+        for page in pages.values() {
+            let Some(date) = page.meta.date.as_deref().and_then(parse_date) else {
+                continue;
+            };
+
+            *pages_per_year.entry(date.year()).or_default() += 1;
+        }
+
+        Self {
+            page_count: pages.len(),
+            word_count: WordCount(pages.values().map(|page| page.word_count.0).sum()),
+            read_time: ReadTime(pages.values().map(|page| page.read_time.0).sum()),
+            pages_per_section: sections
+                .iter()
+                .map(|(path, section)| (path.clone(), section.pages.len()))
+                .collect(),
+            pages_per_taxonomy_term: taxonomies
+                .iter()
+                .map(|(taxonomy, pages_by_term)| {
+                    let pages_per_term = pages_by_term
+                        .iter()
+                        .map(|(term, pages)| (term.clone(), pages.len()))
+                        .collect();
+
+                    (taxonomy.clone(), pages_per_term)
+                })
+                .collect(),
+            pages_per_year,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::content::Taxonomy;
+    use crate::test_site::TestSite;
+
+    use super::*;
+
+    #[test]
+    fn test_site_stats() {
+        let test_site = TestSite::new("site_stats");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("blog/_index.md", "+++\n+++\n")
+            .write(
+                "blog/a.md",
+                "+++\ndate = 2023-05-01\n[taxonomies]\ntags = [\"rust\"]\n+++\n\nOne two three.\n",
+            )
+            .write(
+                "blog/b.md",
+                "+++\ndate = 2024-01-01\n[taxonomies]\ntags = [\"rust\", \"web\"]\n+++\n\nFour five six seven eight.\n",
+            )
+            .write("blog/c.md", "+++\n+++\n")
+            .write("notes/_index.md", "+++\n+++\n");
+
+        let mut site = test_site
+            .builder()
+            .add_taxonomy(Taxonomy::new("tags"), |_| auk::html(), |_| auk::html())
+            .build();
+        site.load().unwrap();
+
+        let content_path = test_site.content_path();
+        assert_eq!(
+            SiteStats::compute(&site.pages, &site.sections, &site.taxonomies),
+            SiteStats {
+                page_count: 3,
+                word_count: WordCount(8),
+                read_time: ReadTime(2),
+                pages_per_section: BTreeMap::from_iter([
+                    (content_path.join("_index.md"), 0),
+                    (content_path.join("blog/_index.md"), 3),
+                    (content_path.join("notes/_index.md"), 0),
+                ]),
+                pages_per_taxonomy_term: BTreeMap::from_iter([(
+                    "tags".to_string(),
+                    BTreeMap::from_iter([("rust".to_string(), 2), ("web".to_string(), 1)]),
+                )]),
+                pages_per_year: BTreeMap::from_iter([(2023, 1), (2024, 1)]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_site_stats_for_empty_site() {
+        assert_eq!(
+            SiteStats::compute(&Pages::default(), &Sections::default(), &HashMap::new()),
+            SiteStats::default()
+        );
+    }
+}
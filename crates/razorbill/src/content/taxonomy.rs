@@ -1,10 +1,129 @@
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::content::{parse_front_matter, Pages, ParseSectionError};
 use crate::permalink::Permalink;
 
+/// The name of the taxonomy that pages are added to via their `authors`.
+pub const AUTHORS_TAXONOMY: &str = "authors";
+
 #[derive(Debug, Clone)]
 pub struct Taxonomy {
     pub name: String,
+    /// The path that the taxonomy is rendered under (e.g., `topics` for
+    /// `/topics/{term}`).
+    ///
+    /// Defaults to the name of the taxonomy.
+    pub path: Option<String>,
+    /// Whether to render the page that lists all of the taxonomy's terms.
+    pub render_list: bool,
+    /// Whether to render a page (and feed) for each of the taxonomy's terms.
+    pub render_terms: bool,
+}
+
+impl Taxonomy {
+    /// Returns a new [`Taxonomy`] with the given name, which is rendered in full
+    /// under a path of the same name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: None,
+            render_list: true,
+            render_terms: true,
+        }
+    }
+
+    /// Returns the path that the taxonomy is rendered under, without any
+    /// leading or trailing slashes.
+    pub fn base_path(&self) -> &str {
+        self.path.as_deref().unwrap_or(&self.name).trim_matches('/')
+    }
+
+    /// Returns the permalink of the page that lists the taxonomy's terms.
+    pub(crate) fn permalink(&self, base_url: &str) -> Permalink {
+        Permalink::from_base_url(base_url, &format!("/{}", self.base_path()))
+    }
+
+    /// Returns the permalink of the page for the given term.
+    pub(crate) fn term_permalink(&self, base_url: &str, term: &str) -> Permalink {
+        Permalink::from_base_url(base_url, &format!("/{}/{term}", self.base_path()))
+    }
+}
+
+/// How taxonomy terms are normalized when grouping pages by term.
+///
+/// Terms that normalize to the same value are merged into a single term, which
+/// is displayed using the most common spelling (or the first one, in the case
+/// of a tie).
+///
+/// By default, terms are used exactly as written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TermNormalization {
+    trim: bool,
+    nfc: bool,
+    case_fold: bool,
+}
+
+impl TermNormalization {
+    /// Returns a [`TermNormalization`] that uses terms exactly as written.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a [`TermNormalization`] with all of the normalizations enabled.
+    pub fn all() -> Self {
+        Self {
+            trim: true,
+            nfc: true,
+            case_fold: true,
+        }
+    }
+
+    /// Sets whether leading and trailing whitespace is ignored.
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Sets whether terms are compared in Unicode Normalization Form C.
+    pub fn nfc(mut self, nfc: bool) -> Self {
+        self.nfc = nfc;
+        self
+    }
+
+    /// Sets whether terms are compared case-insensitively.
+    pub fn case_fold(mut self, case_fold: bool) -> Self {
+        self.case_fold = case_fold;
+        self
+    }
+
+    /// Returns the spelling of the given term, as it should be displayed.
+    pub(crate) fn spelling<'a>(&self, term: &'a str) -> &'a str {
+        if self.trim {
+            term.trim()
+        } else {
+            term
+        }
+    }
+
+    /// Returns the value used to determine whether two terms are the same.
+    pub(crate) fn normalize(&self, term: &str) -> String {
+        let term = self.spelling(term);
+        let term = if self.nfc {
+            term.nfc().collect::<String>()
+        } else {
+            term.to_owned()
+        };
+
+        if self.case_fold {
+            term.to_lowercase()
+        } else {
+            term
+        }
+    }
 }
 
 /// A taxonomy term.
@@ -14,3 +133,178 @@ pub struct TaxonomyTerm {
     pub permalink: Permalink,
     pub pages: Vec<PathBuf>,
 }
+
+/// A page that declares terms for a taxonomy that isn't registered with the site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTaxonomy {
+    /// The name of the unregistered taxonomy.
+    pub taxonomy: String,
+    /// The path to the page that declared terms for the taxonomy.
+    pub page: PathBuf,
+}
+
+/// Returns the usages of unregistered taxonomies across the given pages,
+/// ordered by page path.
+pub(crate) fn find_unknown_taxonomies(
+    pages: &Pages,
+    taxonomies: &[Taxonomy],
+) -> Vec<UnknownTaxonomy> {
+    let mut unknown_taxonomies = pages
+        .values()
+        .flat_map(|page| {
+            page.meta
+                .taxonomies
+                .keys()
+                .filter(|name| !taxonomies.iter().any(|taxonomy| &taxonomy.name == *name))
+                .map(|name| UnknownTaxonomy {
+                    taxonomy: name.clone(),
+                    page: page.file.path.clone(),
+                })
+        })
+        .collect::<Vec<_>>();
+
+    unknown_taxonomies.sort_by(|a, b| (&a.page, &a.taxonomy).cmp(&(&b.page, &b.taxonomy)));
+    unknown_taxonomies
+}
+
+/// The front matter for a taxonomy term, defined in either
+/// `content/{taxonomy}/{term}/_index.md` or `content/{taxonomy}/{term}.md`.
+#[derive(Debug, Default, Deserialize)]
+pub struct TaxonomyTermFrontMatter {
+    /// The name to display for the term (e.g., an author's full name).
+    pub title: Option<String>,
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub extra: toml::Table,
+}
+
+impl TaxonomyTermFrontMatter {
+    /// Reads the front matter for the taxonomy term in the given directory, if
+    /// it has an `_index.md`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Option<Self>, ParseSectionError> {
+        let index_path = path.as_ref().join("_index.md");
+        if !index_path.exists() {
+            return Ok(None);
+        }
+
+        Self::from_file(index_path).map(Some)
+    }
+
+    /// Reads the front matter for a taxonomy term from the given file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ParseSectionError> {
+        let index_path = path.as_ref().to_owned();
+        let contents = fs::read_to_string(&index_path).map_err(|err| ParseSectionError::Io {
+            err,
+            index_path: index_path.clone(),
+        })?;
+
+        let (front_matter, _content) = parse_front_matter::<Self>(&contents).map_err(|err| {
+            ParseSectionError::InvalidFrontMatter {
+                err,
+                filepath: index_path,
+            }
+        })?;
+
+        Ok(front_matter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::content::Page;
+    use crate::SiteConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_taxonomy_permalinks() {
+        let base_url = "https://example.com";
+
+        let tags = Taxonomy::new("tags");
+        assert_eq!(
+            tags.permalink(base_url).as_str(),
+            "https://example.com/tags/"
+        );
+        assert_eq!(
+            tags.term_permalink(base_url, "rust").as_str(),
+            "https://example.com/tags/rust/"
+        );
+
+        let topics = Taxonomy {
+            path: Some("/blog/topics/".to_string()),
+            ..Taxonomy::new("tags")
+        };
+        assert_eq!(topics.base_path(), "blog/topics");
+        assert_eq!(
+            topics.permalink(base_url).as_str(),
+            "https://example.com/blog/topics/"
+        );
+        assert_eq!(
+            topics.term_permalink(base_url, "rust").as_str(),
+            "https://example.com/blog/topics/rust/"
+        );
+    }
+
+    #[test]
+    fn test_find_unknown_taxonomies() {
+        let config = SiteConfig::for_tests("https://example.com");
+
+        let hello_world = indoc! {r#"
+            +++
+            title = "Hello, world"
+
+            [taxonomies]
+            tags = ["rust"]
+            categories = ["news"]
+            +++
+        "#};
+        let goodbye = indoc! {r#"
+            +++
+            title = "Goodbye"
+
+            [taxonomies]
+            tags = ["rust"]
+            +++
+        "#};
+
+        let mut pages = Pages::default();
+        for (filepath, text) in [
+            ("content/hello-world.md", hello_world),
+            ("content/goodbye.md", goodbye),
+        ] {
+            let page = Page::parse(&config, text, "content", Path::new(filepath)).unwrap();
+            pages.insert(page.file.path.clone(), page);
+        }
+
+        let taxonomies = vec![Taxonomy::new("tags")];
+
+        assert_eq!(
+            find_unknown_taxonomies(&pages, &taxonomies),
+            vec![UnknownTaxonomy {
+                taxonomy: "categories".to_string(),
+                page: PathBuf::from("content/hello-world.md"),
+            }]
+        );
+        assert_eq!(
+            find_unknown_taxonomies(&pages, &[]),
+            vec![
+                UnknownTaxonomy {
+                    taxonomy: "tags".to_string(),
+                    page: PathBuf::from("content/goodbye.md"),
+                },
+                UnknownTaxonomy {
+                    taxonomy: "categories".to_string(),
+                    page: PathBuf::from("content/hello-world.md"),
+                },
+                UnknownTaxonomy {
+                    taxonomy: "tags".to_string(),
+                    page: PathBuf::from("content/hello-world.md"),
+                },
+            ]
+        );
+    }
+}
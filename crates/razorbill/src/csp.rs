@@ -0,0 +1,408 @@
+//! Generating a [Content Security Policy](https://developer.mozilla.org/en-US/docs/Web/HTTP/CSP)
+//! for the rendered pages.
+//!
+//! Each rendered page is scanned for its inline scripts and styles, as well as
+//! the external origins it loads resources from, and a policy allowing exactly
+//! those is produced. The policy is then either embedded in the page itself or
+//! written out to a host-specific headers file.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::content::{Page, Section};
+use crate::permalink::Permalink;
+use crate::static_files::CopyMode;
+use crate::storage::{is_html, Store};
+
+/// Where the generated Content Security Policy is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CspOutput {
+    /// A `<meta http-equiv="Content-Security-Policy">` tag in each page's `<head>`.
+    MetaTag,
+    /// A `_headers` file, as used by Netlify and Cloudflare Pages.
+    HeadersFile,
+    /// A `vercel.json` file, as used by Vercel.
+    VercelJson,
+}
+
+/// The configuration for generating a Content Security Policy.
+#[derive(Debug, Clone)]
+pub struct ContentSecurityPolicy {
+    output: CspOutput,
+    /// Additional sources to allow, keyed by directive.
+    sources: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ContentSecurityPolicy {
+    pub fn new(output: CspOutput) -> Self {
+        Self {
+            output,
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Allows an additional source for the given directive (e.g., `img-src` and `data:`).
+    ///
+    /// This is useful for resources that can't be discovered from the rendered
+    /// pages, such as those loaded by scripts.
+    pub fn source(mut self, directive: impl Into<String>, source: impl Into<String>) -> Self {
+        self.sources
+            .entry(directive.into())
+            .or_default()
+            .insert(source.into());
+        self
+    }
+
+    /// Returns the policy for the given HTML document.
+    pub fn policy_for(&self, html: &str) -> String {
+        static SCRIPT: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?is)<script\b([^>]*)>(.*?)</script>").unwrap());
+        static STYLE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?is)<style\b[^>]*>(.*?)</style>").unwrap());
+        static TAG: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?i)<(link|img|iframe|audio|video|source)\b([^>]*)>").unwrap()
+        });
+        static STYLE_ATTR: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r#"(?i)<[a-z][^>]*\sstyle=["']"#).unwrap());
+
+        let mut sources = self.sources.clone();
+        let mut add_source = |directive: &str, source: String| {
+            sources
+                .entry(directive.to_owned())
+                .or_default()
+                .insert(source);
+        };
+
+        for script in SCRIPT.captures_iter(html) {
+            let (attrs, body) = (&script[1], &script[2]);
+
+            // Data blocks (e.g., JSON-LD) are never executed.
+            if attr(attrs, "type").is_some_and(|ty| ty.contains("json")) {
+                continue;
+            }
+
+            match attr(attrs, "src") {
+                Some(src) => {
+                    if let Some(origin) = external_origin(src) {
+                        add_source("script-src", origin);
+                    }
+                }
+                None if !body.trim().is_empty() => add_source("script-src", hash_source(body)),
+                None => {}
+            }
+        }
+
+        if STYLE_ATTR.is_match(html) {
+            // Hashes can't be used for `style` attributes without `'unsafe-hashes'`,
+            // which isn't widely supported.
+            add_source("style-src", "'unsafe-inline'".to_string());
+        } else {
+            for style in STYLE.captures_iter(html) {
+                add_source("style-src", hash_source(&style[1]));
+            }
+        }
+
+        for tag in TAG.captures_iter(html) {
+            let (tag_name, attrs) = (tag[1].to_ascii_lowercase(), &tag[2]);
+
+            let (directive, url) = match tag_name.as_str() {
+                "link" => {
+                    let is_stylesheet = attr(attrs, "rel").is_some_and(|rel| {
+                        rel.split_whitespace()
+                            .any(|rel| rel.eq_ignore_ascii_case("stylesheet"))
+                    });
+                    if !is_stylesheet {
+                        continue;
+                    }
+
+                    ("style-src", attr(attrs, "href"))
+                }
+                "img" => ("img-src", attr(attrs, "src")),
+                "iframe" => ("frame-src", attr(attrs, "src")),
+                _ => ("media-src", attr(attrs, "src")),
+            };
+
+            if let Some(origin) = url.and_then(external_origin) {
+                add_source(directive, origin);
+            }
+        }
+
+        let mut policy = vec!["default-src 'self'".to_string()];
+        for (directive, sources) in sources {
+            let sources = sources.into_iter().collect::<Vec<_>>().join(" ");
+
+            if directive == "default-src" {
+                policy[0] = format!("default-src 'self' {sources}");
+            } else {
+                policy.push(format!("{directive} 'self' {sources}"));
+            }
+        }
+
+        policy.join("; ")
+    }
+}
+
+/// A [`Store`] that applies a [`ContentSecurityPolicy`] to the HTML that passes through it.
+pub(crate) struct CspStorage<S> {
+    inner: S,
+    csp: Option<ContentSecurityPolicy>,
+    /// The policy for each page, keyed by its path.
+    policies: Mutex<BTreeMap<String, String>>,
+}
+
+impl<S: Store> CspStorage<S> {
+    pub fn new(inner: S, csp: Option<ContentSecurityPolicy>) -> Self {
+        Self {
+            inner,
+            csp,
+            policies: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the inner store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Writes out the headers file, if needed, and returns the inner store.
+    pub fn finish(self) -> Result<S, S::Error> {
+        let Some(csp) = self.csp else {
+            return Ok(self.inner);
+        };
+
+        let policies = self
+            .policies
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner());
+
+        match csp.output {
+            CspOutput::MetaTag => {}
+            CspOutput::HeadersFile => {
+                let headers = policies
+                    .iter()
+                    .map(|(path, policy)| format!("{path}\n  Content-Security-Policy: {policy}\n"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                self.inner
+                    .store_static_file(Path::new("_headers"), headers)?;
+            }
+            CspOutput::VercelJson => {
+                let headers = policies
+                    .iter()
+                    .map(|(path, policy)| {
+                        json!({
+                            "source": path,
+                            "headers": [{ "key": "Content-Security-Policy", "value": policy }],
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                let vercel_json = serde_json::to_string_pretty(&json!({ "headers": headers }))
+                    .map_err(io::Error::from)?;
+
+                self.inner
+                    .store_static_file(Path::new("vercel.json"), vercel_json)?;
+            }
+        }
+
+        Ok(self.inner)
+    }
+
+    fn apply(&self, permalink: &Permalink, html: String) -> String {
+        let Some(csp) = self.csp.as_ref() else {
+            return html;
+        };
+
+        let policy = csp.policy_for(&html);
+
+        match csp.output {
+            CspOutput::MetaTag => inject_meta_tag(html, &policy),
+            CspOutput::HeadersFile | CspOutput::VercelJson => {
+                self.policies
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .insert(permalink.path().to_owned(), policy);
+
+                html
+            }
+        }
+    }
+}
+
+impl<S: Store> Store for CspStorage<S> {
+    type Error = S::Error;
+
+    fn store_rendered_section(
+        &self,
+        section: &Section,
+        rendered_html: String,
+    ) -> Result<(), Self::Error> {
+        let rendered_html = self.apply(&section.permalink, rendered_html);
+
+        self.inner.store_rendered_section(section, rendered_html)
+    }
+
+    fn store_rendered_page(&self, page: &Page, rendered_html: String) -> Result<(), Self::Error> {
+        let rendered_html = self.apply(&page.permalink, rendered_html);
+
+        self.inner.store_rendered_page(page, rendered_html)
+    }
+
+    fn store_content(&self, permalink: Permalink, content: String) -> Result<(), Self::Error> {
+        let content = if is_html(&permalink) {
+            self.apply(&permalink, content)
+        } else {
+            content
+        };
+
+        self.inner.store_content(permalink, content)
+    }
+
+    fn store_content_streaming<F>(&self, permalink: Permalink, write: F) -> Result<(), Self::Error>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        self.inner.store_content_streaming(permalink, write)
+    }
+
+    fn store_static_file(&self, path: &Path, content: String) -> Result<(), Self::Error> {
+        self.inner.store_static_file(path, content)
+    }
+
+    fn store_binary_file(&self, path: &Path, content: Vec<u8>) -> Result<(), Self::Error> {
+        self.inner.store_binary_file(path, content)
+    }
+
+    fn copy_file(
+        &self,
+        source_path: &Path,
+        path: &Path,
+        mode: CopyMode,
+    ) -> Result<bool, Self::Error> {
+        self.inner.copy_file(source_path, path, mode)
+    }
+}
+
+/// Inserts a `<meta>` tag with the given policy at the start of the document's `<head>`,
+/// so that it applies to everything that follows.
+fn inject_meta_tag(mut html: String, policy: &str) -> String {
+    static HEAD: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<head\b[^>]*>").unwrap());
+
+    if let Some(head) = HEAD.find(&html) {
+        html.insert_str(
+            head.end(),
+            &format!(r#"<meta http-equiv="Content-Security-Policy" content="{policy}">"#),
+        );
+    }
+
+    html
+}
+
+/// Returns the value of the given attribute within a tag's attributes.
+fn attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!(
+        r#"(?i)(?:^|\s){}\s*=\s*["']([^"']*)["']"#,
+        regex::escape(name)
+    );
+
+    Regex::new(&pattern)
+        .ok()?
+        .captures(attrs)
+        .and_then(|captures| captures.get(1))
+        .map(|value| value.as_str())
+}
+
+/// Returns the origin of the given URL, if it points outside of the site.
+fn external_origin(url: &str) -> Option<String> {
+    let url = if url.starts_with("//") {
+        Url::parse(&format!("https:{url}")).ok()?
+    } else {
+        Url::parse(url).ok()?
+    };
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    Some(url.origin().ascii_serialization())
+}
+
+fn hash_source(content: &str) -> String {
+    format!("'sha256-{}'", base64(&Sha256::digest(content.as_bytes())))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - index * 6)) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_policy_for() {
+        let csp = ContentSecurityPolicy::new(CspOutput::MetaTag).source("img-src", "data:");
+
+        let html = r#"<html><head><link rel="stylesheet" href="https://fonts.googleapis.com/css?family=Inter"><script src="/app.js"></script><script type="application/ld+json">{}</script><script>alert(1)</script></head><body><img src="https://images.example.com/cat.png"><iframe src="//www.youtube.com/embed/123"></iframe></body></html>"#;
+
+        assert_eq!(
+            csp.policy_for(html),
+            format!(
+                "default-src 'self'; frame-src 'self' https://www.youtube.com; img-src 'self' data: https://images.example.com; script-src 'self' {}; style-src 'self' https://fonts.googleapis.com",
+                hash_source("alert(1)")
+            )
+        );
+    }
+
+    #[test]
+    fn test_inject_meta_tag() {
+        assert_eq!(
+            inject_meta_tag(
+                "<html><head><title>Hello</title></head></html>".to_string(),
+                "default-src 'self'"
+            ),
+            r#"<html><head><meta http-equiv="Content-Security-Policy" content="default-src 'self'"><title>Hello</title></head></html>"#
+        );
+    }
+}
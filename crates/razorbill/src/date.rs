@@ -1,19 +1,117 @@
-use chrono::{DateTime, NaiveDate};
+use std::fmt::Write;
+
+use chrono::{DateTime, Locale, NaiveDate};
 use chrono_tz::Tz;
 
-pub fn format_date(date: &str, format: &str, timezone: Tz) -> String {
-    let date = if date.contains("T") {
+/// The format used for dates when the site doesn't specify one.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Formats a date from front matter in the given timezone.
+///
+/// Returns `None` if the date is not valid, or if the format is invalid.
+pub fn format_date(date: &str, format: &str, timezone: Tz) -> Option<String> {
+    let date = parse_datetime(date, timezone)?;
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(format)).ok()?;
+
+    Some(formatted)
+}
+
+/// Formats a date from front matter in the given timezone, using the month and
+/// day names from the given locale.
+///
+/// Returns `None` if the date is not valid, or if the format is invalid.
+pub fn format_date_localized(
+    date: &str,
+    format: &str,
+    timezone: Tz,
+    locale: Locale,
+) -> Option<String> {
+    let date = parse_datetime(date, timezone)?;
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format_localized(format, locale)).ok()?;
+
+    Some(formatted)
+}
+
+/// Parses a date from front matter in the given timezone.
+///
+/// Dates without a time are taken to be at midnight in the timezone.
+///
+/// Returns `None` if the date is not valid.
+pub fn parse_datetime(date: &str, timezone: Tz) -> Option<DateTime<Tz>> {
+    if date.contains('T') {
         DateTime::parse_from_rfc3339(date)
-            .unwrap()
-            .with_timezone(&timezone)
+            .ok()
+            .map(|date| date.with_timezone(&timezone))
     } else {
-        NaiveDate::parse_from_str(&date, "%Y-%m-%d")
-            .unwrap()
-            .and_hms_opt(0, 0, 0)
-            .unwrap()
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
             .and_local_timezone(timezone)
-            .unwrap()
-    };
+            .earliest()
+    }
+}
+
+/// Parses the calendar date out of a date from front matter.
+///
+/// Returns `None` if the date is not valid.
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+    if date.contains('T') {
+        DateTime::parse_from_rfc3339(date)
+            .ok()
+            .map(|date| date.date_naive())
+    } else {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date("2024-01-31"),
+            NaiveDate::from_ymd_opt(2024, 1, 31)
+        );
+        assert_eq!(
+            parse_date("2024-01-31T23:00:00-05:00"),
+            NaiveDate::from_ymd_opt(2024, 1, 31)
+        );
+        assert_eq!(parse_date("January 31st"), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        let new_york = chrono_tz::America::New_York;
+
+        assert_eq!(
+            format_date("2024-01-31", "%+", new_york).as_deref(),
+            Some("2024-01-31T00:00:00-05:00")
+        );
+        assert_eq!(
+            format_date("2024-02-01T03:00:00Z", "%B %-d, %Y", new_york).as_deref(),
+            Some("January 31, 2024")
+        );
+        assert_eq!(format_date("2024-01-31", "%Q", Tz::UTC), None);
+        assert_eq!(format_date("January 31st", "%Y", Tz::UTC), None);
+    }
 
-    date.format(format).to_string()
+    #[test]
+    fn test_format_date_localized() {
+        assert_eq!(
+            format_date_localized("2024-01-31", "%A %-d %B %Y", Tz::UTC, Locale::fr_FR).as_deref(),
+            Some("mercredi 31 janvier 2024")
+        );
+        assert_eq!(
+            format_date_localized("2024-01-31", "%A, %B %-d", Tz::UTC, Locale::POSIX).as_deref(),
+            Some("Wednesday, January 31")
+        );
+    }
 }
@@ -0,0 +1,867 @@
+//! Deploying a built site.
+//!
+//! Deployers use the [`BuildManifest`] from the previous deployment to only
+//! upload the files that changed since then.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use thiserror::Error;
+
+use crate::manifest::{BuildManifest, BuildManifestEntry};
+use crate::Site;
+
+/// The path to the build manifest, relative to the output directory.
+const MANIFEST_PATH: &str = "manifest.json";
+
+#[derive(Error, Debug)]
+pub enum DeployError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("the site must be built before it can be deployed")]
+    NotBuilt,
+
+    #[error("failed to parse deployed manifest: {0}")]
+    ParseManifest(#[from] serde_json::Error),
+
+    #[error("`{command}` failed: {stderr}")]
+    CommandFailed { command: String, stderr: String },
+}
+
+/// The changes needed to bring a deployment up to date with a build.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DeployPlan {
+    /// The files that are new or changed since the previous deployment.
+    pub upload: Vec<BuildManifestEntry>,
+    /// The paths to the files from the previous deployment that are no longer
+    /// part of the build.
+    pub delete: Vec<String>,
+}
+
+impl DeployPlan {
+    /// Returns the plan for deploying the `current` build over the `deployed` one.
+    ///
+    /// When nothing has been deployed yet, every file is uploaded.
+    pub fn new(deployed: Option<&BuildManifest>, current: &BuildManifest) -> Self {
+        let deployed_hashes = deployed
+            .map(|deployed| {
+                deployed
+                    .files
+                    .iter()
+                    .map(|entry| (entry.path.as_str(), entry.hash.as_str()))
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default();
+
+        let upload = current
+            .files
+            .iter()
+            .filter(|entry| deployed_hashes.get(entry.path.as_str()) != Some(&entry.hash.as_str()))
+            .cloned()
+            .collect();
+
+        let delete = deployed
+            .map(|deployed| {
+                deployed
+                    .files
+                    .iter()
+                    .filter(|entry| current.get(&entry.path).is_none())
+                    .map(|entry| entry.path.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { upload, delete }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.upload.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// A destination that a built site can be deployed to.
+pub trait Deploy {
+    /// Returns the manifest of the currently deployed build, if there is one.
+    fn deployed_manifest(&self) -> Result<Option<BuildManifest>, DeployError>;
+
+    /// Applies the plan, using the files in the given output directory.
+    ///
+    /// Implementations should write the `manifest.json` from the output
+    /// directory last, so that it only reflects the deployment once it has
+    /// succeeded.
+    fn apply(&self, output_path: &Path, plan: &DeployPlan) -> Result<(), DeployError>;
+}
+
+impl Site {
+    /// Deploys the most recent build of the site using the given deployer,
+    /// returning the plan that was applied.
+    pub fn deploy(&self, deployer: &impl Deploy) -> Result<DeployPlan, DeployError> {
+        if self.build_manifest().files.is_empty() || !self.output_path.join(MANIFEST_PATH).exists()
+        {
+            return Err(DeployError::NotBuilt);
+        }
+
+        let deployed = deployer.deployed_manifest()?;
+        let plan = DeployPlan::new(deployed.as_ref(), self.build_manifest());
+
+        if !plan.is_empty() {
+            deployer.apply(&self.output_path, &plan)?;
+        }
+
+        Ok(plan)
+    }
+}
+
+/// Deploys to an S3-compatible bucket using the `aws` CLI.
+pub struct S3Deployer {
+    bucket: String,
+    prefix: String,
+    endpoint_url: Option<String>,
+}
+
+impl S3Deployer {
+    pub fn new(bucket: impl Into<String>) -> Self {
+        Self {
+            bucket: bucket.into(),
+            prefix: String::new(),
+            endpoint_url: None,
+        }
+    }
+
+    /// Sets the prefix that the site's files are stored under within the bucket.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// Sets the endpoint to use for S3-compatible services other than AWS.
+    pub fn with_endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(endpoint_url.into());
+        self
+    }
+
+    fn object_url(&self, path: &str) -> String {
+        if self.prefix.is_empty() {
+            format!("s3://{}/{path}", self.bucket)
+        } else {
+            format!("s3://{}/{}/{path}", self.bucket, self.prefix)
+        }
+    }
+
+    fn aws_s3(&self) -> Command {
+        let mut command = Command::new("aws");
+        command.arg("s3");
+
+        if let Some(endpoint_url) = self.endpoint_url.as_ref() {
+            command.args(["--endpoint-url", endpoint_url]);
+        }
+
+        command
+    }
+}
+
+/// Returns the manifest downloaded by `aws s3 cp`.
+///
+/// The manifest won't exist if the site hasn't been deployed before. Any other
+/// failure (e.g., missing credentials) is an error, as deploying without the
+/// previous manifest would upload every file and delete nothing.
+fn read_s3_manifest(output: Output) -> Result<Option<BuildManifest>, DeployError> {
+    if !output.status.success() && is_missing_object(&String::from_utf8_lossy(&output.stderr)) {
+        return Ok(None);
+    }
+
+    check_output("aws", &output)?;
+
+    Ok(Some(serde_json::from_slice(&output.stdout)?))
+}
+
+/// Returns whether the given error from the `aws` CLI is for an object that
+/// doesn't exist.
+fn is_missing_object(stderr: &str) -> bool {
+    // Downloads check that the object exists first, which fails with a bare
+    // `404` rather than `NoSuchKey`.
+    stderr.contains("NoSuchKey") || stderr.contains("(404)")
+}
+
+impl Deploy for S3Deployer {
+    fn deployed_manifest(&self) -> Result<Option<BuildManifest>, DeployError> {
+        let output = self
+            .aws_s3()
+            .args(["cp", &self.object_url(MANIFEST_PATH), "-"])
+            .output()?;
+
+        read_s3_manifest(output)
+    }
+
+    fn apply(&self, output_path: &Path, plan: &DeployPlan) -> Result<(), DeployError> {
+        for entry in &plan.upload {
+            let file_path = output_path.join(&entry.path);
+
+            run(self
+                .aws_s3()
+                .arg("cp")
+                .arg(file_path)
+                .arg(self.object_url(&entry.path)))?;
+        }
+
+        for path in &plan.delete {
+            run(self.aws_s3().args(["rm", &self.object_url(path)]))?;
+        }
+
+        run(self
+            .aws_s3()
+            .arg("cp")
+            .arg(output_path.join(MANIFEST_PATH))
+            .arg(self.object_url(MANIFEST_PATH)))?;
+
+        Ok(())
+    }
+}
+
+/// Deploys to a branch of a Git repository (e.g., `gh-pages` for GitHub Pages).
+pub struct GitBranchDeployer {
+    repo_path: PathBuf,
+    branch: String,
+    remote: Option<String>,
+    commit_message: String,
+}
+
+impl GitBranchDeployer {
+    pub fn new(repo_path: impl AsRef<Path>, branch: impl Into<String>) -> Self {
+        Self {
+            repo_path: repo_path.as_ref().to_owned(),
+            branch: branch.into(),
+            remote: None,
+            commit_message: "Deploy site".to_string(),
+        }
+    }
+
+    /// Sets the name of the remote (e.g., `origin`) to push the branch to after
+    /// committing the deployment.
+    ///
+    /// The branch is fetched from the remote before deploying, so that
+    /// deployments made from other clones are built upon.
+    pub fn with_remote(mut self, remote: impl Into<String>) -> Self {
+        self.remote = Some(remote.into());
+        self
+    }
+
+    pub fn with_commit_message(mut self, commit_message: impl Into<String>) -> Self {
+        self.commit_message = commit_message.into();
+        self
+    }
+
+    fn git(&self, dir: &Path) -> Command {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(dir);
+        command
+    }
+
+    fn branch_exists(&self) -> Result<bool, DeployError> {
+        self.object_exists(&format!("refs/heads/{}", self.branch))
+    }
+
+    fn object_exists(&self, object: &str) -> Result<bool, DeployError> {
+        let status = self
+            .git(&self.repo_path)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(object)
+            .stdout(Stdio::null())
+            .status()?;
+
+        Ok(status.success())
+    }
+
+    /// Returns the ref for the remote's copy of the branch.
+    fn remote_ref(&self, remote: &str) -> String {
+        format!("refs/remotes/{remote}/{}", self.branch)
+    }
+
+    /// Fetches the branch from the remote, if there is one, returning the ref
+    /// it was fetched to.
+    ///
+    /// Returns `None` when there is no remote, or the remote doesn't have the
+    /// branch yet.
+    fn fetch_remote_branch(&self) -> Result<Option<String>, DeployError> {
+        let Some(remote) = self.remote.as_ref() else {
+            return Ok(None);
+        };
+
+        let output = self
+            .git(&self.repo_path)
+            .args(["ls-remote", "--exit-code", "--heads", remote])
+            .arg(format!("refs/heads/{}", self.branch))
+            .output()?;
+
+        // `--exit-code` exits with `2` when the remote doesn't have the branch.
+        if output.status.code() == Some(2) {
+            return Ok(None);
+        }
+
+        check_output("git", &output)?;
+
+        let remote_ref = self.remote_ref(remote);
+        run(self
+            .git(&self.repo_path)
+            .args(["fetch", "--quiet", remote])
+            .arg(format!("+refs/heads/{}:{remote_ref}", self.branch)))?;
+
+        Ok(Some(remote_ref))
+    }
+
+    fn commit_worktree(
+        &self,
+        worktree_path: &Path,
+        output_path: &Path,
+        plan: &DeployPlan,
+    ) -> Result<(), DeployError> {
+        if !self.branch_exists()? {
+            run(self
+                .git(worktree_path)
+                .args(["checkout", "--orphan", &self.branch]))?;
+            run(self.git(worktree_path).args([
+                "rm",
+                "-r",
+                "--quiet",
+                "--force",
+                "--ignore-unmatch",
+                ".",
+            ]))?;
+        }
+
+        for entry in &plan.upload {
+            copy_file(output_path, worktree_path, &entry.path)?;
+        }
+
+        for path in &plan.delete {
+            match fs::remove_file(worktree_path.join(path)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        copy_file(output_path, worktree_path, MANIFEST_PATH)?;
+
+        run(self.git(worktree_path).args(["add", "--all"]))?;
+        run(self.git(worktree_path).args([
+            "commit",
+            "--quiet",
+            "--message",
+            &self.commit_message,
+        ]))?;
+
+        if let Some(remote) = self.remote.as_ref() {
+            run(self
+                .git(worktree_path)
+                .args(["push", "--quiet", remote, &self.branch]))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Deploy for GitBranchDeployer {
+    fn deployed_manifest(&self) -> Result<Option<BuildManifest>, DeployError> {
+        let deployed_ref = match self.fetch_remote_branch()? {
+            Some(remote_ref) => remote_ref,
+            None if self.branch_exists()? => {
+                format!("refs/heads/{}", self.branch)
+            }
+            // The branch won't exist if the site hasn't been deployed before.
+            None => return Ok(None),
+        };
+
+        // Nor will the manifest, if the branch was created some other way.
+        let manifest = format!("{deployed_ref}:{MANIFEST_PATH}");
+        if !self.object_exists(&manifest)? {
+            return Ok(None);
+        }
+
+        let output = self
+            .git(&self.repo_path)
+            .arg("show")
+            .arg(manifest)
+            .output()?;
+        check_output("git", &output)?;
+
+        Ok(Some(serde_json::from_slice(&output.stdout)?))
+    }
+
+    fn apply(&self, output_path: &Path, plan: &DeployPlan) -> Result<(), DeployError> {
+        let output_path = output_path.canonicalize()?;
+        let worktree_path = std::env::temp_dir().join(format!(
+            "razorbill-deploy-{}-{}",
+            self.branch.replace('/', "-"),
+            std::process::id()
+        ));
+
+        let mut worktree_add = self.git(&self.repo_path);
+        worktree_add.args(["worktree", "add", "--quiet"]);
+        if let Some(remote_ref) = self.fetch_remote_branch()? {
+            // The deployment is committed on top of the remote's branch, so
+            // that it can be pushed.
+            worktree_add
+                .args(["-B", &self.branch])
+                .arg(&worktree_path)
+                .arg(remote_ref);
+        } else if self.branch_exists()? {
+            worktree_add.arg(&worktree_path).arg(&self.branch);
+        } else {
+            // An orphan branch is created once the worktree has been added.
+            worktree_add.arg("--detach").arg(&worktree_path);
+        }
+        run(&mut worktree_add)?;
+
+        let result = self.commit_worktree(&worktree_path, &output_path, plan);
+
+        run(self
+            .git(&self.repo_path)
+            .args(["worktree", "remove", "--force"])
+            .arg(&worktree_path))?;
+
+        result
+    }
+}
+
+/// Deploys to a local or remote (e.g., `user@host:/var/www/site`) directory using `rsync`.
+pub struct RsyncDeployer {
+    destination: String,
+}
+
+impl RsyncDeployer {
+    pub fn new(destination: impl Into<String>) -> Self {
+        Self {
+            destination: destination.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Returns the host and path of the destination, if it is on a remote host.
+    fn remote_destination(&self) -> Option<(&str, &str)> {
+        // Local paths containing a colon must include a `/` before it.
+        self.destination
+            .split_once(':')
+            .filter(|(host, _path)| !host.contains('/'))
+    }
+}
+
+/// Returns the shell command that prints the manifest in the given directory
+/// on a remote host, or nothing if there isn't one.
+///
+/// `ssh` runs the command through the remote user's shell, so the path is
+/// quoted rather than passed as a separate argument.
+fn cat_manifest_command(path: &str) -> String {
+    let manifest_path = shell_quote(&format!("{path}/{MANIFEST_PATH}"));
+
+    format!("[ ! -e {manifest_path} ] || cat -- {manifest_path}")
+}
+
+/// Quotes the given value for use as a single word in a POSIX shell command.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Reads the manifest from the output of [`cat_manifest_command`].
+fn read_remote_manifest(output: Output) -> Result<Option<BuildManifest>, DeployError> {
+    check_output("ssh", &output)?;
+
+    if output.stdout.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_slice(&output.stdout)?))
+}
+
+impl Deploy for RsyncDeployer {
+    fn deployed_manifest(&self) -> Result<Option<BuildManifest>, DeployError> {
+        let manifest = match self.remote_destination() {
+            Some((host, path)) => {
+                let output = Command::new("ssh")
+                    .arg(host)
+                    .arg(cat_manifest_command(path))
+                    .output()?;
+
+                return read_remote_manifest(output);
+            }
+            None => match fs::read(Path::new(&self.destination).join(MANIFEST_PATH)) {
+                Ok(manifest) => manifest,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            },
+        };
+
+        Ok(Some(serde_json::from_slice(&manifest)?))
+    }
+
+    fn apply(&self, output_path: &Path, plan: &DeployPlan) -> Result<(), DeployError> {
+        // Deleted files are included in the file list, as `--delete-missing-args`
+        // removes any listed files that are missing from the output directory.
+        let file_list = plan
+            .upload
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .chain(plan.delete.iter().map(|path| path.as_str()))
+            .chain([MANIFEST_PATH])
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut rsync = Command::new("rsync")
+            .args([
+                "--archive",
+                "--compress",
+                "--delete-missing-args",
+                "--files-from=-",
+            ])
+            .arg(format!("{}/", output_path.display()))
+            .arg(format!("{}/", self.destination))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = rsync.stdin.take() {
+            stdin.write_all(file_list.as_bytes())?;
+        }
+
+        check_output("rsync", &rsync.wait_with_output()?)
+    }
+}
+
+fn copy_file(from_dir: &Path, to_dir: &Path, path: &str) -> io::Result<()> {
+    let destination = to_dir.join(path);
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::copy(from_dir.join(path), destination)?;
+
+    Ok(())
+}
+
+fn run(command: &mut Command) -> Result<(), DeployError> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command.stdout(Stdio::null()).output()?;
+
+    check_output(&program, &output)
+}
+
+fn check_output(program: &str, output: &Output) -> Result<(), DeployError> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(DeployError::CommandFailed {
+        command: program.to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn entry(path: &str, hash: &str) -> BuildManifestEntry {
+        BuildManifestEntry {
+            path: path.to_string(),
+            permalink: format!("/{path}"),
+            size: 0,
+            hash: hash.to_string(),
+            source: None,
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_deploy_plan() {
+        let deployed = BuildManifest {
+            files: vec![
+                entry("about/index.html", "a"),
+                entry("index.html", "b"),
+                entry("old/index.html", "c"),
+            ],
+        };
+        let current = BuildManifest {
+            files: vec![
+                entry("about/index.html", "a"),
+                entry("index.html", "B"),
+                entry("new/index.html", "d"),
+            ],
+        };
+
+        assert_eq!(
+            DeployPlan::new(Some(&deployed), &current),
+            DeployPlan {
+                upload: vec![entry("index.html", "B"), entry("new/index.html", "d")],
+                delete: vec!["old/index.html".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_deploy_plan_without_previous_deployment() {
+        let current = BuildManifest {
+            files: vec![entry("index.html", "a")],
+        };
+
+        assert_eq!(
+            DeployPlan::new(None, &current),
+            DeployPlan {
+                upload: current.files.clone(),
+                delete: Vec::new(),
+            }
+        );
+    }
+
+    /// Returns the output of the given shell script, standing in for the
+    /// output of the commands run by the deployers.
+    fn sh(script: &str) -> Output {
+        Command::new("sh").arg("-c").arg(script).output().unwrap()
+    }
+
+    #[test]
+    fn test_read_s3_manifest() {
+        let manifest = BuildManifest {
+            files: vec![entry("index.html", "a")],
+        };
+
+        let output = sh(&format!("printf '%s' '{}'", manifest.to_json().unwrap()));
+        assert_eq!(read_s3_manifest(output).unwrap(), Some(manifest));
+    }
+
+    #[test]
+    fn test_read_s3_manifest_when_not_deployed() {
+        let output = sh(
+            r#"echo 'fatal error: An error occurred (404) when calling the HeadObject operation: Key "manifest.json" does not exist' >&2; exit 1"#,
+        );
+        assert_eq!(read_s3_manifest(output).unwrap(), None);
+
+        let output = sh(
+            "echo 'An error occurred (NoSuchKey) when calling the GetObject operation' >&2; exit 1",
+        );
+        assert_eq!(read_s3_manifest(output).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_s3_manifest_fails_for_other_errors() {
+        let output = sh(
+            "echo 'An error occurred (AccessDenied) when calling the HeadObject operation: Forbidden' >&2; exit 1",
+        );
+
+        match read_s3_manifest(output) {
+            Err(DeployError::CommandFailed { command, stderr }) => {
+                assert_eq!(command, "aws");
+                assert!(stderr.contains("AccessDenied"));
+            }
+            result => panic!("expected the command to fail, got {result:?}"),
+        }
+    }
+
+    /// Runs `git` in the given directory, returning its output.
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "`git {}` failed", args.join(" "));
+
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// Creates a Git repository at the given path, with `remote` as its `origin`.
+    fn init_repo(path: &Path, remote: &Path) -> PathBuf {
+        fs::create_dir_all(path).unwrap();
+        git(path, &["init", "--quiet"]);
+        git(path, &["config", "user.name", "Razorbill"]);
+        git(path, &["config", "user.email", "razorbill@example.com"]);
+        git(
+            path,
+            &[
+                "commit",
+                "--quiet",
+                "--allow-empty",
+                "--message",
+                "Initial commit",
+            ],
+        );
+        git(path, &["remote", "add", "origin", remote.to_str().unwrap()]);
+        path.to_owned()
+    }
+
+    /// Writes out a build with the given files and manifest.
+    fn write_build(output_path: &Path, files: &[(&str, &str)], manifest: &BuildManifest) {
+        if output_path.exists() {
+            fs::remove_dir_all(output_path).unwrap();
+        }
+        fs::create_dir_all(output_path).unwrap();
+
+        for (path, contents) in files {
+            fs::write(output_path.join(path), contents).unwrap();
+        }
+        fs::write(output_path.join(MANIFEST_PATH), manifest.to_json().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_git_branch_deployer_builds_on_remote_branch() {
+        let root_path = std::env::temp_dir()
+            .join("razorbill-tests")
+            .join(format!("git-branch-deployer-{}", std::process::id()));
+        if root_path.exists() {
+            fs::remove_dir_all(&root_path).unwrap();
+        }
+        fs::create_dir_all(&root_path).unwrap();
+
+        let remote = root_path.join("remote.git");
+        git(&root_path, &["init", "--quiet", "--bare", "remote.git"]);
+        let output_path = root_path.join("public");
+
+        let first = BuildManifest {
+            files: vec![entry("index.html", "a"), entry("old.html", "b")],
+        };
+        write_build(
+            &output_path,
+            &[("index.html", "Hello"), ("old.html", "Old")],
+            &first,
+        );
+
+        let deployer = GitBranchDeployer::new(init_repo(&root_path.join("a"), &remote), "gh-pages")
+            .with_remote("origin");
+        assert_eq!(deployer.deployed_manifest().unwrap(), None);
+        deployer
+            .apply(&output_path, &DeployPlan::new(None, &first))
+            .unwrap();
+
+        // A clone that has never had the branch picks up from the remote's.
+        let deployer = GitBranchDeployer::new(init_repo(&root_path.join("b"), &remote), "gh-pages")
+            .with_remote("origin");
+        let deployed = deployer.deployed_manifest().unwrap();
+        assert_eq!(deployed.as_ref(), Some(&first));
+
+        let second = BuildManifest {
+            files: vec![entry("index.html", "c")],
+        };
+        write_build(&output_path, &[("index.html", "Goodbye")], &second);
+
+        let plan = DeployPlan::new(deployed.as_ref(), &second);
+        assert_eq!(plan.delete, vec!["old.html".to_string()]);
+        deployer.apply(&output_path, &plan).unwrap();
+
+        assert_eq!(git(&remote, &["show", "gh-pages:index.html"]), "Goodbye");
+        assert_eq!(
+            git(&remote, &["ls-tree", "--name-only", "gh-pages"]),
+            "index.html\nmanifest.json\n"
+        );
+        assert_eq!(git(&remote, &["rev-list", "--count", "gh-pages"]), "2\n");
+
+        fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/var/www/site"), "'/var/www/site'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+
+        let output = sh(&format!("printf '%s' {}", shell_quote("'; echo pwned; '")));
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "'; echo pwned; '"
+        );
+    }
+
+    #[test]
+    fn test_read_remote_manifest() {
+        let root_path = std::env::temp_dir()
+            .join("razorbill-tests")
+            .join(format!("rsync-manifest-{}", std::process::id()))
+            .join("it's; touch pwned");
+        if root_path.exists() {
+            fs::remove_dir_all(&root_path).unwrap();
+        }
+        fs::create_dir_all(&root_path).unwrap();
+        let path = root_path.to_str().unwrap();
+
+        assert_eq!(
+            read_remote_manifest(sh(&cat_manifest_command(path))).unwrap(),
+            None
+        );
+        assert!(!Path::new("pwned").exists());
+
+        let manifest = BuildManifest {
+            files: vec![entry("index.html", "a")],
+        };
+        fs::write(root_path.join(MANIFEST_PATH), manifest.to_json().unwrap()).unwrap();
+        assert_eq!(
+            read_remote_manifest(sh(&cat_manifest_command(path))).unwrap(),
+            Some(manifest)
+        );
+
+        fs::remove_dir_all(root_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_read_remote_manifest_fails_for_errors() {
+        let output = sh("echo 'ssh: Could not resolve hostname example.invalid' >&2; exit 255");
+
+        match read_remote_manifest(output) {
+            Err(DeployError::CommandFailed { command, stderr }) => {
+                assert_eq!(command, "ssh");
+                assert!(stderr.contains("Could not resolve hostname"));
+            }
+            result => panic!("expected the command to fail, got {result:?}"),
+        }
+
+        match read_remote_manifest(sh("echo 'not json'")) {
+            Err(DeployError::ParseManifest(_)) => {}
+            result => panic!("expected the manifest to fail to parse, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_rsync_deployed_manifest_for_local_destination() {
+        let root_path = std::env::temp_dir()
+            .join("razorbill-tests")
+            .join(format!("rsync-local-{}", std::process::id()));
+        if root_path.exists() {
+            fs::remove_dir_all(&root_path).unwrap();
+        }
+        fs::create_dir_all(&root_path).unwrap();
+
+        let deployer = RsyncDeployer::new(root_path.to_str().unwrap());
+        assert_eq!(deployer.deployed_manifest().unwrap(), None);
+
+        let manifest = BuildManifest {
+            files: vec![entry("index.html", "a")],
+        };
+        fs::write(root_path.join(MANIFEST_PATH), manifest.to_json().unwrap()).unwrap();
+        assert_eq!(deployer.deployed_manifest().unwrap(), Some(manifest));
+
+        fs::write(root_path.join(MANIFEST_PATH), "not json").unwrap();
+        match deployer.deployed_manifest() {
+            Err(DeployError::ParseManifest(_)) => {}
+            result => panic!("expected the manifest to fail to parse, got {result:?}"),
+        }
+
+        fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[test]
+    fn test_rsync_remote_destination() {
+        assert_eq!(
+            RsyncDeployer::new("deploy@example.com:/var/www/site/").remote_destination(),
+            Some(("deploy@example.com", "/var/www/site"))
+        );
+        assert_eq!(
+            RsyncDeployer::new("./public:backup").remote_destination(),
+            None
+        );
+        assert_eq!(RsyncDeployer::new("/var/www").remote_destination(), None);
+    }
+}
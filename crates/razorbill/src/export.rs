@@ -0,0 +1,277 @@
+//! Exporting a built site for offline reading.
+//!
+//! Exported sites have their links rewritten to relative file paths, so that
+//! they can be browsed directly from the filesystem without a web server.
+
+mod zip;
+
+use std::convert::Infallible;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use auk::visitor::{noop_visit_element, MutVisitor};
+use auk::HtmlElement;
+use thiserror::Error;
+
+use crate::export::zip::ZipWriter;
+use crate::permalink::Permalink;
+use crate::storage::{content_output_path, InMemoryStorage, SiteContent};
+use crate::{RenderSiteError, Site};
+
+/// The format to export a site in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A directory containing the site's files.
+    Folder,
+    /// A single ZIP archive containing the site's files.
+    Zip,
+}
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("failed to render site: {0}")]
+    Render(#[from] RenderSiteError),
+}
+
+impl Site {
+    /// Exports the site to the given destination, with all of its links
+    /// rewritten to relative file paths.
+    ///
+    /// The site is rendered again for the export, so it must have been loaded
+    /// first. The rendered files are kept in memory rather than being written
+    /// to the output directory.
+    pub fn export(
+        &mut self,
+        destination: impl AsRef<Path>,
+        format: ExportFormat,
+    ) -> Result<(), ExportError> {
+        let destination = destination.as_ref();
+
+        let content = SiteContent::default();
+        self.is_exporting = true;
+        let rendered = self.render_to(InMemoryStorage::new(content.clone()));
+        self.is_exporting = false;
+        rendered?;
+
+        let content = content.read().unwrap_or_else(|err| err.into_inner());
+
+        // The files are sorted so that archives of the same site are identical.
+        let mut files = content
+            .iter()
+            .map(|(path, content)| (export_path(path), content))
+            .collect::<Vec<_>>();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut zip = match format {
+            ExportFormat::Folder => None,
+            ExportFormat::Zip => {
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                Some(ZipWriter::new(BufWriter::new(File::create(destination)?)))
+            }
+        };
+
+        for (path, content) in files {
+            match zip.as_mut() {
+                Some(zip) => zip.add_file(&path, content)?,
+                None => {
+                    let export_path = destination.join(&path);
+
+                    if let Some(parent) = export_path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+
+                    fs::write(export_path, content)?;
+                }
+            }
+        }
+
+        if let Some(zip) = zip {
+            zip.finish()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the path that the file stored at the given URL path is exported to,
+/// relative to the destination.
+fn export_path(url_path: &str) -> String {
+    let path = url_path.trim_start_matches('/');
+
+    if path.is_empty() || path.ends_with('/') {
+        format!("{path}index.html")
+    } else {
+        path.to_owned()
+    }
+}
+
+/// A visitor that rewrites the links to the site within a document to be
+/// relative to the file the document is exported to.
+pub(crate) struct RelativeLinkRewriter<'a> {
+    /// The path of the file the document is exported to.
+    file_path: String,
+    base_url: &'a str,
+}
+
+impl<'a> RelativeLinkRewriter<'a> {
+    /// Returns a new [`RelativeLinkRewriter`] for the document with the given
+    /// permalink, on a site with the given base URL.
+    pub fn new(permalink: &Permalink, base_url: &'a str) -> Self {
+        Self {
+            file_path: content_output_path(permalink),
+            base_url: base_url.trim_end_matches('/'),
+        }
+    }
+
+    /// Returns the root-relative path of the given URL, if it points to the site.
+    fn site_path<'url>(&self, url: &'url str) -> Option<&'url str> {
+        match url.strip_prefix(self.base_url) {
+            Some(path) if path.is_empty() || path.starts_with('/') => Some(path),
+            _ => url
+                .strip_prefix('/')
+                .filter(|path| !path.starts_with('/'))
+                .map(|_| url),
+        }
+    }
+}
+
+impl<'a> MutVisitor for RelativeLinkRewriter<'a> {
+    type Error = Infallible;
+
+    fn visit(&mut self, element: &mut HtmlElement) -> Result<(), Self::Error> {
+        noop_visit_element(self, element)
+    }
+
+    fn visit_attr(&mut self, name: &str, value: &mut String) -> Result<(), Self::Error> {
+        if name == "href" || name == "src" {
+            if let Some(path) = self.site_path(value) {
+                *value = relative_url(&self.file_path, path);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the URL of the given root-relative path (e.g., `/blog/hello-world/#intro`),
+/// relative to the file at the given path.
+///
+/// Links to directories are pointed at their `index.html`, as there won't be a
+/// web server to do that when browsing from the filesystem.
+fn relative_url(from_file: &str, to: &str) -> String {
+    let suffix_start = to.find(['?', '#']).unwrap_or(to.len());
+    let (to, suffix) = to.split_at(suffix_start);
+
+    let mut to = to.trim_start_matches('/').to_string();
+    if to.is_empty() || to.ends_with('/') {
+        to.push_str("index.html");
+    }
+
+    let from_dir = from_file.split('/').collect::<Vec<_>>();
+    let from_dir = &from_dir[..from_dir.len() - 1];
+    let to_segments = to.split('/').collect::<Vec<_>>();
+
+    let common_len = from_dir
+        .iter()
+        .zip(&to_segments)
+        .take_while(|(a, b)| a == b)
+        .count()
+        // The file name can never be part of the common directory.
+        .min(to_segments.len() - 1);
+
+    let mut segments = vec![".."; from_dir.len() - common_len];
+    segments.extend(&to_segments[common_len..]);
+
+    format!("{}{suffix}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_site::TestSite;
+
+    use super::*;
+
+    #[test]
+    fn test_relative_url() {
+        assert_eq!(relative_url("index.html", "/"), "index.html");
+        assert_eq!(
+            relative_url("index.html", "/blog/hello-world/"),
+            "blog/hello-world/index.html"
+        );
+        assert_eq!(
+            relative_url("blog/hello-world/index.html", "/"),
+            "../../index.html"
+        );
+        assert_eq!(
+            relative_url("blog/hello-world/index.html", "/blog/other-post/#intro"),
+            "../other-post/index.html#intro"
+        );
+        assert_eq!(relative_url("blog/index.html", "/blog/"), "index.html");
+        assert_eq!(
+            relative_url("blog/hello-world/index.html", "/style.css?v=1"),
+            "../../style.css?v=1"
+        );
+    }
+
+    #[test]
+    fn test_relative_link_rewriter() {
+        use auk::renderer::HtmlElementRenderer;
+        use auk::*;
+
+        let config = crate::SiteConfig::for_tests("https://example.com");
+        let mut document = div()
+            .child(link().href("/style.css"))
+            .child(a().href("https://example.com/blog/").child("Blog"))
+            .child(a().href("https://example.org/").child("Elsewhere"))
+            .child(img().src("//cdn.example.org/image.png"))
+            .child(a().href("#top").child("Top"));
+
+        RelativeLinkRewriter::new(
+            &Permalink::from_path(&config, "about/"),
+            "https://example.com/",
+        )
+        .visit(&mut document)
+        .unwrap();
+
+        assert_eq!(
+            HtmlElementRenderer::new()
+                .render_to_string(&document)
+                .unwrap(),
+            r##"<div><link href="../style.css"><a href="../blog/index.html">Blog</a><a href="https://example.org/">Elsewhere</a><img src="//cdn.example.org/image.png"><a href="#top">Top</a></div>"##
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let test_site = TestSite::new("export");
+        test_site
+            .write("_index.md", "+++\ntitle = \"Home\"\n+++\n")
+            .write(
+                "blog/hello.md",
+                "+++\ntitle = \"Hello\"\n+++\n\n[Home](@/_index.md)\n",
+            );
+
+        let mut site = test_site.load();
+        let destination = test_site.root_path().join("export");
+        site.export(&destination, ExportFormat::Folder).unwrap();
+
+        let hello = fs::read_to_string(destination.join("blog/hello/index.html")).unwrap();
+        assert!(
+            hello.contains(r#"<a href="../../index.html">Home</a>"#),
+            "{hello}"
+        );
+        assert!(destination.join("index.html").exists());
+
+        // Nothing is written to the output directory.
+        assert!(!test_site.root_path().join("public").exists());
+    }
+}
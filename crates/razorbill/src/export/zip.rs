@@ -0,0 +1,217 @@
+use std::io::{self, Write};
+
+/// Writes a ZIP archive, storing each file without compression.
+pub(crate) struct ZipWriter<W: Write> {
+    writer: W,
+    offset: u32,
+    entries: Vec<CentralDirectoryEntry>,
+}
+
+struct CentralDirectoryEntry {
+    name: String,
+    crc32: u32,
+    size: u32,
+    offset: u32,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds a file with the given name (using `/` as the separator) to the archive.
+    pub fn add_file(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
+        let size = u32::try_from(content.len()).map_err(|_| too_large("file"))?;
+        let name_len = name_len(name)?;
+        let crc32 = crc32(content);
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        // Version needed to extract (2.0).
+        header.extend_from_slice(&20u16.to_le_bytes());
+        // General purpose flags (UTF-8 file names).
+        header.extend_from_slice(&(1u16 << 11).to_le_bytes());
+        // Compression method (stored).
+        header.extend_from_slice(&0u16.to_le_bytes());
+        // Modification time and date (1980-01-01 00:00).
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0x21u16.to_le_bytes());
+        header.extend_from_slice(&crc32.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&name_len.to_le_bytes());
+        // Extra field length.
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+
+        let next_offset = u32::try_from(header.len())
+            .ok()
+            .and_then(|header_len| self.offset.checked_add(header_len))
+            .and_then(|offset| offset.checked_add(size))
+            .ok_or_else(|| too_large("archive"))?;
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(content)?;
+
+        self.entries.push(CentralDirectoryEntry {
+            name: name.to_owned(),
+            crc32,
+            size,
+            offset: self.offset,
+        });
+        self.offset = next_offset;
+
+        Ok(())
+    }
+
+    /// Writes the central directory, completing the archive.
+    pub fn finish(mut self) -> io::Result<W> {
+        let central_directory_offset = self.offset;
+        let mut central_directory_size = 0u32;
+
+        for entry in &self.entries {
+            let mut header = Vec::with_capacity(46 + entry.name.len());
+            header.extend_from_slice(&0x02014b50u32.to_le_bytes());
+            // Version made by and version needed to extract (2.0).
+            header.extend_from_slice(&20u16.to_le_bytes());
+            header.extend_from_slice(&20u16.to_le_bytes());
+            header.extend_from_slice(&(1u16 << 11).to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0x21u16.to_le_bytes());
+            header.extend_from_slice(&entry.crc32.to_le_bytes());
+            header.extend_from_slice(&entry.size.to_le_bytes());
+            header.extend_from_slice(&entry.size.to_le_bytes());
+            header.extend_from_slice(&name_len(&entry.name)?.to_le_bytes());
+            // Extra field, comment, disk number, and internal/external attributes.
+            header.extend_from_slice(&[0; 12]);
+            header.extend_from_slice(&entry.offset.to_le_bytes());
+            header.extend_from_slice(entry.name.as_bytes());
+
+            self.writer.write_all(&header)?;
+            central_directory_size = u32::try_from(header.len())
+                .ok()
+                .and_then(|header_len| central_directory_size.checked_add(header_len))
+                .ok_or_else(|| too_large("central directory"))?;
+        }
+
+        let entry_count =
+            u16::try_from(self.entries.len()).map_err(|_| too_large("number of files"))?;
+
+        let mut end_of_central_directory = Vec::with_capacity(22);
+        end_of_central_directory.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        // Disk numbers.
+        end_of_central_directory.extend_from_slice(&[0; 4]);
+        end_of_central_directory.extend_from_slice(&entry_count.to_le_bytes());
+        end_of_central_directory.extend_from_slice(&entry_count.to_le_bytes());
+        end_of_central_directory.extend_from_slice(&central_directory_size.to_le_bytes());
+        end_of_central_directory.extend_from_slice(&central_directory_offset.to_le_bytes());
+        // Comment length.
+        end_of_central_directory.extend_from_slice(&0u16.to_le_bytes());
+
+        self.writer.write_all(&end_of_central_directory)?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Returns the length of the given file name, as stored in the archive.
+fn name_len(name: &str) -> io::Result<u16> {
+    u16::try_from(name.len()).map_err(|_| too_large("file name"))
+}
+
+fn too_large(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{what} is too large for a ZIP archive"),
+    )
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414fa339
+        );
+    }
+
+    #[test]
+    fn test_zip_writer() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("index.html", b"<h1>Hello</h1>").unwrap();
+        zip.add_file("blog/index.html", b"<h1>Blog</h1>").unwrap();
+        let archive = zip.finish().unwrap();
+
+        let local_headers_len = (30 + 10 + 14) + (30 + 15 + 13);
+        let central_directory_len = (46 + 10) + (46 + 15);
+
+        assert_eq!(
+            archive.len(),
+            local_headers_len + central_directory_len + 22
+        );
+        assert_eq!(&archive[..4], b"PK\x03\x04");
+        assert_eq!(&archive[local_headers_len..][..4], b"PK\x01\x02");
+        assert_eq!(&archive[archive.len() - 22..][..4], b"PK\x05\x06");
+    }
+
+    #[test]
+    fn test_zip_writer_with_long_file_name() {
+        let mut zip = ZipWriter::new(Vec::new());
+        let name = "a".repeat(u16::MAX as usize + 1);
+
+        let error = zip.add_file(&name, b"").unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(zip.entries.is_empty());
+    }
+
+    #[test]
+    fn test_zip_writer_with_archive_too_large() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.offset = u32::MAX - 10;
+
+        let error = zip.add_file("index.html", b"").unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(zip.writer.is_empty());
+    }
+
+    #[test]
+    fn test_zip_writer_with_too_many_files() {
+        let mut zip = ZipWriter::new(io::sink());
+        for _ in 0..=u16::MAX {
+            zip.add_file("a", b"").unwrap();
+        }
+
+        let error = zip.finish().unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
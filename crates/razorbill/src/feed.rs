@@ -1,25 +1,112 @@
-use std::fmt::Write;
+//! Rendering Atom feeds.
+//!
+//! The site's feeds are rendered from its pages, but a feed can be rendered from
+//! any other data (e.g., a changelog loaded from a data file) by describing each
+//! of its items as a [`FeedEntry`].
 
-use auk::visitor::Visitor;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use auk::visitor::{MutVisitor, Visitor};
 use auk::*;
+use chrono::Utc;
 use chrono_tz::Tz;
 
 use crate::content::Page;
 use crate::date::format_date;
 use crate::permalink::Permalink;
+use crate::site::{LinkReplacer, RenderSiteError};
 use crate::storage::Store;
 use crate::{Site, SiteConfig};
 
-pub fn render_feed(
+/// How the content of feed entries is embedded in the feed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum FeedContent {
+    /// The content is embedded as escaped HTML.
+    #[default]
+    EscapedHtml,
+    /// The content is embedded as HTML within a CDATA section, so that it
+    /// doesn't need to be escaped.
+    Cdata,
+    /// The content is embedded as an XHTML tree.
+    ///
+    /// The content of [`FeedEntry`]s rendered with this option must be
+    /// well-formed XHTML.
+    Xhtml,
+}
+
+/// An entry in a feed.
+#[derive(Debug, Clone, Default)]
+pub struct FeedEntry {
+    pub title: String,
+    /// The absolute URL of the entry.
+    pub url: String,
+    /// The date the entry was published, in the same format as dates in front
+    /// matter (e.g., `2024-01-31` or `2024-01-31T09:00:00Z`).
+    pub published: String,
+    /// The date the entry was last updated, in the same format as `published`.
+    pub updated: Option<String>,
+    /// The names of the entry's authors.
+    pub authors: Vec<String>,
+    pub summary: Option<String>,
+    /// The content of the entry, as HTML.
+    pub content: Option<String>,
+}
+
+/// Writes an Atom feed containing the given entries to `writer`, returning the
+/// writer once the feed is finished.
+///
+/// The entries are ordered from newest to oldest, and the feed is marked as
+/// updated when its most recent entry was.
+pub fn write_atom_feed<W: Write>(
+    writer: W,
+    config: &SiteConfig,
+    feed_url: &str,
+    title_suffix: Option<&str>,
+    content: FeedContent,
+    entries: impl IntoIterator<Item = FeedEntry>,
+) -> io::Result<W> {
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    sort_entries(&mut entries);
+
+    let last_updated_at = entries
+        .iter()
+        .flat_map(|entry| entry.updated.iter().chain([&entry.published]))
+        .max()
+        .cloned()
+        .unwrap_or_else(|| Utc::now().to_rfc3339());
+
+    let mut feed_writer =
+        AtomFeedWriter::new(writer, config, feed_url, title_suffix, &last_updated_at)?
+            .content(content);
+
+    for entry in &entries {
+        feed_writer.write_entry(entry)?;
+    }
+
+    feed_writer.finish()
+}
+
+/// Orders the given entries from newest to oldest.
+fn sort_entries(entries: &mut [FeedEntry]) {
+    entries.sort_by(|a, b| {
+        b.published
+            .cmp(&a.published)
+            .then_with(|| a.url.cmp(&b.url))
+    });
+}
+
+pub(crate) fn render_feed(
     site: &Site,
     permalink: Permalink,
     title_suffix: Option<&str>,
     pages: Vec<&Page>,
+    limit: Option<usize>,
     storage: &impl Store,
 ) {
     let mut pages = pages
         .into_iter()
-        .filter(|page| page.meta.date.is_some())
+        .filter(|page| page.meta.date.is_some() && !page.meta.no_index)
         .collect::<Vec<_>>();
 
     if pages.is_empty() {
@@ -33,92 +120,297 @@ pub fn render_feed(
             .then_with(|| a.permalink.cmp(&b.permalink))
     });
 
-    let rendered = XmlRenderer::new()
-        .render_to_string(&atom_feed_template(
-            &site.config,
-            &permalink,
-            title_suffix,
-            pages,
-        ))
+    if let Some(limit) = limit {
+        pages.truncate(limit);
+    }
+
+    let last_updated_at = pages
+        .iter()
+        .filter_map(|page| page.meta.updated.as_ref())
+        .chain(pages[0].meta.date.as_ref())
+        .max()
+        .unwrap();
+
+    storage
+        .store_content_streaming(permalink.clone(), |writer| {
+            let mut feed_writer = AtomFeedWriter::new(
+                writer,
+                &site.config,
+                permalink.as_str(),
+                title_suffix,
+                last_updated_at,
+            )?
+            .content(site.feed_content);
+
+            // Entries are created one at a time so that only a single page's
+            // content is held in memory.
+            for page in &pages {
+                let entry = page_entry(site, page)
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+
+                feed_writer.write_entry(&entry)?;
+            }
+
+            feed_writer.finish()?;
+
+            Ok(())
+        })
         .unwrap();
+}
+
+/// Renders a feed of the given entries, keeping only the `limit` most recent.
+pub(crate) fn render_entries_feed(
+    site: &Site,
+    permalink: Permalink,
+    title_suffix: Option<&str>,
+    mut entries: Vec<FeedEntry>,
+    limit: Option<usize>,
+    storage: &impl Store,
+) {
+    if entries.is_empty() {
+        return;
+    }
 
-    const XML_PROLOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+    if let Some(limit) = limit {
+        sort_entries(&mut entries);
+        entries.truncate(limit);
+    }
 
-    let sitemap_xml = format!("{XML_PROLOG}\n{rendered}");
+    storage
+        .store_content_streaming(permalink.clone(), |writer| {
+            write_atom_feed(
+                writer,
+                &site.config,
+                permalink.as_str(),
+                title_suffix,
+                site.feed_content,
+                entries,
+            )?;
+
+            Ok(())
+        })
+        .unwrap();
+}
 
-    storage.store_content(permalink, sitemap_xml).unwrap();
+/// Returns the [`FeedEntry`] for the given [`Page`].
+///
+/// Feed readers display the content away from the site, so any links that are
+/// still relative are made absolute.
+fn page_entry(site: &Site, page: &Page) -> Result<FeedEntry, RenderSiteError> {
+    let mut content = page.content.to_vec();
+
+    LinkReplacer::new(site, &page.permalink)
+        .with_absolute_urls()
+        .visit_children(&mut content)?;
+
+    // We're rendering the HTML with the `XmlRenderer` primarily so that
+    // void elements (e.g., `img`, `hr`) get self-closing tags.
+    let mut html_renderer = XmlRenderer::new();
+    html_renderer.visit_children(&content)?;
+
+    Ok(FeedEntry {
+        title: page.meta.title.clone().unwrap_or_default(),
+        url: page.permalink.as_str().to_string(),
+        published: page.meta.date.clone().unwrap_or_default(),
+        updated: page.meta.updated.clone(),
+        authors: site
+            .author_names(page)
+            .into_iter()
+            .map(ToString::to_string)
+            .collect(),
+        summary: page.meta.description.clone(),
+        content: Some(html_renderer.xml),
+    })
 }
 
-pub fn atom_feed_template(
-    config: &SiteConfig,
-    feed_url: &Permalink,
+/// Returns the title of a feed for a site with the given title.
+fn feed_title(site_title: Option<&str>, title_suffix: Option<&str>) -> String {
+    let site_title = site_title.unwrap_or_default();
+
+    match title_suffix {
+        Some(suffix) => format!("{site_title} - {suffix}"),
+        None => site_title.to_string(),
+    }
+}
+
+/// Returns the `<link>` element used to advertise the feed at the given URL.
+pub(crate) fn feed_link(
+    feed_url: &str,
+    site_title: Option<&str>,
     title_suffix: Option<&str>,
-    pages: Vec<&Page>,
 ) -> HtmlElement {
-    let last_updated_at = pages
-        .iter()
-        .filter_map(|page| page.meta.updated.as_ref())
-        .chain(pages[0].meta.date.as_ref())
-        .max()
-        .unwrap();
+    link()
+        .rel("alternate")
+        .attr("type", "application/atom+xml")
+        .attr("title", feed_title(site_title, title_suffix))
+        .href(feed_url)
+}
 
-    feed()
-        .attr("xmlns", "http://www.w3.org/2005/Atom")
-        .attr("xml:lang", "en")
-        .child(
-            title()
-                .child(config.title.clone().unwrap_or_default())
-                .children(title_suffix.map(|suffix| format!(" - {suffix}"))),
-        )
-        .child(
-            link()
-                .rel("self")
-                .attr("type", "application/atom+xml")
-                .href(feed_url.as_str()),
+/// A writer that serializes an Atom feed incrementally.
+///
+/// Only a single entry is held in memory at a time, so feeds with a large
+/// number of entries can be written without building the whole element tree.
+pub struct AtomFeedWriter<W: Write> {
+    writer: W,
+    renderer: XmlRenderer,
+    timezone: Tz,
+    content: FeedContent,
+}
+
+impl<W: Write> AtomFeedWriter<W> {
+    /// Returns a new [`AtomFeedWriter`] after writing the feed header to `writer`.
+    pub fn new(
+        mut writer: W,
+        config: &SiteConfig,
+        feed_url: &str,
+        title_suffix: Option<&str>,
+        last_updated_at: &str,
+    ) -> io::Result<Self> {
+        writeln!(writer, "{XML_PROLOG}")?;
+        write!(
+            writer,
+            r#"<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="en">"#
+        )?;
+
+        let mut renderer = XmlRenderer::new();
+        for element in atom_feed_header(config, feed_url, title_suffix, last_updated_at) {
+            write_xml(&mut writer, &mut renderer, &element)?;
+        }
+
+        Ok(Self {
+            writer,
+            renderer,
+            timezone: config.timezone,
+            content: FeedContent::default(),
+        })
+    }
+
+    /// Sets how the content of the entries is embedded.
+    pub fn content(mut self, content: FeedContent) -> Self {
+        self.content = content;
+        self
+    }
+
+    /// Writes the given entry to the feed.
+    pub fn write_entry(&mut self, entry: &FeedEntry) -> io::Result<()> {
+        write_xml(
+            &mut self.writer,
+            &mut self.renderer,
+            &atom_feed_entry(entry, self.content, self.timezone),
         )
+    }
+
+    /// Closes the feed and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        write!(self.writer, "</feed>")?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+const XML_PROLOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// Writes the given element as XML, reusing the renderer's buffer across calls.
+fn write_xml(
+    writer: &mut impl Write,
+    renderer: &mut XmlRenderer,
+    element: &HtmlElement,
+) -> io::Result<()> {
+    renderer.xml.clear();
+    renderer
+        .visit(element)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    writer.write_all(renderer.xml.as_bytes())
+}
+
+fn atom_feed_header(
+    config: &SiteConfig,
+    feed_url: &str,
+    title_suffix: Option<&str>,
+    last_updated_at: &str,
+) -> Vec<HtmlElement> {
+    vec![
+        title().child(feed_title(config.title.as_deref(), title_suffix)),
+        link()
+            .rel("self")
+            .attr("type", "application/atom+xml")
+            .href(feed_url),
+        link()
+            .rel("alternate")
+            .attr("type", "text/html")
+            .href(&config.base_url),
+        generator()
+            .attr("uri", "https://github.com/maxdeviant/razorbill")
+            .child("Razorbill"),
+        updated().child(format_feed_date(last_updated_at, config.timezone)),
+        id().child(feed_url),
+    ]
+}
+
+fn atom_feed_entry(entry: &FeedEntry, content_mode: FeedContent, timezone: Tz) -> HtmlElement {
+    let updated_at = entry.updated.as_deref().unwrap_or(&entry.published);
+
+    let mut atom_entry = atom_entry()
+        .attr("xml:lang", "en")
+        .child(title().child(entry.title.as_str()))
+        .child(published().child(format_feed_date(&entry.published, timezone)))
+        .child(updated().child(format_feed_date(updated_at, timezone)));
+
+    // Atom requires every entry to have an author.
+    if entry.authors.is_empty() {
+        atom_entry = atom_entry.child(author().child(name().child("Unknown")));
+    }
+
+    for author_name in &entry.authors {
+        atom_entry = atom_entry.child(author().child(name().child(escape_xml(author_name))));
+    }
+
+    atom_entry = atom_entry
         .child(
             link()
                 .rel("alternate")
                 .attr("type", "text/html")
-                .href(&config.base_url),
+                .href(entry.url.as_str()),
         )
-        .child(
-            generator()
-                .attr("uri", "https://github.com/maxdeviant/razorbill")
-                .child("Razorbill"),
-        )
-        .child(updated().child(format_date(last_updated_at, "%+", Tz::UTC)))
-        .child(id().child(feed_url.as_str()))
-        .children(pages.into_iter().map(|page| {
-            let date = page.meta.date.clone().unwrap();
-            let updated_at = page.meta.updated.clone().unwrap_or(date.clone());
-
-            // We're rendering the HTML with the `XmlRenderer` primarily so that
-            // void elements (e.g., `img`, `hr`) get self-closing tags.
-            let mut html_renderer = XmlRenderer::new();
-            html_renderer.visit_children(&page.content).unwrap();
-            let content_html = html_renderer.xml;
-
-            entry()
-                .attr("xml:lang", "en")
-                .child(title().child(page.meta.title.clone().unwrap_or_default()))
-                .child(published().child(format_date(&date, "%+", Tz::UTC)))
-                .child(updated().child(format_date(&updated_at, "%+", Tz::UTC)))
-                .child(author().child(name().child("Unknown")))
-                .child(
-                    link()
-                        .rel("alternate")
-                        .attr("type", "text/html")
-                        .href(page.permalink.as_str()),
-                )
-                .child(id().child(page.permalink.as_str()))
-                .child(
-                    content()
-                        .attr("type", "html")
-                        .attr("xml:base", page.permalink.as_str())
-                        .child(escape_xml(&content_html)),
-                )
-        }))
+        .child(id().child(entry.url.as_str()));
+
+    if let Some(summary_text) = entry.summary.as_ref() {
+        atom_entry = atom_entry.child(summary().child(escape_xml(summary_text)));
+    }
+
+    if let Some(content_html) = entry.content.as_ref() {
+        let entry_content = content().attr("xml:base", entry.url.as_str());
+
+        atom_entry = atom_entry.child(match content_mode {
+            FeedContent::EscapedHtml => entry_content
+                .attr("type", "html")
+                .child(escape_xml(content_html)),
+            FeedContent::Cdata => entry_content
+                .attr("type", "html")
+                .child(cdata(content_html)),
+            FeedContent::Xhtml => entry_content.attr("type", "xhtml").child(format!(
+                r#"<div xmlns="http://www.w3.org/1999/xhtml">{content_html}</div>"#
+            )),
+        });
+    }
+
+    atom_entry
+}
+
+/// Formats the given date as RFC 3339, as required by Atom.
+fn format_feed_date(date: &str, timezone: Tz) -> String {
+    // Dates are validated when the pages are loaded.
+    format_date(date, "%+", timezone).unwrap_or_default()
+}
+
+/// Wraps the given content in a CDATA section.
+///
+/// A CDATA section can't contain `]]>`, so any occurrences are split across
+/// two sections.
+fn cdata(content: &str) -> String {
+    format!("<![CDATA[{}]]>", content.replace("]]>", "]]]]><![CDATA[>"))
 }
 
 fn escape_xml(content: &str) -> String {
@@ -131,10 +423,6 @@ fn escape_xml(content: &str) -> String {
         .replace('/', "&#x2F;")
 }
 
-fn feed() -> HtmlElement {
-    HtmlElement::new("feed")
-}
-
 fn generator() -> HtmlElement {
     HtmlElement::new("generator")
 }
@@ -147,7 +435,7 @@ fn id() -> HtmlElement {
     HtmlElement::new("id")
 }
 
-fn entry() -> HtmlElement {
+fn atom_entry() -> HtmlElement {
     HtmlElement::new("entry")
 }
 
@@ -163,6 +451,10 @@ fn name() -> HtmlElement {
     HtmlElement::new("name")
 }
 
+fn summary() -> HtmlElement {
+    HtmlElement::new("summary")
+}
+
 fn content() -> HtmlElement {
     HtmlElement::new("content")
 }
@@ -239,3 +531,54 @@ impl Visitor for XmlRenderer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_write_atom_feed() {
+        let config = SiteConfig {
+            title: Some("Example".to_string()),
+            ..SiteConfig::for_tests("https://example.com")
+        };
+
+        let entry = |version: &str, published: &str| FeedEntry {
+            title: version.to_string(),
+            url: format!("https://example.com/changelog/#{version}"),
+            published: published.to_string(),
+            ..FeedEntry::default()
+        };
+
+        let feed = write_atom_feed(
+            Vec::new(),
+            &config,
+            "https://example.com/changelog.xml",
+            Some("Changelog"),
+            FeedContent::default(),
+            [entry("v1", "2024-01-01"), entry("v2", "2024-02-01")],
+        )
+        .unwrap();
+        let feed = String::from_utf8(feed).unwrap();
+
+        assert!(feed.contains("<title>Example - Changelog</title>"));
+        assert!(feed.contains("<updated>2024-02-01T00:00:00+00:00</updated>"));
+
+        let titles = feed
+            .match_indices("<title>v")
+            .map(|(index, _)| &feed[index + 7..index + 9])
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["v2", "v1"]);
+    }
+
+    #[test]
+    fn test_cdata() {
+        assert_eq!(cdata("<p>Hello</p>"), "<![CDATA[<p>Hello</p>]]>");
+        assert_eq!(
+            cdata("<code>a[b[0]]>c</code>"),
+            "<![CDATA[<code>a[b[0]]]]><![CDATA[>c</code>]]>"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Checks for structural problems in rendered HTML.
+//!
+//! Building documents out of elements rules out unbalanced tags, but custom
+//! components can still produce invalid documents, like ones that reuse an
+//! `id` or put an `<li>` outside of a list.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use auk::{Element, HtmlElement};
+
+use crate::lint::{Diagnostic, Severity};
+
+/// The elements that an `<li>` may be placed in.
+const LIST_TAGS: &[&str] = &["ul", "ol", "menu"];
+
+/// A problem found in a rendered document.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct HtmlProblem {
+    pub severity: Severity,
+    /// The name of the check that found the problem (e.g., `duplicate-id`).
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl HtmlProblem {
+    fn error(rule: &'static str, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            rule,
+            message,
+        }
+    }
+
+    fn warning(rule: &'static str, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            rule,
+            message,
+        }
+    }
+
+    /// Returns the [`Diagnostic`] for this problem in the content file at the
+    /// given path.
+    pub fn into_diagnostic(self, path: PathBuf) -> Diagnostic {
+        Diagnostic {
+            severity: self.severity,
+            rule: self.rule.to_string(),
+            message: self.message,
+            path,
+            line: None,
+        }
+    }
+}
+
+/// Checks the given document for structural problems.
+pub(crate) fn validate_html(document: &HtmlElement) -> Vec<HtmlProblem> {
+    let mut validator = HtmlValidator::default();
+    validator.visit(document, None);
+    validator.finish()
+}
+
+#[derive(Default)]
+struct HtmlValidator<'a> {
+    /// The number of times each `id` is used, in the order they first appear.
+    ids: Vec<(&'a str, usize)>,
+    id_indices: HashMap<&'a str, usize>,
+    problems: Vec<HtmlProblem>,
+}
+
+impl<'a> HtmlValidator<'a> {
+    fn visit(&mut self, element: &'a HtmlElement, parent: Option<&str>) {
+        let tag_name = element.tag_name.as_str();
+
+        if let Some(id) = element.attrs.get("id") {
+            match self.id_indices.get(id.as_str()) {
+                Some(index) => self.ids[*index].1 += 1,
+                None => {
+                    self.id_indices.insert(id.as_str(), self.ids.len());
+                    self.ids.push((id, 1));
+                }
+            }
+        }
+
+        if tag_name == "li" && !parent.is_some_and(|parent| LIST_TAGS.contains(&parent)) {
+            self.problems.push(HtmlProblem::error(
+                "li-outside-list",
+                format!(
+                    "<li> is placed in <{}> instead of a list",
+                    parent.unwrap_or("document")
+                ),
+            ));
+        }
+
+        if tag_name == "img" && !element.attrs.contains_key("alt") {
+            let src = element.attrs.get("src").map_or("", String::as_str);
+
+            self.problems.push(HtmlProblem::error(
+                "missing-alt",
+                format!("<img src=\"{src}\"> has no alt attribute"),
+            ));
+        }
+
+        if tag_name == "a"
+            && element
+                .attrs
+                .get("href")
+                .is_some_and(|href| href.trim().is_empty())
+        {
+            self.problems.push(HtmlProblem::warning(
+                "empty-href",
+                "<a> has an empty href".to_string(),
+            ));
+        }
+
+        for child in &element.children {
+            if let Element::Html(child) = child {
+                self.visit(child, Some(tag_name));
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<HtmlProblem> {
+        for (id, count) in self.ids {
+            if count > 1 {
+                self.problems.push(HtmlProblem::error(
+                    "duplicate-id",
+                    format!("id '{id}' is used {count} times"),
+                ));
+            }
+        }
+
+        self.problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use auk::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_html() {
+        let document = html().child(
+            body()
+                .child(h2().attr("id", "intro").child("Intro"))
+                .child(h2().attr("id", "intro").child("Intro, again"))
+                .child(ul().child(li().child("Listed")))
+                .child(div().child(li().child("Stray")))
+                .child(img().src("/cover.png"))
+                .child(img().src("/logo.png").attr("alt", "Logo"))
+                .child(a().href("").child("Nowhere"))
+                .child(a().href("/about/").child("About")),
+        );
+
+        assert_eq!(
+            validate_html(&document),
+            vec![
+                HtmlProblem::error(
+                    "li-outside-list",
+                    "<li> is placed in <div> instead of a list".to_string()
+                ),
+                HtmlProblem::error(
+                    "missing-alt",
+                    "<img src=\"/cover.png\"> has no alt attribute".to_string()
+                ),
+                HtmlProblem::warning("empty-href", "<a> has an empty href".to_string()),
+                HtmlProblem::error("duplicate-id", "id 'intro' is used 2 times".to_string()),
+            ]
+        );
+    }
+}
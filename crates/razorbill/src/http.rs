@@ -0,0 +1,225 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{self, SeekFrom};
+use std::ops::RangeInclusive;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use hyper::header::{self, HeaderMap};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Returns a strong entity tag for the given content.
+pub(crate) fn content_etag(content: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Returns a weak entity tag for a file with the given length and modification time.
+pub(crate) fn file_etag(len: u64, modified: Option<SystemTime>) -> String {
+    let modified = modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_nanos())
+        .unwrap_or_default();
+
+    format!("W/\"{len:x}-{modified:x}\"")
+}
+
+/// Formats the given time as an HTTP date.
+pub(crate) fn http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// Returns whether the client's cached copy of a resource is still fresh,
+/// based on the conditional headers in the request.
+pub(crate) fn is_not_modified(
+    headers: &HeaderMap,
+    etag: &str,
+    last_modified: Option<SystemTime>,
+) -> bool {
+    // `If-None-Match` takes precedence over `If-Modified-Since` when both are present.
+    if let Some(if_none_match) = header_str(headers, header::IF_NONE_MATCH) {
+        return if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || weak_etag_eq(tag, etag));
+    }
+
+    let (Some(if_modified_since), Some(last_modified)) = (
+        header_str(headers, header::IF_MODIFIED_SINCE),
+        last_modified,
+    ) else {
+        return false;
+    };
+
+    let Ok(if_modified_since) = DateTime::parse_from_rfc2822(if_modified_since) else {
+        return false;
+    };
+
+    // HTTP dates only have a precision of one second.
+    DateTime::<Utc>::from(last_modified).timestamp() <= if_modified_since.timestamp()
+}
+
+fn weak_etag_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// The portion of a resource requested via the `Range` header.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ByteRange {
+    /// The entire resource.
+    Full,
+    /// The bytes within the given (inclusive) range.
+    Partial(RangeInclusive<u64>),
+    /// The requested range lies outside of the resource.
+    Unsatisfiable,
+}
+
+/// Parses the value of a `Range` header for a resource of the given length.
+///
+/// Only single byte ranges are supported. Any other range is ignored, in which
+/// case the entire resource should be served.
+pub(crate) fn parse_range(range: Option<&str>, len: u64) -> ByteRange {
+    let Some(range) = range.and_then(|range| range.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+
+    if range.contains(',') {
+        return ByteRange::Full;
+    }
+
+    let Some((start, end)) = range.split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    match (start.trim(), end.trim()) {
+        ("", suffix_len) => {
+            let Ok(suffix_len) = suffix_len.parse::<u64>() else {
+                return ByteRange::Full;
+            };
+
+            if suffix_len == 0 || len == 0 {
+                return ByteRange::Unsatisfiable;
+            }
+
+            ByteRange::Partial(len.saturating_sub(suffix_len)..=len - 1)
+        }
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else {
+                return ByteRange::Full;
+            };
+
+            let end = if end.is_empty() {
+                u64::MAX
+            } else {
+                let Ok(end) = end.parse::<u64>() else {
+                    return ByteRange::Full;
+                };
+
+                end
+            };
+
+            if start > end {
+                return ByteRange::Full;
+            }
+
+            if start >= len {
+                return ByteRange::Unsatisfiable;
+            }
+
+            ByteRange::Partial(start..=end.min(len - 1))
+        }
+    }
+}
+
+/// Reads the bytes within the given range from the file.
+pub(crate) async fn read_range(
+    file: &mut File,
+    range: &RangeInclusive<u64>,
+) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![0; (range.end() - range.start() + 1) as usize];
+
+    file.seek(SeekFrom::Start(*range.start())).await?;
+    file.read_exact(&mut buffer).await?;
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::HeaderValue;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range(None, 100), ByteRange::Full);
+        assert_eq!(
+            parse_range(Some("bytes=0-9"), 100),
+            ByteRange::Partial(0..=9)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=90-"), 100),
+            ByteRange::Partial(90..=99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-10"), 100),
+            ByteRange::Partial(90..=99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-200"), 100),
+            ByteRange::Partial(0..=99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=50-500"), 100),
+            ByteRange::Partial(50..=99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=100-"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(parse_range(Some("bytes=-0"), 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=0-9,20-29"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=9-0"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("items=0-9"), 100), ByteRange::Full);
+    }
+
+    #[test]
+    fn test_is_not_modified() {
+        let etag = content_etag(b"Hello, world!");
+
+        let mut headers = HeaderMap::new();
+        assert!(!is_not_modified(&headers, &etag, None));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&etag).unwrap());
+        assert!(is_not_modified(&headers, &etag, None));
+        assert!(!is_not_modified(
+            &headers,
+            &content_etag(b"Goodbye, world!"),
+            None
+        ));
+
+        let last_modified = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_str(&http_date(last_modified)).unwrap(),
+        );
+        assert!(is_not_modified(&headers, &etag, Some(last_modified)));
+        assert!(!is_not_modified(
+            &headers,
+            &etag,
+            Some(last_modified + std::time::Duration::from_secs(1))
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! Translations for multilingual sites.
+//!
+//! Translations are read from a TOML file per language in the site's `i18n`
+//! directory (e.g., `i18n/fr.toml`):
+//!
+//! ```toml
+//! read_more = "Lire la suite"
+//!
+//! [nav]
+//! home = "Accueil"
+//! ```
+//!
+//! Templates can then look up a translation in the language of the page being
+//! rendered with [`RenderPageContext::t`](crate::render::RenderPageContext::t).
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Locale;
+use thiserror::Error;
+
+/// The language that content is written in when the site doesn't specify one.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+#[derive(Error, Debug)]
+pub enum LoadTranslationsError {
+    #[error("failed to read translations '{filepath}': {err}")]
+    Io {
+        err: std::io::Error,
+        filepath: PathBuf,
+    },
+
+    #[error("invalid translations in '{filepath}': {err}")]
+    InvalidTranslations {
+        err: toml::de::Error,
+        filepath: PathBuf,
+    },
+}
+
+/// The translations for each of a site's languages.
+#[derive(Debug, Default)]
+pub struct Translations {
+    languages: HashMap<String, toml::Table>,
+}
+
+impl Translations {
+    /// Loads the translations from the TOML files in the given directory,
+    /// keyed by the name of each file (e.g., `fr.toml` holds the translations
+    /// for `fr`).
+    ///
+    /// A directory that doesn't exist holds no translations.
+    pub fn from_dir(path: impl AsRef<Path>) -> Result<Self, LoadTranslationsError> {
+        let path = path.as_ref();
+        let mut languages = HashMap::new();
+
+        if !path.is_dir() {
+            return Ok(Self { languages });
+        }
+
+        let entries = fs::read_dir(path).map_err(|err| LoadTranslationsError::Io {
+            err,
+            filepath: path.to_owned(),
+        })?;
+
+        for entry in entries {
+            let filepath = entry
+                .map_err(|err| LoadTranslationsError::Io {
+                    err,
+                    filepath: path.to_owned(),
+                })?
+                .path();
+
+            if filepath
+                .extension()
+                .map_or(true, |extension| extension != "toml")
+            {
+                continue;
+            }
+
+            let Some(language) = filepath.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let contents =
+                fs::read_to_string(&filepath).map_err(|err| LoadTranslationsError::Io {
+                    err,
+                    filepath: filepath.clone(),
+                })?;
+            let translations = toml::from_str(&contents).map_err(|err| {
+                LoadTranslationsError::InvalidTranslations {
+                    err,
+                    filepath: filepath.clone(),
+                }
+            })?;
+
+            languages.insert(language.to_owned(), translations);
+        }
+
+        Ok(Self { languages })
+    }
+
+    /// Returns the translation of the given key in the given language.
+    ///
+    /// Nested keys can be accessed using a dotted key (e.g., `nav.home`).
+    pub fn get(&self, language: &str, key: &str) -> Option<&str> {
+        let (parents, name) = match key.rsplit_once('.') {
+            Some((parents, name)) => (Some(parents), name),
+            None => (None, key),
+        };
+
+        let mut table = self.languages.get(language)?;
+        for segment in parents.into_iter().flat_map(|parents| parents.split('.')) {
+            table = table.get(segment)?.as_table()?;
+        }
+
+        table.get(name)?.as_str()
+    }
+
+    /// Returns the locale used for the month and day names in dates in the
+    /// given language.
+    ///
+    /// The locale can be set with a `locale` key in the language's
+    /// translations (e.g., `locale = "fr_CA"`). Otherwise it is derived from
+    /// the language (e.g., `fr` uses `fr_FR`), falling back to English.
+    pub fn locale(&self, language: &str) -> Locale {
+        let (code, region) = language
+            .split_once(['-', '_'])
+            .unwrap_or((language, language));
+        let derived_locale = format!("{code}_{}", region.to_uppercase());
+
+        let locale = self
+            .get(language, "locale")
+            .into_iter()
+            .chain([derived_locale.as_str()])
+            .find_map(|locale| Locale::try_from(locale).ok());
+
+        locale.unwrap_or(Locale::POSIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn translations() -> Translations {
+        let french = toml::from_str(indoc! {r#"
+            read_more = "Lire la suite"
+
+            [nav]
+            home = "Accueil"
+        "#})
+        .unwrap();
+        let portuguese = toml::from_str(r#"locale = "pt_BR""#).unwrap();
+
+        Translations {
+            languages: HashMap::from_iter([
+                ("fr".to_string(), french),
+                ("pt".to_string(), portuguese),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_get_translation() {
+        let translations = translations();
+
+        assert_eq!(translations.get("fr", "read_more"), Some("Lire la suite"));
+        assert_eq!(translations.get("fr", "nav.home"), Some("Accueil"));
+        assert_eq!(translations.get("fr", "nav"), None);
+        assert_eq!(translations.get("fr", "missing"), None);
+        assert_eq!(translations.get("de", "read_more"), None);
+    }
+
+    #[test]
+    fn test_locale() {
+        let translations = translations();
+
+        assert_eq!(translations.locale("fr"), Locale::fr_FR);
+        assert_eq!(translations.locale("pt"), Locale::pt_BR);
+        assert_eq!(translations.locale("de-AT"), Locale::de_AT);
+        assert_eq!(translations.locale("xx"), Locale::POSIX);
+    }
+}
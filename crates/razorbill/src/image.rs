@@ -0,0 +1,205 @@
+use std::fs;
+use std::path::Path;
+
+/// The dimensions of an image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ImageDimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Returns the dimensions of the image at the given path.
+///
+/// Only the image's header is inspected, so the image isn't decoded. PNG, JPEG,
+/// GIF, and WebP images are supported, and `None` is returned for anything else.
+pub(crate) fn image_dimensions(path: &Path) -> Option<ImageDimensions> {
+    let bytes = fs::read(path).ok()?;
+
+    image_dimensions_from_bytes(&bytes)
+}
+
+fn image_dimensions_from_bytes(bytes: &[u8]) -> Option<ImageDimensions> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return png_dimensions(bytes);
+    }
+
+    if bytes.starts_with(b"\xff\xd8") {
+        return jpeg_dimensions(bytes);
+    }
+
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return gif_dimensions(bytes);
+    }
+
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP".as_slice()) {
+        return webp_dimensions(bytes);
+    }
+
+    None
+}
+
+fn png_dimensions(bytes: &[u8]) -> Option<ImageDimensions> {
+    if bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+
+    Some(ImageDimensions {
+        width: read_u32_be(bytes, 16)?,
+        height: read_u32_be(bytes, 20)?,
+    })
+}
+
+fn jpeg_dimensions(bytes: &[u8]) -> Option<ImageDimensions> {
+    let mut index = 2;
+
+    loop {
+        if *bytes.get(index)? != 0xff {
+            return None;
+        }
+
+        // Any number of fill bytes may precede a marker.
+        while *bytes.get(index)? == 0xff {
+            index += 1;
+        }
+
+        let marker = *bytes.get(index)?;
+        index += 1;
+
+        // These markers stand alone, without a segment.
+        if marker == 0x01 || (0xd0..=0xd7).contains(&marker) {
+            continue;
+        }
+
+        let segment_len = read_u16_be(bytes, index)? as usize;
+
+        // The start-of-frame markers, excluding DHT (C4), JPG (C8), and DAC (CC).
+        let is_start_of_frame =
+            (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+        if is_start_of_frame {
+            return Some(ImageDimensions {
+                width: read_u16_be(bytes, index + 5)? as u32,
+                height: read_u16_be(bytes, index + 3)? as u32,
+            });
+        }
+
+        index += segment_len;
+    }
+}
+
+fn gif_dimensions(bytes: &[u8]) -> Option<ImageDimensions> {
+    Some(ImageDimensions {
+        width: read_u16_le(bytes, 6)? as u32,
+        height: read_u16_le(bytes, 8)? as u32,
+    })
+}
+
+fn webp_dimensions(bytes: &[u8]) -> Option<ImageDimensions> {
+    match bytes.get(12..16)? {
+        // Lossy
+        b"VP8 " => Some(ImageDimensions {
+            width: (read_u16_le(bytes, 26)? & 0x3fff) as u32,
+            height: (read_u16_le(bytes, 28)? & 0x3fff) as u32,
+        }),
+        // Lossless
+        b"VP8L" => {
+            let bits = read_u32_le(bytes, 21)?;
+
+            Some(ImageDimensions {
+                width: (bits & 0x3fff) + 1,
+                height: ((bits >> 14) & 0x3fff) + 1,
+            })
+        }
+        // Extended
+        b"VP8X" => Some(ImageDimensions {
+            width: read_u24_le(bytes, 24)? + 1,
+            height: read_u24_le(bytes, 27)? + 1,
+        }),
+        _ => None,
+    }
+}
+
+fn read_u16_be(bytes: &[u8], index: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        bytes.get(index..index + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u16_le(bytes: &[u8], index: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(index..index + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u24_le(bytes: &[u8], index: usize) -> Option<u32> {
+    let bytes = bytes.get(index..index + 3)?;
+
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+fn read_u32_be(bytes: &[u8], index: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(index..index + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32_le(bytes: &[u8], index: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(index..index + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn dimensions(width: u32, height: u32) -> Option<ImageDimensions> {
+        Some(ImageDimensions { width, height })
+    }
+
+    #[test]
+    fn test_image_dimensions_from_bytes() {
+        let png = [
+            b"\x89PNG\r\n\x1a\n".as_slice(),
+            &13u32.to_be_bytes(),
+            b"IHDR",
+            &640u32.to_be_bytes(),
+            &480u32.to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(image_dimensions_from_bytes(&png), dimensions(640, 480));
+
+        let jpeg = [
+            b"\xff\xd8".as_slice(),
+            // An APP0 segment, which is skipped.
+            b"\xff\xe0\x00\x04\x00\x00",
+            // A baseline start-of-frame segment.
+            b"\xff\xc0\x00\x11\x08",
+            &600u16.to_be_bytes(),
+            &800u16.to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(image_dimensions_from_bytes(&jpeg), dimensions(800, 600));
+
+        let gif = [
+            b"GIF89a".as_slice(),
+            &320u16.to_le_bytes(),
+            &200u16.to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(image_dimensions_from_bytes(&gif), dimensions(320, 200));
+
+        let webp = [
+            b"RIFF\x00\x00\x00\x00WEBPVP8X".as_slice(),
+            &[0; 8],
+            &1023u32.to_le_bytes()[..3],
+            &767u32.to_le_bytes()[..3],
+        ]
+        .concat();
+        assert_eq!(image_dimensions_from_bytes(&webp), dimensions(1024, 768));
+
+        assert_eq!(image_dimensions_from_bytes(b"<svg></svg>"), None);
+        assert_eq!(image_dimensions_from_bytes(b"\x89PNG\r\n\x1a\n"), None);
+    }
+}
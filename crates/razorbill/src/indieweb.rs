@@ -0,0 +1,415 @@
+//! Support for [IndieWeb](https://indieweb.org) building blocks.
+//!
+//! Currently this covers sending [Webmentions](https://www.w3.org/TR/webmention/)
+//! to the sites linked to from newly-published pages.
+
+use auk::Element;
+use indexmap::IndexSet;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use thiserror::Error;
+use tokio::process::Command;
+use url::Url;
+
+use crate::manifest::BuildManifest;
+use crate::Site;
+
+/// A Webmention to send, notifying `target` that it was linked to from `source`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webmention {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Error, Debug)]
+pub enum SendWebmentionError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("request to {url} failed: {message}")]
+    RequestFailed { url: String, message: String },
+
+    #[error("no Webmention endpoint found for {0}")]
+    NoEndpoint(String),
+}
+
+impl Site {
+    /// Returns the Webmentions to send for the most recent build, compared to
+    /// the `previous` build.
+    ///
+    /// A Webmention is produced for each external link in the pages that are
+    /// new since the previous build, and for each external link that has been
+    /// added to a page since then. Links that were already sent for a page
+    /// aren't sent again when the page changes.
+    pub fn outgoing_webmentions(&self, previous: Option<&BuildManifest>) -> Vec<Webmention> {
+        self.build_manifest()
+            .files
+            .iter()
+            .filter_map(|entry| {
+                let source = entry.source.as_ref()?;
+                let page = self.pages.get(&self.content_path.join(source))?;
+                let previous_links = previous
+                    .and_then(|previous| previous.get(&entry.path))
+                    .map(|previous_entry| previous_entry.links.as_slice())
+                    .unwrap_or_default();
+
+                Some(
+                    new_links(&entry.links, previous_links).map(|target| Webmention {
+                        source: page.permalink.as_str().to_owned(),
+                        target: target.to_owned(),
+                    }),
+                )
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+/// Returns the links in `links` that aren't in `previous_links`.
+fn new_links<'a>(
+    links: &'a [String],
+    previous_links: &'a [String],
+) -> impl Iterator<Item = &'a str> + 'a {
+    links
+        .iter()
+        .filter(|link| !previous_links.contains(link))
+        .map(|link| link.as_str())
+}
+
+/// Returns the links in the given elements that point outside of the site with the given base URL.
+///
+/// A link is external when it is an absolute HTTP(S) URL with a different
+/// scheme, host, or port than the base URL.
+pub fn external_links(elements: &[Element], base_url: &str) -> Vec<String> {
+    fn collect(elements: &[Element], base_url: Option<&Url>, links: &mut IndexSet<String>) {
+        for element in elements {
+            let Element::Html(element) = element else {
+                continue;
+            };
+
+            if element.tag_name == "a" {
+                if let Some(href) = element.attrs.get("href") {
+                    if is_external(href, base_url) {
+                        links.insert(href.clone());
+                    }
+                }
+            }
+
+            collect(&element.children, base_url, links);
+        }
+    }
+
+    let base_url = Url::parse(base_url).ok();
+
+    let mut links = IndexSet::new();
+    collect(elements, base_url.as_ref(), &mut links);
+    links.into_iter().collect()
+}
+
+fn is_external(href: &str, base_url: Option<&Url>) -> bool {
+    let Ok(url) = Url::parse(href) else {
+        return false;
+    };
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+
+    !base_url.is_some_and(|base_url| url.origin() == base_url.origin())
+}
+
+/// Sends the given Webmentions, returning the result for each one in order.
+///
+/// Requests are made using `curl`, which must be available on the `PATH`.
+pub async fn send_webmentions(webmentions: &[Webmention]) -> Vec<Result<(), SendWebmentionError>> {
+    let mut results = Vec::with_capacity(webmentions.len());
+
+    for webmention in webmentions {
+        results.push(send_webmention(webmention).await);
+    }
+
+    results
+}
+
+async fn send_webmention(webmention: &Webmention) -> Result<(), SendWebmentionError> {
+    let endpoint = discover_endpoint(&webmention.target).await?;
+
+    curl(
+        &endpoint,
+        &[
+            "--fail",
+            "--data-urlencode",
+            &format!("source={}", webmention.source),
+            "--data-urlencode",
+            &format!("target={}", webmention.target),
+        ],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Discovers the Webmention endpoint for the given target URL.
+async fn discover_endpoint(target: &str) -> Result<String, SendWebmentionError> {
+    let response = curl(target, &["--location", "--include"]).await?;
+    let (headers, body) = split_response(&response);
+
+    let endpoint = headers
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("link").then_some(value)
+        })
+        .find_map(webmention_endpoint_from_link_header)
+        .or_else(|| webmention_endpoint_from_html(body))
+        .ok_or_else(|| SendWebmentionError::NoEndpoint(target.to_owned()))?;
+
+    resolve_endpoint(target, &endpoint)
+        .ok_or_else(|| SendWebmentionError::NoEndpoint(target.to_owned()))
+}
+
+async fn curl(url: &str, args: &[&str]) -> Result<String, SendWebmentionError> {
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--max-time", "30"])
+        .args(args)
+        .arg(url)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(SendWebmentionError::RequestFailed {
+            url: url.to_owned(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Splits a response, as output by `curl --include`, into the headers of the
+/// final response and its body.
+fn split_response(response: &str) -> (&str, &str) {
+    let mut rest = response;
+    let mut headers = "";
+
+    // When redirects are followed, the headers for each response are included.
+    while rest.starts_with("HTTP/") {
+        let Some((response_headers, body)) = rest
+            .split_once("\r\n\r\n")
+            .or_else(|| rest.split_once("\n\n"))
+        else {
+            return (rest, "");
+        };
+
+        headers = response_headers;
+        rest = body;
+    }
+
+    (headers, rest)
+}
+
+fn webmention_endpoint_from_link_header(value: &str) -> Option<String> {
+    static LINK: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"<([^>]*)>\s*;\s*rel="?([^";,]*)"?"#).unwrap());
+
+    LINK.captures_iter(value).find_map(|captures| {
+        let is_webmention = captures[2]
+            .split_whitespace()
+            .any(|rel| rel == "webmention");
+        is_webmention.then(|| captures[1].to_owned())
+    })
+}
+
+fn webmention_endpoint_from_html(html: &str) -> Option<String> {
+    static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<(?:link|a)\b[^>]*>").unwrap());
+    static REL: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?i)\brel\s*=\s*["']([^"']*)["']"#).unwrap());
+    static HREF: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?i)\bhref\s*=\s*["']([^"']*)["']"#).unwrap());
+
+    TAG.find_iter(html).find_map(|tag| {
+        let tag = tag.as_str();
+
+        let rel = REL.captures(tag)?;
+        if !rel[1].split_whitespace().any(|rel| rel == "webmention") {
+            return None;
+        }
+
+        HREF.captures(tag).map(|href| href[1].to_owned())
+    })
+}
+
+/// Resolves a (possibly relative) endpoint against the URL it was discovered at.
+fn resolve_endpoint(target: &str, endpoint: &str) -> Option<String> {
+    let target = Url::parse(target).ok()?;
+
+    target.join(endpoint).ok().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use auk::*;
+    use pretty_assertions::assert_eq;
+
+    use crate::test_site::{render, TestSite};
+
+    use super::*;
+
+    fn webmention(source: &str, target: &str) -> Webmention {
+        Webmention {
+            source: source.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_outgoing_webmentions() {
+        let test_site = TestSite::new("outgoing_webmentions");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write(
+                "first.md",
+                "+++\n+++\n\nSee [IndieWeb](https://indieweb.org/).\n",
+            )
+            .write(
+                "second.md",
+                "+++\n+++\n\nSee [Webmention](https://webmention.net/).\n",
+            );
+
+        let mut site = test_site.builder().build();
+        site.load().unwrap();
+        render(&mut site).unwrap();
+        let previous = site.build_manifest().clone();
+
+        assert_eq!(
+            site.outgoing_webmentions(None),
+            vec![
+                webmention("https://example.com/first/", "https://indieweb.org/"),
+                webmention("https://example.com/second/", "https://webmention.net/"),
+            ]
+        );
+
+        test_site
+            .write(
+                "first.md",
+                "+++\n+++\n\nSee [IndieWeb](https://indieweb.org/) and [Microformats](https://microformats.org/).\n",
+            )
+            .write(
+                "third.md",
+                "+++\n+++\n\nSee [IndieWeb](https://indieweb.org/).\n",
+            );
+
+        let mut site = test_site.builder().build();
+        site.load().unwrap();
+        render(&mut site).unwrap();
+
+        assert_eq!(
+            site.outgoing_webmentions(Some(&previous)),
+            vec![
+                webmention("https://example.com/first/", "https://microformats.org/"),
+                webmention("https://example.com/third/", "https://indieweb.org/"),
+            ]
+        );
+        assert_eq!(
+            site.outgoing_webmentions(Some(site.build_manifest())),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_external_links() {
+        let elements: Vec<Element> = vec![p()
+            .child(
+                a().href("https://example.com/blog/other-post/")
+                    .child("Internal"),
+            )
+            .child(a().href("/about/").child("Relative"))
+            .child(
+                a().href("https://indieweb.org/Webmention")
+                    .child("External"),
+            )
+            .child(span().child(a().href("http://example.org/post").child("Nested external")))
+            .child(
+                a().href("https://indieweb.org/Webmention")
+                    .child("Duplicate"),
+            )
+            .child(a().href("mailto:hello@example.org").child("Email"))
+            .into()];
+
+        assert_eq!(
+            external_links(&elements, "https://example.com/"),
+            vec![
+                "https://indieweb.org/Webmention".to_string(),
+                "http://example.org/post".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_external_links_compare_origins() {
+        let elements: Vec<Element> = vec![p()
+            .child(
+                a().href("https://EXAMPLE.com:443/about/")
+                    .child("Same origin"),
+            )
+            .child(
+                a().href("https://example.com.evil.org/")
+                    .child("Lookalike host"),
+            )
+            .child(a().href("http://example.com/").child("Other scheme"))
+            .child(a().href("https://example.com:8443/").child("Other port"))
+            .child(a().href("https://blog.example.com/").child("Subdomain"))
+            .into()];
+
+        assert_eq!(
+            external_links(&elements, "https://example.com"),
+            vec![
+                "https://example.com.evil.org/".to_string(),
+                "http://example.com/".to_string(),
+                "https://example.com:8443/".to_string(),
+                "https://blog.example.com/".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_webmention_endpoint_discovery() {
+        assert_eq!(
+            webmention_endpoint_from_link_header(
+                r#"<https://example.com/style.css>; rel="stylesheet", <https://example.com/webmention>; rel="webmention""#
+            ),
+            Some("https://example.com/webmention".to_string())
+        );
+        assert_eq!(
+            webmention_endpoint_from_html(
+                r#"<head><link href="/endpoint" rel="me webmention"></head>"#
+            ),
+            Some("/endpoint".to_string())
+        );
+        assert_eq!(
+            webmention_endpoint_from_html(r#"<link rel="stylesheet" href="/style.css">"#),
+            None
+        );
+        assert_eq!(
+            resolve_endpoint("https://example.com/posts/1", "/endpoint"),
+            Some("https://example.com/endpoint".to_string())
+        );
+        assert_eq!(
+            resolve_endpoint("https://example.com/posts/1", ""),
+            Some("https://example.com/posts/1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_split_response() {
+        let response = "HTTP/1.1 301 Moved Permanently\r\nLocation: /new\r\n\r\nHTTP/1.1 200 OK\r\nLink: </webmention>; rel=webmention\r\n\r\n<html></html>";
+
+        assert_eq!(
+            split_response(response),
+            (
+                "HTTP/1.1 200 OK\r\nLink: </webmention>; rel=webmention",
+                "<html></html>"
+            )
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Layouts with named slots that templates can fill in.
+//!
+//! A [`Layout`] wraps a function that builds the page chrome, which reads its
+//! content out of named [`Slots`]. Templates derive from a shared layout by
+//! cloning it and overriding only the slots they care about:
+//!
+//! ```ignore
+//! let base = Layout::new(|slots| {
+//!     html()
+//!         .child(head().child(title().children(slots.get_or("title", || vec!["Razorbill".into()]))))
+//!         .child(body().children(slots.get("content")))
+//! });
+//!
+//! let page = base.clone().slot("title", ["About"]).slot("content", [p().child("Hello!")]);
+//! let element = page.render();
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use auk::{Element, HtmlElement};
+
+/// The content provided to each named slot of a [`Layout`].
+#[derive(Clone, Default)]
+pub struct Slots {
+    slots: HashMap<String, Vec<Element>>,
+}
+
+impl Slots {
+    /// Returns the content of the slot with the given name.
+    ///
+    /// Returns no elements if the slot was not filled.
+    pub fn get(&self, name: &str) -> Vec<Element> {
+        self.slots.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Returns the content of the slot with the given name, falling back to
+    /// the provided default content if the slot was not filled.
+    pub fn get_or(&self, name: &str, default: impl FnOnce() -> Vec<Element>) -> Vec<Element> {
+        self.slots.get(name).cloned().unwrap_or_else(default)
+    }
+
+    /// Returns whether the slot with the given name was filled.
+    pub fn has(&self, name: &str) -> bool {
+        self.slots.contains_key(name)
+    }
+}
+
+/// A layout with named slots.
+#[derive(Clone)]
+pub struct Layout {
+    render: Arc<dyn Fn(&Slots) -> HtmlElement + Send + Sync>,
+    slots: Slots,
+}
+
+impl Layout {
+    /// Returns a new [`Layout`] that is built using the given function.
+    pub fn new(render: impl Fn(&Slots) -> HtmlElement + Send + Sync + 'static) -> Self {
+        Self {
+            render: Arc::new(render),
+            slots: Slots::default(),
+        }
+    }
+
+    /// Fills the slot with the given name, replacing any existing content.
+    pub fn slot<T: Into<Element>>(
+        mut self,
+        name: impl Into<String>,
+        children: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.slots
+            .slots
+            .insert(name.into(), children.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Appends content to the slot with the given name, after any existing content.
+    pub fn append_slot<T: Into<Element>>(
+        mut self,
+        name: impl Into<String>,
+        children: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.slots
+            .slots
+            .entry(name.into())
+            .or_default()
+            .extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    /// Renders the layout with the slots that have been filled.
+    pub fn render(&self) -> HtmlElement {
+        (self.render)(&self.slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use auk::renderer::HtmlElementRenderer;
+    use auk::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn base_layout() -> Layout {
+        Layout::new(|slots| {
+            div()
+                .child(h1().children(slots.get_or("title", || vec!["Razorbill".into()])))
+                .child(div().children(slots.get("content")))
+        })
+    }
+
+    fn render_to_string(layout: &Layout) -> String {
+        HtmlElementRenderer::new()
+            .render_to_string(&layout.render())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_layout_defaults() {
+        assert_eq!(
+            render_to_string(&base_layout()),
+            "<div><h1>Razorbill</h1><div></div></div>"
+        );
+    }
+
+    #[test]
+    fn test_layout_overrides() {
+        let layout = base_layout()
+            .slot("title", ["About"])
+            .slot("content", [p().child("Hello!")])
+            .append_slot("content", [p().child("Goodbye!")]);
+
+        assert_eq!(
+            render_to_string(&layout),
+            "<div><h1>About</h1><div><p>Hello!</p><p>Goodbye!</p></div></div>"
+        );
+    }
+}
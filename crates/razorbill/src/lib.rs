@@ -1,16 +1,50 @@
 #![doc = include_str!("../README.md")]
 
+pub mod a11y;
+pub mod absolute_url;
+pub mod analytics;
+mod build_overlay;
+mod build_report;
+pub mod config;
 pub mod content;
+pub mod csp;
 mod date;
-mod feed;
+pub mod deploy;
+pub mod export;
+pub mod feed;
+mod html_validation;
+mod http;
+pub mod i18n;
+mod image;
+pub mod indieweb;
+pub mod layout;
+pub mod lint;
+mod live_reload;
+pub mod manifest;
 pub mod markdown;
+pub mod menu;
+mod parallel;
+pub mod pdf;
 mod permalink;
+mod profile;
+pub mod query;
+pub mod redirects;
 pub mod render;
+mod serve;
 mod site;
 mod sitemap;
+pub mod social_card;
+pub mod static_files;
 mod storage;
 mod style;
+#[cfg(test)]
+mod test_site;
+pub mod testing;
+pub mod versions;
 
+pub use build_report::*;
+pub use profile::*;
+pub use serve::*;
 pub use site::*;
 pub use style::*;
 
@@ -0,0 +1,676 @@
+//! Checks for problems in a site's content.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use auk::Element;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::content::{find_unknown_taxonomies, Page, SortBy};
+use crate::markdown::find_shortcode_calls;
+use crate::Site;
+
+/// The length (in characters) beyond which a title is considered too long.
+///
+/// Search engines truncate titles at around this length.
+const MAX_TITLE_LENGTH: usize = 70;
+
+/// The length (in characters) beyond which a description is considered too long.
+const MAX_DESCRIPTION_LENGTH: usize = 160;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found in a content file.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The name of the rule that found the problem (e.g., `missing-title`).
+    pub rule: String,
+    pub message: String,
+    /// The path to the content file, relative to the content directory.
+    pub path: PathBuf,
+    /// The line in the content file that the problem is on, starting from 1.
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+
+        write!(f, ": {}[{}]: {}", self.severity, self.rule, self.message)
+    }
+}
+
+/// The problems found by [`Site::lint`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize)]
+pub struct LintReport {
+    /// The diagnostics, ordered by path and then by line.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    /// Returns whether any of the diagnostics are errors.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+    }
+}
+
+/// A check that is run against the content of each page, such as a prose
+/// style rule.
+///
+/// Rules are registered with
+/// [`SiteBuilder::add_content_rule`](crate::SiteBuilder::add_content_rule).
+/// Their findings are included in [`Site::lint`], as well as in the
+/// [`BuildReport`](crate::BuildReport) for each build.
+pub trait ContentRule: Send + Sync {
+    /// The name of the rule, which identifies its diagnostics (e.g., `banned-words`).
+    fn name(&self) -> &str;
+
+    /// Checks the content of the given page.
+    fn check(&self, ctx: &ContentRuleContext) -> Vec<RuleViolation>;
+}
+
+/// The page being checked by a [`ContentRule`].
+pub struct ContentRuleContext<'a> {
+    pub page: &'a Page,
+    /// The page's Markdown content, without its front matter.
+    pub markdown: &'a str,
+    /// The page's rendered content.
+    pub elements: &'a [Element],
+}
+
+/// A problem found by a [`ContentRule`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RuleViolation {
+    pub severity: Severity,
+    pub message: String,
+    /// The line in the Markdown content that the problem is on, starting from 1.
+    pub line: Option<usize>,
+}
+
+impl RuleViolation {
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            line: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            line: None,
+        }
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+}
+
+impl Site {
+    /// Checks the site's content for problems.
+    ///
+    /// The site must have been loaded with [`Site::load`] first. Content that
+    /// hasn't been rendered yet is only rendered for the site's content rules,
+    /// so this is cheap enough to run as a pre-commit check.
+    pub fn lint(&self) -> LintReport {
+        let mut linter = Linter::new(self);
+
+        for section in self.sections.values() {
+            let source = SourceFile::read(&section.file.path, &section.raw_content);
+
+            linter.check_lengths(
+                &source,
+                section.meta.title.as_deref(),
+                section.meta.description.as_deref(),
+            );
+            linter.check_content(&source);
+        }
+
+        for page in self.pages.values() {
+            let source = SourceFile::read(&page.file.path, &page.raw_content);
+
+            if page.meta.title.is_none() {
+                linter.report(
+                    &source.path,
+                    None,
+                    Severity::Warning,
+                    "missing-title",
+                    "page has no title",
+                );
+            }
+
+            let parent = page
+                .ancestors
+                .last()
+                .and_then(|path| self.sections.get(path));
+            let is_dated = parent.is_some_and(|section| {
+                matches!(
+                    Option::<SortBy>::from(section.meta.sort_by),
+                    Some(SortBy::Date | SortBy::UpdateDate)
+                )
+            });
+            if is_dated && page.meta.date.is_none() {
+                linter.report(
+                    &source.path,
+                    None,
+                    Severity::Warning,
+                    "missing-date",
+                    "page is in a section sorted by date, but has no date",
+                );
+            }
+
+            linter.check_lengths(
+                &source,
+                page.meta.title.as_deref(),
+                page.meta.description.as_deref(),
+            );
+            let is_renderable = linter.check_content(&source);
+
+            if is_renderable && !self.content_rules.is_empty() {
+                let rendered;
+                let elements = if page.content.is_empty() {
+                    rendered = match self.render_markdown(&page.raw_content) {
+                        Ok(rendered) => rendered,
+                        Err(err) => {
+                            linter.report(
+                                &source.path,
+                                None,
+                                Severity::Error,
+                                "invalid-shortcode",
+                                &err.to_string(),
+                            );
+                            continue;
+                        }
+                    };
+                    rendered.as_slice()
+                } else {
+                    page.content.as_slice()
+                };
+
+                linter.check_rules(page, elements);
+            }
+        }
+
+        for unknown in find_unknown_taxonomies(&self.pages, &self.config.taxonomies) {
+            let Some(page) = self.pages.get(&unknown.page) else {
+                continue;
+            };
+
+            let source = SourceFile::read(&page.file.path, &page.raw_content);
+            let line = source.front_matter_line(&unknown.taxonomy);
+
+            linter.report(
+                &source.path,
+                line,
+                Severity::Warning,
+                "unknown-taxonomy",
+                &format!("taxonomy '{}' is not registered", unknown.taxonomy),
+            );
+        }
+
+        LintReport {
+            diagnostics: linter.finish(),
+        }
+    }
+
+    /// Runs the site's content rules against the rendered content of each page.
+    ///
+    /// Unlike [`Site::lint`], this only uses the content that has already been
+    /// loaded, rather than reading each content file again.
+    pub(crate) fn check_content_rules(&self) -> Vec<Diagnostic> {
+        if self.content_rules.is_empty() {
+            return Vec::new();
+        }
+
+        let mut linter = Linter::new(self);
+
+        for page in self.pages.values() {
+            linter.check_rules(page, &page.content);
+        }
+
+        linter.finish()
+    }
+}
+
+struct Linter<'a> {
+    site: &'a Site,
+    shortcode_names: HashSet<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(site: &'a Site) -> Self {
+        Self {
+            site,
+            shortcode_names: site
+                .shortcodes()
+                .into_iter()
+                .map(|shortcode| shortcode.name)
+                .collect(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Returns the diagnostics, ordered by path and then by line.
+    fn finish(mut self) -> Vec<Diagnostic> {
+        self.diagnostics
+            .sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        self.diagnostics
+    }
+
+    fn report(
+        &mut self,
+        path: &Path,
+        line: Option<usize>,
+        severity: Severity,
+        rule: &str,
+        message: &str,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            rule: rule.to_string(),
+            message: message.to_string(),
+            path: self.site.relative_content_path(path),
+            line,
+        });
+    }
+
+    fn check_lengths(
+        &mut self,
+        source: &SourceFile,
+        title: Option<&str>,
+        description: Option<&str>,
+    ) {
+        let fields = [
+            ("title", title, MAX_TITLE_LENGTH),
+            ("description", description, MAX_DESCRIPTION_LENGTH),
+        ];
+
+        for (key, value, max_length) in fields {
+            let Some(length) = value.map(|value| value.chars().count()) else {
+                continue;
+            };
+
+            if length > max_length {
+                self.report(
+                    &source.path,
+                    source.front_matter_line(key),
+                    Severity::Warning,
+                    &format!("long-{key}"),
+                    &format!("{key} is {length} characters long (the limit is {max_length})"),
+                );
+            }
+        }
+    }
+
+    /// Checks the Markdown content for unknown shortcodes and broken internal links.
+    ///
+    /// Returns whether the content can be rendered, which isn't the case when
+    /// its shortcodes are unknown or can't be parsed.
+    fn check_content(&mut self, source: &SourceFile) -> bool {
+        let content = source.body();
+        let mut is_renderable = true;
+
+        match find_shortcode_calls(content) {
+            Ok(calls) => {
+                for call in calls {
+                    if !self.shortcode_names.contains(&call.name) {
+                        is_renderable = false;
+                        self.report(
+                            &source.path,
+                            Some(source.body_line(call.span.start)),
+                            Severity::Error,
+                            "unknown-shortcode",
+                            &format!("shortcode '{}' is not registered", call.name),
+                        );
+                    }
+                }
+            }
+            Err(err) => {
+                is_renderable = false;
+                self.report(
+                    &source.path,
+                    None,
+                    Severity::Error,
+                    "invalid-shortcode",
+                    &format!("failed to parse shortcodes: {err}"),
+                );
+            }
+        }
+
+        for captures in INTERNAL_LINK.captures_iter(content) {
+            let link = captures.get(1).unwrap();
+            let path = self
+                .site
+                .content_path
+                .join(link.as_str().replacen("@/", "", 1));
+
+            let exists =
+                self.site.pages.contains_key(&path) || self.site.sections.contains_key(&path);
+            if !exists {
+                self.report(
+                    &source.path,
+                    Some(source.body_line(link.start())),
+                    Severity::Error,
+                    "broken-link",
+                    &format!("link to '{}' does not point at any content", link.as_str()),
+                );
+            }
+        }
+
+        is_renderable
+    }
+
+    fn check_rules(&mut self, page: &Page, elements: &[Element]) {
+        let ctx = ContentRuleContext {
+            page,
+            markdown: &page.raw_content,
+            elements,
+        };
+
+        let site = self.site;
+        for rule in &site.content_rules {
+            for violation in rule.check(&ctx) {
+                self.report(
+                    &page.file.path,
+                    violation.line.map(|line| page.content_line_offset + line),
+                    violation.severity,
+                    rule.name(),
+                    &violation.message,
+                );
+            }
+        }
+    }
+}
+
+/// Matches `@/` links in Markdown links and link reference definitions,
+/// capturing the link without its fragment.
+static INTERNAL_LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)(?:\]\(\s*<?|^\s*\[[^\]]+\]:\s*<?)(@/[^)\s>#]+)")
+        .expect("failed to compile regex for internal links")
+});
+
+/// A content file, for locating problems within it.
+struct SourceFile {
+    path: PathBuf,
+    text: String,
+    /// The offset of the Markdown content (after the front matter) in the text.
+    body_start: usize,
+}
+
+impl SourceFile {
+    fn read(path: &Path, raw_content: &str) -> Self {
+        let text = fs::read_to_string(path).unwrap_or_default();
+        let body_start = if text.ends_with(raw_content) {
+            text.len() - raw_content.len()
+        } else {
+            0
+        };
+
+        Self {
+            path: path.to_owned(),
+            text,
+            body_start,
+        }
+    }
+
+    fn body(&self) -> &str {
+        &self.text[self.body_start..]
+    }
+
+    /// Returns the line for the given offset into the Markdown content.
+    fn body_line(&self, offset: usize) -> usize {
+        self.text[..self.body_start + offset].matches('\n').count() + 1
+    }
+
+    /// Returns the line the given key is defined on in the front matter.
+    fn front_matter_line(&self, key: &str) -> Option<usize> {
+        self.text[..self.body_start]
+            .lines()
+            .position(|line| {
+                line.trim_start()
+                    .strip_prefix(key)
+                    .is_some_and(|rest| rest.trim_start().starts_with('='))
+            })
+            .map(|index| index + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::content::Taxonomy;
+    use crate::test_site::TestSite;
+
+    use super::*;
+
+    /// Lints a site that has one page that breaks each rule, along with a page
+    /// that breaks none of them.
+    fn lint_site(name: &str) -> LintReport {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\ntitle = \"Home\"\n+++\n")
+            .write(
+                "blog/_index.md",
+                "+++\ntitle = \"Blog\"\nsort_by = \"date\"\n+++\n",
+            )
+            .write(
+                "blog/good.md",
+                indoc! {r#"
+                    +++
+                    title = "Good"
+                    date = "2024-01-01"
+
+                    [taxonomies]
+                    tags = ["rust"]
+                    +++
+
+                    See [the untitled post](@/blog/untitled.md#intro).
+
+                    {{ youtube(id="dQw4w9WgXcQ") }}
+                "#},
+            )
+            .write(
+                "blog/untitled.md",
+                "+++\ndate = \"2024-01-02\"\n+++\n\nUntitled.\n",
+            )
+            .write("blog/undated.md", "+++\ntitle = \"Undated\"\n+++\n")
+            .write(
+                "blog/long-title.md",
+                &format!(
+                    "+++\ndate = \"2024-01-03\"\ntitle = \"{}\"\n+++\n",
+                    "A".repeat(MAX_TITLE_LENGTH + 1)
+                ),
+            )
+            .write(
+                "blog/unknown-shortcode.md",
+                "+++\ntitle = \"Unknown shortcode\"\ndate = \"2024-01-04\"\n+++\n\n{{ unknown() }}\n",
+            )
+            .write(
+                "blog/broken-link.md",
+                "+++\ntitle = \"Broken link\"\ndate = \"2024-01-05\"\n+++\n\nSee [a missing post](@/blog/missing.md).\n",
+            )
+            .write(
+                "blog/unknown-taxonomy.md",
+                indoc! {r#"
+                    +++
+                    title = "Unknown taxonomy"
+                    date = "2024-01-06"
+
+                    [taxonomies]
+                    categories = ["misc"]
+                    +++
+                "#},
+            );
+
+        let mut site = test_site
+            .builder()
+            .with_builtin_shortcodes()
+            .add_taxonomy(Taxonomy::new("tags"), |_| auk::html(), |_| auk::html())
+            .build();
+        site.load().unwrap();
+        site.lint()
+    }
+
+    /// Returns the paths and lines of the diagnostics for the given rule.
+    fn diagnostics_for(report: &LintReport, rule: &str) -> Vec<(PathBuf, Option<usize>)> {
+        report
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.rule == rule)
+            .map(|diagnostic| (diagnostic.path.clone(), diagnostic.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_missing_title() {
+        let report = lint_site("lint_missing_title");
+
+        assert_eq!(
+            diagnostics_for(&report, "missing-title"),
+            vec![(PathBuf::from("blog/untitled.md"), None)]
+        );
+    }
+
+    #[test]
+    fn test_lint_missing_date() {
+        let report = lint_site("lint_missing_date");
+
+        assert_eq!(
+            diagnostics_for(&report, "missing-date"),
+            vec![(PathBuf::from("blog/undated.md"), None)]
+        );
+    }
+
+    #[test]
+    fn test_lint_long_title() {
+        let report = lint_site("lint_long_title");
+
+        assert_eq!(
+            diagnostics_for(&report, "long-title"),
+            vec![(PathBuf::from("blog/long-title.md"), Some(3))]
+        );
+    }
+
+    #[test]
+    fn test_lint_unknown_shortcode() {
+        let report = lint_site("lint_unknown_shortcode");
+
+        assert_eq!(
+            diagnostics_for(&report, "unknown-shortcode"),
+            vec![(PathBuf::from("blog/unknown-shortcode.md"), Some(6))]
+        );
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_lint_broken_link() {
+        let report = lint_site("lint_broken_link");
+
+        assert_eq!(
+            diagnostics_for(&report, "broken-link"),
+            vec![(PathBuf::from("blog/broken-link.md"), Some(6))]
+        );
+    }
+
+    #[test]
+    fn test_lint_unknown_taxonomy() {
+        let report = lint_site("lint_unknown_taxonomy");
+
+        assert_eq!(
+            diagnostics_for(&report, "unknown-taxonomy"),
+            vec![(PathBuf::from("blog/unknown-taxonomy.md"), Some(6))]
+        );
+    }
+
+    #[test]
+    fn test_lint_good_page() {
+        let report = lint_site("lint_good_page");
+
+        assert_eq!(
+            report
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.path == Path::new("blog/good.md"))
+                .collect::<Vec<_>>(),
+            Vec::<&Diagnostic>::new()
+        );
+    }
+
+    #[test]
+    fn test_source_file_lines() {
+        let text = indoc! {r#"
+            +++
+            title = "Hello"
+            description = "A greeting."
+            +++
+
+            See [the other post](@/blog/other.md) or [this one][1].
+
+            [1]: @/blog/missing.md#intro
+        "#};
+        let raw_content = text.split_once("+++\n\n").unwrap().1;
+
+        let source = SourceFile {
+            path: PathBuf::from("content/blog/hello.md"),
+            text: text.to_string(),
+            body_start: text.len() - raw_content.len(),
+        };
+
+        assert_eq!(source.front_matter_line("title"), Some(2));
+        assert_eq!(source.front_matter_line("description"), Some(3));
+        assert_eq!(source.front_matter_line("date"), None);
+
+        let links = INTERNAL_LINK
+            .captures_iter(source.body())
+            .map(|captures| {
+                let link = captures.get(1).unwrap();
+                (link.as_str(), source.body_line(link.start()))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![("@/blog/other.md", 6), ("@/blog/missing.md", 8)]
+        );
+    }
+}
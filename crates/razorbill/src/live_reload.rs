@@ -0,0 +1,548 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderMap};
+use hyper::upgrade::Upgraded;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use indexmap::IndexSet;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde_json::json;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::WebSocketStream;
+use url::Url;
+
+use crate::http::content_etag;
+use crate::permalink::decode_path;
+use crate::serve::{empty, ResponseBody};
+use crate::storage::SiteContent;
+
+/// [v4.0.2](https://github.com/livereload/livereload-js/blob/v4.0.2/dist/livereload.min.js)
+pub(crate) const LIVE_RELOAD_JS: &str = include_str!("../assets/livereload.min.js");
+
+/// The path that live reload clients connect to.
+pub(crate) const LIVE_RELOAD_PATH: &str = "/livereload";
+
+/// A reload message, along with the pages it applies to.
+#[derive(Debug, Clone)]
+struct Reload {
+    message: String,
+    /// The paths to the pages whose clients should reload, or `None` if every
+    /// client should.
+    pages: Option<Arc<HashSet<String>>>,
+}
+
+impl Reload {
+    /// Returns whether a client viewing the page at the given path should
+    /// reload.
+    ///
+    /// Clients that haven't told us which page they're viewing always reload.
+    fn applies_to(&self, page: Option<&str>) -> bool {
+        match (&self.pages, page) {
+            (Some(pages), Some(page)) => pages.contains(page),
+            _ => true,
+        }
+    }
+}
+
+/// Broadcasts reload messages to all of the connected live reload clients.
+#[derive(Clone)]
+pub(crate) struct LiveReloadBroadcaster {
+    tx: broadcast::Sender<Reload>,
+}
+
+impl LiveReloadBroadcaster {
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(16);
+
+        Self { tx }
+    }
+
+    /// Instructs all connected clients to reload the given path.
+    pub fn reload(&self, path: &str) {
+        // Sending only fails when there are no clients connected, which is fine.
+        let _ = self.tx.send(Reload {
+            message: reload_message(path),
+            pages: None,
+        });
+    }
+
+    /// Instructs the clients viewing any of the given pages to reload.
+    pub fn reload_pages(&self, pages: HashSet<String>) {
+        let Some(path) = pages.iter().min().cloned() else {
+            return;
+        };
+
+        let _ = self.tx.send(Reload {
+            message: reload_message(&path),
+            pages: Some(Arc::new(pages)),
+        });
+    }
+
+    /// Returns whether the request is a live reload client trying to connect.
+    pub fn is_connect_request(req: &Request<Incoming>) -> bool {
+        req.uri().path() == LIVE_RELOAD_PATH && is_websocket_upgrade(req.headers())
+    }
+
+    /// Accepts the connection from a live reload client, upgrading it to a WebSocket.
+    pub fn connect(&self, req: Request<Incoming>) -> Response<ResponseBody> {
+        let reloads = self.tx.subscribe();
+
+        accept_websocket(req, "live reload", |socket| handle_client(socket, reloads))
+    }
+}
+
+/// Accepts a WebSocket connection, upgrading the request and handing the
+/// socket to `handle` once the upgrade completes.
+///
+/// The kind of connection is used when reporting errors.
+pub(crate) fn accept_websocket<F, Fut>(
+    mut req: Request<Incoming>,
+    kind: &'static str,
+    handle: F,
+) -> Response<ResponseBody>
+where
+    F: FnOnce(WebSocketStream<TokioIo<Upgraded>>) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), tungstenite::Error>> + Send,
+{
+    let Some(key) = req.headers().get(header::SEC_WEBSOCKET_KEY) else {
+        let mut bad_request = Response::new(empty());
+        *bad_request.status_mut() = StatusCode::BAD_REQUEST;
+        return bad_request;
+    };
+
+    let accept_key = derive_accept_key(key.as_bytes());
+    let on_upgrade = hyper::upgrade::on(&mut req);
+
+    tokio::task::spawn(async move {
+        let upgraded = match on_upgrade.await {
+            Ok(upgraded) => upgraded,
+            Err(err) => {
+                eprintln!("Failed to upgrade {kind} connection: {err}");
+                return;
+            }
+        };
+
+        let socket =
+            WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None).await;
+
+        if let Err(err) = handle(socket).await {
+            eprintln!("Error in {kind} connection: {err}");
+        }
+    });
+
+    Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::CONNECTION, "upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept_key)
+        .body(empty())
+        .unwrap()
+}
+
+pub(crate) fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    let header_contains = |name: header::HeaderName, value: &str| {
+        headers
+            .get(name)
+            .and_then(|header| header.to_str().ok())
+            .map(|header| {
+                header
+                    .split(',')
+                    .any(|part| part.trim().eq_ignore_ascii_case(value))
+            })
+            .unwrap_or(false)
+    };
+
+    header_contains(header::CONNECTION, "upgrade") && header_contains(header::UPGRADE, "websocket")
+}
+
+/// Speaks the live reload protocol with a connected client.
+async fn handle_client(
+    mut socket: WebSocketStream<TokioIo<Upgraded>>,
+    mut reloads: broadcast::Receiver<Reload>,
+) -> Result<(), tungstenite::Error> {
+    // The path to the page the client is viewing, once it tells us.
+    let mut page = None;
+
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                let Some(message) = message else {
+                    return Ok(());
+                };
+
+                match message? {
+                    Message::Text(text) if text.contains("\"hello\"") => {
+                        socket.send(Message::Text(hello_message())).await?;
+                    }
+                    Message::Text(text) if text.contains("\"info\"") => {
+                        page = client_page_path(&text).or(page);
+                    }
+                    Message::Close(_) => return Ok(()),
+                    _ => {}
+                }
+            }
+            reload = reloads.recv() => {
+                match reload {
+                    Ok(reload) => {
+                        if reload.applies_to(page.as_deref()) {
+                            socket.send(Message::Text(reload.message)).await?;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Returns the path to the page a live reload client is viewing, from the
+/// `info` message it sends after the handshake.
+fn client_page_path(message: &str) -> Option<String> {
+    let message = serde_json::from_str::<serde_json::Value>(message).ok()?;
+    if message.get("command")?.as_str()? != "info" {
+        return None;
+    }
+
+    let url = Url::parse(message.get("url")?.as_str()?).ok()?;
+    let path = decode_path(url.path());
+
+    Some(path.trim_end_matches("index.html").to_string())
+}
+
+/// Returns the entity tags of the rendered content of the site, keyed by
+/// path, for finding out which pages a rebuild changed.
+pub(crate) fn content_etags(content: &SiteContent) -> HashMap<String, String> {
+    content
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(path, content)| (path.clone(), content_etag(content)))
+        .collect()
+}
+
+/// Returns the paths to the content that was added, changed, or removed
+/// between the two sets of entity tags.
+pub(crate) fn changed_content(
+    before: &HashMap<String, String>,
+    after: &HashMap<String, String>,
+) -> HashSet<String> {
+    let changed = after
+        .iter()
+        .filter(|(path, etag)| before.get(*path) != Some(etag))
+        .map(|(path, _)| path.clone());
+    let removed = before
+        .keys()
+        .filter(|path| !after.contains_key(*path))
+        .cloned();
+
+    changed.chain(removed).collect()
+}
+
+/// Returns the response to a live reload client's handshake.
+fn hello_message() -> String {
+    let message = json!({
+        "command": "hello",
+        "protocols": ["http://livereload.com/protocols/official-7"],
+        "serverName": "Razorbill"
+    });
+
+    serde_json::to_string(&message).unwrap()
+}
+
+/// The amount of time to wait for further filesystem events before rebuilding.
+///
+/// Editors tend to emit several events for a single save, so we wait for them
+/// to settle down rather than rebuilding for each one.
+pub(crate) const DEBOUNCE_WINDOW: Duration = Duration::from_millis(200);
+
+/// Waits for the next batch of changed paths from the filesystem watcher.
+///
+/// Events that arrive within [`DEBOUNCE_WINDOW`] of each other are coalesced,
+/// and each changed path is only reported once.
+///
+/// Returns `None` once the watcher has shut down.
+pub(crate) async fn next_changed_paths(
+    watcher_rx: &mut UnboundedReceiver<Event>,
+) -> Option<Vec<PathBuf>> {
+    let mut changed_paths = IndexSet::new();
+
+    loop {
+        let event = if changed_paths.is_empty() {
+            watcher_rx.recv().await?
+        } else {
+            match tokio::time::timeout(DEBOUNCE_WINDOW, watcher_rx.recv()).await {
+                Ok(Some(event)) => event,
+                Ok(None) | Err(_) => break,
+            }
+        };
+
+        match event.kind {
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+                changed_paths.extend(event.paths);
+            }
+            _ => {}
+        }
+    }
+
+    Some(changed_paths.into_iter().collect())
+}
+
+/// The directories that are watched for changes while serving a site.
+///
+/// The site's root is watched as well, so that directories that don't exist
+/// yet (e.g., a `sass/` directory added while serving) are watched as soon as
+/// they are created.
+pub(crate) struct WatchedDirectories {
+    root_path: PathBuf,
+    directories: Vec<PathBuf>,
+    watched: HashSet<PathBuf>,
+}
+
+impl WatchedDirectories {
+    pub fn new(root_path: PathBuf, directories: Vec<PathBuf>) -> Self {
+        Self {
+            root_path,
+            directories,
+            watched: HashSet::new(),
+        }
+    }
+
+    /// Watches the directories that have been created since the last update.
+    ///
+    /// Directories that have been removed are forgotten, so that they are
+    /// watched again if they are recreated.
+    pub fn update(&mut self, watcher: &mut impl Watcher) -> notify::Result<()> {
+        if self.watched.insert(self.root_path.clone()) {
+            watcher.watch(&self.root_path, RecursiveMode::NonRecursive)?;
+        }
+
+        for directory in &self.directories {
+            if directory.is_dir() {
+                if self.watched.insert(directory.clone()) {
+                    watcher.watch(directory, RecursiveMode::Recursive)?;
+                }
+            } else if self.watched.remove(directory) {
+                // The watch may already be gone along with the directory.
+                let _ = watcher.unwatch(directory);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether a change to the given path should trigger a rebuild.
+    ///
+    /// Changes to other files in the site's root (e.g., the output directory
+    /// or editor swap files) are ignored.
+    pub fn is_relevant(&self, path: &Path) -> bool {
+        self.directories
+            .iter()
+            .any(|directory| path.starts_with(directory))
+    }
+}
+
+/// Returns the message instructing live reload clients to reload the given path.
+///
+/// When the path refers to a stylesheet the client will swap it in place,
+/// otherwise it reloads the whole page.
+pub(crate) fn reload_message(path: &str) -> String {
+    let message = json!({
+        "command": "reload",
+        "path": path,
+        "originalPath": "",
+        "liveCSS": true,
+        "liveImg": true,
+        "protocol": ["http://livereload.com/protocols/official-7"]
+    });
+
+    serde_json::to_string(&message).unwrap()
+}
+
+/// The path sent to live reload clients when the whole page should reload.
+pub(crate) const FULL_RELOAD_PATH: &str = "/";
+
+/// Returns the paths that live reload clients should reload in response to
+/// the given changed files.
+///
+/// If every changed file is a stylesheet in the Sass directory then the paths
+/// to the compiled stylesheets are returned, so that they can be hot-swapped.
+/// Any other change results in a full page reload.
+pub(crate) fn live_reload_paths(
+    sass_path: Option<&Path>,
+    changed_paths: &[PathBuf],
+) -> Vec<String> {
+    let stylesheet_paths = changed_paths
+        .iter()
+        .map(|path| sass_path.and_then(|sass_path| compiled_stylesheet_path(sass_path, path)))
+        .collect::<Option<Vec<_>>>();
+
+    match stylesheet_paths {
+        Some(mut stylesheet_paths) if !stylesheet_paths.is_empty() => {
+            stylesheet_paths.sort();
+            stylesheet_paths.dedup();
+            stylesheet_paths
+        }
+        _ => vec![FULL_RELOAD_PATH.to_string()],
+    }
+}
+
+/// Returns the URL path to the stylesheet compiled from the Sass file at the given path.
+///
+/// Partials don't have a stylesheet of their own, but live reload clients will
+/// reload all of the stylesheets on the page when they don't find a match.
+fn compiled_stylesheet_path(sass_path: &Path, path: &Path) -> Option<String> {
+    let relative_path = path.strip_prefix(sass_path).ok().or_else(|| {
+        let sass_path = sass_path.canonicalize().ok()?;
+        path.strip_prefix(sass_path).ok()
+    })?;
+
+    match relative_path.extension()?.to_str()? {
+        "sass" | "scss" | "css" => {}
+        _ => return None,
+    }
+
+    let stylesheet_path = relative_path
+        .with_extension("css")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some(format!("/{stylesheet_path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use notify::event::{CreateKind, ModifyKind};
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+
+    #[test]
+    fn test_watched_directories_is_relevant() {
+        let watched_directories = WatchedDirectories::new(
+            PathBuf::from("site"),
+            vec![PathBuf::from("site/content"), PathBuf::from("site/sass")],
+        );
+
+        assert!(watched_directories.is_relevant(Path::new("site/content/blog/hello.md")));
+        assert!(watched_directories.is_relevant(Path::new("site/sass")));
+        assert!(!watched_directories.is_relevant(Path::new("site/public/index.html")));
+        assert!(!watched_directories.is_relevant(Path::new("site/.notes.md.swp")));
+    }
+
+    #[tokio::test]
+    async fn test_next_changed_paths_coalesces_events() {
+        let (watcher_tx, mut watcher_rx) = unbounded_channel();
+
+        let style_path = PathBuf::from("site/sass/style.scss");
+        let page_path = PathBuf::from("site/content/about.md");
+
+        watcher_tx
+            .send(Event::new(EventKind::Create(CreateKind::File)).add_path(style_path.clone()))
+            .unwrap();
+        watcher_tx
+            .send(Event::new(EventKind::Modify(ModifyKind::Any)).add_path(style_path.clone()))
+            .unwrap();
+        watcher_tx
+            .send(Event::new(EventKind::Modify(ModifyKind::Any)).add_path(page_path.clone()))
+            .unwrap();
+        drop(watcher_tx);
+
+        assert_eq!(
+            next_changed_paths(&mut watcher_rx).await,
+            Some(vec![style_path, page_path])
+        );
+        assert_eq!(next_changed_paths(&mut watcher_rx).await, None);
+    }
+
+    #[test]
+    fn test_client_page_path() {
+        assert_eq!(
+            client_page_path(
+                r#"{"command":"info","plugins":{},"url":"http://127.0.0.1:3000/blog/hello%20world/"}"#
+            ),
+            Some("/blog/hello world/".to_string())
+        );
+        assert_eq!(
+            client_page_path(r#"{"command":"info","url":"http://127.0.0.1:3000/index.html"}"#),
+            Some("/".to_string())
+        );
+        assert_eq!(client_page_path(r#"{"command":"hello"}"#), None);
+    }
+
+    #[test]
+    fn test_changed_content() {
+        let etags = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(path, etag)| (path.to_string(), etag.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let before = etags(&[("/", "a"), ("/about/", "b"), ("/blog/old/", "c")]);
+        let after = etags(&[("/", "a"), ("/about/", "x"), ("/blog/new/", "d")]);
+
+        let mut changed = changed_content(&before, &after)
+            .into_iter()
+            .collect::<Vec<_>>();
+        changed.sort();
+
+        assert_eq!(changed, vec!["/about/", "/blog/new/", "/blog/old/"]);
+    }
+
+    #[test]
+    fn test_reload_applies_to() {
+        let reload = Reload {
+            message: String::new(),
+            pages: Some(Arc::new(HashSet::from(["/about/".to_string()]))),
+        };
+
+        assert!(reload.applies_to(Some("/about/")));
+        assert!(!reload.applies_to(Some("/")));
+        assert!(reload.applies_to(None));
+    }
+
+    #[test]
+    fn test_live_reload_paths() {
+        let sass_path = Path::new("site/sass");
+
+        assert_eq!(
+            live_reload_paths(
+                Some(sass_path),
+                &[
+                    PathBuf::from("site/sass/style.scss"),
+                    PathBuf::from("site/sass/pages/prose.sass"),
+                    PathBuf::from("site/sass/style.scss"),
+                ]
+            ),
+            vec!["/pages/prose.css".to_string(), "/style.css".to_string()]
+        );
+        assert_eq!(
+            live_reload_paths(
+                Some(sass_path),
+                &[
+                    PathBuf::from("site/sass/style.scss"),
+                    PathBuf::from("site/content/_index.md"),
+                ]
+            ),
+            vec!["/".to_string()]
+        );
+        assert_eq!(
+            live_reload_paths(None, &[PathBuf::from("site/sass/style.scss")]),
+            vec!["/".to_string()]
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::content::ContentDocument;
+use crate::markdown::ShortcodeDescriptor;
+use crate::Site;
+
+/// A record of the permalinks produced for each piece of content in a build.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermalinkManifest {
+    pub entries: Vec<PermalinkManifestEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct PermalinkManifestEntry {
+    /// The path to the content file, relative to the content directory.
+    pub source: PathBuf,
+    /// The path portion of the permalink (e.g., `/blog/hello-world/`).
+    pub permalink: String,
+    pub title: Option<String>,
+}
+
+impl PermalinkManifest {
+    /// Reads a [`PermalinkManifest`] from the JSON file at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// A record of every file produced by a build.
+///
+/// This is written to `manifest.json` at the end of each build, so that
+/// deployment tools can tell which files changed between builds.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// The files in the build, ordered by path.
+    pub files: Vec<BuildManifestEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BuildManifestEntry {
+    /// The path to the file, relative to the output directory (e.g., `blog/hello-world/index.html`).
+    pub path: String,
+    /// The path portion of the permalink the file is served from (e.g., `/blog/hello-world/`).
+    pub permalink: String,
+    /// The size of the file, in bytes.
+    pub size: u64,
+    /// The hex-encoded SHA-256 hash of the file's contents.
+    pub hash: String,
+    /// The path to the content file the file was rendered from, relative to
+    /// the content directory.
+    pub source: Option<PathBuf>,
+    /// The links to other sites in the page the file was rendered from, so
+    /// that the links added in later builds can be told apart.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<String>,
+}
+
+impl BuildManifest {
+    /// Reads a [`BuildManifest`] from the JSON file at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Returns the entry for the file at the given path, if it is in the build.
+    pub fn get(&self, path: &str) -> Option<&BuildManifestEntry> {
+        self.files
+            .binary_search_by(|entry| entry.path.as_str().cmp(path))
+            .ok()
+            .map(|index| &self.files[index])
+    }
+}
+
+/// A record of the shortcodes registered for a site.
+///
+/// This is written to `shortcodes.json` with each build, so that editor
+/// tooling and content linters can validate shortcode usage in Markdown files.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ShortcodeManifest {
+    /// The shortcodes, ordered by name.
+    pub shortcodes: Vec<ShortcodeDescriptor>,
+}
+
+impl ShortcodeManifest {
+    /// Reads a [`ShortcodeManifest`] from the JSON file at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Returns the hex-encoded SHA-256 hash of the given content.
+pub(crate) fn content_hash(content: &[u8]) -> String {
+    hex_digest(Sha256::digest(content).as_slice())
+}
+
+pub(crate) fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The differences between the permalinks in a previous build and the current one.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PermalinkDiff {
+    /// The content whose permalink changed since the previous build.
+    pub moved: Vec<MovedPermalink>,
+    /// The permalinks from the previous build that no longer point at any content.
+    pub removed: Vec<PermalinkManifestEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MovedPermalink {
+    /// The path to the content file, relative to the content directory.
+    pub source: PathBuf,
+    pub previous_permalink: String,
+    pub current_permalink: String,
+}
+
+impl MovedPermalink {
+    /// Returns the alias that should be added to the content's front matter
+    /// so that the previous permalink redirects to the current one.
+    pub fn suggested_alias(&self) -> &str {
+        &self.previous_permalink
+    }
+}
+
+impl Site {
+    /// Returns the [`PermalinkManifest`] for the currently-loaded content.
+    pub fn permalink_manifest(&self) -> PermalinkManifest {
+        let sections = self
+            .sections
+            .values()
+            .map(|section| PermalinkManifestEntry {
+                source: self.relative_content_path(&section.file.path),
+                permalink: section.permalink.path().to_owned(),
+                title: section.meta.title.clone(),
+            });
+        let pages = self.pages.values().map(|page| PermalinkManifestEntry {
+            source: self.relative_content_path(&page.file.path),
+            permalink: page.permalink.path().to_owned(),
+            title: page.meta.title.clone(),
+        });
+
+        let mut entries = sections.chain(pages).collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.source.cmp(&b.source));
+
+        PermalinkManifest { entries }
+    }
+
+    /// Compares the permalinks for the currently-loaded content against the
+    /// manifest from a previous build.
+    ///
+    /// Renamed content files are matched up with their previous entry by
+    /// title or, failing that, by file name.
+    pub fn diff_permalinks(&self, previous: &PermalinkManifest) -> PermalinkDiff {
+        let current = self.permalink_manifest();
+
+        let current_by_source = current
+            .entries
+            .iter()
+            .map(|entry| (entry.source.as_path(), entry))
+            .collect::<HashMap<_, _>>();
+        let previous_sources = previous
+            .entries
+            .iter()
+            .map(|entry| entry.source.as_path())
+            .collect::<BTreeSet<_>>();
+        let current_permalinks = current
+            .entries
+            .iter()
+            .map(|entry| entry.permalink.as_str())
+            .chain(self.aliases())
+            .collect::<BTreeSet<_>>();
+
+        let added = current
+            .entries
+            .iter()
+            .filter(|entry| !previous_sources.contains(entry.source.as_path()))
+            .collect::<Vec<_>>();
+
+        let mut diff = PermalinkDiff::default();
+
+        for previous_entry in &previous.entries {
+            if current_permalinks.contains(previous_entry.permalink.as_str()) {
+                continue;
+            }
+
+            let current_entry = current_by_source
+                .get(previous_entry.source.as_path())
+                .copied()
+                .or_else(|| {
+                    added
+                        .iter()
+                        .find(|entry| {
+                            previous_entry.title.is_some() && entry.title == previous_entry.title
+                        })
+                        .or_else(|| {
+                            added.iter().find(|entry| {
+                                entry.source.file_name() == previous_entry.source.file_name()
+                            })
+                        })
+                        .copied()
+                });
+
+            match current_entry {
+                Some(current_entry) => diff.moved.push(MovedPermalink {
+                    source: current_entry.source.clone(),
+                    previous_permalink: previous_entry.permalink.clone(),
+                    current_permalink: current_entry.permalink.clone(),
+                }),
+                None => diff.removed.push(previous_entry.clone()),
+            }
+        }
+
+        diff
+    }
+
+    /// Writes the suggested aliases from the given [`PermalinkDiff`] into the
+    /// front matter of the corresponding content files.
+    pub fn write_suggested_aliases(&self, diff: &PermalinkDiff) -> Result<()> {
+        for moved in &diff.moved {
+            let path = self.content_path.join(&moved.source);
+
+            let mut document = ContentDocument::read(&path)?;
+            document.add_alias(moved.suggested_alias());
+            document.write(&path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns all of the aliases declared by the loaded content.
+    fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.sections
+            .values()
+            .flat_map(|section| section.meta.aliases.iter())
+            .chain(
+                self.pages
+                    .values()
+                    .flat_map(|page| page.meta.aliases.iter()),
+            )
+            .map(|alias| alias.as_str())
+    }
+
+    pub(crate) fn relative_content_path(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.content_path)
+            .unwrap_or(path)
+            .to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::test_site::TestSite;
+
+    use super::*;
+
+    fn entry(source: &str, permalink: &str, title: Option<&str>) -> PermalinkManifestEntry {
+        PermalinkManifestEntry {
+            source: PathBuf::from(source),
+            permalink: permalink.to_string(),
+            title: title.map(|title| title.to_string()),
+        }
+    }
+
+    fn test_site(name: &str) -> TestSite {
+        let site = TestSite::new(name);
+        site.write("_index.md", "+++\ntitle = \"Home\"\n+++\n")
+            .write("blog/_index.md", "+++\ntitle = \"Blog\"\n+++\n")
+            .write("notes/_index.md", "+++\ntitle = \"Notes\"\n+++\n")
+            .write(
+                "blog/unchanged.md",
+                "+++\ntitle = \"Unchanged\"\naliases = [\"/blog/older-unchanged/\"]\n+++\n",
+            )
+            .write(
+                "blog/renamed.md",
+                "+++\ntitle = \"Renamed\"\n+++\n\nSome content.\n",
+            )
+            .write("notes/untitled.md", "+++\n+++\n")
+            .write("blog/added.md", "+++\ntitle = \"Added\"\n+++\n");
+        site
+    }
+
+    fn previous_manifest() -> PermalinkManifest {
+        PermalinkManifest {
+            entries: vec![
+                entry("_index.md", "/", Some("Home")),
+                entry("blog/_index.md", "/blog/", Some("Blog")),
+                entry("blog/unchanged.md", "/blog/unchanged/", Some("Unchanged")),
+                entry(
+                    "blog/old-unchanged.md",
+                    "/blog/older-unchanged/",
+                    Some("Unchanged"),
+                ),
+                entry("blog/old-name.md", "/blog/old-name/", Some("Renamed")),
+                entry("blog/untitled.md", "/blog/untitled/", None),
+                entry("blog/deleted.md", "/blog/deleted/", Some("Deleted")),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_diff_permalinks() {
+        let test_site = test_site("diff_permalinks");
+        let site = test_site.load();
+
+        let diff = site.diff_permalinks(&previous_manifest());
+
+        assert_eq!(
+            diff,
+            PermalinkDiff {
+                moved: vec![
+                    MovedPermalink {
+                        source: PathBuf::from("blog/renamed.md"),
+                        previous_permalink: "/blog/old-name/".to_string(),
+                        current_permalink: "/blog/renamed/".to_string(),
+                    },
+                    MovedPermalink {
+                        source: PathBuf::from("notes/untitled.md"),
+                        previous_permalink: "/blog/untitled/".to_string(),
+                        current_permalink: "/notes/untitled/".to_string(),
+                    },
+                ],
+                removed: vec![entry("blog/deleted.md", "/blog/deleted/", Some("Deleted"))],
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_permalinks_with_no_changes() {
+        let test_site = test_site("diff_permalinks_with_no_changes");
+        let site = test_site.load();
+
+        let diff = site.diff_permalinks(&site.permalink_manifest());
+
+        assert_eq!(diff, PermalinkDiff::default());
+    }
+
+    #[test]
+    fn test_write_suggested_aliases() {
+        let test_site = test_site("write_suggested_aliases");
+        let site = test_site.load();
+
+        let diff = site.diff_permalinks(&previous_manifest());
+        site.write_suggested_aliases(&diff).unwrap();
+
+        assert_eq!(
+            test_site.read("blog/renamed.md"),
+            indoc! {r#"
+                +++
+                title = "Renamed"
+                aliases = ["/blog/old-name/"]
+                +++
+
+                Some content.
+            "#}
+        );
+        assert_eq!(
+            test_site.read("notes/untitled.md"),
+            indoc! {r#"
+                +++
+                aliases = ["/blog/untitled/"]
+                +++
+            "#}
+        );
+        assert_eq!(
+            test_site.read("blog/added.md"),
+            "+++\ntitle = \"Added\"\n+++\n"
+        );
+
+        let site = test_site.load();
+        assert_eq!(
+            site.diff_permalinks(&previous_manifest()),
+            PermalinkDiff {
+                moved: Vec::new(),
+                removed: vec![entry("blog/deleted.md", "/blog/deleted/", Some("Deleted"))],
+            }
+        );
+    }
+}
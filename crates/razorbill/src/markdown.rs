@@ -1,7 +1,18 @@
+mod cache;
+mod figures;
+mod headings;
+mod math;
 mod shortcodes;
 
-use auk_markdown::MarkdownComponents;
+use std::collections::HashMap;
 
+use auk::Element;
+use auk_markdown::{MarkdownComponents, TableOfContents};
+
+pub(crate) use cache::*;
+pub use figures::*;
+pub use headings::*;
+pub use math::*;
 pub use shortcodes::*;
 
 #[derive(Debug, Clone, Copy)]
@@ -15,3 +26,70 @@ impl DefaultMarkdownComponents {
 }
 
 impl MarkdownComponents for DefaultMarkdownComponents {}
+
+/// The site-wide options that affect how Markdown content is rendered.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct MarkdownOptions<'a> {
+    /// Whether math is rendered.
+    pub math: bool,
+    /// How standalone images are rendered as figures, if at all.
+    pub figures: Option<&'a Figures>,
+}
+
+/// Renders the given Markdown text with the given options.
+pub(crate) fn markdown_with_options(
+    input: &str,
+    components: &Box<dyn MarkdownComponents>,
+    shortcodes: &HashMap<String, Shortcode>,
+    options: MarkdownOptions,
+) -> Result<(Vec<Element>, TableOfContents), ShortcodeError> {
+    let (mut elements, table_of_contents) =
+        markdown_with_math(input, components, shortcodes, options.math)?;
+
+    if let Some(figures) = options.figures {
+        elements = render_figures(elements, figures);
+    }
+
+    Ok((elements, table_of_contents))
+}
+
+/// Unwraps the paragraph surrounding the given elements, if they consist of a single paragraph.
+///
+/// This allows short snippets of Markdown, like a description, to be rendered
+/// inline within other elements.
+pub(crate) fn unwrap_paragraph(mut elements: Vec<Element>) -> Vec<Element> {
+    match elements.as_slice() {
+        [Element::Html(element)] if element.tag_name == "p" => match elements.remove(0) {
+            Element::Html(paragraph) => paragraph.children,
+            Element::Text(_) => unreachable!(),
+        },
+        _ => elements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::testing::{render_elements_to_string, MarkdownHarness};
+
+    use super::*;
+
+    #[test]
+    fn test_unwrap_paragraph() {
+        let harness = MarkdownHarness::new()
+            .add_shortcode("greeting", Shortcode::new_thunk(|| "Hello, world!".into()));
+
+        let render_inline =
+            |text: &str| render_elements_to_string(unwrap_paragraph(harness.render_elements(text)));
+
+        assert_eq!(
+            render_inline("A *short* description: {{ greeting() }}"),
+            "A <em>short</em> description: Hello, world!"
+        );
+        assert_eq!(
+            render_inline("First paragraph.\n\nSecond paragraph."),
+            "<p>First paragraph.</p><p>Second paragraph.</p>"
+        );
+    }
+}
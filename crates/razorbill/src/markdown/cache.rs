@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+
+use auk::Element;
+use auk_markdown::{MarkdownComponents, TableOfContents};
+
+use crate::markdown::{
+    markdown_with_options, resolve_headings, Heading, MarkdownOptions, Shortcode, ShortcodeError,
+};
+
+/// A cache of rendered Markdown, keyed by the source text.
+///
+/// The cache lives as long as the [`Site`](crate::Site), so when serving only
+/// the content that changed between renders needs to be rendered again.
+///
+/// The rendered content is shared with the pages and sections it was rendered
+/// for, so it is only copied if it needs to be modified.
+///
+/// The cache is not persisted between builds. The rendered element trees
+/// can't be serialized, and shortcodes are arbitrary functions whose output
+/// may depend on more than the source text, so a result from a previous build
+/// can't be trusted to still be correct.
+#[derive(Default)]
+pub(crate) struct MarkdownCache {
+    entries: HashMap<String, CachedMarkdown>,
+}
+
+struct CachedMarkdown {
+    content: Arc<Vec<Element>>,
+    table_of_contents: TableOfContents,
+    headings: Vec<Heading>,
+    /// Whether the entry has been used since the last call to [`MarkdownCache::evict_unused`].
+    used: bool,
+}
+
+impl MarkdownCache {
+    /// Renders the given Markdown text, reusing the previously rendered
+    /// result if the text has been rendered before.
+    ///
+    /// Text that fails to render isn't cached.
+    pub fn render(
+        &mut self,
+        text: &str,
+        components: &Box<dyn MarkdownComponents>,
+        shortcodes: &HashMap<String, Shortcode>,
+        options: MarkdownOptions,
+    ) -> Result<(Arc<Vec<Element>>, TableOfContents, Vec<Heading>), ShortcodeError> {
+        if !self.entries.contains_key(text) {
+            let (content, table_of_contents, headings) =
+                render_uncached(text, components, shortcodes, options)?;
+
+            self.entries.insert(
+                text.to_owned(),
+                CachedMarkdown {
+                    content,
+                    table_of_contents,
+                    headings,
+                    used: false,
+                },
+            );
+        }
+
+        let entry = self.entries.get_mut(text).unwrap();
+        entry.used = true;
+
+        Ok((
+            Arc::clone(&entry.content),
+            entry.table_of_contents.clone(),
+            entry.headings.clone(),
+        ))
+    }
+
+    /// Evicts the entries that have not been used since the last eviction.
+    ///
+    /// This should be called after each render so that content that no longer
+    /// exists doesn't stay in the cache.
+    pub fn evict_unused(&mut self) {
+        self.entries
+            .retain(|_, entry| mem::replace(&mut entry.used, false));
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Renders the given Markdown text without caching the result.
+///
+/// Unlike content from the cache, the rendered content isn't shared, so it can
+/// be modified without being copied.
+pub(crate) fn render_uncached(
+    text: &str,
+    components: &Box<dyn MarkdownComponents>,
+    shortcodes: &HashMap<String, Shortcode>,
+    options: MarkdownOptions,
+) -> Result<(Arc<Vec<Element>>, TableOfContents, Vec<Heading>), ShortcodeError> {
+    let (mut content, table_of_contents) =
+        markdown_with_options(text, components, shortcodes, options)?;
+    let headings = resolve_headings(&mut content);
+
+    Ok((Arc::new(content), table_of_contents, headings))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use pretty_assertions::assert_eq;
+
+    use crate::markdown::DefaultMarkdownComponents;
+
+    use super::*;
+
+    #[test]
+    fn test_markdown_cache() {
+        let render_count = Arc::new(AtomicUsize::new(0));
+        let shortcodes = HashMap::from_iter([(
+            "count".to_string(),
+            Shortcode::new_thunk({
+                let render_count = render_count.clone();
+                move || {
+                    render_count.fetch_add(1, Ordering::SeqCst);
+                    "counted".into()
+                }
+            }),
+        )]);
+        let components = DefaultMarkdownComponents.boxed();
+
+        let options = MarkdownOptions::default();
+
+        let mut cache = MarkdownCache::default();
+
+        let (first, _, _) = cache
+            .render("First {{ count() }}", &components, &shortcodes, options)
+            .unwrap();
+        let (again, _, _) = cache
+            .render("First {{ count() }}", &components, &shortcodes, options)
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(render_count.load(Ordering::SeqCst), 1);
+
+        cache.evict_unused();
+        cache
+            .render("Second {{ count() }}", &components, &shortcodes, options)
+            .unwrap();
+        assert_eq!(render_count.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.len(), 2);
+
+        cache.evict_unused();
+        assert_eq!(cache.len(), 1);
+
+        cache
+            .render("First {{ count() }}", &components, &shortcodes, options)
+            .unwrap();
+        assert_eq!(render_count.load(Ordering::SeqCst), 3);
+    }
+}
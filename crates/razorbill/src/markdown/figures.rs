@@ -0,0 +1,174 @@
+use auk::*;
+
+/// Rendering of standalone images as figures.
+///
+/// An image that stands alone in a paragraph is rendered as a `<figure>`
+/// instead of an `<img>` inside of a `<p>`, with the image's title (if it has
+/// one) used as the `<figcaption>`:
+///
+/// ```markdown
+/// ![A razorbill](razorbill.jpg "A razorbill in flight.")
+/// ```
+///
+/// Images that appear inline with other content are left as they are, as a
+/// `<figure>` can't be placed inside of a paragraph.
+#[derive(Debug, Clone, Default)]
+pub struct Figures {
+    class: Option<String>,
+    caption_class: Option<String>,
+}
+
+impl Figures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the class applied to each `<figure>`.
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// Sets the class applied to each `<figcaption>`.
+    pub fn caption_class(mut self, caption_class: impl Into<String>) -> Self {
+        self.caption_class = Some(caption_class.into());
+        self
+    }
+
+    fn figure(&self, image: HtmlElement, caption: Option<String>) -> HtmlElement {
+        let figure = with_class(figure(), self.class.as_deref()).child(image);
+
+        match caption {
+            Some(caption) => {
+                figure.child(with_class(figcaption(), self.caption_class.as_deref()).child(caption))
+            }
+            None => figure,
+        }
+    }
+}
+
+fn with_class(element: HtmlElement, class: Option<&str>) -> HtmlElement {
+    match class {
+        Some(class) => element.class(class),
+        None => element,
+    }
+}
+
+/// Replaces the paragraphs in the given elements that only contain an image
+/// with figures.
+pub(crate) fn render_figures(elements: Vec<Element>, figures: &Figures) -> Vec<Element> {
+    elements
+        .into_iter()
+        .map(|element| match element {
+            Element::Html(element) if element.tag_name == "p" => match standalone_image(element) {
+                Ok(mut image) => {
+                    let caption = take_title(&mut image);
+                    figures.figure(image, caption).into()
+                }
+                Err(paragraph) => paragraph.into(),
+            },
+            Element::Html(element) => HtmlElement {
+                tag_name: element.tag_name,
+                attrs: element.attrs,
+                children: render_figures(element.children, figures),
+            }
+            .into(),
+            Element::Text(element) => element.into(),
+        })
+        .collect()
+}
+
+/// Returns the image (or linked image) that the given paragraph consists of,
+/// or the paragraph itself if it contains anything else.
+fn standalone_image(paragraph: HtmlElement) -> Result<HtmlElement, HtmlElement> {
+    let mut children = paragraph.children.iter().filter(|child| match child {
+        Element::Text(text) => !text.text.trim().is_empty(),
+        Element::Html(_) => true,
+    });
+
+    let is_standalone = match (children.next(), children.next()) {
+        (Some(Element::Html(child)), None) => is_image(child),
+        _ => false,
+    };
+
+    if !is_standalone {
+        return Err(paragraph);
+    }
+
+    Ok(paragraph
+        .children
+        .into_iter()
+        .find_map(|child| match child {
+            Element::Html(child) => Some(child),
+            Element::Text(_) => None,
+        })
+        .unwrap())
+}
+
+fn is_image(element: &HtmlElement) -> bool {
+    match element.tag_name.as_str() {
+        "img" => true,
+        "a" => matches!(
+            element.children.as_slice(),
+            [Element::Html(child)] if child.tag_name == "img"
+        ),
+        _ => false,
+    }
+}
+
+/// Removes the title from the given image (or linked image), so that it can be
+/// used as the caption instead.
+fn take_title(element: &mut HtmlElement) -> Option<String> {
+    let image = if element.tag_name == "img" {
+        element
+    } else {
+        match element.children.first_mut() {
+            Some(Element::Html(image)) => image,
+            _ => return None,
+        }
+    };
+
+    let title = image.attrs.get("title").cloned();
+    image.attrs.retain(|name, _| name != "title");
+
+    title.filter(|title| !title.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::testing::render_elements_to_string;
+
+    use super::*;
+
+    #[test]
+    fn test_render_figures() {
+        let elements: Vec<Element> = vec![
+            p().child(
+                img()
+                    .src("razorbill.jpg")
+                    .attr("title", "A razorbill in flight."),
+            )
+            .into(),
+            p().child(a().href("auk.jpg").child(img().src("auk.jpg")))
+                .into(),
+            p().child("An inline ")
+                .child(img().src("puffin.jpg"))
+                .child(".")
+                .into(),
+        ];
+
+        let figures = Figures::new().class("figure").caption_class("caption");
+
+        assert_eq!(
+            render_elements_to_string(render_figures(elements, &figures)),
+            [
+                r#"<figure class="figure"><img src="razorbill.jpg"><figcaption class="caption">A razorbill in flight.</figcaption></figure>"#,
+                r#"<figure class="figure"><a href="auk.jpg"><img src="auk.jpg"></a></figure>"#,
+                r#"<p>An inline <img src="puffin.jpg">.</p>"#,
+            ]
+            .concat()
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+
+use auk::{Element, HtmlElement};
+
+/// A heading within rendered content, for building a table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// The ID of the heading, for linking to it.
+    pub id: String,
+    /// The level of the heading (e.g., `2` for an `<h2>`).
+    pub level: u8,
+    /// The text of the heading.
+    pub text: String,
+    /// The classes applied to the heading.
+    pub classes: Vec<String>,
+    /// The headings nested beneath this one.
+    pub children: Vec<Heading>,
+}
+
+/// Resolves the IDs of the headings in the given elements, and returns the
+/// headings as a tree.
+///
+/// Headings with an explicit ID (e.g., `# Intro { #intro }`) keep it, while
+/// the rest are given an ID derived from their text. Duplicate IDs are made
+/// unique by appending a numeric suffix, and the resolved IDs are written back
+/// to the elements so that links to them work.
+pub(crate) fn resolve_headings(elements: &mut [Element]) -> Vec<Heading> {
+    let mut resolver = HeadingResolver {
+        // IDs on other elements (e.g., footnotes) are reserved up front, so
+        // that headings don't collide with them.
+        reserved_ids: non_heading_ids(elements),
+        used_ids: HashSet::new(),
+        headings: Vec::new(),
+    };
+    resolver.visit(elements);
+
+    let mut tree = Vec::new();
+    for heading in resolver.headings {
+        insert_heading(&mut tree, heading);
+    }
+
+    tree
+}
+
+struct HeadingResolver {
+    reserved_ids: HashSet<String>,
+    used_ids: HashSet<String>,
+    headings: Vec<Heading>,
+}
+
+impl HeadingResolver {
+    fn visit(&mut self, elements: &mut [Element]) {
+        for element in elements {
+            let Element::Html(element) = element else {
+                continue;
+            };
+
+            match heading_level(element) {
+                Some(level) => self.resolve(element, level),
+                None => self.visit(&mut element.children),
+            }
+        }
+    }
+
+    fn resolve(&mut self, element: &mut HtmlElement, level: u8) {
+        let text = text_content(&element.children);
+        let explicit_id = element.attrs.get("id").filter(|id| !id.is_empty());
+
+        let id = match explicit_id {
+            Some(id) if !self.used_ids.contains(id) => id.clone(),
+            Some(id) => self.unique_id(id),
+            None => {
+                let slug = slug::slugify(&text);
+                self.unique_id(if slug.is_empty() { "section" } else { &slug })
+            }
+        };
+
+        self.used_ids.insert(id.clone());
+        element.attrs.insert("id".to_string(), id.clone());
+
+        let classes = element
+            .attrs
+            .get("class")
+            .map(|class| class.split_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default();
+
+        self.headings.push(Heading {
+            id,
+            level,
+            text,
+            classes,
+            children: Vec::new(),
+        });
+    }
+
+    /// Returns the first ID, starting with `base` and then trying `base-1`,
+    /// `base-2`, and so on, that isn't already taken.
+    fn unique_id(&self, base: &str) -> String {
+        let is_taken = |id: &str| self.used_ids.contains(id) || self.reserved_ids.contains(id);
+
+        if !is_taken(base) {
+            return base.to_owned();
+        }
+
+        (1..)
+            .map(|suffix| format!("{base}-{suffix}"))
+            .find(|id| !is_taken(id))
+            .unwrap()
+    }
+}
+
+/// Adds the given heading to the tree, nesting it beneath the last heading
+/// with a lower level.
+fn insert_heading(siblings: &mut Vec<Heading>, heading: Heading) {
+    match siblings.last_mut() {
+        Some(last) if last.level < heading.level => insert_heading(&mut last.children, heading),
+        _ => siblings.push(heading),
+    }
+}
+
+/// Returns the IDs that links into the content can point at: the headings in
+/// its table of contents, along with any other elements that have an ID
+/// (e.g., footnotes).
+pub(crate) fn anchor_ids(headings: &[Heading], elements: &[Element]) -> HashSet<String> {
+    fn collect(headings: &[Heading], ids: &mut HashSet<String>) {
+        for heading in headings {
+            ids.insert(heading.id.clone());
+            collect(&heading.children, ids);
+        }
+    }
+
+    let mut ids = non_heading_ids(elements);
+    collect(headings, &mut ids);
+    ids
+}
+
+fn heading_level(element: &HtmlElement) -> Option<u8> {
+    match element.tag_name.as_str() {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn non_heading_ids(elements: &[Element]) -> HashSet<String> {
+    fn collect(elements: &[Element], ids: &mut HashSet<String>) {
+        for element in elements {
+            let Element::Html(element) = element else {
+                continue;
+            };
+
+            if heading_level(element).is_some() {
+                continue;
+            }
+
+            if let Some(id) = element.attrs.get("id") {
+                ids.insert(id.clone());
+            }
+
+            collect(&element.children, ids);
+        }
+    }
+
+    let mut ids = HashSet::new();
+    collect(elements, &mut ids);
+    ids
+}
+
+fn text_content(elements: &[Element]) -> String {
+    fn collect(elements: &[Element], text: &mut String) {
+        for element in elements {
+            match element {
+                Element::Text(element) => text.push_str(&element.text),
+                Element::Html(element) => collect(&element.children, text),
+            }
+        }
+    }
+
+    let mut text = String::new();
+    collect(elements, &mut text);
+    text.trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use auk::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn heading(id: &str, level: u8, text: &str, children: Vec<Heading>) -> Heading {
+        Heading {
+            id: id.to_string(),
+            level,
+            text: text.to_string(),
+            classes: Vec::new(),
+            children,
+        }
+    }
+
+    #[test]
+    fn test_resolve_headings() {
+        let mut elements: Vec<Element> = vec![
+            h1().attr("id", "home")
+                .class("class1 class2")
+                .child("Homepage")
+                .into(),
+            h2().child("Setup").into(),
+            h3().child("Install ")
+                .child(code().child("razorbill"))
+                .into(),
+            h2().child("Setup").into(),
+            h2().attr("id", "home").child("Again").into(),
+            p().attr("id", "usage").child("A paragraph.").into(),
+            h2().child("Usage").into(),
+        ];
+
+        let headings = resolve_headings(&mut elements);
+
+        assert_eq!(
+            headings,
+            vec![Heading {
+                classes: vec!["class1".to_string(), "class2".to_string()],
+                ..heading(
+                    "home",
+                    1,
+                    "Homepage",
+                    vec![
+                        heading(
+                            "setup",
+                            2,
+                            "Setup",
+                            vec![heading("install-razorbill", 3, "Install razorbill", vec![])]
+                        ),
+                        heading("setup-1", 2, "Setup", vec![]),
+                        heading("home-1", 2, "Again", vec![]),
+                        heading("usage-1", 2, "Usage", vec![]),
+                    ]
+                )
+            }]
+        );
+
+        let mut ids = anchor_ids(&headings, &elements)
+            .into_iter()
+            .collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![
+                "home",
+                "home-1",
+                "install-razorbill",
+                "setup",
+                "setup-1",
+                "usage",
+                "usage-1"
+            ]
+        );
+    }
+}
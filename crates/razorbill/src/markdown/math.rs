@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use auk::*;
+use auk_markdown::{MarkdownComponents, TableOfContents};
+
+use crate::markdown::{markdown_with_shortcodes, Shortcode, ShortcodeError};
+
+const MATH_PLACEHOLDER: &str = "@@RAZORBILL_MATH@@";
+
+/// The URL that the KaTeX assets are loaded from by default.
+const KATEX_ASSETS_URL: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist";
+
+/// Rendering of math in Markdown content.
+///
+/// When enabled, `$...$` (inline) and `$$...$$` (display) expressions, as well
+/// as fenced `math` code blocks, are passed through the Markdown renderer
+/// untouched. They are rendered in the browser with [KaTeX](https://katex.org),
+/// whose assets are added to any page that contains math.
+#[derive(Debug, Clone)]
+pub struct Math {
+    assets_url: String,
+}
+
+impl Math {
+    /// Returns a [`Math`] that renders math with KaTeX.
+    pub fn katex() -> Self {
+        Self {
+            assets_url: KATEX_ASSETS_URL.to_string(),
+        }
+    }
+
+    /// Sets the URL that the KaTeX assets are loaded from (e.g., to self-host them).
+    ///
+    /// The URL should point at KaTeX's `dist` directory.
+    pub fn assets_url(mut self, assets_url: impl Into<String>) -> Self {
+        self.assets_url = assets_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Returns the elements for loading KaTeX, for placing in the `<head>`.
+    pub(crate) fn head_elements(&self) -> Vec<Element> {
+        let assets_url = &self.assets_url;
+
+        vec![
+            link()
+                .rel("stylesheet")
+                .href(format!("{assets_url}/katex.min.css"))
+                .into(),
+            script()
+                .attr("defer", "")
+                .src(format!("{assets_url}/katex.min.js"))
+                .into(),
+            script()
+                .attr("defer", "")
+                .attr("onload", "renderMathInElement(document.body)")
+                .src(format!("{assets_url}/contrib/auto-render.min.js"))
+                .into(),
+        ]
+    }
+}
+
+/// Renders the given Markdown text, with support for math when `math` is `true`.
+pub(crate) fn markdown_with_math(
+    input: &str,
+    components: &Box<dyn MarkdownComponents>,
+    shortcodes: &HashMap<String, Shortcode>,
+    math: bool,
+) -> Result<(Vec<Element>, TableOfContents), ShortcodeError> {
+    if !math {
+        return markdown_with_shortcodes(input, components, shortcodes);
+    }
+
+    let (output, expressions) = extract_math(input);
+    let (elements, table_of_contents) = markdown_with_shortcodes(&output, components, shortcodes)?;
+    let elements = replace_math(elements, &mut expressions.into_iter());
+
+    Ok((elements, table_of_contents))
+}
+
+/// Returns whether the given element contains any rendered math.
+pub(crate) fn contains_math(element: &HtmlElement) -> bool {
+    let is_math = element
+        .attrs
+        .get("class")
+        .is_some_and(|class| class.split_whitespace().any(|class| class == "math"));
+
+    is_math
+        || element.children.iter().any(|child| match child {
+            Element::Html(child) => contains_math(child),
+            Element::Text(_) => false,
+        })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct MathExpression {
+    tex: String,
+    display: bool,
+}
+
+/// Replaces the math in the given Markdown text with placeholders, so that the
+/// Markdown renderer doesn't interpret any of the TeX.
+///
+/// Math inside of code spans and (non-`math`) code blocks is left alone.
+fn extract_math(input: &str) -> (String, Vec<MathExpression>) {
+    let mut output = String::with_capacity(input.len());
+    let mut expressions = Vec::new();
+
+    let mut text = String::new();
+    let mut fence: Option<Fence> = None;
+
+    for line in input.split_inclusive('\n') {
+        let trimmed = line.trim();
+
+        match fence.as_mut() {
+            Some(current) => {
+                if trimmed == current.marker {
+                    if current.is_math {
+                        expressions.push(MathExpression {
+                            tex: current.content.trim().to_string(),
+                            display: true,
+                        });
+                        output.push_str(MATH_PLACEHOLDER);
+                        output.push('\n');
+                    } else {
+                        output.push_str(line);
+                    }
+
+                    fence = None;
+                } else if current.is_math {
+                    current.content.push_str(line);
+                } else {
+                    output.push_str(line);
+                }
+            }
+            None => {
+                let Some(marker) = fence_marker(trimmed) else {
+                    text.push_str(line);
+                    continue;
+                };
+
+                extract_inline_math(&text, &mut output, &mut expressions);
+                text.clear();
+
+                let is_math = trimmed[marker.len()..].trim() == "math";
+                if !is_math {
+                    output.push_str(line);
+                }
+
+                fence = Some(Fence {
+                    marker: marker.to_string(),
+                    opening_line: line.to_string(),
+                    is_math,
+                    content: String::new(),
+                });
+            }
+        }
+    }
+
+    extract_inline_math(&text, &mut output, &mut expressions);
+
+    // An unclosed `math` block is left as it was written.
+    if let Some(fence) = fence.filter(|fence| fence.is_math) {
+        output.push_str(&fence.opening_line);
+        output.push_str(&fence.content);
+    }
+
+    (output, expressions)
+}
+
+struct Fence {
+    marker: String,
+    opening_line: String,
+    is_math: bool,
+    content: String,
+}
+
+/// Returns the marker for the code fence that the given line opens, if any.
+fn fence_marker(line: &str) -> Option<&str> {
+    let fence_char = line
+        .chars()
+        .next()
+        .filter(|char| *char == '`' || *char == '~')?;
+    let len = line.chars().take_while(|char| *char == fence_char).count();
+
+    (len >= 3).then(|| &line[..len])
+}
+
+/// Replaces the `$...$` and `$$...$$` expressions in the given text with placeholders.
+fn extract_inline_math(text: &str, output: &mut String, expressions: &mut Vec<MathExpression>) {
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    let mut last_index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'`' => {
+                let run = count_run(bytes, index, b'`');
+                let closing = text[index + run..]
+                    .find(&"`".repeat(run))
+                    .map(|offset| index + run + offset);
+
+                index = closing.map_or(index + run, |closing| closing + run);
+            }
+            b'$' => {
+                let display = bytes.get(index + 1) == Some(&b'$');
+                let delimiter_len = if display { 2 } else { 1 };
+                let start = index + delimiter_len;
+
+                match find_closing_delimiter(text, start, display) {
+                    Some(end) => {
+                        output.push_str(&text[last_index..index]);
+                        output.push_str(MATH_PLACEHOLDER);
+                        expressions.push(MathExpression {
+                            tex: text[start..end].trim().to_string(),
+                            display,
+                        });
+
+                        index = end + delimiter_len;
+                        last_index = index;
+                    }
+                    None => index = start,
+                }
+            }
+            _ => index += 1,
+        }
+    }
+
+    output.push_str(&text[last_index..]);
+}
+
+fn count_run(bytes: &[u8], start: usize, byte: u8) -> usize {
+    bytes[start..].iter().take_while(|b| **b == byte).count()
+}
+
+/// Returns the index of the delimiter that closes the expression starting at `start`.
+///
+/// Like Pandoc, an inline expression can't start or end with whitespace, and
+/// the closing `$` can't be followed by a digit, so that prices like "$5 and $10"
+/// aren't treated as math.
+fn find_closing_delimiter(text: &str, start: usize, display: bool) -> Option<usize> {
+    if display {
+        return text[start..]
+            .find("$$")
+            .map(|offset| start + offset)
+            .filter(|end| !text[start..*end].trim().is_empty());
+    }
+
+    let bytes = text.as_bytes();
+    if bytes
+        .get(start)
+        .map_or(true, |byte| byte.is_ascii_whitespace())
+    {
+        return None;
+    }
+
+    let mut index = start;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'\n' => return None,
+            b'$' => {
+                let is_closing = !bytes[index - 1].is_ascii_whitespace()
+                    && !bytes.get(index + 1).is_some_and(u8::is_ascii_digit);
+
+                return is_closing.then_some(index);
+            }
+            _ => index += 1,
+        }
+    }
+
+    None
+}
+
+fn replace_math(
+    elements: Vec<Element>,
+    expressions: &mut std::vec::IntoIter<MathExpression>,
+) -> Vec<Element> {
+    let mut new_elements = Vec::with_capacity(elements.len());
+
+    for child in elements {
+        match child {
+            Element::Text(element) => {
+                if !element.text.contains(MATH_PLACEHOLDER) {
+                    new_elements.push(element.into());
+                    continue;
+                }
+
+                let mut text = element.text.as_str();
+                while let Some((before, after)) = text.split_once(MATH_PLACEHOLDER) {
+                    if !before.is_empty() {
+                        new_elements.push(before.into());
+                    }
+
+                    if let Some(expression) = expressions.next() {
+                        new_elements.push(math_element(&expression).into());
+                    }
+
+                    text = after;
+                }
+
+                if !text.is_empty() {
+                    new_elements.push(text.into());
+                }
+            }
+            Element::Html(element) => {
+                new_elements.push(
+                    HtmlElement {
+                        tag_name: element.tag_name,
+                        attrs: element.attrs,
+                        children: replace_math(element.children, expressions),
+                    }
+                    .into(),
+                );
+            }
+        }
+    }
+
+    new_elements
+}
+
+/// Returns the element for the given expression, using the delimiters that
+/// KaTeX's auto-render extension looks for.
+fn math_element(expression: &MathExpression) -> HtmlElement {
+    let tex = escape_html(&expression.tex);
+
+    if expression.display {
+        span()
+            .class("math math-display")
+            .child(format!("\\[{tex}\\]"))
+    } else {
+        span()
+            .class("math math-inline")
+            .child(format!("\\({tex}\\)"))
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::markdown::DefaultMarkdownComponents;
+    use crate::testing::render_elements_to_string;
+
+    use super::*;
+
+    fn inline(tex: &str) -> MathExpression {
+        MathExpression {
+            tex: tex.to_string(),
+            display: false,
+        }
+    }
+
+    fn display(tex: &str) -> MathExpression {
+        MathExpression {
+            tex: tex.to_string(),
+            display: true,
+        }
+    }
+
+    #[test]
+    fn test_extract_math() {
+        let (output, expressions) = extract_math(indoc! {r#"
+            Euler's identity is $e^{i\pi} + 1 = 0$, which costs $5 and $10.
+
+            $$
+            \sum_{n=1}^\infty \frac{1}{n^2} = \frac{\pi^2}{6}
+            $$
+
+            Code like `$x$` and \$y\$ is left alone.
+
+            ```math
+            a_1 < b_1
+            ```
+
+            ```sh
+            echo $HOME $PATH
+            ```
+        "#});
+
+        assert_eq!(
+            output,
+            indoc! {r#"
+                Euler's identity is @@RAZORBILL_MATH@@, which costs $5 and $10.
+
+                @@RAZORBILL_MATH@@
+
+                Code like `$x$` and \$y\$ is left alone.
+
+                @@RAZORBILL_MATH@@
+
+                ```sh
+                echo $HOME $PATH
+                ```
+            "#}
+        );
+        assert_eq!(
+            expressions,
+            vec![
+                inline(r"e^{i\pi} + 1 = 0"),
+                display(r"\sum_{n=1}^\infty \frac{1}{n^2} = \frac{\pi^2}{6}"),
+                display("a_1 < b_1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_with_math() {
+        let components = DefaultMarkdownComponents.boxed();
+        let (elements, _table_of_contents) = markdown_with_math(
+            "The area is $\\pi r_1^2$.",
+            &components,
+            &HashMap::new(),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            render_elements_to_string(elements),
+            r#"<p>The area is <span class="math math-inline">\(\pi r_1^2\)</span>.</p>"#
+        );
+    }
+}
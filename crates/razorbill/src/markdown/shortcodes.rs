@@ -1,22 +1,42 @@
+mod builtin;
 mod parser;
 
 use std::collections::HashMap;
 use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use auk::{Element, HtmlElement};
 use auk_markdown::{render_markdown, MarkdownComponents, TableOfContents};
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use thiserror::Error;
 
 use crate::markdown::shortcodes::parser::parse_document;
 
+pub use builtin::*;
+
 const SHORTCODE_PLACEHOLDER: &str = "@@RAZORBILL_SHORTCODE@@";
 
 pub type RenderShortcode = Arc<dyn Fn(Map<String, Value>) -> Element + Send + Sync>;
 
 pub struct Shortcode {
     pub render: RenderShortcode,
+
+    /// The names of the arguments that may be passed positionally, in order.
+    arg_names: Vec<String>,
+
+    /// The values of any arguments that were not provided in the call.
+    defaults: Map<String, Value>,
+
+    /// The name of the first default that failed to serialize, along with the
+    /// reason, which is reported when the shortcode is called.
+    invalid_default: Option<(String, String)>,
+
+    /// The description of the arguments the shortcode accepts, if one was provided.
+    schema: Option<ShortcodeSchema>,
 }
 
 impl Shortcode {
@@ -28,19 +48,229 @@ impl Shortcode {
                 let args = serde_json::from_value(Value::Object(args)).unwrap();
                 render(args)
             }),
+            arg_names: Vec::new(),
+            defaults: Map::new(),
+            invalid_default: None,
+            schema: None,
         }
     }
 
     pub fn new_thunk(render: impl Fn() -> Element + Send + Sync + 'static) -> Self {
         Self {
             render: Arc::new(move |_args| render()),
+            arg_names: Vec::new(),
+            defaults: Map::new(),
+            invalid_default: None,
+            schema: None,
+        }
+    }
+
+    /// Sets the names of the arguments that may be passed positionally.
+    ///
+    /// Positional arguments are mapped to the names in the order they appear,
+    /// so `{{ youtube("8o3i10OuMFQ") }}` is equivalent to
+    /// `{{ youtube(id="8o3i10OuMFQ") }}` when the names are `["id"]`.
+    pub fn with_positional_args(
+        mut self,
+        arg_names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.arg_names = arg_names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the value to use for the named argument when it is not provided in the call.
+    ///
+    /// If the value can't be serialized, calls to the shortcode fail with a
+    /// [`ShortcodeError::InvalidDefault`].
+    pub fn with_default(mut self, arg_name: impl Into<String>, value: impl Serialize) -> Self {
+        let arg_name = arg_name.into();
+        match serde_json::to_value(value) {
+            Ok(value) => {
+                self.defaults.insert(arg_name, value);
+            }
+            Err(err) => {
+                self.invalid_default
+                    .get_or_insert_with(|| (arg_name, err.to_string()));
+            }
         }
+        self
+    }
+
+    /// Sets the description of the arguments this shortcode accepts.
+    ///
+    /// The schema isn't used when rendering, but is exposed through
+    /// [`Site::shortcodes`](crate::Site::shortcodes) for editor tooling and
+    /// content linters.
+    pub fn with_schema(mut self, schema: ShortcodeSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Returns a copy of this shortcode that adds the time spent rendering it
+    /// to `elapsed`, in nanoseconds.
+    pub(crate) fn timed(&self, elapsed: Arc<AtomicU64>) -> Self {
+        let render = self.render.clone();
+
+        Self {
+            render: Arc::new(move |args| {
+                let started_at = Instant::now();
+                let element = render(args);
+                elapsed.fetch_add(started_at.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                element
+            }),
+            arg_names: self.arg_names.clone(),
+            defaults: self.defaults.clone(),
+            invalid_default: self.invalid_default.clone(),
+            schema: self.schema.clone(),
+        }
+    }
+
+    /// Returns the description of this shortcode, registered under the given name.
+    pub(crate) fn describe(&self, name: &str) -> ShortcodeDescriptor {
+        ShortcodeDescriptor {
+            name: name.to_owned(),
+            positional_args: self.arg_names.clone(),
+            defaults: self.defaults.clone(),
+            schema: self.schema.clone(),
+        }
+    }
+
+    /// Resolves the arguments passed in a call to this shortcode into a map of named arguments.
+    ///
+    /// Named arguments take precedence over positional ones, which in turn
+    /// take precedence over the declared defaults.
+    fn resolve_args(
+        &self,
+        name: &str,
+        positional_args: Vec<Value>,
+        args: Map<String, Value>,
+    ) -> Result<Map<String, Value>, ShortcodeError> {
+        if let Some((arg, message)) = self.invalid_default.clone() {
+            return Err(ShortcodeError::InvalidDefault {
+                name: name.to_owned(),
+                arg,
+                message,
+            });
+        }
+
+        if positional_args.len() > self.arg_names.len() {
+            return Err(ShortcodeError::TooManyPositionalArgs {
+                name: name.to_owned(),
+                given: positional_args.len(),
+                accepted: self.arg_names.len(),
+            });
+        }
+
+        let mut resolved_args = self.defaults.clone();
+        resolved_args.extend(self.arg_names.iter().cloned().zip(positional_args));
+        resolved_args.extend(args);
+        Ok(resolved_args)
+    }
+}
+
+/// A description of the arguments a shortcode accepts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShortcodeSchema {
+    pub description: Option<String>,
+    pub args: Vec<ShortcodeArg>,
+}
+
+impl ShortcodeSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn arg(mut self, arg: ShortcodeArg) -> Self {
+        self.args.push(arg);
+        self
+    }
+}
+
+/// An argument accepted by a shortcode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShortcodeArg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ShortcodeArgKind,
+    /// Whether the argument must be provided in every call.
+    pub required: bool,
+    pub description: Option<String>,
+}
+
+impl ShortcodeArg {
+    pub fn new(name: impl Into<String>, kind: ShortcodeArgKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            required: false,
+            description: None,
+        }
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
     }
 }
 
+/// The type of value a shortcode argument accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShortcodeArgKind {
+    String,
+    Number,
+    Boolean,
+    Any,
+}
+
+/// A description of a registered shortcode, as written to `shortcodes.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShortcodeDescriptor {
+    pub name: String,
+    /// The names of the arguments that may be passed positionally, in order.
+    pub positional_args: Vec<String>,
+    /// The values used for arguments that aren't provided in a call.
+    pub defaults: Map<String, Value>,
+    pub schema: Option<ShortcodeSchema>,
+}
+
+#[derive(Error, Debug)]
+pub enum ShortcodeError {
+    #[error("failed to parse shortcodes: {0}")]
+    Parse(String),
+
+    #[error("shortcode '{0}' is not registered")]
+    Unknown(String),
+
+    #[error("shortcode '{name}' was called with {given} positional arguments, but only accepts {accepted}")]
+    TooManyPositionalArgs {
+        name: String,
+        given: usize,
+        accepted: usize,
+    },
+
+    #[error("invalid default for argument '{arg}' of shortcode '{name}': {message}")]
+    InvalidDefault {
+        name: String,
+        arg: String,
+        message: String,
+    },
+}
+
 #[derive(Debug)]
 pub struct ShortcodeCall {
     pub name: String,
+    pub positional_args: Vec<Value>,
     pub args: Map<String, Value>,
     pub span: Range<usize>,
 }
@@ -49,19 +279,28 @@ pub fn markdown_with_shortcodes(
     input: &str,
     components: &Box<dyn MarkdownComponents>,
     shortcodes: &HashMap<String, Shortcode>,
-) -> (Vec<Element>, TableOfContents) {
-    let (output, shortcode_calls) = parse_document(input).unwrap();
+) -> Result<(Vec<Element>, TableOfContents), ShortcodeError> {
+    let (output, shortcode_calls) =
+        parse_document(input).map_err(|err| ShortcodeError::Parse(err.to_string()))?;
     let (elements, table_of_contents) = render_markdown(&output, components);
-    let elements = replace_shortcodes(elements, shortcodes, &mut shortcode_calls.into_iter());
+    let elements = replace_shortcodes(elements, shortcodes, &mut shortcode_calls.into_iter())?;
+
+    Ok((elements, table_of_contents))
+}
 
-    (elements, table_of_contents)
+/// Returns the shortcode calls in the given Markdown text, or an error if the
+/// shortcodes in it can't be parsed.
+pub(crate) fn find_shortcode_calls(input: &str) -> Result<Vec<ShortcodeCall>, String> {
+    parse_document(input)
+        .map(|(_output, calls)| calls)
+        .map_err(|err| err.to_string())
 }
 
 fn replace_shortcodes(
     elements: Vec<Element>,
     shortcodes: &HashMap<String, Shortcode>,
     calls: &mut std::vec::IntoIter<ShortcodeCall>,
-) -> Vec<Element> {
+) -> Result<Vec<Element>, ShortcodeError> {
     let mut new_elements = Vec::with_capacity(elements.len());
 
     for child in elements {
@@ -74,9 +313,13 @@ fn replace_shortcodes(
                         new_elements.push(before.into());
 
                         let call = calls.next().unwrap();
-                        let shortcode = shortcodes.get(&call.name).unwrap();
+                        let shortcode = shortcodes
+                            .get(&call.name)
+                            .ok_or_else(|| ShortcodeError::Unknown(call.name.clone()))?;
 
-                        new_elements.push((shortcode.render)(call.args));
+                        let args =
+                            shortcode.resolve_args(&call.name, call.positional_args, call.args)?;
+                        new_elements.push((shortcode.render)(args));
 
                         text = after;
                     }
@@ -93,7 +336,7 @@ fn replace_shortcodes(
                     HtmlElement {
                         tag_name: element.tag_name,
                         attrs: element.attrs,
-                        children: replace_shortcodes(element.children, shortcodes, calls),
+                        children: replace_shortcodes(element.children, shortcodes, calls)?,
                     }
                     .into(),
                 );
@@ -101,7 +344,7 @@ fn replace_shortcodes(
         }
     }
 
-    new_elements
+    Ok(new_elements)
 }
 
 #[cfg(test)]
@@ -109,9 +352,11 @@ mod tests {
     use auk::renderer::HtmlElementRenderer;
     use auk::*;
     use indoc::indoc;
+    use pretty_assertions::assert_eq;
     use serde::Deserialize;
 
     use crate::markdown::DefaultMarkdownComponents;
+    use crate::testing::MarkdownHarness;
 
     use super::*;
 
@@ -120,7 +365,8 @@ mod tests {
         shortcodes: HashMap<String, Shortcode>,
     ) -> String {
         let (elements, _table_of_contents) =
-            markdown_with_shortcodes(text, &DefaultMarkdownComponents.boxed(), &shortcodes);
+            markdown_with_shortcodes(text, &DefaultMarkdownComponents.boxed(), &shortcodes)
+                .unwrap();
 
         elements
             .into_iter()
@@ -205,4 +451,127 @@ mod tests {
 
         insta::assert_yaml_snapshot!(parse_and_render_markdown_with_shortcodes(text, shortcodes));
     }
+
+    #[test]
+    fn test_describe_shortcode() {
+        let shortcode = Shortcode::new_thunk(|| "".into())
+            .with_positional_args(["id"])
+            .with_default("autoplay", false)
+            .with_schema(
+                ShortcodeSchema::new()
+                    .arg(ShortcodeArg::new("id", ShortcodeArgKind::String).required()),
+            );
+
+        assert_eq!(
+            serde_json::to_value(shortcode.describe("youtube")).unwrap(),
+            serde_json::json!({
+                "name": "youtube",
+                "positional_args": ["id"],
+                "defaults": { "autoplay": false },
+                "schema": {
+                    "description": null,
+                    "args": [
+                        { "name": "id", "type": "string", "required": true, "description": null }
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_shortcodes_with_positional_args() {
+        let text = indoc! {r#"
+            {{ repeat("hey") }}
+            {{ repeat("ho", 3) }}
+            {{ repeat("yo", times=5) }}
+
+            {{ youtube("8o3i10OuMFQ") }}
+
+            {{ youtube("8o3i10OuMFQ", autoplay=true) }}
+        "#};
+
+        #[derive(Deserialize)]
+        struct RepeatArgs {
+            message: String,
+            times: usize,
+        }
+
+        #[derive(Deserialize)]
+        struct YoutubeArgs {
+            id: String,
+            autoplay: bool,
+        }
+
+        let harness = MarkdownHarness::new()
+            .add_shortcode(
+                "repeat",
+                Shortcode::new(|args: RepeatArgs| args.message.repeat(args.times).into())
+                    .with_positional_args(["message", "times"])
+                    .with_default("times", 1),
+            )
+            .add_shortcode(
+                "youtube",
+                Shortcode::new(|args: YoutubeArgs| {
+                    iframe()
+                        .src(format!(
+                            "https://youtube.com/embed/{id}{autoplay}",
+                            id = args.id,
+                            autoplay = if args.autoplay { "?autoplay=1" } else { "" }
+                        ))
+                        .into()
+                })
+                .with_positional_args(["id"])
+                .with_default("autoplay", false),
+            );
+
+        insta::assert_yaml_snapshot!(harness.render(text));
+    }
+
+    #[test]
+    fn test_shortcodes_with_too_many_positional_args() {
+        let shortcodes = HashMap::from_iter([(
+            "youtube".to_string(),
+            Shortcode::new_thunk(|| "".into()).with_positional_args(["id"]),
+        )]);
+
+        let result = markdown_with_shortcodes(
+            r#"{{ youtube("8o3i10OuMFQ", 30) }}"#,
+            &DefaultMarkdownComponents.boxed(),
+            &shortcodes,
+        );
+
+        match result {
+            Err(ShortcodeError::TooManyPositionalArgs {
+                name,
+                given,
+                accepted,
+            }) => {
+                assert_eq!(name, "youtube");
+                assert_eq!((given, accepted), (2, 1));
+            }
+            _ => panic!("expected too many positional arguments, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_shortcodes_with_invalid_default() {
+        let shortcodes = HashMap::from_iter([(
+            "chart".to_string(),
+            Shortcode::new_thunk(|| "".into()).with_default("points", HashMap::from([((0, 0), 1)])),
+        )]);
+
+        let result = markdown_with_shortcodes(
+            "{{ chart() }}",
+            &DefaultMarkdownComponents.boxed(),
+            &shortcodes,
+        );
+
+        match result {
+            Err(ShortcodeError::InvalidDefault { name, arg, .. }) => {
+                assert_eq!(name, "chart");
+                assert_eq!(arg, "points");
+            }
+            _ => panic!("expected an invalid default, got {result:?}"),
+        }
+    }
 }
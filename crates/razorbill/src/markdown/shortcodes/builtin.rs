@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use auk::*;
+use serde::Deserialize;
+
+use crate::markdown::{Shortcode, ShortcodeArg, ShortcodeArgKind, ShortcodeSchema};
+
+/// The permissions granted to embedded video players.
+const VIDEO_PLAYER_ALLOW: &str =
+    "accelerometer; autoplay; encrypted-media; gyroscope; picture-in-picture; fullscreen";
+
+/// Returns the built-in shortcodes, keyed by name.
+///
+/// | Shortcode | Example |
+/// | --- | --- |
+/// | `youtube` | `{{ youtube("8o3i10OuMFQ", start=30) }}` |
+/// | `vimeo` | `{{ vimeo("76979871") }}` |
+/// | `audio` | `{{ audio("/episodes/1.mp3", title="Episode 1") }}` |
+/// | `video` | `{{ video("clip.mp4", poster="clip.jpg") }}` |
+/// | `gist` | `{{ gist("maxdeviant", "4a3d4c5e") }}` |
+///
+/// The embeds favor privacy by default: YouTube videos are served from
+/// `youtube-nocookie.com`, Vimeo is asked not to track viewers, nothing is
+/// preloaded, and iframes are lazy-loaded.
+pub fn builtin_shortcodes() -> HashMap<String, Shortcode> {
+    HashMap::from_iter([
+        ("youtube".to_string(), youtube()),
+        ("vimeo".to_string(), vimeo()),
+        ("audio".to_string(), audio()),
+        ("video".to_string(), video()),
+        ("gist".to_string(), gist()),
+    ])
+}
+
+#[derive(Deserialize)]
+struct YoutubeArgs {
+    id: String,
+    title: Option<String>,
+    start: Option<u32>,
+    autoplay: bool,
+    /// Whether the video is served from `youtube-nocookie.com`, which doesn't
+    /// set cookies until the video is played.
+    privacy_enhanced: bool,
+}
+
+fn youtube() -> Shortcode {
+    Shortcode::new(|args: YoutubeArgs| {
+        let host = if args.privacy_enhanced {
+            "www.youtube-nocookie.com"
+        } else {
+            "www.youtube.com"
+        };
+
+        let mut params = Vec::new();
+        if let Some(start) = args.start {
+            params.push(format!("start={start}"));
+        }
+        if args.autoplay {
+            params.push("autoplay=1".to_string());
+        }
+
+        video_player(
+            "youtube",
+            with_query(format!("https://{host}/embed/{}", args.id), params),
+            args.title.as_deref().unwrap_or("YouTube video"),
+        )
+    })
+    .with_positional_args(["id"])
+    .with_default("autoplay", false)
+    .with_default("privacy_enhanced", true)
+    .with_schema(
+        ShortcodeSchema::new()
+            .description("Embeds a YouTube video.")
+            .arg(ShortcodeArg::new("id", ShortcodeArgKind::String).required())
+            .arg(ShortcodeArg::new("title", ShortcodeArgKind::String))
+            .arg(
+                ShortcodeArg::new("start", ShortcodeArgKind::Number)
+                    .description("The time to start the video at, in seconds."),
+            )
+            .arg(ShortcodeArg::new("autoplay", ShortcodeArgKind::Boolean))
+            .arg(ShortcodeArg::new(
+                "privacy_enhanced",
+                ShortcodeArgKind::Boolean,
+            )),
+    )
+}
+
+#[derive(Deserialize)]
+struct VimeoArgs {
+    id: String,
+    title: Option<String>,
+    autoplay: bool,
+    /// Whether Vimeo is asked not to track the viewer.
+    do_not_track: bool,
+}
+
+fn vimeo() -> Shortcode {
+    Shortcode::new(|args: VimeoArgs| {
+        let mut params = Vec::new();
+        if args.do_not_track {
+            params.push("dnt=1".to_string());
+        }
+        if args.autoplay {
+            params.push("autoplay=1".to_string());
+        }
+
+        video_player(
+            "vimeo",
+            with_query(
+                format!("https://player.vimeo.com/video/{}", args.id),
+                params,
+            ),
+            args.title.as_deref().unwrap_or("Vimeo video"),
+        )
+    })
+    .with_positional_args(["id"])
+    .with_default("autoplay", false)
+    .with_default("do_not_track", true)
+    .with_schema(
+        ShortcodeSchema::new()
+            .description("Embeds a Vimeo video.")
+            .arg(ShortcodeArg::new("id", ShortcodeArgKind::String).required())
+            .arg(ShortcodeArg::new("title", ShortcodeArgKind::String))
+            .arg(ShortcodeArg::new("autoplay", ShortcodeArgKind::Boolean))
+            .arg(ShortcodeArg::new("do_not_track", ShortcodeArgKind::Boolean)),
+    )
+}
+
+#[derive(Deserialize)]
+struct AudioArgs {
+    src: String,
+    title: Option<String>,
+}
+
+fn audio() -> Shortcode {
+    Shortcode::new(|args: AudioArgs| {
+        let src = escape_attr(&args.src);
+
+        let mut audio = auk::audio().attr("controls", "").attr("preload", "none");
+        if let Some(title) = args.title.as_deref() {
+            audio = audio.attr("title", escape_attr(title));
+        }
+
+        div()
+            .class("embed embed-audio")
+            .child(
+                audio
+                    .src(src.clone())
+                    .child(a().href(src).child("Download the audio")),
+            )
+            .into()
+    })
+    .with_positional_args(["src"])
+    .with_schema(
+        ShortcodeSchema::new()
+            .description("Embeds an audio player.")
+            .arg(ShortcodeArg::new("src", ShortcodeArgKind::String).required())
+            .arg(ShortcodeArg::new("title", ShortcodeArgKind::String)),
+    )
+}
+
+#[derive(Deserialize)]
+struct VideoArgs {
+    src: String,
+    title: Option<String>,
+    poster: Option<String>,
+    /// Whether the video plays automatically, which requires it to be muted.
+    autoplay: bool,
+    #[serde(rename = "loop")]
+    loop_: bool,
+}
+
+fn video() -> Shortcode {
+    Shortcode::new(|args: VideoArgs| {
+        let src = escape_attr(&args.src);
+
+        let mut video = auk::video().attr("controls", "").attr("preload", "none");
+        if let Some(title) = args.title.as_deref() {
+            video = video.attr("title", escape_attr(title));
+        }
+        if let Some(poster) = args.poster.as_deref() {
+            video = video.attr("poster", escape_attr(poster));
+        }
+        if args.autoplay {
+            video = video
+                .attr("autoplay", "")
+                .attr("muted", "")
+                .attr("playsinline", "");
+        }
+        if args.loop_ {
+            video = video.attr("loop", "");
+        }
+
+        div()
+            .class("embed embed-video")
+            .child(
+                video
+                    .src(src.clone())
+                    .child(a().href(src).child("Download the video")),
+            )
+            .into()
+    })
+    .with_positional_args(["src"])
+    .with_default("autoplay", false)
+    .with_default("loop", false)
+    .with_schema(
+        ShortcodeSchema::new()
+            .description("Embeds a video player.")
+            .arg(ShortcodeArg::new("src", ShortcodeArgKind::String).required())
+            .arg(ShortcodeArg::new("title", ShortcodeArgKind::String))
+            .arg(ShortcodeArg::new("poster", ShortcodeArgKind::String))
+            .arg(
+                ShortcodeArg::new("autoplay", ShortcodeArgKind::Boolean)
+                    .description("Whether the video plays automatically, muted."),
+            )
+            .arg(ShortcodeArg::new("loop", ShortcodeArgKind::Boolean)),
+    )
+}
+
+#[derive(Deserialize)]
+struct GistArgs {
+    user: String,
+    id: String,
+    file: Option<String>,
+}
+
+fn gist() -> Shortcode {
+    Shortcode::new(|args: GistArgs| {
+        let url = format!("https://gist.github.com/{}/{}", args.user, args.id);
+        let params = args
+            .file
+            .iter()
+            .map(|file| format!("file={}", escape_attr(file)))
+            .collect();
+
+        div()
+            .class("embed embed-gist")
+            .child(script().src(with_query(format!("{url}.js"), params)))
+            .child(noscript().child(a().href(url).child("View the gist on GitHub")))
+            .into()
+    })
+    .with_positional_args(["user", "id", "file"])
+    .with_schema(
+        ShortcodeSchema::new()
+            .description("Embeds a GitHub gist.")
+            .arg(ShortcodeArg::new("user", ShortcodeArgKind::String).required())
+            .arg(ShortcodeArg::new("id", ShortcodeArgKind::String).required())
+            .arg(
+                ShortcodeArg::new("file", ShortcodeArgKind::String)
+                    .description("The file within the gist to embed."),
+            ),
+    )
+}
+
+fn video_player(provider: &str, src: String, title: &str) -> Element {
+    div()
+        .class(format!("embed embed-{provider}"))
+        .child(
+            iframe()
+                .src(src)
+                .attr("title", escape_attr(title))
+                .attr("loading", "lazy")
+                .attr("allow", VIDEO_PLAYER_ALLOW)
+                .attr("allowfullscreen", "")
+                .attr("referrerpolicy", "strict-origin-when-cross-origin"),
+        )
+        .into()
+}
+
+fn with_query(url: String, params: Vec<String>) -> String {
+    if params.is_empty() {
+        return url;
+    }
+
+    format!("{url}?{}", params.join("&amp;"))
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::testing::MarkdownHarness;
+
+    use super::*;
+
+    fn harness() -> MarkdownHarness {
+        builtin_shortcodes()
+            .into_iter()
+            .fold(MarkdownHarness::new(), |harness, (name, shortcode)| {
+                harness.add_shortcode(name, shortcode)
+            })
+    }
+
+    fn find_element<'a>(elements: &'a [Element], tag_name: &str) -> Option<&'a HtmlElement> {
+        elements.iter().find_map(|element| match element {
+            Element::Html(element) if element.tag_name == tag_name => Some(element),
+            Element::Html(element) => find_element(&element.children, tag_name),
+            Element::Text(_) => None,
+        })
+    }
+
+    fn attr<'a>(element: &'a HtmlElement, name: &str) -> Option<&'a str> {
+        element.attrs.get(name).map(String::as_str)
+    }
+
+    #[test]
+    fn test_builtin_shortcodes() {
+        let harness = harness();
+
+        let elements = harness.render_elements(r#"{{ youtube("8o3i10OuMFQ", start=30) }}"#);
+        let iframe = find_element(&elements, "iframe").unwrap();
+        assert_eq!(
+            attr(iframe, "src"),
+            Some("https://www.youtube-nocookie.com/embed/8o3i10OuMFQ?start=30")
+        );
+        assert_eq!(attr(iframe, "loading"), Some("lazy"));
+
+        let elements = harness.render_elements(r#"{{ vimeo("76979871", autoplay=true) }}"#);
+        let iframe = find_element(&elements, "iframe").unwrap();
+        assert_eq!(
+            attr(iframe, "src"),
+            Some("https://player.vimeo.com/video/76979871?dnt=1&amp;autoplay=1")
+        );
+
+        let elements = harness.render_elements(r#"{{ audio("/episodes/1.mp3") }}"#);
+        let audio = find_element(&elements, "audio").unwrap();
+        assert_eq!(attr(audio, "src"), Some("/episodes/1.mp3"));
+        assert_eq!(attr(audio, "preload"), Some("none"));
+
+        let elements = harness.render_elements(r#"{{ video("clip.mp4", autoplay=true) }}"#);
+        let video = find_element(&elements, "video").unwrap();
+        assert_eq!(attr(video, "muted"), Some(""));
+        assert_eq!(attr(video, "loop"), None);
+
+        let elements =
+            harness.render_elements(r#"{{ gist("maxdeviant", "4a3d4c5e", "main.rs") }}"#);
+        let script = find_element(&elements, "script").unwrap();
+        assert_eq!(
+            attr(script, "src"),
+            Some("https://gist.github.com/maxdeviant/4a3d4c5e.js?file=main.rs")
+        );
+    }
+}
@@ -23,13 +23,16 @@ pub fn parse_document(
 
     for pair in pairs.next().unwrap().into_inner() {
         match pair.as_rule() {
-            Rule::text => output.push_str(pair.as_span().as_str()),
+            Rule::text | Rule::fenced_code_block | Rule::indented_code_block | Rule::code_span => {
+                output.push_str(pair.as_span().as_str())
+            }
             Rule::shortcode_call => {
                 let start = output.len();
                 let end = start + SHORTCODE_PLACEHOLDER.len();
-                let (name, args) = parse_shortcode_call(pair);
+                let (name, positional_args, args) = parse_shortcode_call(pair);
                 shortcode_calls.push(ShortcodeCall {
                     name,
+                    positional_args,
                     args,
                     span: start..end,
                 });
@@ -43,8 +46,9 @@ pub fn parse_document(
     Ok((output, shortcode_calls))
 }
 
-fn parse_shortcode_call(pair: Pair<Rule>) -> (String, Map<String, Value>) {
+fn parse_shortcode_call(pair: Pair<Rule>) -> (String, Vec<Value>, Map<String, Value>) {
     let mut name = None;
+    let mut positional_args = Vec::new();
     let mut args = Map::new();
 
     for pair in pair.into_inner() {
@@ -70,11 +74,15 @@ fn parse_shortcode_call(pair: Pair<Rule>) -> (String, Map<String, Value>) {
 
                 args.insert(arg_name.unwrap(), arg_value.unwrap());
             }
+            Rule::positional_arg => {
+                let literal = pair.into_inner().next().unwrap();
+                positional_args.push(parse_literal(literal));
+            }
             _ => unreachable!("Failed to parse shortcode call: {pair:?}"),
         }
     }
 
-    (name.unwrap(), args)
+    (name.unwrap(), positional_args, args)
 }
 
 fn parse_literal(pair: Pair<Rule>) -> Value {
@@ -121,3 +129,143 @@ fn unquote_string(value: &str) -> String {
 fn parse_ident(pair: Pair<Rule>) -> String {
     pair.as_span().as_str().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn shortcode_names(calls: &[ShortcodeCall]) -> Vec<&str> {
+        calls.iter().map(|call| call.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_document_skips_fenced_code_blocks() {
+        let document = indoc! {r#"
+            {{ before() }}
+
+            ```md
+            {{ youtube(id="8o3i10OuMFQ") }}
+            ```
+
+            ~~~~
+            {{ broken(
+            ~~~~
+
+            {{ after() }}
+        "#};
+
+        let (output, calls) = parse_document(document).unwrap();
+
+        assert_eq!(shortcode_names(&calls), vec!["before", "after"]);
+        assert_eq!(
+            output,
+            indoc! {r#"
+                @@RAZORBILL_SHORTCODE@@
+
+                ```md
+                {{ youtube(id="8o3i10OuMFQ") }}
+                ```
+
+                ~~~~
+                {{ broken(
+                ~~~~
+
+                @@RAZORBILL_SHORTCODE@@
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_parse_document_skips_unterminated_fenced_code_blocks() {
+        let document = "```\n{{ youtube() }}\n";
+
+        let (output, calls) = parse_document(document).unwrap();
+
+        assert_eq!(shortcode_names(&calls), Vec::<&str>::new());
+        assert_eq!(output, document);
+    }
+
+    #[test]
+    fn test_parse_document_only_opens_fenced_code_blocks_at_line_start() {
+        let document = indoc! {r#"
+               ```
+               {{ yi() }}
+               ```
+
+            Wrap it in ```{{ er() }}``` to show it, or ``` to get {{ san() }}.
+        "#};
+
+        let (output, calls) = parse_document(document).unwrap();
+
+        assert_eq!(shortcode_names(&calls), vec!["san"]);
+        assert_eq!(
+            output,
+            indoc! {r#"
+                   ```
+                   {{ yi() }}
+                   ```
+
+                Wrap it in ```{{ er() }}``` to show it, or ``` to get @@RAZORBILL_SHORTCODE@@.
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_parse_document_skips_indented_code_blocks() {
+        let document = indoc! {r#"
+                {{ yi() }}
+
+            Show it like this:
+
+                {{ er() }}
+
+                {{ san() }}
+
+            A line indented right after a paragraph {{ si() }}
+                continues it, so {{ wu() }} is expanded.
+
+            {{ liu() }}
+        "#};
+
+        let (output, calls) = parse_document(document).unwrap();
+
+        assert_eq!(shortcode_names(&calls), vec!["si", "wu", "liu"]);
+        assert_eq!(
+            output,
+            indoc! {r#"
+                    {{ yi() }}
+
+                Show it like this:
+
+                    {{ er() }}
+
+                    {{ san() }}
+
+                A line indented right after a paragraph @@RAZORBILL_SHORTCODE@@
+                    continues it, so @@RAZORBILL_SHORTCODE@@ is expanded.
+
+                @@RAZORBILL_SHORTCODE@@
+            "#}
+        );
+
+        let (_output, calls) = parse_document("\t{{ qi() }}").unwrap();
+        assert_eq!(shortcode_names(&calls), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_parse_document_skips_code_spans() {
+        let document =
+            "Call `{{ yi() }}` or ``{{ er(`x`) }}`` to get {{ san() }}, but not ` {{ si() }}.";
+
+        let (output, calls) = parse_document(document).unwrap();
+
+        assert_eq!(shortcode_names(&calls), vec!["san", "si"]);
+        assert_eq!(
+            output,
+            "Call `{{ yi() }}` or ``{{ er(`x`) }}`` to get @@RAZORBILL_SHORTCODE@@, but not ` @@RAZORBILL_SHORTCODE@@."
+        );
+    }
+}
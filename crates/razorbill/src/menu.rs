@@ -0,0 +1,255 @@
+//! Navigation menus.
+//!
+//! Menus are declared on the [`SiteBuilder`](crate::SiteBuilder) and made
+//! available to templates with
+//! [`RenderPageContext::menu`](crate::render::RenderPageContext::menu):
+//!
+//! ```
+//! use razorbill::menu::MenuItem;
+//! use razorbill::Site;
+//!
+//! let builder = Site::builder().add_menu(
+//!     "main",
+//!     [
+//!         MenuItem::page("@/blog/_index.md"),
+//!         MenuItem::page("@/about.md").title("About Me"),
+//!         MenuItem::url("GitHub", "https://github.com/maxdeviant/razorbill"),
+//!     ],
+//! );
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::content::{Pages, Sections};
+
+/// An item in a menu.
+#[derive(Debug, Clone)]
+pub struct MenuItem {
+    target: MenuItemTarget,
+    title: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum MenuItemTarget {
+    /// A section or page, referenced by its `@/` path.
+    Content(String),
+    Url(String),
+}
+
+impl MenuItem {
+    /// Returns a [`MenuItem`] that links to the section or page at the given
+    /// path (e.g., `@/about.md` or `@/blog/_index.md`).
+    ///
+    /// The item uses the title of the section or page, unless it is given one
+    /// with [`title`](Self::title).
+    pub fn page(path: impl Into<String>) -> Self {
+        Self {
+            target: MenuItemTarget::Content(path.into()),
+            title: None,
+        }
+    }
+
+    /// Returns a [`MenuItem`] that links to the given URL.
+    pub fn url(title: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            target: MenuItemTarget::Url(url.into()),
+            title: Some(title.into()),
+        }
+    }
+
+    /// Sets the title of the item.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Returns the path to the content file this item links to, if it links
+    /// to a section or page.
+    pub(crate) fn content_path(&self, content_path: &Path) -> Option<PathBuf> {
+        let MenuItemTarget::Content(path) = &self.target else {
+            return None;
+        };
+
+        Some(content_path.join(path.trim_start_matches("@/")))
+    }
+
+    /// Resolves the item against the site's sections and pages.
+    ///
+    /// Returns `None` if the item links to content that doesn't exist.
+    pub(crate) fn resolve<'a>(
+        &'a self,
+        content_path: &Path,
+        sections: &'a Sections,
+        pages: &'a Pages,
+    ) -> Option<ResolvedMenuItem<'a>> {
+        if let MenuItemTarget::Url(url) = &self.target {
+            return Some(ResolvedMenuItem {
+                title: self.title.as_deref(),
+                permalink: url,
+                file_path: None,
+            });
+        }
+
+        let file_path = self.content_path(content_path)?;
+        let (title, permalink, file_path) = match sections.get_key_value(&file_path) {
+            Some((file_path, section)) => (&section.meta.title, &section.permalink, file_path),
+            None => {
+                let (file_path, page) = pages.get_key_value(&file_path)?;
+                (&page.meta.title, &page.permalink, file_path)
+            }
+        };
+
+        Some(ResolvedMenuItem {
+            title: self.title.as_deref().or(title.as_deref()),
+            permalink: permalink.as_str(),
+            file_path: Some(file_path),
+        })
+    }
+}
+
+/// A [`MenuItem`] that has been resolved to a URL.
+pub(crate) struct ResolvedMenuItem<'a> {
+    pub title: Option<&'a str>,
+    pub permalink: &'a str,
+    /// The path to the section or page the item links to, if any.
+    pub file_path: Option<&'a Path>,
+}
+
+/// An item in a menu, relative to the section or page being rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItemToRender<'a> {
+    pub title: Option<&'a str>,
+    pub permalink: &'a str,
+    /// Whether the item links to the section or page being rendered.
+    pub is_active: bool,
+    /// Whether the item links to a section containing the section or page
+    /// being rendered.
+    pub is_ancestor_active: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_site::{render, TestSite};
+    use crate::{Site, UnknownMenuItem};
+
+    use super::*;
+
+    fn menu_test_site(name: &str) -> (TestSite, Site) {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("blog/_index.md", "+++\ntitle = \"Blog\"\n+++\n")
+            .write("blog/hello.md", "+++\ntitle = \"Hello\"\n+++\n")
+            .write("about.md", "+++\ntitle = \"About\"\n+++\n");
+
+        let mut site = test_site
+            .builder()
+            .add_menu(
+                "main",
+                [
+                    MenuItem::page("@/blog/_index.md"),
+                    MenuItem::page("@/about.md").title("About Me"),
+                    MenuItem::url("Home", "https://example.com/"),
+                    MenuItem::page("@/missing.md"),
+                ],
+            )
+            .build();
+        site.load().unwrap();
+
+        (test_site, site)
+    }
+
+    /// Returns the titles of the active and ancestor-active items in the main
+    /// menu, when rendering the content at the given path.
+    fn active_items(site: &Site, test_site: &TestSite, path: &str) -> (Vec<String>, Vec<String>) {
+        let path = test_site.content_path().join(path);
+        let (permalink, ancestors) = match site.sections.get(&path) {
+            Some(section) => (&section.permalink, &section.ancestors),
+            None => {
+                let page = &site.pages[&path];
+                (&page.permalink, &page.ancestors)
+            }
+        };
+        let ancestors = ancestors.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+
+        let ctx = site.base_render_context();
+        let items = ctx.menu_for("main", Some((&path, permalink.as_str())), &ancestors);
+
+        let titles = |predicate: fn(&MenuItemToRender) -> bool| {
+            items
+                .iter()
+                .filter(|item| predicate(item))
+                .map(|item| item.title.unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        (
+            titles(|item| item.is_active),
+            titles(|item| item.is_ancestor_active),
+        )
+    }
+
+    #[test]
+    fn test_menu_exact_match() {
+        let (test_site, site) = menu_test_site("menu_exact_match");
+
+        assert_eq!(
+            active_items(&site, &test_site, "about.md"),
+            (vec!["About Me".to_string()], Vec::new())
+        );
+        assert_eq!(
+            active_items(&site, &test_site, "blog/_index.md"),
+            (vec!["Blog".to_string()], Vec::new())
+        );
+        assert_eq!(
+            active_items(&site, &test_site, "_index.md"),
+            (vec!["Home".to_string()], Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_menu_ancestor_match() {
+        let (test_site, site) = menu_test_site("menu_ancestor_match");
+
+        assert_eq!(
+            active_items(&site, &test_site, "blog/hello.md"),
+            (Vec::new(), vec!["Blog".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_menu_no_match() {
+        let (_test_site, site) = menu_test_site("menu_no_match");
+
+        let ctx = site.base_render_context();
+        let items = ctx.menu("main");
+
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| item.title.unwrap_or_default())
+                .collect::<Vec<_>>(),
+            vec!["Blog", "About Me", "Home"]
+        );
+        assert!(items
+            .iter()
+            .all(|item| !item.is_active && !item.is_ancestor_active));
+    }
+
+    #[test]
+    fn test_unknown_menu_items_are_reported() {
+        let (_test_site, mut site) = menu_test_site("unknown_menu_items");
+
+        render(&mut site).unwrap();
+
+        assert_eq!(
+            site.report().unknown_menu_items,
+            vec![UnknownMenuItem {
+                menu: "main".to_string(),
+                path: PathBuf::from("missing.md"),
+            }]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Maps each of the items using a pool of worker threads, returning the results
+/// in the same order as the items.
+///
+/// Items are handed out one at a time, so that a few slow items don't hold up
+/// the rest of the work.
+pub(crate) fn par_map<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let worker_count = thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+        .min(items.len());
+
+    if worker_count <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next_index = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            scope.spawn(|| {
+                let mut worker_results = Vec::new();
+
+                loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+
+                    worker_results.push((index, f(item)));
+                }
+
+                results.lock().unwrap().extend(worker_results);
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_par_map_preserves_order() {
+        let items = (0..1000).collect::<Vec<_>>();
+
+        assert_eq!(
+            par_map(&items, |item| item * 2),
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+        assert_eq!(par_map(&[] as &[u32], |item| *item), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,109 @@
+//! Exporting pages to PDF.
+//!
+//! Pages opt in with `pdf = true` in their front matter. The rendered HTML of
+//! each page is handed to a [`PdfBackend`], which is responsible for turning it
+//! into a PDF (e.g., by driving a headless browser).
+
+use std::error::Error;
+
+/// The name of the PDF within a page's output directory.
+pub(crate) const PDF_FILE_NAME: &str = "index.pdf";
+
+/// A backend that converts rendered pages to PDFs.
+pub trait PdfBackend: Send + Sync {
+    /// Renders the given HTML document to a PDF.
+    ///
+    /// The `base_url` is the permalink of the page, for resolving any relative
+    /// URLs within the document.
+    fn render_pdf(
+        &self,
+        html: &str,
+        base_url: &str,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+}
+
+/// The configuration for exporting pages to PDF.
+pub struct PdfExport {
+    backend: Box<dyn PdfBackend>,
+    print_stylesheet: Option<String>,
+}
+
+impl PdfExport {
+    /// Returns a new [`PdfExport`] that renders PDFs using the given backend.
+    pub fn new(backend: impl PdfBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+            print_stylesheet: None,
+        }
+    }
+
+    /// Sets the CSS that is applied to pages when printing them to PDF.
+    pub fn print_stylesheet(mut self, css: impl Into<String>) -> Self {
+        self.print_stylesheet = Some(css.into());
+        self
+    }
+
+    /// Renders the given HTML document to a PDF.
+    pub fn render(
+        &self,
+        html: &str,
+        base_url: &str,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        self.backend.render_pdf(&self.prepare_html(html), base_url)
+    }
+
+    /// Adds the print stylesheet, if any, to the given HTML document.
+    fn prepare_html(&self, html: &str) -> String {
+        let Some(css) = self.print_stylesheet.as_ref() else {
+            return html.to_owned();
+        };
+
+        let style = format!(r#"<style media="print">{css}</style>"#);
+
+        match html.find("</head>") {
+            Some(index) => {
+                let mut html = html.to_owned();
+                html.insert_str(index, &style);
+                html
+            }
+            None => format!("{style}{html}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct HtmlBackend;
+
+    impl PdfBackend for HtmlBackend {
+        fn render_pdf(
+            &self,
+            html: &str,
+            _base_url: &str,
+        ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+            Ok(html.as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_print_stylesheet() {
+        let pdf_export = PdfExport::new(HtmlBackend).print_stylesheet("nav { display: none; }");
+
+        assert_eq!(
+            pdf_export.prepare_html("<html><head><title>Hello</title></head><body></body></html>"),
+            r#"<html><head><title>Hello</title><style media="print">nav { display: none; }</style></head><body></body></html>"#
+        );
+        assert_eq!(
+            pdf_export.prepare_html("<p>Hello</p>"),
+            r#"<style media="print">nav { display: none; }</style><p>Hello</p>"#
+        );
+        assert_eq!(
+            PdfExport::new(HtmlBackend).prepare_html("<p>Hello</p>"),
+            "<p>Hello</p>"
+        );
+    }
+}
@@ -1,14 +1,54 @@
+use std::borrow::Cow;
 use std::str::FromStr;
 
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use url::Url;
 
 use crate::SiteConfig;
 
+/// The characters that are percent-encoded within a path segment.
+///
+/// This matches the set used by [`Url`], with the addition of `%`, `?`, and `#`,
+/// so that paths built from raw filenames or taxonomy terms are never
+/// mistaken for escapes, queries, or fragments.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Percent-encodes the given (unencoded) path for use in a URL.
+pub(crate) fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Decodes the given percent-encoded URL path.
+///
+/// The decoded path is what is used on the filesystem, and as the key for
+/// content when serving.
+pub(crate) fn decode_path(path: &str) -> Cow<'_, str> {
+    percent_decode_str(path).decode_utf8_lossy()
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct Permalink(Url);
 
 impl Permalink {
     pub fn from_path(config: &SiteConfig, path: &str) -> Self {
+        Self::from_base_url(&config.base_url, path)
+    }
+
+    /// Returns the permalink for the given path on the site at `base_url`.
+    pub(crate) fn from_base_url(base_url: &str, path: &str) -> Self {
         // HACK: We probably need to deal with this elsewhere.
         let path = path.trim_end_matches("_index");
 
@@ -21,8 +61,8 @@ impl Permalink {
         } else {
             "/"
         };
-        let base_url = config.base_url.trim_end_matches('/');
-        let path = path.trim_start_matches('/');
+        let base_url = base_url.trim_end_matches('/');
+        let path = encode_path(path.trim_start_matches('/'));
 
         Self(Url::from_str(&format!("{base_url}/{path}{suffix}")).unwrap())
     }
@@ -31,52 +71,107 @@ impl Permalink {
         &self.0.as_str()
     }
 
+    pub(crate) fn as_url(&self) -> &Url {
+        &self.0
+    }
+
+    /// Returns the percent-encoded path of the permalink.
     pub fn path(&self) -> &str {
         &self.0.path()
     }
+
+    /// Returns the decoded path of the permalink.
+    pub fn decoded_path(&self) -> Cow<'_, str> {
+        decode_path(self.path())
+    }
+
+    /// Resolves the given relative URL against this permalink.
+    pub fn join(&self, relative_url: &str) -> Option<Self> {
+        self.0.join(relative_url).ok().map(Self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
-    use crate::content::AVERAGE_ADULT_WPM;
-
     use super::*;
 
-    fn make_config(base_url: &str) -> SiteConfig {
-        SiteConfig {
-            base_url: base_url.to_string(),
-            title: None,
-            taxonomies: Vec::new(),
-            reading_speed: AVERAGE_ADULT_WPM,
-        }
-    }
-
     #[test]
     fn test_permalink() {
         assert_eq!(
-            Permalink::from_path(&make_config("https://example.com/"), "/"),
+            Permalink::from_path(&SiteConfig::for_tests("https://example.com/"), "/"),
             Permalink("https://example.com/".parse().unwrap())
         );
         assert_eq!(
-            Permalink::from_path(&make_config("https://example.com"), "/"),
+            Permalink::from_path(&SiteConfig::for_tests("https://example.com"), "/"),
             Permalink("https://example.com/".parse().unwrap())
         );
         assert_eq!(
-            Permalink::from_path(&make_config("https://example.com"), ""),
+            Permalink::from_path(&SiteConfig::for_tests("https://example.com"), ""),
             Permalink("https://example.com/".parse().unwrap())
         );
         assert_eq!(
-            Permalink::from_path(&make_config("https://example.com"), "/atom.xml"),
+            Permalink::from_path(&SiteConfig::for_tests("https://example.com"), "/atom.xml"),
             Permalink("https://example.com/atom.xml".parse().unwrap())
         );
         assert_eq!(
-            Permalink::from_path(&make_config("https://example.com"), "atom.xml"),
+            Permalink::from_path(&SiteConfig::for_tests("https://example.com"), "atom.xml"),
             Permalink("https://example.com/atom.xml".parse().unwrap())
         );
     }
 
+    #[test]
+    fn test_permalink_join() {
+        let permalink = Permalink("https://example.com/blog/hello-world/".parse().unwrap());
+        assert_eq!(
+            permalink.join("diagram.png"),
+            Some(Permalink(
+                "https://example.com/blog/hello-world/diagram.png"
+                    .parse()
+                    .unwrap()
+            ))
+        );
+        assert_eq!(
+            permalink.join("images/diagram.png"),
+            Some(Permalink(
+                "https://example.com/blog/hello-world/images/diagram.png"
+                    .parse()
+                    .unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_permalink_unicode() {
+        let config = SiteConfig::for_tests("https://example.com");
+
+        let permalink = Permalink::from_path(&config, "/blog/café");
+        assert_eq!(permalink.as_str(), "https://example.com/blog/caf%C3%A9/");
+        assert_eq!(permalink.path(), "/blog/caf%C3%A9/");
+        assert_eq!(permalink.decoded_path(), "/blog/café/");
+
+        let permalink = Permalink::from_path(&config, "/tags/c#");
+        assert_eq!(permalink.as_str(), "https://example.com/tags/c%23/");
+        assert_eq!(permalink.decoded_path(), "/tags/c#/");
+
+        let permalink = Permalink::from_path(&config, "/tags/100% rust");
+        assert_eq!(permalink.path(), "/tags/100%25%20rust/");
+        assert_eq!(permalink.decoded_path(), "/tags/100% rust/");
+    }
+
+    #[test]
+    fn test_encode_path_round_trip() {
+        for path in [
+            "/",
+            "/blog/hello-world/",
+            "/日本語/ページ/",
+            "/a b/c?d/e#f/",
+        ] {
+            assert_eq!(decode_path(&encode_path(path)), path);
+        }
+    }
+
     #[test]
     fn test_permalink_path() {
         let permalink = Permalink("https://example.com/this/is/a/cool/site/".parse().unwrap());
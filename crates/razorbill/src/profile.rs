@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::env;
+
+/// The environment variable used to select the build profile.
+pub const PROFILE_ENV_VAR: &str = "RAZORBILL_PROFILE";
+
+/// A named set of overrides applied when building for a particular environment
+/// (e.g., `dev`, `staging`, or `production`).
+#[derive(Debug, Clone)]
+pub struct BuildProfile {
+    pub(crate) name: String,
+    pub(crate) base_url: Option<String>,
+    pub(crate) include_drafts: Option<bool>,
+    pub(crate) features: HashMap<String, bool>,
+}
+
+impl BuildProfile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            base_url: None,
+            include_drafts: None,
+            features: HashMap::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Overrides the base URL of the site when building with this profile.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Overrides whether drafts are included when building with this profile.
+    pub fn include_drafts(mut self, include_drafts: bool) -> Self {
+        self.include_drafts = Some(include_drafts);
+        self
+    }
+
+    /// Turns the named feature on or off when building with this profile.
+    ///
+    /// Templates can check for features using
+    /// [`BaseRenderContext::is_feature_enabled`](crate::render::BaseRenderContext::is_feature_enabled).
+    pub fn feature(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.features.insert(name.into(), enabled);
+        self
+    }
+
+    pub fn is_feature_enabled(&self, name: &str) -> bool {
+        self.features.get(name).copied().unwrap_or(false)
+    }
+}
+
+/// Options for building a site with [`Site::build_with_options`](crate::Site::build_with_options).
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    pub(crate) profile: Option<String>,
+    pub(crate) low_memory: bool,
+    pub(crate) timings: bool,
+}
+
+impl BuildOptions {
+    /// Returns the default [`BuildOptions`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the [`BuildOptions`] selected by the environment.
+    ///
+    /// The profile is read from the `RAZORBILL_PROFILE` environment variable.
+    pub fn from_env() -> Self {
+        Self {
+            profile: env::var(PROFILE_ENV_VAR)
+                .ok()
+                .filter(|profile| !profile.is_empty()),
+            low_memory: false,
+            timings: false,
+        }
+    }
+
+    /// Sets the name of the [`BuildProfile`] to build with.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Sets whether the site should be built using as little memory as possible.
+    ///
+    /// Rendered Markdown isn't cached, so it doesn't need to be copied when
+    /// links in it are replaced, and the raw content of each page and section
+    /// is dropped once it has been rendered. This means that
+    /// [`PageToRender::raw_content`](crate::render::PageToRender::raw_content)
+    /// and [`SectionToRender::raw_content`](crate::render::SectionToRender::raw_content)
+    /// are empty in templates.
+    ///
+    /// This is useful for building large sites on memory-constrained machines,
+    /// like CI runners. To use it for every build, including the ones made
+    /// while serving, use [`SiteBuilder::low_memory`](crate::SiteBuilder::low_memory).
+    pub fn low_memory(mut self, low_memory: bool) -> Self {
+        self.low_memory = low_memory;
+        self
+    }
+
+    /// Sets whether the time spent rendering each page should be collected.
+    ///
+    /// The timings are included in the [`BuildReport`](crate::BuildReport)
+    /// and written to `timings.json` in the site's root directory (not the
+    /// output directory, so that they aren't deployed). Rendered Markdown
+    /// isn't cached while collecting timings, so that every page's Markdown is
+    /// actually rendered.
+    pub fn timings(mut self, timings: bool) -> Self {
+        self.timings = timings;
+        self
+    }
+}
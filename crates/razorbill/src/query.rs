@@ -0,0 +1,336 @@
+//! Queries over the pages of a site, for use in templates.
+//!
+//! Queries start from [`BaseRenderContext::pages`](crate::render::BaseRenderContext::pages)
+//! and narrow down the pages from there:
+//!
+//! ```
+//! use razorbill::content::SortBy;
+//! use razorbill::render::{PageToRender, RenderPageContext};
+//!
+//! fn recent_rust_posts<'a>(ctx: &RenderPageContext<'a>) -> Vec<PageToRender<'a>> {
+//!     ctx.pages()
+//!         .in_section("@/posts/_index.md")
+//!         .with_taxonomy("tags", "rust")
+//!         .sorted_by(SortBy::Date)
+//!         .take(5)
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::content::{
+    sort_pages_by, Page, Pages, Section, Sections, SortBy, SortOrder, TermNormalization,
+};
+use crate::render::{resolve_content_path, PageToRender};
+
+/// A query over the pages of a site.
+///
+/// Unless the query is sorted with [`sorted_by`](Self::sorted_by), the pages
+/// are ordered by permalink, or in the order of their section when narrowed
+/// down with [`in_section`](Self::in_section).
+pub struct PageQuery<'a> {
+    content_path: &'a Path,
+    sections: &'a Sections,
+    pages: &'a Pages,
+    term_normalization: TermNormalization,
+    matches: Vec<&'a Page>,
+    sort: Option<(SortBy, SortOrder)>,
+}
+
+impl<'a> PageQuery<'a> {
+    pub(crate) fn new(
+        content_path: &'a Path,
+        sections: &'a Sections,
+        pages: &'a Pages,
+        term_normalization: TermNormalization,
+    ) -> Self {
+        let mut matches = pages.values().collect::<Vec<_>>();
+        matches.sort_by(|a, b| a.permalink.cmp(&b.permalink));
+
+        Self {
+            content_path,
+            sections,
+            pages,
+            term_normalization,
+            matches,
+            sort: None,
+        }
+    }
+
+    /// Only includes the pages listed in the section at the given path (e.g.,
+    /// `@/posts/_index.md`), including those in its transparent subsections.
+    ///
+    /// Matches nothing if the section doesn't exist.
+    pub fn in_section(self, path: impl AsRef<Path>) -> Self {
+        self.in_section_pages(path, |section| section.pages.as_slice())
+    }
+
+    /// Only includes the pages in the section at the given path and all of the
+    /// sections nested beneath it.
+    ///
+    /// Matches nothing if the section doesn't exist.
+    pub fn under_section(self, path: impl AsRef<Path>) -> Self {
+        self.in_section_pages(path, |section| section.all_pages.as_slice())
+    }
+
+    fn in_section_pages(
+        mut self,
+        path: impl AsRef<Path>,
+        section_pages: impl Fn(&'a Section) -> &'a [PathBuf],
+    ) -> Self {
+        let path = resolve_content_path(self.content_path, path.as_ref());
+        let Some(section) = self.sections.get(&path) else {
+            self.matches.clear();
+            return self;
+        };
+
+        let pages = self.pages;
+        let matches = self
+            .matches
+            .iter()
+            .map(|page| page.file.path.as_path())
+            .collect::<HashSet<_>>();
+
+        let section_matches = section_pages(section)
+            .iter()
+            .filter(|path| matches.contains(path.as_path()))
+            .filter_map(|path| pages.get(path))
+            .collect();
+
+        self.matches = section_matches;
+        self
+    }
+
+    /// Only includes the pages with the given term in the given taxonomy.
+    ///
+    /// Terms are compared using the site's [`TermNormalization`], so any
+    /// spelling of a term matches it.
+    pub fn with_taxonomy(mut self, taxonomy: &str, term: &str) -> Self {
+        let term_normalization = self.term_normalization;
+        let term = term_normalization.normalize(term);
+
+        self.matches.retain(|page| {
+            page.meta.taxonomies.get(taxonomy).is_some_and(|terms| {
+                terms
+                    .iter()
+                    .any(|candidate| term_normalization.normalize(candidate) == term)
+            })
+        });
+        self
+    }
+
+    /// Only includes the pages that match the given predicate.
+    pub fn filter(mut self, predicate: impl Fn(&PageToRender<'a>) -> bool) -> Self {
+        self.matches
+            .retain(|page| predicate(&PageToRender::from_page(page)));
+        self
+    }
+
+    /// Sorts the pages by the given field, in its
+    /// [default order](SortBy::default_order).
+    ///
+    /// Pages without a value for the field are placed after all of the others.
+    pub fn sorted_by(self, sort_by: SortBy) -> Self {
+        self.sorted_by_in(sort_by, sort_by.default_order())
+    }
+
+    /// Sorts the pages by the given field, in the given order.
+    ///
+    /// Pages without a value for the field are placed after all of the others.
+    pub fn sorted_by_in(mut self, sort_by: SortBy, sort_order: SortOrder) -> Self {
+        self.sort = Some((sort_by, sort_order));
+        self
+    }
+
+    /// Returns the number of matching pages.
+    pub fn count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Returns the first matching page.
+    pub fn first(self) -> Option<PageToRender<'a>> {
+        self.take(1).into_iter().next()
+    }
+
+    /// Returns up to the given number of matching pages.
+    pub fn take(self, limit: usize) -> Vec<PageToRender<'a>> {
+        let mut pages = self.into_pages();
+        pages.truncate(limit);
+        pages
+    }
+
+    /// Returns all of the matching pages.
+    pub fn all(self) -> Vec<PageToRender<'a>> {
+        self.into_pages()
+    }
+
+    fn into_pages(self) -> Vec<PageToRender<'a>> {
+        let pages = self.pages;
+        let matches = match self.sort {
+            Some((sort_by, sort_order)) => {
+                let (sorted, unsorted) = sort_pages_by(sort_by, sort_order, self.matches);
+
+                sorted
+                    .iter()
+                    .chain(unsorted.iter())
+                    .filter_map(|path| pages.get(path))
+                    .collect::<Vec<_>>()
+            }
+            None => self.matches,
+        };
+
+        matches.into_iter().map(PageToRender::from_page).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::content::Taxonomy;
+    use crate::test_site::TestSite;
+    use crate::Site;
+
+    use super::*;
+
+    fn test_site(name: &str) -> TestSite {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("posts/_index.md", "+++\nsort_by = \"date\"\n+++\n")
+            .write(
+                "posts/alpha.md",
+                "+++\ntitle = \"Alpha\"\ndate = 2024-01-01\n[taxonomies]\ntags = [\"Rust\"]\n+++\n",
+            )
+            .write(
+                "posts/bravo.md",
+                "+++\ntitle = \"Bravo\"\ndate = 2024-03-01\n[taxonomies]\ntags = [\"rust\", \"web\"]\n+++\n",
+            )
+            .write(
+                "posts/charlie.md",
+                "+++\ntitle = \"Charlie\"\ndate = 2024-02-01\n[taxonomies]\ntags = [\"web\"]\n+++\n",
+            )
+            .write("notes/_index.md", "+++\n+++\n")
+            .write(
+                "notes/delta.md",
+                "+++\ntitle = \"Delta\"\n[taxonomies]\ntags = [\"rust\"]\n+++\n",
+            );
+        test_site
+    }
+
+    fn load(test_site: &TestSite) -> Site {
+        let mut site = test_site
+            .builder()
+            .add_taxonomy(Taxonomy::new("tags"), |_| auk::html(), |_| auk::html())
+            .term_normalization(TermNormalization::all())
+            .build();
+        site.load().unwrap();
+        site
+    }
+
+    fn query(site: &Site) -> PageQuery<'_> {
+        PageQuery::new(
+            &site.content_path,
+            &site.sections,
+            &site.pages,
+            site.term_normalization,
+        )
+    }
+
+    fn titles<'a>(pages: Vec<PageToRender<'a>>) -> Vec<&'a str> {
+        pages
+            .into_iter()
+            .map(|page| page.title.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_all_pages_ordered_by_permalink() {
+        let test_site = test_site("query_all");
+        let site = load(&test_site);
+
+        assert_eq!(
+            titles(query(&site).all()),
+            vec!["Delta", "Alpha", "Bravo", "Charlie"]
+        );
+    }
+
+    #[test]
+    fn test_in_section() {
+        let test_site = test_site("query_in_section");
+        let site = load(&test_site);
+
+        assert_eq!(
+            titles(query(&site).in_section("@/posts/_index.md").all()),
+            vec!["Bravo", "Charlie", "Alpha"]
+        );
+        assert_eq!(query(&site).in_section("@/missing/_index.md").count(), 0);
+    }
+
+    #[test]
+    fn test_with_taxonomy() {
+        let test_site = test_site("query_with_taxonomy");
+        let site = load(&test_site);
+
+        assert_eq!(
+            titles(query(&site).with_taxonomy("tags", "rust").all()),
+            vec!["Delta", "Alpha", "Bravo"]
+        );
+        assert_eq!(
+            titles(query(&site).with_taxonomy("tags", " RUST ").all()),
+            vec!["Delta", "Alpha", "Bravo"]
+        );
+        assert_eq!(query(&site).with_taxonomy("tags", "go").count(), 0);
+        assert_eq!(query(&site).with_taxonomy("categories", "rust").count(), 0);
+    }
+
+    #[test]
+    fn test_sorted_by() {
+        let test_site = test_site("query_sorted_by");
+        let site = load(&test_site);
+
+        assert_eq!(
+            titles(query(&site).sorted_by(SortBy::Title).all()),
+            vec!["Alpha", "Bravo", "Charlie", "Delta"]
+        );
+        assert_eq!(
+            titles(
+                query(&site)
+                    .sorted_by_in(SortBy::Title, SortOrder::Descending)
+                    .all()
+            ),
+            vec!["Delta", "Charlie", "Bravo", "Alpha"]
+        );
+        // Pages without a date are placed last.
+        assert_eq!(
+            titles(query(&site).sorted_by(SortBy::Date).all()),
+            vec!["Bravo", "Charlie", "Alpha", "Delta"]
+        );
+    }
+
+    #[test]
+    fn test_take() {
+        let test_site = test_site("query_take");
+        let site = load(&test_site);
+
+        assert_eq!(
+            titles(
+                query(&site)
+                    .in_section("@/posts/_index.md")
+                    .with_taxonomy("tags", "Rust")
+                    .sorted_by(SortBy::Date)
+                    .take(1)
+            ),
+            vec!["Bravo"]
+        );
+        assert_eq!(titles(query(&site).take(10)).len(), 4);
+        assert_eq!(
+            query(&site)
+                .sorted_by(SortBy::Title)
+                .first()
+                .and_then(|page| page.title.clone()),
+            Some("Alpha".to_string())
+        );
+    }
+}
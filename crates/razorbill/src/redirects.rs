@@ -0,0 +1,318 @@
+//! Generating host-specific redirect configuration.
+//!
+//! The site's `base_url` is treated as the canonical scheme and host. Requests
+//! for any of the alternate hosts (e.g., `www.example.com`), as well as plain
+//! HTTP requests when the site is served over HTTPS, are redirected to it. The
+//! aliases of the site's content are included as redirects as well.
+//!
+//! ```
+//! use razorbill::redirects::{HostRedirects, RedirectsOutput};
+//! use razorbill::Site;
+//!
+//! let builder = Site::builder().host_redirects(
+//!     HostRedirects::new(RedirectsOutput::RedirectsFile).alternate_host("www.example.com"),
+//! );
+//! ```
+//!
+//! The configuration is written to the output directory, alongside the rest of
+//! the site.
+
+use url::Url;
+
+use crate::permalink::Permalink;
+
+/// Where the generated redirects are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectsOutput {
+    /// A `_redirects` file, as used by Netlify and Cloudflare Pages.
+    RedirectsFile,
+    /// A `Caddyfile` with a site block for the alternate hosts, and an
+    /// `aliases` snippet for importing into the site block of the canonical
+    /// host.
+    Caddyfile,
+    /// A `redirects.nginx.conf` file with `server` blocks for the alternate
+    /// hosts, and an `aliases.nginx.conf` file with `location` blocks for
+    /// including in the `server` block of the canonical host.
+    Nginx,
+}
+
+/// The configuration for redirecting to the site's canonical scheme and host.
+#[derive(Debug, Clone)]
+pub struct HostRedirects {
+    output: RedirectsOutput,
+    alternate_hosts: Vec<String>,
+    redirect_http: bool,
+}
+
+impl HostRedirects {
+    /// Returns a new [`HostRedirects`] that redirects plain HTTP requests to
+    /// HTTPS, when the site is served over HTTPS.
+    pub fn new(output: RedirectsOutput) -> Self {
+        Self {
+            output,
+            alternate_hosts: Vec::new(),
+            redirect_http: true,
+        }
+    }
+
+    /// Redirects requests for the given host (e.g., `www.example.com`) to the
+    /// canonical host.
+    pub fn alternate_host(mut self, host: impl Into<String>) -> Self {
+        self.alternate_hosts.push(host.into());
+        self
+    }
+
+    /// Sets whether plain HTTP requests to the canonical host are redirected
+    /// to HTTPS.
+    ///
+    /// Caddy always does this on its own, so this has no effect on the
+    /// [`Caddyfile`](RedirectsOutput::Caddyfile).
+    pub fn redirect_http(mut self, redirect_http: bool) -> Self {
+        self.redirect_http = redirect_http;
+        self
+    }
+
+    /// Returns the files to write for the site at `base_url` with the given
+    /// aliases, as pairs of their paths and contents.
+    pub(crate) fn files(
+        &self,
+        base_url: &str,
+        aliases: &[(Permalink, &Permalink)],
+    ) -> Vec<(&'static str, String)> {
+        let canonical = CanonicalHost::new(Permalink::from_base_url(base_url, "").as_url());
+        let aliases = aliases
+            .iter()
+            .map(|(alias, permalink)| (alias.path(), permalink.path()))
+            .collect::<Vec<_>>();
+
+        match self.output {
+            RedirectsOutput::RedirectsFile => {
+                vec![("_redirects", self.redirects_file(&canonical, &aliases))]
+            }
+            RedirectsOutput::Caddyfile => vec![("Caddyfile", self.caddyfile(&canonical, &aliases))],
+            RedirectsOutput::Nginx => vec![
+                ("redirects.nginx.conf", self.nginx_servers(&canonical)),
+                ("aliases.nginx.conf", nginx_locations(&aliases)),
+            ],
+        }
+    }
+
+    /// Returns whether plain HTTP requests to the canonical host need to be
+    /// redirected.
+    fn redirects_http(&self, canonical: &CanonicalHost) -> bool {
+        self.redirect_http && canonical.scheme == "https"
+    }
+
+    fn redirects_file(&self, canonical: &CanonicalHost, aliases: &[(&str, &str)]) -> String {
+        let origin = &canonical.origin;
+        let mut lines = Vec::new();
+
+        for host in &self.alternate_hosts {
+            for scheme in ["http", "https"] {
+                lines.push(format!("{scheme}://{host}/* {origin}/:splat 301!"));
+            }
+        }
+
+        if self.redirects_http(canonical) {
+            lines.push(format!("http://{}/* {origin}/:splat 301!", canonical.host));
+        }
+
+        for (alias, permalink) in aliases {
+            lines.push(format!("{alias} {permalink} 301"));
+        }
+
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    fn caddyfile(&self, canonical: &CanonicalHost, aliases: &[(&str, &str)]) -> String {
+        let origin = &canonical.origin;
+        let mut blocks = Vec::new();
+
+        // Caddy already redirects plain HTTP requests to HTTPS for the canonical
+        // host, so only the alternate hosts need a site block.
+        if !self.alternate_hosts.is_empty() {
+            let addresses = self
+                .alternate_hosts
+                .iter()
+                .flat_map(|host| [format!("http://{host}"), format!("https://{host}")])
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            blocks.push(format!(
+                "{addresses} {{\n\tredir {origin}{{uri}} permanent\n}}\n"
+            ));
+        }
+
+        let mut snippet = String::from("(aliases) {\n");
+        for (alias, permalink) in aliases {
+            snippet.push_str(&format!("\tredir {alias} {permalink} permanent\n"));
+        }
+        snippet.push_str("}\n");
+        blocks.push(snippet);
+
+        blocks.join("\n")
+    }
+
+    fn nginx_servers(&self, canonical: &CanonicalHost) -> String {
+        let origin = &canonical.origin;
+        let mut blocks = Vec::new();
+
+        let mut http_hosts = self.alternate_hosts.clone();
+        if self.redirects_http(canonical) {
+            http_hosts.push(canonical.host.clone());
+        }
+
+        if !http_hosts.is_empty() {
+            blocks.push(nginx_server(
+                &["listen 80;", "listen [::]:80;"],
+                &http_hosts,
+                origin,
+            ));
+        }
+
+        if !self.alternate_hosts.is_empty() {
+            blocks.push(nginx_server(
+                &[
+                    "listen 443 ssl;",
+                    "listen [::]:443 ssl;",
+                    "# The `ssl_certificate` and `ssl_certificate_key` for these hosts go here.",
+                ],
+                &self.alternate_hosts,
+                origin,
+            ));
+        }
+
+        blocks.join("\n")
+    }
+}
+
+/// The canonical scheme and host of a site, taken from its `base_url`.
+struct CanonicalHost {
+    scheme: String,
+    /// The host, including the port if it isn't the default one.
+    host: String,
+    /// The scheme and host, without a trailing `/` (e.g., `https://example.com`).
+    origin: String,
+}
+
+impl CanonicalHost {
+    fn new(base_url: &Url) -> Self {
+        let host = match (base_url.host_str(), base_url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => String::new(),
+        };
+
+        Self {
+            scheme: base_url.scheme().to_string(),
+            origin: format!("{}://{host}", base_url.scheme()),
+            host,
+        }
+    }
+}
+
+fn nginx_server(directives: &[&str], hosts: &[String], origin: &str) -> String {
+    let mut server = String::from("server {\n");
+    for directive in directives {
+        server.push_str(&format!("    {directive}\n"));
+    }
+    server.push_str(&format!("    server_name {};\n", hosts.join(" ")));
+    server.push_str(&format!("    return 301 {origin}$request_uri;\n"));
+    server.push_str("}\n");
+    server
+}
+
+fn nginx_locations(aliases: &[(&str, &str)]) -> String {
+    aliases
+        .iter()
+        .map(|(alias, permalink)| {
+            format!("location = {alias} {{\n    return 301 {permalink};\n}}\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const BASE_URL: &str = "https://example.com";
+
+    fn aliases() -> (Permalink, Permalink) {
+        (
+            Permalink::from_base_url(BASE_URL, "/old-post"),
+            Permalink::from_base_url(BASE_URL, "/blog/new-post"),
+        )
+    }
+
+    #[test]
+    fn test_redirects_file() {
+        let (alias, permalink) = aliases();
+        let files = HostRedirects::new(RedirectsOutput::RedirectsFile)
+            .alternate_host("www.example.com")
+            .files(BASE_URL, &[(alias, &permalink)]);
+
+        assert_eq!(
+            files,
+            vec![(
+                "_redirects",
+                [
+                    "http://www.example.com/* https://example.com/:splat 301!",
+                    "https://www.example.com/* https://example.com/:splat 301!",
+                    "http://example.com/* https://example.com/:splat 301!",
+                    "/old-post/ /blog/new-post/ 301",
+                    "",
+                ]
+                .join("\n")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_caddyfile() {
+        let (alias, permalink) = aliases();
+        let files = HostRedirects::new(RedirectsOutput::Caddyfile)
+            .alternate_host("www.example.com")
+            .files(BASE_URL, &[(alias, &permalink)]);
+
+        assert_eq!(
+            files,
+            vec![(
+                "Caddyfile",
+                [
+                    "http://www.example.com, https://www.example.com {",
+                    "\tredir https://example.com{uri} permanent",
+                    "}",
+                    "",
+                    "(aliases) {",
+                    "\tredir /old-post/ /blog/new-post/ permanent",
+                    "}",
+                    "",
+                ]
+                .join("\n")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_nginx() {
+        let (alias, permalink) = aliases();
+        let files = HostRedirects::new(RedirectsOutput::Nginx)
+            .redirect_http(false)
+            .files(BASE_URL, &[(alias, &permalink)]);
+
+        assert_eq!(
+            files,
+            vec![
+                ("redirects.nginx.conf", String::new()),
+                (
+                    "aliases.nginx.conf",
+                    "location = /old-post/ {\n    return 301 /blog/new-post/;\n}\n".to_string()
+                ),
+            ]
+        );
+    }
+}
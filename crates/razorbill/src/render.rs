@@ -1,21 +1,56 @@
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use auk::Element;
+use auk::{link, meta, Element, HtmlElement};
 use auk_markdown::{MarkdownComponents, TableOfContents};
+use chrono::{Datelike, NaiveDate};
+use chrono_tz::Tz;
 use serde::Deserialize;
+use thiserror::Error;
 
-use crate::content::{Page, Pages, ReadTime, Section, Sections, WordCount};
-use crate::markdown::{markdown_with_shortcodes, Shortcode};
+use crate::content::{
+    compare_sections_by_weight, merge_tables, ContentRef, Page, Pages, ReadTime, Section, Sections,
+    SiteStats, Taxonomy, TermNormalization, WordCount,
+};
+use crate::date::{format_date_localized, parse_date};
+use crate::feed::feed_link;
+use crate::i18n::Translations;
+use crate::markdown::{
+    markdown_with_options, unwrap_paragraph, Heading, MarkdownOptions, Shortcode, ShortcodeError,
+};
+use crate::menu::{MenuItem, MenuItemToRender};
+use crate::permalink::Permalink;
+use crate::profile::BuildProfile;
+use crate::query::PageQuery;
+use crate::versions::{VersionToRender, Versions};
 
 pub struct BaseRenderContext<'a> {
     pub(crate) base_url: &'a str,
+    pub(crate) site_title: Option<&'a str>,
     pub(crate) content_path: &'a Path,
     pub(crate) markdown_components: &'a Box<dyn MarkdownComponents>,
     pub(crate) shortcodes: &'a HashMap<String, Shortcode>,
+    pub(crate) markdown_options: MarkdownOptions<'a>,
     pub(crate) sections: &'a Sections,
     pub(crate) pages: &'a Pages,
+    pub(crate) stats: &'a SiteStats,
+    pub(crate) dated_pages: &'a [PathBuf],
+    pub(crate) webmention_endpoint: Option<&'a str>,
+    pub(crate) extra: &'a toml::Table,
+    pub(crate) taxonomies: &'a [Taxonomy],
+    pub(crate) term_normalization: TermNormalization,
+    pub(crate) profile: Option<&'a BuildProfile>,
+    pub(crate) timezone: Tz,
+    pub(crate) date_format: &'a str,
+    pub(crate) default_language: &'a str,
+    pub(crate) translations: &'a Translations,
+    pub(crate) versions: Option<&'a Versions>,
+    pub(crate) version_permalinks: &'a HashMap<String, Permalink>,
+    pub(crate) menus: &'a HashMap<String, Vec<MenuItem>>,
+    /// Whether the site's feeds are rendered.
+    pub(crate) feeds: bool,
 }
 
 impl<'a> BaseRenderContext<'a> {
@@ -23,58 +58,416 @@ impl<'a> BaseRenderContext<'a> {
         self.base_url
     }
 
-    /// Renders the provided Markdown text.
-    pub fn render_markdown(&self, text: &str) -> Vec<Element> {
-        let (markdown, _table_of_contents) =
-            markdown_with_shortcodes(text, self.markdown_components, self.shortcodes);
-        markdown
+    /// Returns the URL of the page for the given term of a taxonomy, or `None`
+    /// if the taxonomy isn't registered.
+    pub fn taxonomy_term_url(&self, taxonomy: &str, term: &str) -> Option<String> {
+        let taxonomy = self
+            .taxonomies
+            .iter()
+            .find(|candidate| candidate.name == taxonomy)?;
+
+        Some(
+            taxonomy
+                .term_permalink(self.base_url, term)
+                .as_str()
+                .to_owned(),
+        )
     }
 
-    pub fn get_section(&self, path: impl AsRef<Path>) -> Option<SectionToRender<'a>> {
-        let path = path.as_ref();
-        let path = if path.starts_with("@/") {
-            let mut new_path = self.content_path.to_owned();
+    /// Returns the site-wide settings.
+    pub fn site(&self) -> SiteToRender<'a> {
+        SiteToRender {
+            title: self.site_title,
+            base_url: self.base_url,
+            taxonomies: self.taxonomies,
+            extra: self.extra,
+        }
+    }
 
-            let mut components = path.components();
-            components.next();
+    /// Returns the site's user-defined settings.
+    pub fn extra<'de, T>(&self) -> Result<T, toml::de::Error>
+    where
+        T: Deserialize<'de>,
+    {
+        T::deserialize(self.extra.clone())
+    }
 
-            for component in components {
-                new_path.push(component);
-            }
+    /// Returns the site's default language.
+    pub fn lang(&self) -> &'a str {
+        self.default_language
+    }
+
+    /// Returns the translation of the given key in the site's default language.
+    ///
+    /// See [`translate`](Self::translate) for how missing translations are handled.
+    pub fn t(&self, key: &str) -> String {
+        self.translate(self.default_language, key)
+    }
+
+    /// Returns the translation of the given key in the given language.
+    ///
+    /// Falls back to the translation in the site's default language, and then
+    /// to the key itself, so missing translations are easy to spot.
+    pub fn translate(&self, language: &str, key: &str) -> String {
+        self.translations
+            .get(language, key)
+            .or_else(|| self.translations.get(self.default_language, key))
+            .unwrap_or(key)
+            .to_string()
+    }
+
+    /// Formats the given date (e.g., a page's `date`) in the site's timezone
+    /// and default language.
+    ///
+    /// See [`format_date_in`](Self::format_date_in) for more details.
+    pub fn format_date(&self, date: &str, format: Option<&str>) -> String {
+        self.format_date_in(self.default_language, date, format)
+    }
+
+    /// Formats the given date (e.g., a page's `date`) in the site's timezone,
+    /// with the month and day names in the given language.
+    ///
+    /// The site's default date format is used when `format` is `None`. Dates
+    /// that can't be formatted are returned as-is.
+    pub fn format_date_in(&self, language: &str, date: &str, format: Option<&str>) -> String {
+        let format = format.unwrap_or(self.date_format);
+        let locale = self.translations.locale(language);
+
+        format_date_localized(date, format, self.timezone, locale)
+            .unwrap_or_else(|| date.to_string())
+    }
+
+    /// Returns the items in the menu with the given name.
+    ///
+    /// Since there is no section or page being rendered, none of the items are
+    /// marked as active.
+    pub fn menu(&self, name: &str) -> Vec<MenuItemToRender<'a>> {
+        self.menu_for(name, None, &[])
+    }
 
-            new_path
-        } else {
-            path.to_owned()
+    /// Returns the items in the menu with the given name, marking the ones
+    /// that link to the current section or page, or to the sections
+    /// containing it, as active.
+    ///
+    /// Items that link to content that doesn't exist are left out.
+    pub(crate) fn menu_for(
+        &self,
+        name: &str,
+        current: Option<(&Path, &str)>,
+        ancestors: &[&Path],
+    ) -> Vec<MenuItemToRender<'a>> {
+        let Some(items) = self.menus.get(name) else {
+            return Vec::new();
         };
+        let sections = self.sections;
+        let pages = self.pages;
 
-        let section = self.sections.get(&path)?;
+        items
+            .iter()
+            .filter_map(|item| item.resolve(self.content_path, sections, pages))
+            .map(|item| {
+                let is_active =
+                    current.map_or(false, |(current_path, current_permalink)| {
+                        match item.file_path {
+                            Some(file_path) => file_path == current_path,
+                            None => item.permalink == current_permalink,
+                        }
+                    });
+                let is_ancestor_active = item
+                    .file_path
+                    .map_or(false, |file_path| ancestors.contains(&file_path));
 
-        Some(SectionToRender::from_section(section, &self.pages))
+                MenuItemToRender {
+                    title: item.title,
+                    permalink: item.permalink,
+                    is_active,
+                    is_ancestor_active,
+                }
+            })
+            .collect()
     }
 
-    pub fn get_page(&self, path: impl AsRef<Path>) -> Option<PageToRender<'a>> {
-        let path = path.as_ref();
-        let path = if path.starts_with("@/") {
-            let mut new_path = self.content_path.to_owned();
+    /// Returns the versions of the content at the given path, for use in a
+    /// version picker.
+    ///
+    /// Returns an empty list if the site isn't versioned or the content isn't
+    /// within a version.
+    pub(crate) fn versions_of(&self, path: &str) -> Vec<VersionToRender<'a>> {
+        self.versions
+            .map(|versions| versions.versions_of(path, self.version_permalinks))
+            .unwrap_or_default()
+    }
 
-            let mut components = path.components();
-            components.next();
+    /// Returns the canonical URL of the content at the given path, which is
+    /// its permalink in the latest version, if it exists there.
+    pub(crate) fn canonical_url_of(&self, path: &str, permalink: &'a str) -> &'a str {
+        self.versions
+            .and_then(|versions| versions.latest_permalink(path, self.version_permalinks))
+            .map_or(permalink, Permalink::as_str)
+    }
 
-            for component in components {
-                new_path.push(component);
+    /// Returns the name of the build profile the site is being built with, if any.
+    pub fn profile(&self) -> Option<&'a str> {
+        self.profile.map(|profile| profile.name())
+    }
+
+    /// Returns whether the given feature is enabled by the current build profile.
+    pub fn is_feature_enabled(&self, name: &str) -> bool {
+        self.profile
+            .map(|profile| profile.is_feature_enabled(name))
+            .unwrap_or(false)
+    }
+
+    /// Returns the `<link>` elements for the feeds relevant to the current page,
+    /// for placing in the `<head>` to enable feed autodiscovery.
+    ///
+    /// Returns no links when the feeds aren't rendered.
+    pub fn feed_links(&self) -> Vec<HtmlElement> {
+        if !self.feeds {
+            return Vec::new();
+        }
+
+        let site_feed_url = format!("{}/atom.xml", self.base_url.trim_end_matches('/'));
+
+        vec![feed_link(&site_feed_url, self.site_title, None)]
+    }
+
+    /// Returns the `<link>` element advertising the site's Webmention endpoint,
+    /// if it has one, for placing in the `<head>`.
+    pub fn webmention_links(&self) -> Vec<HtmlElement> {
+        self.webmention_endpoint
+            .map(|endpoint| link().rel("webmention").href(endpoint))
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns the `<link>` element for the feed of the given section, if it has one.
+    fn section_feed_link(&self, section: &SectionToRender) -> Option<HtmlElement> {
+        if !self.feeds || !section.generate_feed {
+            return None;
+        }
+
+        let title = section.title.as_deref().unwrap_or(section.path);
+
+        Some(feed_link(
+            &format!("{}atom.xml", section.permalink),
+            self.site_title,
+            Some(title),
+        ))
+    }
+
+    /// Returns the aggregate statistics about the site's content.
+    pub fn stats(&self) -> &'a SiteStats {
+        self.stats
+    }
+
+    /// Returns the dated pages grouped by the year they were published in.
+    ///
+    /// Both the years and the pages within them are ordered from newest to oldest.
+    pub fn pages_by_year(&self) -> Vec<PagesByYear<'a>> {
+        let mut pages_by_year: Vec<PagesByYear<'a>> = Vec::new();
+
+        for (date, page) in self.dated_pages() {
+            let page = PageToRender::from_page(page);
+
+            match pages_by_year.last_mut() {
+                Some(bucket) if bucket.year == date.year() => bucket.pages.push(page),
+                _ => pages_by_year.push(PagesByYear {
+                    year: date.year(),
+                    pages: vec![page],
+                }),
             }
+        }
 
-            new_path
-        } else {
-            path.to_owned()
+        pages_by_year
+    }
+
+    /// Returns the dated pages grouped by the month they were published in.
+    ///
+    /// Both the months and the pages within them are ordered from newest to oldest.
+    pub fn pages_by_month(&self) -> Vec<PagesByMonth<'a>> {
+        let mut pages_by_month: Vec<PagesByMonth<'a>> = Vec::new();
+
+        for (date, page) in self.dated_pages() {
+            let page = PageToRender::from_page(page);
+
+            match pages_by_month.last_mut() {
+                Some(bucket) if bucket.year == date.year() && bucket.month == date.month() => {
+                    bucket.pages.push(page)
+                }
+                _ => pages_by_month.push(PagesByMonth {
+                    year: date.year(),
+                    month: date.month(),
+                    pages: vec![page],
+                }),
+            }
+        }
+
+        pages_by_month
+    }
+
+    fn dated_pages(&self) -> impl Iterator<Item = (NaiveDate, &'a Page)> + 'a {
+        let pages = self.pages;
+
+        self.dated_pages.iter().filter_map(move |path| {
+            let page = pages.get(path)?;
+            let date = parse_date(page.meta.date.as_deref()?)?;
+
+            Some((date, page))
+        })
+    }
+
+    /// Renders the provided Markdown text.
+    ///
+    /// Returns an error if the shortcodes in the text can't be rendered.
+    pub fn render_markdown(&self, text: &str) -> Result<Vec<Element>, ShortcodeError> {
+        let (markdown, _table_of_contents) = markdown_with_options(
+            text,
+            self.markdown_components,
+            self.shortcodes,
+            self.markdown_options,
+        )?;
+        Ok(markdown)
+    }
+
+    /// Renders the provided Markdown text for use inline within other elements.
+    ///
+    /// This is useful for rendering front matter fields, like a description,
+    /// through the same shortcode and Markdown pipeline as the content, without
+    /// the result being wrapped in a paragraph.
+    pub fn render_markdown_inline(&self, text: &str) -> Result<Vec<Element>, ShortcodeError> {
+        self.render_markdown(text).map(unwrap_paragraph)
+    }
+
+    /// Returns the section with the given path, [`SectionPath`](crate::content::SectionPath),
+    /// or permalink.
+    pub fn get_section(&self, section: impl Into<ContentRef>) -> Option<SectionToRender<'a>> {
+        let sections = self.sections;
+
+        let section = match section.into() {
+            ContentRef::File(path) => {
+                sections.get(&resolve_content_path(self.content_path, &path))?
+            }
+            ContentRef::Section(path) => sections.values().find(|section| section.path == path)?,
+            ContentRef::Permalink(permalink) => sections
+                .values()
+                .find(|section| section.permalink == permalink)?,
+            ContentRef::Page(_) => return None,
         };
 
-        let page = self.pages.get(&path)?;
+        Some(SectionToRender::from_section(section, self.pages))
+    }
+
+    /// Returns a query over all of the site's pages.
+    pub fn pages(&self) -> PageQuery<'a> {
+        PageQuery::new(
+            self.content_path,
+            self.sections,
+            self.pages,
+            self.term_normalization,
+        )
+    }
+
+    /// Returns the top-level sections of the site, ordered by weight.
+    ///
+    /// These are the sections nested directly beneath the root section, making
+    /// them suitable for driving the site's navigation.
+    pub fn top_level_sections(&self) -> Vec<SectionToRender<'a>> {
+        let sections = self.sections;
+
+        match sections.get(&self.content_path.join("_index.md")) {
+            Some(root) => root
+                .subsections
+                .iter()
+                .map(|path| SectionToRender::from_section(&sections[path], self.pages))
+                .collect(),
+            None => {
+                let mut top_level_sections = sections
+                    .values()
+                    .filter(|section| section.file.components.len() == 1)
+                    .collect::<Vec<_>>();
+                top_level_sections.sort_by(|a, b| compare_sections_by_weight(a, b));
+
+                top_level_sections
+                    .into_iter()
+                    .map(|section| SectionToRender::from_section(section, self.pages))
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the sections nested directly beneath the given section, ordered by weight.
+    pub fn subsections(&self, section: &SectionToRender) -> Vec<SectionToRender<'a>> {
+        let sections = self.sections;
+
+        section
+            .subsections
+            .iter()
+            .filter_map(|path| sections.get(path))
+            .map(|section| SectionToRender::from_section(section, self.pages))
+            .collect()
+    }
+
+    /// Returns the page with the given path, [`PagePath`](crate::content::PagePath), or
+    /// permalink.
+    pub fn get_page(&self, page: impl Into<ContentRef>) -> Option<PageToRender<'a>> {
+        let pages = self.pages;
+
+        let page = match page.into() {
+            ContentRef::File(path) => pages.get(&resolve_content_path(self.content_path, &path))?,
+            ContentRef::Page(path) => pages.values().find(|page| page.path == path)?,
+            ContentRef::Permalink(permalink) => {
+                pages.values().find(|page| page.permalink == permalink)?
+            }
+            ContentRef::Section(_) => return None,
+        };
 
         Some(PageToRender::from_page(page))
     }
 }
 
+/// Returns the `<meta>` element that asks search engines not to index a page.
+fn robots_noindex_meta() -> HtmlElement {
+    meta().attr("name", "robots").content("noindex")
+}
+
+/// Resolves a path to a content file, where paths starting with `@/` are
+/// relative to the content directory (e.g., `@/blog/_index.md`).
+pub(crate) fn resolve_content_path(content_path: &Path, path: &Path) -> PathBuf {
+    if !path.starts_with("@/") {
+        return path.to_owned();
+    }
+
+    let mut resolved_path = content_path.to_owned();
+
+    let mut components = path.components();
+    components.next();
+
+    for component in components {
+        resolved_path.push(component);
+    }
+
+    resolved_path
+}
+
+/// The site-wide settings, as configured on the [`SiteBuilder`](crate::SiteBuilder).
+pub struct SiteToRender<'a> {
+    pub title: Option<&'a str>,
+    pub base_url: &'a str,
+    pub taxonomies: &'a [Taxonomy],
+    /// User-defined settings.
+    pub extra: &'a toml::Table,
+}
+
+impl<'a> SiteToRender<'a> {
+    pub fn extra<'de, T>(&self) -> Result<T, toml::de::Error>
+    where
+        T: Deserialize<'de>,
+    {
+        T::deserialize(self.extra.clone())
+    }
+}
+
 pub struct RenderSectionContext<'a> {
     pub(crate) base: BaseRenderContext<'a>,
     pub section: SectionToRender<'a>,
@@ -88,55 +481,223 @@ impl<'a> Deref for RenderSectionContext<'a> {
     }
 }
 
-impl<'a> RenderSectionContext<'a> {}
+impl<'a> RenderSectionContext<'a> {
+    /// Returns the language of the section.
+    pub fn lang(&self) -> &'a str {
+        self.section.lang.unwrap_or(self.base.default_language)
+    }
+
+    /// Returns the translation of the given key in the section's language.
+    pub fn t(&self, key: &str) -> String {
+        self.base.translate(self.lang(), key)
+    }
+
+    /// Formats the given date in the site's timezone, with the month and day
+    /// names in the section's language.
+    pub fn format_date(&self, date: &str, format: Option<&str>) -> String {
+        self.base.format_date_in(self.lang(), date, format)
+    }
+
+    /// Returns the breadcrumbs leading from the root section down to this
+    /// section.
+    pub fn breadcrumbs(&self) -> Vec<Breadcrumb<'a>> {
+        let sections = self.base.sections;
+
+        self.section
+            .ancestors
+            .iter()
+            .filter_map(|path| sections.get(path))
+            .map(|section| Breadcrumb {
+                title: &section.meta.title,
+                permalink: section.permalink.as_str(),
+            })
+            .chain([Breadcrumb {
+                title: self.section.title,
+                permalink: self.section.permalink,
+            }])
+            .collect()
+    }
+
+    /// Returns the items in the menu with the given name, with the ones
+    /// linking to this section or the sections containing it marked as active.
+    pub fn menu(&self, name: &str) -> Vec<MenuItemToRender<'a>> {
+        let ancestors = self
+            .section
+            .ancestors
+            .iter()
+            .map(PathBuf::as_path)
+            .collect::<Vec<_>>();
+
+        self.base.menu_for(
+            name,
+            Some((self.section.file_path, self.section.permalink)),
+            &ancestors,
+        )
+    }
+
+    /// Returns the versions of this section, for use in a version picker.
+    pub fn versions(&self) -> Vec<VersionToRender<'a>> {
+        self.base.versions_of(self.section.path)
+    }
+
+    /// Returns the canonical URL of the section, which points at the latest
+    /// version of the section in versioned documentation.
+    pub fn canonical_url(&self) -> &'a str {
+        self.base
+            .canonical_url_of(self.section.path, self.section.permalink)
+    }
+
+    /// Returns the `<link rel="canonical">` element for the section, for
+    /// placing in the `<head>`.
+    pub fn canonical_link(&self) -> HtmlElement {
+        link().rel("canonical").href(self.canonical_url())
+    }
+
+    /// Returns the `<meta name="robots">` element for the section, for placing
+    /// in the `<head>`, if the section is marked as `no_index`.
+    pub fn robots_meta(&self) -> Option<HtmlElement> {
+        self.section.no_index.then(robots_noindex_meta)
+    }
+
+    /// Returns the `<link>` elements for the site feed and this section's feed,
+    /// for placing in the `<head>` to enable feed autodiscovery.
+    pub fn feed_links(&self) -> Vec<HtmlElement> {
+        let mut feed_links = self.base.feed_links();
+        feed_links.extend(self.base.section_feed_link(&self.section));
+        feed_links
+    }
+
+    /// Returns the value at the given key in the section's `extra` table,
+    /// falling back to the site's `extra` table.
+    pub fn extra_field<'de, T>(&self, key: &str) -> Result<T, ExtraFieldError>
+    where
+        T: Deserialize<'de>,
+    {
+        extra_field(
+            self.section.extra,
+            Some(self.base.extra),
+            key,
+            self.section.file_path,
+        )
+    }
+}
 
 pub struct SectionToRender<'a> {
     pub title: &'a Option<String>,
+    pub description: &'a Option<String>,
+    /// The language of the section, if it specifies one.
+    pub lang: Option<&'a str>,
     pub path: &'a str,
     pub permalink: &'a str,
     pub raw_content: &'a str,
-    pub content: &'a Vec<Element>,
+    pub content: Arc<Vec<Element>>,
     pub table_of_contents: &'a TableOfContents,
+    /// The headings in the content, nested by level.
+    pub headings: &'a [Heading],
     pub word_count: WordCount,
     pub read_time: ReadTime,
+    pub weight: Option<usize>,
+    /// Whether an Atom feed is generated for the section's pages.
+    pub generate_feed: bool,
+    /// Whether the section should be kept out of search engines.
+    pub no_index: bool,
     pub extra: &'a toml::Table,
+    pub(crate) file_path: &'a Path,
     pub pages: Vec<PageToRender<'a>>,
+    /// The paths to the sections nested directly beneath this one, ordered by weight.
+    pub subsections: &'a [PathBuf],
+    pub(crate) ancestors: &'a [PathBuf],
+    pub(crate) all_page_paths: &'a [PathBuf],
+    pub(crate) site_pages: &'a HashMap<PathBuf, Page>,
 }
 
 impl<'a> SectionToRender<'a> {
-    pub fn from_section(section: &'a Section, pages: &'a HashMap<PathBuf, Page>) -> Self {
+    pub fn from_section(section: &'a Section, site_pages: &'a HashMap<PathBuf, Page>) -> Self {
         let pages = section
             .pages
             .iter()
-            .map(|page| pages.get(page).unwrap())
+            .map(|page| site_pages.get(page).unwrap())
             .map(PageToRender::from_page)
             .collect::<Vec<_>>();
 
         Self {
             title: &section.meta.title,
+            description: &section.meta.description,
+            lang: section.meta.lang.as_deref(),
             path: &section.path.0,
             permalink: &section.permalink.as_str(),
             raw_content: &section.raw_content,
-            content: &section.content,
+            content: Arc::clone(&section.content),
             table_of_contents: &section.table_of_contents,
+            headings: &section.headings,
             word_count: section.word_count,
             read_time: section.read_time,
+            weight: section.meta.weight,
+            generate_feed: section.meta.generate_feed,
+            no_index: section.meta.no_index,
             extra: &section.meta.extra,
+            file_path: &section.file.path,
             pages,
+            subsections: &section.subsections,
+            ancestors: &section.ancestors,
+            all_page_paths: &section.all_pages,
+            site_pages,
         }
     }
 
+    /// Returns the pages in this section along with those in all of the
+    /// sections nested beneath it, including transparent ones.
+    ///
+    /// The section's own pages come first, followed by those of each of its
+    /// subsections (in order of weight), with each section's pages in its
+    /// sort order.
+    pub fn all_pages(&self) -> Vec<PageToRender<'a>> {
+        self.all_page_paths
+            .iter()
+            .filter_map(|path| self.site_pages.get(path))
+            .map(PageToRender::from_page)
+            .collect()
+    }
+
+    /// Returns the pages in this section, and in all of the sections nested
+    /// beneath it, that match the given predicate.
+    pub fn pages_where(
+        &self,
+        predicate: impl Fn(&PageToRender<'a>) -> bool,
+    ) -> Vec<PageToRender<'a>> {
+        self.all_pages()
+            .into_iter()
+            .filter(|page| predicate(page))
+            .collect()
+    }
+
     pub fn extra<'de, T>(&self) -> Result<T, toml::de::Error>
     where
         T: Deserialize<'de>,
     {
         T::deserialize(self.extra.clone())
     }
+
+    /// Returns the value at the given key in the section's `extra` table.
+    ///
+    /// Nested values can be accessed using a dotted key (e.g., `social.twitter`).
+    pub fn extra_field<'de, T>(&self, key: &str) -> Result<T, ExtraFieldError>
+    where
+        T: Deserialize<'de>,
+    {
+        extra_field(self.extra, None, key, self.file_path)
+    }
 }
 
 pub struct RenderPageContext<'a> {
     pub(crate) base: BaseRenderContext<'a>,
     pub page: PageToRender<'a>,
+    /// The sections containing the page, ordered from the root section down.
+    pub ancestors: Vec<SectionToRender<'a>>,
+    /// The section the page belongs to.
+    pub parent_section: Option<SectionToRender<'a>>,
+    /// The series the page belongs to, if any.
+    pub series: Option<SeriesToRender<'a>>,
 }
 
 impl<'a> Deref for RenderPageContext<'a> {
@@ -147,38 +708,272 @@ impl<'a> Deref for RenderPageContext<'a> {
     }
 }
 
+impl<'a> RenderPageContext<'a> {
+    /// Returns the language of the page.
+    pub fn lang(&self) -> &'a str {
+        self.page.lang.unwrap_or(self.base.default_language)
+    }
+
+    /// Returns the translation of the given key in the page's language.
+    pub fn t(&self, key: &str) -> String {
+        self.base.translate(self.lang(), key)
+    }
+
+    /// Formats the given date in the site's timezone, with the month and day
+    /// names in the page's language.
+    pub fn format_date(&self, date: &str, format: Option<&str>) -> String {
+        self.base.format_date_in(self.lang(), date, format)
+    }
+
+    /// Returns the breadcrumbs leading from the root section down to this
+    /// page.
+    ///
+    /// A page in a transparent section belongs to the nearest section that
+    /// isn't transparent, so its transparent sections are left out.
+    pub fn breadcrumbs(&self) -> Vec<Breadcrumb<'a>> {
+        self.ancestors
+            .iter()
+            .map(|section| Breadcrumb {
+                title: section.title,
+                permalink: section.permalink,
+            })
+            .chain([Breadcrumb {
+                title: self.page.title,
+                permalink: self.page.permalink,
+            }])
+            .collect()
+    }
+
+    /// Returns the items in the menu with the given name, with the ones
+    /// linking to this page or the sections containing it marked as active.
+    pub fn menu(&self, name: &str) -> Vec<MenuItemToRender<'a>> {
+        let ancestors = self
+            .ancestors
+            .iter()
+            .map(|section| section.file_path)
+            .collect::<Vec<_>>();
+
+        self.base.menu_for(
+            name,
+            Some((self.page.file_path, self.page.permalink)),
+            &ancestors,
+        )
+    }
+
+    /// Returns the versions of this page, for use in a version picker.
+    ///
+    /// Versions that don't have this page link to their root section instead.
+    pub fn versions(&self) -> Vec<VersionToRender<'a>> {
+        self.base.versions_of(self.page.path)
+    }
+
+    /// Returns the canonical URL of the page, which points at the latest
+    /// version of the page in versioned documentation.
+    pub fn canonical_url(&self) -> &'a str {
+        self.base
+            .canonical_url_of(self.page.path, self.page.permalink)
+    }
+
+    /// Returns the `<link rel="canonical">` element for the page, for placing
+    /// in the `<head>`.
+    pub fn canonical_link(&self) -> HtmlElement {
+        link().rel("canonical").href(self.canonical_url())
+    }
+
+    /// Returns the `<meta name="robots">` element for the page, for placing in
+    /// the `<head>`, if the page is marked as `no_index`.
+    pub fn robots_meta(&self) -> Option<HtmlElement> {
+        self.page.no_index.then(robots_noindex_meta)
+    }
+
+    /// Returns the page before this one in its section's sort order.
+    pub fn previous_page(&self) -> Option<PageToRender<'a>> {
+        self.page.previous.and_then(|path| self.get_page(path))
+    }
+
+    /// Returns the page after this one in its section's sort order.
+    pub fn next_page(&self) -> Option<PageToRender<'a>> {
+        self.page.next.and_then(|path| self.get_page(path))
+    }
+
+    /// Returns the `<link>` elements for the site feed and the feed of the
+    /// closest section containing this page that has one, for placing in the
+    /// `<head>` to enable feed autodiscovery.
+    pub fn feed_links(&self) -> Vec<HtmlElement> {
+        let mut feed_links = self.base.feed_links();
+
+        let section_feed_link = self
+            .ancestors
+            .iter()
+            .rev()
+            .find_map(|section| self.base.section_feed_link(section));
+        feed_links.extend(section_feed_link);
+
+        feed_links
+    }
+
+    /// Returns up to `limit` pages that are related to this one.
+    ///
+    /// Pages are considered related when they share taxonomy terms with this
+    /// page, with the pages sharing the most terms coming first. Ties are
+    /// broken by date, newest first.
+    pub fn related_pages(&self, limit: usize) -> Vec<PageToRender<'a>> {
+        let pages = self.base.pages;
+
+        let mut related_pages = pages
+            .values()
+            .filter(|page| page.path.0 != self.page.path)
+            .filter_map(|page| {
+                let shared_terms = count_shared_terms(self.page.taxonomies, &page.meta.taxonomies);
+                (shared_terms > 0).then_some((shared_terms, page))
+            })
+            .collect::<Vec<_>>();
+
+        related_pages.sort_by(|(a_shared_terms, a), (b_shared_terms, b)| {
+            b_shared_terms
+                .cmp(a_shared_terms)
+                .then_with(|| b.meta.date.cmp(&a.meta.date))
+                .then_with(|| a.path.0.cmp(&b.path.0))
+        });
+
+        related_pages
+            .into_iter()
+            .take(limit)
+            .map(|(_, page)| PageToRender::from_page(page))
+            .collect()
+    }
+
+    /// Returns the value at the given key in the page's `extra` table,
+    /// falling back to the site's `extra` table.
+    pub fn extra_field<'de, T>(&self, key: &str) -> Result<T, ExtraFieldError>
+    where
+        T: Deserialize<'de>,
+    {
+        extra_field(
+            self.page.extra,
+            Some(self.base.extra),
+            key,
+            self.page.file_path,
+        )
+    }
+}
+
+fn count_shared_terms(a: &HashMap<String, Vec<String>>, b: &HashMap<String, Vec<String>>) -> usize {
+    a.iter()
+        .filter_map(|(taxonomy, a_terms)| Some((a_terms, b.get(taxonomy)?)))
+        .map(|(a_terms, b_terms)| a_terms.iter().filter(|term| b_terms.contains(term)).count())
+        .sum()
+}
+
+/// The pages published in a given year.
+pub struct PagesByYear<'a> {
+    pub year: i32,
+    pub pages: Vec<PageToRender<'a>>,
+}
+
+/// The pages published in a given month.
+pub struct PagesByMonth<'a> {
+    pub year: i32,
+    /// The month, starting from 1 for January.
+    pub month: u32,
+    pub pages: Vec<PageToRender<'a>>,
+}
+
+/// A link in a breadcrumb trail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breadcrumb<'a> {
+    pub title: &'a Option<String>,
+    pub permalink: &'a str,
+}
+
+/// A series of pages, from the perspective of one of its pages.
+pub struct SeriesToRender<'a> {
+    pub name: &'a str,
+    /// The pages in the series, in reading order.
+    pub pages: Vec<PageToRender<'a>>,
+    /// The index of the current page within [`pages`](Self::pages).
+    pub position: usize,
+}
+
+impl<'a> SeriesToRender<'a> {
+    /// Returns the page before the current one in the series.
+    pub fn previous(&self) -> Option<&PageToRender<'a>> {
+        self.position
+            .checked_sub(1)
+            .and_then(|index| self.pages.get(index))
+    }
+
+    /// Returns the page after the current one in the series.
+    pub fn next(&self) -> Option<&PageToRender<'a>> {
+        self.pages.get(self.position + 1)
+    }
+}
+
 pub struct PageToRender<'a> {
     pub title: &'a Option<String>,
+    pub description: &'a Option<String>,
+    /// The language of the page, if it or its sections specify one.
+    pub lang: Option<&'a str>,
     pub slug: &'a str,
     pub path: &'a str,
     pub permalink: &'a str,
     pub date: &'a Option<String>,
     pub updated: &'a Option<String>,
     pub raw_content: &'a str,
-    pub content: &'a Vec<Element>,
+    pub content: Arc<Vec<Element>>,
+    /// The URL of the page's social card image, if social cards are enabled.
+    pub social_image: Option<&'a str>,
+    /// The URL of the page's PDF, if it is exported to PDF.
+    pub pdf_url: Option<&'a str>,
+    /// The URLs of the files in the page's bundle, when content files are
+    /// passed through to the output.
+    pub assets: Vec<&'a str>,
     pub table_of_contents: &'a TableOfContents,
+    /// The headings in the content, nested by level.
+    pub headings: &'a [Heading],
     pub word_count: WordCount,
     pub read_time: ReadTime,
     pub taxonomies: &'a HashMap<String, Vec<String>>,
+    pub weight: Option<usize>,
+    pub previous: Option<&'a Path>,
+    pub next: Option<&'a Path>,
+    /// Whether the page should be kept out of search engines.
+    pub no_index: bool,
     pub extra: &'a toml::Table,
+    pub(crate) file_path: &'a Path,
 }
 
 impl<'a> PageToRender<'a> {
     pub fn from_page(page: &'a Page) -> Self {
         Self {
             title: &page.meta.title,
+            description: &page.meta.description,
+            lang: page.meta.lang.as_deref(),
             slug: &page.slug,
             path: &page.path.0,
             permalink: &page.permalink.as_str(),
             date: &page.meta.date,
             updated: &page.meta.updated,
             raw_content: &page.raw_content,
-            content: &page.content,
+            content: Arc::clone(&page.content),
+            social_image: page.social_image.as_ref().map(|image| image.as_str()),
+            pdf_url: page.pdf_url.as_ref().map(|pdf_url| pdf_url.as_str()),
+            assets: page
+                .assets
+                .iter()
+                .map(|asset| asset.permalink.as_str())
+                .collect(),
             table_of_contents: &page.table_of_contents,
+            headings: &page.headings,
             word_count: page.word_count,
             read_time: page.read_time,
             taxonomies: &page.meta.taxonomies,
+            weight: page.meta.weight,
+            previous: page.previous.as_deref(),
+            next: page.next.as_deref(),
+            no_index: page.meta.no_index,
             extra: &page.meta.extra,
+            file_path: &page.file.path,
         }
     }
 
@@ -188,6 +983,79 @@ impl<'a> PageToRender<'a> {
     {
         T::deserialize(self.extra.clone())
     }
+
+    /// Returns the value at the given key in the page's `extra` table.
+    ///
+    /// Nested values can be accessed using a dotted key (e.g., `social.twitter`).
+    pub fn extra_field<'de, T>(&self, key: &str) -> Result<T, ExtraFieldError>
+    where
+        T: Deserialize<'de>,
+    {
+        extra_field(self.extra, None, key, self.file_path)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ExtraFieldError {
+    #[error("missing `extra.{key}` in '{}'", filepath.display())]
+    Missing { key: String, filepath: PathBuf },
+
+    #[error("invalid `extra.{key}` in '{}': {err}", filepath.display())]
+    Invalid {
+        key: String,
+        filepath: PathBuf,
+        err: Box<toml::de::Error>,
+    },
+}
+
+/// Returns the value at the given (possibly dotted) key in `extra`, falling
+/// back to `defaults`.
+///
+/// When both contain a table at the key, the tables are merged, with the
+/// values in `extra` taking precedence.
+fn extra_field<'de, T>(
+    extra: &toml::Table,
+    defaults: Option<&toml::Table>,
+    key: &str,
+    filepath: &Path,
+) -> Result<T, ExtraFieldError>
+where
+    T: Deserialize<'de>,
+{
+    fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+        let mut segments = key.split('.');
+        let mut value = table.get(segments.next()?)?;
+
+        for segment in segments {
+            value = value.as_table()?.get(segment)?;
+        }
+
+        Some(value)
+    }
+
+    let value = match (
+        lookup(extra, key),
+        defaults.and_then(|defaults| lookup(defaults, key)),
+    ) {
+        (Some(toml::Value::Table(table)), Some(toml::Value::Table(defaults))) => {
+            let mut table = table.clone();
+            merge_tables(&mut table, defaults);
+            toml::Value::Table(table)
+        }
+        (Some(value), _) | (None, Some(value)) => value.clone(),
+        (None, None) => {
+            return Err(ExtraFieldError::Missing {
+                key: key.to_owned(),
+                filepath: filepath.to_owned(),
+            })
+        }
+    };
+
+    T::deserialize(value).map_err(|err| ExtraFieldError::Invalid {
+        key: key.to_owned(),
+        filepath: filepath.to_owned(),
+        err: Box::new(err),
+    })
 }
 
 pub struct RenderTaxonomyContext<'a> {
@@ -205,6 +1073,7 @@ impl<'a> Deref for RenderTaxonomyContext<'a> {
 
 pub struct TaxonomyToRender<'a> {
     pub name: &'a str,
+    pub permalink: &'a str,
     pub terms: Vec<TaxonomyTermToRender<'a>>,
 }
 
@@ -223,6 +1092,412 @@ impl<'a> Deref for RenderTaxonomyTermContext<'a> {
 
 pub struct TaxonomyTermToRender<'a> {
     pub name: &'a str,
+    /// The name to display for the term, if it differs from the term itself.
+    pub title: &'a Option<String>,
     pub permalink: &'a str,
+    pub description: &'a Option<String>,
+    pub extra: &'a toml::Table,
     pub pages: Vec<PageToRender<'a>>,
+    /// The number of pages with this term.
+    pub page_count: usize,
+}
+
+impl<'a> RenderTaxonomyTermContext<'a> {
+    /// Returns the `<link>` elements for the site feed and this term's feed,
+    /// for placing in the `<head>` to enable feed autodiscovery.
+    pub fn feed_links(&self) -> Vec<HtmlElement> {
+        let mut feed_links = self.base.feed_links();
+        if self.base.feeds {
+            feed_links.push(feed_link(
+                &format!("{}atom.xml", self.term.permalink),
+                self.base.site_title,
+                Some(self.term.name),
+            ));
+        }
+        feed_links
+    }
+}
+
+impl<'a> TaxonomyTermToRender<'a> {
+    pub fn extra<'de, T>(&self) -> Result<T, toml::de::Error>
+    where
+        T: Deserialize<'de>,
+    {
+        T::deserialize(self.extra.clone())
+    }
+}
+
+pub struct RenderGeneratedPageContext<'a> {
+    pub(crate) base: BaseRenderContext<'a>,
+    /// The path the page was registered at.
+    pub path: &'a str,
+    pub permalink: &'a str,
+}
+
+impl<'a> Deref for RenderGeneratedPageContext<'a> {
+    type Target = BaseRenderContext<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+pub struct RenderErrorPageContext<'a> {
+    pub(crate) base: BaseRenderContext<'a>,
+    /// The HTTP status code the page is served with.
+    pub status: u16,
+    /// The error that caused the page to be served, when the site failed to
+    /// build while being served.
+    pub error: Option<&'a str>,
+}
+
+impl<'a> Deref for RenderErrorPageContext<'a> {
+    type Target = BaseRenderContext<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::test_site::TestSite;
+    use crate::Site;
+
+    use super::*;
+
+    fn dated_test_site(name: &str) -> (TestSite, Site) {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("a.md", "+++\ntitle = \"A\"\ndate = 2024-03-15\n+++\n")
+            .write("b.md", "+++\ntitle = \"B\"\ndate = 2024-03-01\n+++\n")
+            .write("c.md", "+++\ntitle = \"C\"\ndate = 2024-01-10\n+++\n")
+            .write(
+                "d.md",
+                "+++\ntitle = \"D\"\ndate = 2023-12-31T12:00:00Z\n+++\n",
+            )
+            .write("undated.md", "+++\ntitle = \"Undated\"\n+++\n");
+        let site = test_site.load();
+
+        (test_site, site)
+    }
+
+    fn titles<'a>(pages: &[PageToRender<'a>]) -> Vec<&'a str> {
+        pages
+            .iter()
+            .map(|page| page.title.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_pages_by_year() {
+        let (_test_site, site) = dated_test_site("pages_by_year");
+        let ctx = site.base_render_context();
+
+        assert_eq!(
+            ctx.pages_by_year()
+                .iter()
+                .map(|bucket| (bucket.year, titles(&bucket.pages)))
+                .collect::<Vec<_>>(),
+            vec![(2024, vec!["A", "B", "C"]), (2023, vec!["D"])]
+        );
+    }
+
+    #[test]
+    fn test_pages_by_month() {
+        let (_test_site, site) = dated_test_site("pages_by_month");
+        let ctx = site.base_render_context();
+
+        assert_eq!(
+            ctx.pages_by_month()
+                .iter()
+                .map(|bucket| (bucket.year, bucket.month, titles(&bucket.pages)))
+                .collect::<Vec<_>>(),
+            vec![
+                (2024, 3, vec!["A", "B"]),
+                (2024, 1, vec!["C"]),
+                (2023, 12, vec!["D"]),
+            ]
+        );
+    }
+
+    fn feed_links_test_site(name: &str) -> TestSite {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write(
+                "blog/_index.md",
+                "+++\ntitle = \"Blog\"\ngenerate_feed = true\n+++\n",
+            )
+            .write(
+                "blog/post.md",
+                "+++\ntitle = \"Post\"\ndate = \"2024-01-01\"\n\n[taxonomies]\ntags = [\"rust\"]\n+++\n",
+            )
+            .write("blog/drafts/_index.md", "+++\ntitle = \"Drafts\"\n+++\n")
+            .write(
+                "blog/drafts/draft.md",
+                "+++\ntitle = \"Draft\"\ndate = \"2024-01-02\"\n+++\n",
+            )
+            .write("notes/_index.md", "+++\ntitle = \"Notes\"\n+++\n")
+            .write("notes/note.md", "+++\ntitle = \"Note\"\n+++\n");
+        test_site
+    }
+
+    fn render_feed_links(test_site: &TestSite, feeds: bool) -> HashMap<String, String> {
+        let mut site = Site::builder()
+            .root(test_site.root_path())
+            .base_url("https://example.com")
+            .title("Example")
+            .feeds(feeds)
+            .templates(
+                |ctx| auk::html().child(auk::head().children(ctx.feed_links())),
+                |ctx| auk::html().child(auk::head().children(ctx.feed_links())),
+                |ctx| auk::html().child(auk::head().children(ctx.feed_links())),
+            )
+            .add_taxonomy(
+                Taxonomy::new("tags"),
+                |_ctx| auk::html(),
+                |ctx| auk::html().child(auk::head().children(ctx.feed_links())),
+            )
+            .build();
+        site.load().unwrap();
+
+        crate::test_site::render(&mut site).unwrap()
+    }
+
+    fn breadcrumb_test_site(name: &str) -> (TestSite, Site) {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\ntitle = \"Home\"\n+++\n")
+            .write("docs/_index.md", "+++\ntitle = \"Docs\"\n+++\n")
+            .write("docs/guides/_index.md", "+++\ntitle = \"Guides\"\n+++\n")
+            .write("docs/guides/setup.md", "+++\ntitle = \"Setup\"\n+++\n")
+            .write(
+                "docs/archive/_index.md",
+                "+++\ntitle = \"Archive\"\ntransparent = true\n+++\n",
+            )
+            .write("docs/archive/old.md", "+++\ntitle = \"Old\"\n+++\n");
+        let site = test_site.load();
+
+        (test_site, site)
+    }
+
+    fn section_breadcrumbs<'a>(
+        site: &'a Site,
+        test_site: &TestSite,
+        path: &str,
+    ) -> Vec<(Option<&'a str>, &'a str)> {
+        let section = &site.sections[&test_site.content_path().join(path)];
+        let ctx = RenderSectionContext {
+            base: site.base_render_context(),
+            section: SectionToRender::from_section(section, &site.pages),
+        };
+
+        ctx.breadcrumbs()
+            .into_iter()
+            .map(|breadcrumb| (breadcrumb.title.as_deref(), breadcrumb.permalink))
+            .collect()
+    }
+
+    fn page_breadcrumbs<'a>(
+        site: &'a Site,
+        test_site: &TestSite,
+        path: &str,
+    ) -> Vec<(Option<&'a str>, &'a str)> {
+        let page = &site.pages[&test_site.content_path().join(path)];
+        let ctx = RenderPageContext {
+            base: site.base_render_context(),
+            page: PageToRender::from_page(page),
+            ancestors: page
+                .ancestors
+                .iter()
+                .filter_map(|path| site.sections.get(path))
+                .map(|section| SectionToRender::from_section(section, &site.pages))
+                .collect(),
+            parent_section: None,
+            series: None,
+        };
+
+        ctx.breadcrumbs()
+            .into_iter()
+            .map(|breadcrumb| (breadcrumb.title.as_deref(), breadcrumb.permalink))
+            .collect()
+    }
+
+    #[test]
+    fn test_breadcrumbs_for_root_section() {
+        let (test_site, site) = breadcrumb_test_site("breadcrumbs_root_section");
+
+        assert_eq!(
+            section_breadcrumbs(&site, &test_site, "_index.md"),
+            vec![(Some("Home"), "https://example.com/")]
+        );
+    }
+
+    #[test]
+    fn test_breadcrumbs_for_nested_section() {
+        let (test_site, site) = breadcrumb_test_site("breadcrumbs_nested_section");
+
+        assert_eq!(
+            section_breadcrumbs(&site, &test_site, "docs/guides/_index.md"),
+            vec![
+                (Some("Home"), "https://example.com/"),
+                (Some("Docs"), "https://example.com/docs/"),
+                (Some("Guides"), "https://example.com/docs/guides/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breadcrumbs_for_page() {
+        let (test_site, site) = breadcrumb_test_site("breadcrumbs_page");
+
+        assert_eq!(
+            page_breadcrumbs(&site, &test_site, "docs/guides/setup.md"),
+            vec![
+                (Some("Home"), "https://example.com/"),
+                (Some("Docs"), "https://example.com/docs/"),
+                (Some("Guides"), "https://example.com/docs/guides/"),
+                (Some("Setup"), "https://example.com/docs/guides/setup/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breadcrumbs_for_page_in_transparent_section() {
+        let (test_site, site) = breadcrumb_test_site("breadcrumbs_transparent_section");
+
+        // The page belongs to the nearest section that isn't transparent, so
+        // the transparent section is left out of its trail.
+        assert_eq!(
+            page_breadcrumbs(&site, &test_site, "docs/archive/old.md"),
+            vec![
+                (Some("Home"), "https://example.com/"),
+                (Some("Docs"), "https://example.com/docs/"),
+                (Some("Old"), "https://example.com/docs/archive/old/"),
+            ]
+        );
+        assert_eq!(
+            section_breadcrumbs(&site, &test_site, "docs/archive/_index.md"),
+            vec![
+                (Some("Home"), "https://example.com/"),
+                (Some("Docs"), "https://example.com/docs/"),
+                (Some("Archive"), "https://example.com/docs/archive/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_feed_links() {
+        let test_site = feed_links_test_site("feed_links");
+        let rendered = render_feed_links(&test_site, true);
+
+        let site_feed = r#"href="https://example.com/atom.xml""#;
+        let blog_feed = r#"href="https://example.com/blog/atom.xml""#;
+
+        for path in ["/", "/notes/", "/notes/note/"] {
+            let html = &rendered[path];
+
+            assert_eq!(html.matches("<link").count(), 1, "{path}: {html}");
+            assert!(html.contains(site_feed), "{path}: {html}");
+            assert!(html.contains(r#"title="Example""#), "{path}: {html}");
+        }
+
+        // Pages link to the feed of the closest section that has one.
+        for path in ["/blog/", "/blog/post/", "/blog/drafts/draft/"] {
+            let html = &rendered[path];
+
+            assert_eq!(html.matches("<link").count(), 2, "{path}: {html}");
+            assert!(html.contains(site_feed), "{path}: {html}");
+            assert!(html.contains(blog_feed), "{path}: {html}");
+            assert!(html.contains(r#"title="Example - Blog""#), "{path}: {html}");
+        }
+
+        let html = &rendered["/tags/rust/"];
+        assert_eq!(html.matches("<link").count(), 2, "{html}");
+        assert!(html.contains(site_feed), "{html}");
+        assert!(
+            html.contains(r#"href="https://example.com/tags/rust/atom.xml""#),
+            "{html}"
+        );
+
+        // The section's feed has the same pages as its listing, including
+        // those in nested sections.
+        let blog_feed = &rendered["/blog/atom.xml"];
+        assert!(blog_feed.contains("<title>Post</title>"), "{blog_feed}");
+        assert!(blog_feed.contains("<title>Draft</title>"), "{blog_feed}");
+    }
+
+    #[test]
+    fn test_feed_links_without_feeds() {
+        let test_site = feed_links_test_site("feed_links_without_feeds");
+        let rendered = render_feed_links(&test_site, false);
+
+        for path in ["/", "/blog/", "/blog/post/", "/tags/rust/"] {
+            assert!(!rendered[path].contains("<link"), "{path}");
+        }
+        assert!(!rendered.contains_key("/tags/rust/atom.xml"));
+    }
+
+    #[test]
+    fn test_extra_field() {
+        let site_extra: toml::Table = toml::from_str(indoc! {r#"
+            author = "Jane Doe"
+
+            [social]
+            twitter = "janedoe"
+            mastodon = "@jane@example.com"
+        "#})
+        .unwrap();
+        let page_extra: toml::Table = toml::from_str(indoc! {r#"
+            reading_time = 5
+
+            [social]
+            twitter = "guest"
+        "#})
+        .unwrap();
+        let filepath = Path::new("content/blog/hello.md");
+
+        assert_eq!(
+            extra_field::<usize>(&page_extra, Some(&site_extra), "reading_time", filepath).unwrap(),
+            5
+        );
+        assert_eq!(
+            extra_field::<String>(&page_extra, Some(&site_extra), "author", filepath).unwrap(),
+            "Jane Doe"
+        );
+        assert_eq!(
+            extra_field::<HashMap<String, String>>(
+                &page_extra,
+                Some(&site_extra),
+                "social",
+                filepath
+            )
+            .unwrap(),
+            HashMap::from_iter([
+                ("twitter".to_string(), "guest".to_string()),
+                ("mastodon".to_string(), "@jane@example.com".to_string()),
+            ])
+        );
+        assert_eq!(
+            extra_field::<String>(&page_extra, None, "social.twitter", filepath).unwrap(),
+            "guest"
+        );
+        assert_eq!(
+            extra_field::<String>(&page_extra, None, "author", filepath)
+                .unwrap_err()
+                .to_string(),
+            "missing `extra.author` in 'content/blog/hello.md'"
+        );
+        assert!(
+            extra_field::<String>(&page_extra, None, "reading_time", filepath)
+                .unwrap_err()
+                .to_string()
+                .starts_with("invalid `extra.reading_time` in 'content/blog/hello.md'")
+        );
+    }
 }
@@ -0,0 +1,866 @@
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use auk::renderer::HtmlElementRenderer;
+use auk::*;
+use chrono::{DateTime, Utc};
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderMap};
+use hyper::{Method, Request, Response, StatusCode};
+use mime_guess::MimeGuess;
+use serde_json::json;
+use tokio::io::AsyncReadExt;
+
+use crate::build_overlay::{BuildStatusBroadcaster, BUILD_OVERLAY_JS, BUILD_OVERLAY_JS_PATH};
+use crate::http::{
+    content_etag, file_etag, http_date, is_not_modified, parse_range, read_range, ByteRange,
+};
+use crate::live_reload::{LiveReloadBroadcaster, LIVE_RELOAD_JS};
+use crate::permalink::{decode_path, encode_path};
+use crate::storage::{SiteAliases, SiteContent};
+
+/// Options for serving a site with [`Site::serve_with_options`](crate::Site::serve_with_options).
+#[derive(Debug, Clone, Default)]
+pub struct ServeOptions {
+    access_log: bool,
+    directory_listing: bool,
+    fallbacks: Vec<(String, String)>,
+    not_found_overlay: bool,
+    pub(crate) open: bool,
+}
+
+impl ServeOptions {
+    /// Returns the default [`ServeOptions`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether each request should be logged, along with its response
+    /// status and how long it took to serve.
+    pub fn access_log(mut self, access_log: bool) -> Self {
+        self.access_log = access_log;
+        self
+    }
+
+    /// Sets whether the contents of static directories without an `index.html`
+    /// should be listed.
+    pub fn directory_listing(mut self, directory_listing: bool) -> Self {
+        self.directory_listing = directory_listing;
+        self
+    }
+
+    /// Sets whether the 404 page should show an overlay with the requested
+    /// path and the pages with similar paths, to help track down broken links.
+    pub fn not_found_overlay(mut self, not_found_overlay: bool) -> Self {
+        self.not_found_overlay = not_found_overlay;
+        self
+    }
+
+    /// Sets whether the site should be opened in the default browser once it
+    /// has been built successfully for the first time.
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Serves the page at the `fallback` path for unknown routes beginning with `prefix`.
+    ///
+    /// This is useful for serving client-side-routed apps embedded within the site:
+    ///
+    /// ```ignore
+    /// ServeOptions::new().fallback("/app/", "/app/")
+    /// ```
+    ///
+    /// Routes with a file extension are not subject to the fallback, so that
+    /// missing assets still result in a 404.
+    pub fn fallback(mut self, prefix: impl Into<String>, fallback: impl Into<String>) -> Self {
+        self.fallbacks.push((prefix.into(), fallback.into()));
+        self
+    }
+
+    /// Returns the fallback path for the given route, if it has one.
+    ///
+    /// The fallback with the longest matching prefix wins.
+    fn fallback_for(&self, path: &str) -> Option<&str> {
+        if extension(path).is_some() {
+            return None;
+        }
+
+        self.fallbacks
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, fallback)| fallback.as_str())
+    }
+}
+
+/// The outcome of the most recent build in serve mode.
+#[derive(Debug, Default)]
+pub(crate) struct BuildStatus {
+    /// Whether a build is in progress.
+    building: bool,
+    finished_at: Option<DateTime<Utc>>,
+    duration: Option<Duration>,
+    errors: Vec<String>,
+}
+
+impl BuildStatus {
+    /// Records that a build has started.
+    pub(crate) fn start(&mut self) {
+        self.building = true;
+    }
+
+    /// Records the outcome of a build that took the given amount of time.
+    pub(crate) fn record(&mut self, duration: Duration, result: Result<(), String>) {
+        self.building = false;
+        self.finished_at = Some(Utc::now());
+        self.duration = Some(duration);
+        self.errors = result.err().into_iter().collect();
+    }
+
+    /// Returns how long the most recent build took.
+    pub(crate) fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns whether the most recent build failed.
+    pub(crate) fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        json!({
+            "building": self.building,
+            "last_build": {
+                "finished_at": self.finished_at.map(|finished_at| finished_at.to_rfc3339()),
+                "duration_ms": self.duration.map(|duration| duration.as_millis() as u64),
+                "errors": self.errors,
+            }
+        })
+    }
+}
+
+pub(crate) type ResponseBody = BoxBody<Bytes, hyper::Error>;
+
+pub(crate) fn empty() -> ResponseBody {
+    Empty::<Bytes>::new()
+        .map_err(|never| match never {})
+        .boxed()
+}
+
+fn full<T: Into<Bytes>>(chunk: T) -> ResponseBody {
+    Full::new(chunk.into())
+        .map_err(|never| match never {})
+        .boxed()
+}
+
+fn not_found() -> Response<ResponseBody> {
+    let mut not_found = Response::new(empty());
+    *not_found.status_mut() = StatusCode::NOT_FOUND;
+    not_found
+}
+
+fn not_modified(etag: &str) -> Response<ResponseBody> {
+    Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header(header::ETAG, etag)
+        .body(empty())
+        .unwrap()
+}
+
+fn internal_server_error() -> Response<ResponseBody> {
+    let mut response = Response::new(empty());
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    response
+}
+
+fn permanent_redirect(location: String) -> Response<ResponseBody> {
+    Response::builder()
+        .header(header::LOCATION, location)
+        .status(StatusCode::PERMANENT_REDIRECT)
+        .body(empty())
+        .unwrap()
+}
+
+fn moved_permanently(location: String) -> Response<ResponseBody> {
+    Response::builder()
+        .header(header::LOCATION, location)
+        .status(StatusCode::MOVED_PERMANENTLY)
+        .body(empty())
+        .unwrap()
+}
+
+/// Opens the given URL in the default browser.
+pub(crate) fn open_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    Ok(())
+}
+
+/// Returns the banner printed when the dev server starts.
+pub(crate) fn startup_banner(url: &str, build_status: &BuildStatus) -> String {
+    let build = match build_status.duration() {
+        Some(_) if build_status.has_errors() => {
+            "The site failed to build. Fix the errors and save to rebuild.".to_string()
+        }
+        Some(duration) => format!("Built in {duration:.2?}."),
+        None => "The site hasn't been built yet.".to_string(),
+    };
+
+    format!("\n  Serving at {url}\n\n  {build}\n  Press Ctrl+C to stop.\n")
+}
+
+fn extension(path: &str) -> Option<&str> {
+    let (_, filename) = path.rsplit_once('/').unwrap_or(("", path));
+    filename.rsplit_once('.').map(|(_, extension)| extension)
+}
+
+/// The state shared between all of the requests to the dev server.
+pub(crate) struct ServerState {
+    pub static_path: PathBuf,
+    pub options: ServeOptions,
+    pub build_status: RwLock<BuildStatus>,
+    pub live_reload: LiveReloadBroadcaster,
+    pub content: SiteContent,
+    pub aliases: SiteAliases,
+    pub build_overlay: BuildStatusBroadcaster,
+}
+
+impl ServerState {
+    /// Records that a build has started, and lets the build overlays know.
+    pub fn start_build(&self) {
+        let mut build_status = self.build_status.write().unwrap();
+        build_status.start();
+        self.build_overlay.send(&build_status);
+    }
+
+    /// Records the outcome of a build, and lets the build overlays know.
+    pub fn finish_build(&self, duration: Duration, result: Result<(), String>) {
+        let mut build_status = self.build_status.write().unwrap();
+        build_status.record(duration, result);
+        self.build_overlay.send(&build_status);
+    }
+}
+
+pub(crate) async fn handle_request(
+    req: Request<hyper::body::Incoming>,
+    state: Arc<ServerState>,
+) -> Result<Response<ResponseBody>, Infallible> {
+    if !state.options.access_log {
+        return route_request(req, &state).await;
+    }
+
+    let started_at = Instant::now();
+    let method = req.method().clone();
+    let path = req.uri().path().to_owned();
+
+    let response = route_request(req, &state).await?;
+
+    eprintln!(
+        "{method} {path} {status} {duration:.2?}",
+        status = response.status().as_u16(),
+        duration = started_at.elapsed()
+    );
+
+    Ok(response)
+}
+
+async fn route_request(
+    req: Request<hyper::body::Incoming>,
+    state: &ServerState,
+) -> Result<Response<ResponseBody>, Infallible> {
+    if req.method() != Method::GET {
+        return Ok(not_found());
+    }
+
+    if LiveReloadBroadcaster::is_connect_request(&req) {
+        return Ok(state.live_reload.connect(req));
+    }
+
+    if BuildStatusBroadcaster::is_connect_request(&req) {
+        let build_status = state.build_status.read().unwrap();
+        return Ok(state.build_overlay.connect(req, &build_status));
+    }
+
+    let static_path = state.static_path.as_path();
+    let options = &state.options;
+
+    // Content and static files are keyed by their decoded paths.
+    let path = decode_path(req.uri().path());
+    let path = path.as_ref();
+    let headers = req.headers();
+
+    if path == "/__razorbill/status" {
+        let status = state.build_status.read().unwrap().to_json();
+
+        return Ok(Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CACHE_CONTROL, "no-store")
+            .status(StatusCode::OK)
+            .body(full(serde_json::to_string(&status).unwrap()))
+            .unwrap());
+    }
+
+    if path == BUILD_OVERLAY_JS_PATH {
+        return Ok(Response::builder()
+            .header(header::CONTENT_TYPE, "text/javascript")
+            .status(StatusCode::OK)
+            .body(full(BUILD_OVERLAY_JS.to_owned()))
+            .unwrap());
+    }
+
+    if path == "/livereload.js" {
+        return Ok(Response::builder()
+            .header(header::CONTENT_TYPE, "text/javascript")
+            .status(StatusCode::OK)
+            .body(full(LIVE_RELOAD_JS.to_owned()))
+            .unwrap());
+    }
+
+    // Don't allow requests to escape the static directory.
+    if path.split('/').any(|segment| segment == "..") {
+        return Ok(not_found());
+    }
+
+    // While the site fails to build, its pages would be stale, so we show
+    // the build error instead.
+    let is_page = extension(path).map_or(true, |extension| extension == "html");
+    if is_page && state.build_status.read().unwrap().has_errors() {
+        if let Some(response) = error_page_response(
+            &state.content,
+            BUILD_ERROR_PATH,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ) {
+            return Ok(response);
+        }
+    }
+
+    if let Some(response) = content_response(&state.content, headers, path) {
+        return Ok(response);
+    }
+
+    if let Some(response) = alias_response(&state.aliases, path) {
+        return Ok(response);
+    }
+
+    // Check if the user forgot to add a trailing `/`.
+    if !path.ends_with('/') && extension(path).is_none() {
+        let path = format!("{path}/");
+        if state.content.read().unwrap().get(&path).is_some() {
+            return Ok(permanent_redirect(encode_path(&path)));
+        }
+    }
+
+    if let Some(response) = static_file_response(headers, static_path, path).await {
+        return Ok(response);
+    }
+
+    if let Some(response) = static_directory_response(headers, static_path, path, options).await {
+        return Ok(response);
+    }
+
+    if let Some(fallback) = options.fallback_for(path) {
+        if let Some(response) = content_response(&state.content, headers, fallback) {
+            return Ok(response);
+        }
+
+        if let Some(response) = static_file_response(headers, static_path, fallback).await {
+            return Ok(response);
+        }
+    }
+
+    Ok(not_found_page(&state.content, path, options))
+}
+
+/// The path to the rendered 404 page.
+const NOT_FOUND_PATH: &str = "/404.html";
+
+/// The path to the page shown while the site fails to build.
+const BUILD_ERROR_PATH: &str = "/50x.html";
+
+/// The maximum number of similar paths listed in the 404 overlay.
+const MAX_NEAR_MISSES: usize = 5;
+
+/// Returns the response for an unknown route, using the site's rendered 404
+/// page.
+fn not_found_page(
+    content: &SiteContent,
+    path: &str,
+    options: &ServeOptions,
+) -> Response<ResponseBody> {
+    if !options.not_found_overlay {
+        return error_page_response(content, NOT_FOUND_PATH, StatusCode::NOT_FOUND)
+            .unwrap_or_else(not_found);
+    }
+
+    let content = content.read().unwrap();
+    let Some(page) = content.get(NOT_FOUND_PATH) else {
+        return not_found();
+    };
+
+    let near_misses = near_misses(path, content.keys().map(String::as_str));
+    let page = inject_not_found_overlay(page.clone(), &not_found_overlay(path, &near_misses));
+
+    error_response(page, StatusCode::NOT_FOUND)
+}
+
+/// Returns the response for the rendered error page at the given path, if
+/// there is one.
+fn error_page_response(
+    content: &SiteContent,
+    path: &str,
+    status: StatusCode,
+) -> Option<Response<ResponseBody>> {
+    let page = content.read().unwrap().get(path).cloned()?;
+
+    Some(error_response(page, status))
+}
+
+fn error_response(page: Vec<u8>, status: StatusCode) -> Response<ResponseBody> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/html")
+        .header(header::CACHE_CONTROL, "no-store")
+        .status(status)
+        .body(full(page))
+        .unwrap()
+}
+
+/// Returns the paths of the pages most similar to the requested path, from
+/// most to least similar.
+fn near_misses<'a>(path: &str, paths: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let max_distance = (path.chars().count() / 3).max(2);
+
+    let mut near_misses = paths
+        .filter(|candidate| *candidate != NOT_FOUND_PATH)
+        .filter(|candidate| candidate.ends_with('/') || candidate.ends_with(".html"))
+        .map(|candidate| (edit_distance(path, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    near_misses.sort();
+
+    near_misses
+        .into_iter()
+        .take(MAX_NEAR_MISSES)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Returns the Levenshtein distance between the given strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut distances = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous_diagonal = distances[0];
+        distances[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_diagonal + usize::from(a_char != *b_char);
+            previous_diagonal = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(distances[j + 1] + 1);
+        }
+    }
+
+    distances[b.len()]
+}
+
+/// Renders the overlay listing the requested path and the similar paths.
+fn not_found_overlay(path: &str, near_misses: &[&str]) -> String {
+    let suggestions = if near_misses.is_empty() {
+        p().child("No similar pages were found.")
+    } else {
+        div()
+            .child(p().child("Did you mean:"))
+            .child(ul().children(near_misses.iter().map(|near_miss| {
+                li().child(
+                    a().href(escape_html(&encode_path(near_miss)))
+                        .child(escape_html(near_miss)),
+                )
+            })))
+    };
+
+    let overlay = div()
+        .attr("id", "razorbill-not-found-overlay")
+        .attr(
+            "style",
+            "position: fixed; bottom: 1rem; right: 1rem; max-width: 32rem; padding: 1rem; \
+             background: #fff; color: #111; border: 1px solid #ccc; border-radius: 4px; \
+             font-family: sans-serif; z-index: 2147483647",
+        )
+        .child(strong().child(format!("No page found at {}", escape_html(path))))
+        .child(suggestions);
+
+    HtmlElementRenderer::new()
+        .render_to_string(&overlay)
+        .unwrap_or_default()
+}
+
+/// Injects the overlay at the end of the `<body>` of the given page.
+fn inject_not_found_overlay(page: Vec<u8>, overlay: &str) -> Vec<u8> {
+    let mut page = match String::from_utf8(page) {
+        Ok(page) => page,
+        Err(err) => return err.into_bytes(),
+    };
+
+    let index = page.rfind("</body>").unwrap_or(page.len());
+    page.insert_str(index, overlay);
+    page.into_bytes()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns the response for the rendered content at the given path.
+fn content_response(
+    content: &SiteContent,
+    headers: &HeaderMap,
+    path: &str,
+) -> Option<Response<ResponseBody>> {
+    let content = content.read().unwrap().get(path).cloned()?;
+
+    let content_type = match extension(path) {
+        Some("css") => "text/css",
+        Some("xml") => "application/xml",
+        Some("json") => "application/json",
+        Some("png") => "image/png",
+        Some("pdf") => "application/pdf",
+        _ => "text/html",
+    };
+
+    let etag = content_etag(&content);
+    if is_not_modified(headers, &etag, None) {
+        return Some(not_modified(&etag));
+    }
+
+    Some(
+        Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::ETAG, etag)
+            .status(StatusCode::OK)
+            .body(full(content))
+            .unwrap(),
+    )
+}
+
+/// Returns the redirect for the alias at the given path, matching the redirect
+/// files used in production.
+fn alias_response(aliases: &SiteAliases, path: &str) -> Option<Response<ResponseBody>> {
+    let aliases = aliases.read().unwrap();
+    let location = aliases.get(path).or_else(|| {
+        // Aliases are matched with or without a trailing `/`.
+        if path.ends_with('/') {
+            None
+        } else {
+            aliases.get(&format!("{path}/"))
+        }
+    })?;
+
+    Some(moved_permanently(location.clone()))
+}
+
+/// Returns the response for the static file at the given path.
+async fn static_file_response(
+    headers: &HeaderMap,
+    static_path: &Path,
+    path: &str,
+) -> Option<Response<ResponseBody>> {
+    let static_file_path = static_path.join(path.trim_start_matches('/'));
+
+    let mut file = tokio::fs::File::open(&static_file_path).await.ok()?;
+    let metadata = file.metadata().await.ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+
+    let len = metadata.len();
+    let last_modified = metadata.modified().ok();
+
+    let etag = file_etag(len, last_modified);
+    if is_not_modified(headers, &etag, last_modified) {
+        return Some(not_modified(&etag));
+    }
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|range| range.to_str().ok());
+
+    let mut response = Response::builder()
+        .header(
+            header::CONTENT_TYPE,
+            MimeGuess::from_path(&static_file_path)
+                .first_or_octet_stream()
+                .essence_str(),
+        )
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag);
+
+    if let Some(last_modified) = last_modified {
+        response = response.header(header::LAST_MODIFIED, http_date(last_modified));
+    }
+
+    Some(match parse_range(range, len) {
+        ByteRange::Full => {
+            let mut contents = Vec::with_capacity(len as usize);
+            if file.read_to_end(&mut contents).await.is_err() {
+                return Some(internal_server_error());
+            }
+
+            response
+                .status(StatusCode::OK)
+                .body(full(contents))
+                .unwrap()
+        }
+        ByteRange::Partial(range) => {
+            let Ok(contents) = read_range(&mut file, &range).await else {
+                return Some(internal_server_error());
+            };
+
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{len}", range.start(), range.end()),
+                )
+                .body(full(contents))
+                .unwrap()
+        }
+        ByteRange::Unsatisfiable => Response::builder()
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+            .body(empty())
+            .unwrap(),
+    })
+}
+
+/// Returns the response for the static directory at the given path.
+///
+/// Directories are served using their `index.html`, if they have one. Otherwise
+/// their contents are listed, if enabled.
+async fn static_directory_response(
+    headers: &HeaderMap,
+    static_path: &Path,
+    path: &str,
+    options: &ServeOptions,
+) -> Option<Response<ResponseBody>> {
+    let directory_path = static_path.join(path.trim_start_matches('/'));
+
+    let metadata = tokio::fs::metadata(&directory_path).await.ok()?;
+    if !metadata.is_dir() {
+        return None;
+    }
+
+    if !path.ends_with('/') {
+        return Some(permanent_redirect(encode_path(&format!("{path}/"))));
+    }
+
+    if let Some(response) =
+        static_file_response(headers, static_path, &format!("{path}index.html")).await
+    {
+        return Some(response);
+    }
+
+    if !options.directory_listing {
+        return None;
+    }
+
+    let Ok(listing) = directory_listing(path, &directory_path).await else {
+        return Some(internal_server_error());
+    };
+
+    Some(
+        Response::builder()
+            .header(header::CONTENT_TYPE, "text/html")
+            .status(StatusCode::OK)
+            .body(full(listing))
+            .unwrap(),
+    )
+}
+
+/// Renders a listing of the contents of the given directory.
+async fn directory_listing(path: &str, directory_path: &Path) -> std::io::Result<String> {
+    let mut entries = Vec::new();
+
+    let mut read_dir = tokio::fs::read_dir(directory_path).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let mut name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type().await?.is_dir() {
+            name.push('/');
+        }
+
+        entries.push(name);
+    }
+
+    entries.sort();
+
+    let parent = (path != "/").then(|| "../".to_string());
+    let heading = format!("Index of {path}");
+
+    let listing = html()
+        .child(
+            head()
+                .child(meta().charset("utf-8"))
+                .child(title().child(heading.clone())),
+        )
+        .child(
+            body().child(h1().child(heading)).child(
+                ul().children(
+                    parent
+                        .into_iter()
+                        .chain(entries)
+                        .map(|name| li().child(a().href(encode_path(&name)).child(name))),
+                ),
+            ),
+        );
+
+    HtmlElementRenderer::new()
+        .render_to_string(&listing)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_build_status_to_json() {
+        let mut build_status = BuildStatus::default();
+        assert_eq!(
+            build_status.to_json(),
+            json!({
+                "building": false,
+                "last_build": {
+                    "finished_at": null,
+                    "duration_ms": null,
+                    "errors": [],
+                }
+            })
+        );
+
+        build_status.record(
+            Duration::from_millis(42),
+            Err("failed to parse front matter".to_string()),
+        );
+        let status = build_status.to_json();
+        assert_eq!(status["last_build"]["duration_ms"], json!(42));
+        assert_eq!(
+            status["last_build"]["errors"],
+            json!(["failed to parse front matter"])
+        );
+        assert!(status["last_build"]["finished_at"].is_string());
+    }
+
+    #[test]
+    fn test_startup_banner() {
+        let url = "http://127.0.0.1:3000/";
+        let mut build_status = BuildStatus::default();
+
+        build_status.record(Duration::from_millis(42), Ok(()));
+        assert_eq!(
+            startup_banner(url, &build_status),
+            "\n  Serving at http://127.0.0.1:3000/\n\n  Built in 42.00ms.\n  Press Ctrl+C to stop.\n"
+        );
+
+        build_status.record(Duration::from_millis(42), Err("oops".to_string()));
+        assert!(startup_banner(url, &build_status).contains("failed to build"));
+    }
+
+    #[test]
+    fn test_fallback_for() {
+        let options = ServeOptions::new()
+            .fallback("/app/", "/app/")
+            .fallback("/app/admin/", "/app/admin/index.html");
+
+        assert_eq!(options.fallback_for("/app/settings"), Some("/app/"));
+        assert_eq!(
+            options.fallback_for("/app/admin/users/1"),
+            Some("/app/admin/index.html")
+        );
+        assert_eq!(options.fallback_for("/app/main.js"), None);
+        assert_eq!(options.fallback_for("/blog/"), None);
+    }
+
+    #[test]
+    fn test_alias_response() {
+        let aliases = SiteAliases::default();
+        aliases.write().unwrap().insert(
+            "/old-post/".to_string(),
+            "https://example.com/blog/new-post/".to_string(),
+        );
+
+        for path in ["/old-post/", "/old-post"] {
+            let response = alias_response(&aliases, path).unwrap();
+            assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+            assert_eq!(
+                response.headers()[header::LOCATION],
+                "https://example.com/blog/new-post/"
+            );
+        }
+
+        assert!(alias_response(&aliases, "/blog/new-post/").is_none());
+    }
+
+    #[test]
+    fn test_near_misses() {
+        let paths = [
+            "/",
+            "/404.html",
+            "/blog/",
+            "/blog/hello-world/",
+            "/blog/hello-word-2/",
+            "/about/",
+            "/style.css",
+        ];
+
+        assert_eq!(
+            near_misses("/blog/helo-world/", paths.into_iter()),
+            vec!["/blog/hello-world/", "/blog/hello-word-2/"]
+        );
+        assert_eq!(near_misses("/abut", paths.into_iter()), vec!["/about/"]);
+        assert_eq!(
+            near_misses("/completely/different/", paths.into_iter()),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn test_inject_not_found_overlay() {
+        let overlay = not_found_overlay("/<script>/", &["/about/"]);
+        assert!(overlay.contains("No page found at /&lt;script&gt;/"));
+        assert!(overlay.contains(r#"<a href="/about/">/about/</a>"#));
+
+        let page = inject_not_found_overlay(
+            b"<html><body><h1>Not Found</h1></body></html>".to_vec(),
+            "<div></div>",
+        );
+        assert_eq!(
+            String::from_utf8(page).unwrap(),
+            "<html><body><h1>Not Found</h1><div></div></body></html>"
+        );
+    }
+}
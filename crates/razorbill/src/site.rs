@@ -1,47 +1,83 @@
-use std::collections::HashMap;
-use std::convert::Infallible;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::marker::PhantomData;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
-use std::{fs, io, thread};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::{Duration, Instant};
+use std::{fs, io, mem};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use auk::renderer::HtmlElementRenderer;
 use auk::visitor::{noop_visit_element, MutVisitor, Visitor};
-use auk::HtmlElement;
+use auk::{Element, HtmlElement};
 use auk_markdown::MarkdownComponents;
-use http_body_util::combinators::BoxBody;
-use http_body_util::{BodyExt, Empty, Full};
-use hyper::body::Bytes;
+use chrono::Utc;
+use chrono_tz::Tz;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
-use hyper::{header, Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
-use mime_guess::MimeGuess;
-use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
-use once_cell::sync::Lazy;
-use serde_json::json;
+use notify::{Event, RecommendedWatcher, Watcher};
 use thiserror::Error;
 use tokio::net::TcpListener;
 use tokio::sync::mpsc::unbounded_channel;
 use walkdir::WalkDir;
-use ws::{Message, Sender, WebSocket};
 
+use crate::a11y::AccessibilityAudit;
+use crate::absolute_url::AbsoluteUrlRewriter;
+use crate::analytics::Analytics;
+use crate::build_overlay::{BuildStatusBroadcaster, BUILD_OVERLAY_JS_PATH};
+use crate::build_report::{
+    BrokenLink, BuildReport, MissingAsset, PageTiming, StaticFilesReport, UnknownMenuItem,
+};
+use crate::config::{ConfigFile, LoadConfigError};
 use crate::content::{
-    ContentAggregator, Page, Pages, ParsePageError, ParseSectionError, Section, SectionPath,
-    Sections, Taxonomy, TaxonomyTerm, AVERAGE_ADULT_WPM,
+    dated_pages, find_unknown_taxonomies, group_series, is_passthrough_file, resolve_content_asset,
+    scheduled_page, ContentAggregator, ContentAsset, Page, Pages, ParsePageError,
+    ParseSectionError, ScheduledPage, Section, SectionPath, Sections, SiteStats, Taxonomy,
+    TaxonomyTerm, TaxonomyTermFrontMatter, TermNormalization, UnknownTaxonomy, AUTHORS_TAXONOMY,
+    AVERAGE_ADULT_WPM,
+};
+use crate::csp::{ContentSecurityPolicy, CspStorage};
+use crate::date::DEFAULT_DATE_FORMAT;
+use crate::export::RelativeLinkRewriter;
+use crate::feed::{render_entries_feed, render_feed, FeedContent, FeedEntry};
+use crate::html_validation::validate_html;
+use crate::i18n::{LoadTranslationsError, Translations, DEFAULT_LANGUAGE};
+use crate::image::image_dimensions;
+use crate::indieweb::external_links;
+use crate::lint::{ContentRule, Diagnostic, Severity};
+use crate::live_reload::{
+    changed_content, content_etags, live_reload_paths, next_changed_paths, LiveReloadBroadcaster,
+    WatchedDirectories, FULL_RELOAD_PATH,
 };
-use crate::feed::render_feed;
-use crate::markdown::{markdown_with_shortcodes, DefaultMarkdownComponents, Shortcode};
-use crate::permalink::Permalink;
+use crate::manifest::{BuildManifest, ShortcodeManifest};
+use crate::markdown::{
+    anchor_ids, builtin_shortcodes, contains_math, markdown_with_options, render_uncached,
+    DefaultMarkdownComponents, Figures, MarkdownCache, MarkdownOptions, Math, Shortcode,
+    ShortcodeDescriptor, ShortcodeError,
+};
+use crate::menu::MenuItem;
+use crate::parallel::par_map;
+use crate::pdf::{PdfExport, PDF_FILE_NAME};
+use crate::permalink::{decode_path, Permalink};
+use crate::profile::{BuildOptions, BuildProfile};
+use crate::redirects::HostRedirects;
 use crate::render::{
-    BaseRenderContext, PageToRender, RenderPageContext, RenderSectionContext,
-    RenderTaxonomyContext, RenderTaxonomyTermContext, SectionToRender, TaxonomyTermToRender,
-    TaxonomyToRender,
+    BaseRenderContext, PageToRender, RenderErrorPageContext, RenderGeneratedPageContext,
+    RenderPageContext, RenderSectionContext, RenderTaxonomyContext, RenderTaxonomyTermContext,
+    SectionToRender, SeriesToRender, TaxonomyTermToRender, TaxonomyToRender,
+};
+use crate::serve::{
+    handle_request, open_browser, startup_banner, BuildStatus, ServeOptions, ServerState,
 };
 use crate::sitemap::render_sitemap;
-use crate::storage::{DiskStorage, InMemoryStorage, Store};
+use crate::social_card::{SocialCard, SocialCards, SOCIAL_CARD_FILE_NAME};
+use crate::static_files::{CopyMode, StaticFiles};
+use crate::storage::{
+    DiskStorage, InMemoryStorage, RecordingStorage, SiteAliases, SiteContent, Store,
+};
+use crate::versions::{Versions, LATEST_PATH};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub enum TemplateKey {
@@ -59,6 +95,11 @@ pub type RenderTaxonomy = Arc<dyn Fn(&RenderTaxonomyContext) -> HtmlElement + Se
 
 pub type RenderTaxonomyTerm = Arc<dyn Fn(&RenderTaxonomyTermContext) -> HtmlElement + Send + Sync>;
 
+pub type RenderGeneratedPage =
+    Arc<dyn Fn(&RenderGeneratedPageContext) -> HtmlElement + Send + Sync>;
+
+pub type RenderErrorPage = Arc<dyn Fn(&RenderErrorPageContext) -> HtmlElement + Send + Sync>;
+
 struct Templates {
     pub index: RenderIndex,
     pub section: HashMap<TemplateKey, RenderSection>,
@@ -66,6 +107,153 @@ struct Templates {
     pub taxonomy: HashMap<String, RenderTaxonomy>,
     pub taxonomy_term: HashMap<String, RenderTaxonomyTerm>,
     pub not_found: Option<Arc<dyn Fn(&BaseRenderContext) -> HtmlElement + Send + Sync>>,
+    /// The templates for error pages, keyed by HTTP status code.
+    pub error: HashMap<u16, RenderErrorPage>,
+    /// The templates for pages that aren't backed by content, keyed by path.
+    pub generated: HashMap<String, RenderGeneratedPage>,
+    /// The template for the home page, when it is rendered independently of
+    /// the root section.
+    pub home: Option<RenderGeneratedPage>,
+    /// The assets (stylesheets and scripts) required by each section template.
+    pub section_assets: HashMap<TemplateKey, Vec<String>>,
+    /// The assets (stylesheets and scripts) required by each page template.
+    pub page_assets: HashMap<TemplateKey, Vec<String>>,
+}
+
+/// The problems found in the rendered documents.
+#[derive(Default)]
+struct DocumentDiagnostics {
+    html: Vec<Diagnostic>,
+    accessibility: Vec<Diagnostic>,
+}
+
+/// Renders documents to HTML, sharing one growing buffer between them.
+///
+/// Rendering each document with a new [`HtmlElementRenderer`] grows a new
+/// buffer from nothing every time. Here the buffer is kept between documents,
+/// and each document's HTML is copied out of it in a single allocation.
+struct DocumentRenderer {
+    renderer: HtmlElementRenderer,
+}
+
+impl DocumentRenderer {
+    /// The size the shared buffer can grow to before it is replaced, so that
+    /// the memory held between documents stays bounded.
+    const MAX_BUFFER_LEN: usize = 4 * 1024 * 1024;
+
+    fn new() -> Self {
+        Self {
+            renderer: HtmlElementRenderer::new(),
+        }
+    }
+
+    /// Renders the given document to HTML.
+    fn render(&mut self, document: &HtmlElement) -> Result<String, std::fmt::Error> {
+        let start = self.reserve();
+        self.renderer.visit(document)?;
+
+        Ok(self.renderer.html()[start..].to_owned())
+    }
+
+    /// Renders the given elements to HTML, prefixed with `prefix`.
+    fn render_children(
+        &mut self,
+        prefix: &str,
+        children: &[Element],
+    ) -> Result<String, std::fmt::Error> {
+        let start = self.reserve();
+        self.renderer.visit_children(children)?;
+
+        let html = &self.renderer.html()[start..];
+        let mut rendered = String::with_capacity(prefix.len() + html.len());
+        rendered.push_str(prefix);
+        rendered.push_str(html);
+
+        Ok(rendered)
+    }
+
+    /// Returns the offset in the buffer that the next document starts at,
+    /// replacing the buffer first if it has grown too large.
+    fn reserve(&mut self) -> usize {
+        if self.renderer.html().len() > Self::MAX_BUFFER_LEN {
+            self.renderer = HtmlElementRenderer::new();
+        }
+
+        self.renderer.html().len()
+    }
+}
+
+/// Returns the path an error page with the given HTTP status code is
+/// rendered to.
+pub(crate) fn error_page_path(status: u16) -> String {
+    if status == 500 {
+        "50x.html".to_string()
+    } else {
+        format!("{status}.html")
+    }
+}
+
+/// A file in the content directory to be loaded.
+enum ContentFile {
+    Page(PathBuf),
+    /// The directory of a section, which may not have an `_index.md`.
+    Section(PathBuf),
+    /// A file holding the metadata for a taxonomy term.
+    TermFile {
+        taxonomy: String,
+        term: String,
+        path: PathBuf,
+    },
+    /// A directory holding the metadata for a taxonomy term.
+    TermDirectory {
+        taxonomy: String,
+        term: String,
+        path: PathBuf,
+    },
+}
+
+enum LoadedContent {
+    Page(Page),
+    Section(Option<Section>),
+    TermMeta {
+        taxonomy: String,
+        term: String,
+        meta: Option<TaxonomyTermFrontMatter>,
+    },
+}
+
+impl ContentFile {
+    /// Reads and parses the file.
+    fn load(
+        &self,
+        config: &SiteConfig,
+        content_path: &Path,
+    ) -> Result<LoadedContent, LoadSiteError> {
+        Ok(match self {
+            Self::Page(path) => LoadedContent::Page(Page::from_path(config, content_path, path)?),
+            Self::Section(path) => {
+                LoadedContent::Section(Section::from_path(config, content_path, path)?)
+            }
+            Self::TermFile {
+                taxonomy,
+                term,
+                path,
+            } => LoadedContent::TermMeta {
+                taxonomy: taxonomy.clone(),
+                term: term.clone(),
+                meta: Some(TaxonomyTermFrontMatter::from_file(path)?),
+            },
+            Self::TermDirectory {
+                taxonomy,
+                term,
+                path,
+            } => LoadedContent::TermMeta {
+                taxonomy: taxonomy.clone(),
+                term: term.clone(),
+                meta: TaxonomyTermFrontMatter::from_path(path)?,
+            },
+        })
+    }
 }
 
 #[derive(Error, Debug)]
@@ -78,6 +266,23 @@ pub enum LoadSiteError {
 
     #[error("failed to parse page: {0}")]
     ParsePage(#[from] ParsePageError),
+
+    #[error("unknown taxonomy '{taxonomy}' in {filepath}")]
+    UnknownTaxonomy { taxonomy: String, filepath: PathBuf },
+
+    #[error("failed to load translations: {0}")]
+    Translations(#[from] LoadTranslationsError),
+
+    #[error("unknown page {path} in menu '{menu}'")]
+    UnknownMenuItem { menu: String, path: PathBuf },
+
+    /// More than one content file failed to parse.
+    #[error(
+        "failed to load {} content files:\n{}",
+        .0.len(),
+        .0.iter().map(|err| format!("  - {err}")).collect::<Vec<_>>().join("\n")
+    )]
+    Multiple(Vec<LoadSiteError>),
 }
 
 #[derive(Error, Debug)]
@@ -91,8 +296,38 @@ pub enum RenderSiteError {
     #[error("template not found: {0:?}")]
     TemplateNotFound(TemplateKey),
 
+    #[error("missing templates for taxonomies: {}", .0.join(", "))]
+    TaxonomyTemplatesNotFound(Vec<String>),
+
     #[error("storage error: {0}")]
     Storage(String),
+
+    #[error("failed to render shortcodes in {}: {err}", filepath.display())]
+    Shortcode {
+        filepath: PathBuf,
+        err: ShortcodeError,
+    },
+
+    #[error("broken anchor '{link}' in {permalink}")]
+    BrokenAnchor { link: String, permalink: String },
+
+    #[error("broken link '{link}' in {permalink}")]
+    BrokenLink { link: String, permalink: String },
+
+    #[error("failed to render social card for {permalink}: {message}")]
+    SocialCard { permalink: String, message: String },
+
+    #[error("failed to render PDF for {permalink}: {message}")]
+    Pdf { permalink: String, message: String },
+
+    #[error("content rule failed: {0}")]
+    ContentRule(Diagnostic),
+
+    #[error("invalid HTML: {0}")]
+    InvalidHtml(Diagnostic),
+
+    #[error("accessibility problem: {0}")]
+    Accessibility(Diagnostic),
 }
 
 #[derive(Error, Debug)]
@@ -101,12 +336,37 @@ pub enum ServeSiteError {
     AsyncIo(#[from] tokio::io::Error),
 }
 
-static SITE_CONTENT: Lazy<Arc<RwLock<HashMap<String, String>>>> =
-    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+/// What happens to `@/` links that don't point to any content.
+///
+/// Links whose fragment doesn't match any of the anchors in the content they
+/// point to are broken as well.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum BrokenLinkPolicy {
+    /// The link is left as-is.
+    ///
+    /// Links with a broken fragment still point to their content, as it exists.
+    #[default]
+    Leave,
+    /// The link is removed, leaving its text in place.
+    Strip,
+    /// The link is replaced with the given URL.
+    Replace(String),
+    /// The build fails.
+    Fail,
+}
 
-struct LinkReplacer<'a> {
+pub(crate) struct LinkReplacer<'a> {
     site: &'a Site,
     current_url: &'a Permalink,
+    /// The directory containing the assets co-located with the content being
+    /// visited.
+    ///
+    /// When present, and the site passes content through to the output,
+    /// relative `src` attributes are resolved against the current URL.
+    bundle_path: Option<PathBuf>,
+    /// Whether links that are still relative after being replaced are made
+    /// absolute, for content that is read outside of the site (e.g., feeds).
+    absolute_urls: bool,
     inside_footnote_reference: bool,
 }
 
@@ -115,13 +375,140 @@ impl<'a> LinkReplacer<'a> {
         Self {
             site,
             current_url,
+            bundle_path: None,
+            absolute_urls: false,
             inside_footnote_reference: false,
         }
     }
+
+    pub fn with_bundle_path(mut self, bundle_path: PathBuf) -> Self {
+        self.bundle_path = Some(bundle_path);
+        self
+    }
+
+    pub fn with_absolute_urls(mut self) -> Self {
+        self.absolute_urls = true;
+        self
+    }
+
+    /// Returns the path to the content file and the permalink for the given `@/`
+    /// link, without its fragment.
+    fn resolve_content_link(&self, link: &str) -> Option<(PathBuf, Permalink)> {
+        let path = self.site.content_path.join(link.replacen("@/", "", 1));
+
+        let permalink = None
+            .or_else(|| {
+                self.site
+                    .pages
+                    .get(&path)
+                    .map(|page| page.permalink.clone())
+            })
+            .or_else(|| {
+                self.site
+                    .sections
+                    .get(&path)
+                    .map(|section| section.permalink.clone())
+            })?;
+
+        Some((path, permalink))
+    }
+
+    /// Returns whether the content file at the given path has an anchor with
+    /// the given name.
+    fn anchor_exists(&self, path: &Path, fragment: &str) -> bool {
+        self.site
+            .anchors
+            .get(path)
+            .is_some_and(|anchors| anchors.contains(fragment))
+    }
+
+    /// Returns whether the given `href` is an `@/` link that doesn't point to
+    /// any content, or to an anchor that doesn't exist.
+    fn is_broken_link(&self, href: &str) -> bool {
+        if !href.starts_with("@/") {
+            return false;
+        }
+
+        let (link, fragment) = match href.split_once('#') {
+            Some((link, fragment)) => (link, Some(fragment)),
+            None => (href, None),
+        };
+
+        match self.resolve_content_link(link) {
+            Some((path, _permalink)) => {
+                fragment.is_some_and(|fragment| !self.anchor_exists(&path, fragment))
+            }
+            None => true,
+        }
+    }
+
+    fn broken_anchor_error(&self, link: &str) -> RenderSiteError {
+        RenderSiteError::BrokenAnchor {
+            link: link.to_owned(),
+            permalink: self.current_url.as_str().to_owned(),
+        }
+    }
+
+    /// Records the given broken link for the build report.
+    fn record_broken_link(&self, link: &str) {
+        self.site
+            .broken_links
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(BrokenLink {
+                link: link.to_owned(),
+                permalink: self.current_url.as_str().to_owned(),
+            });
+    }
+
+    /// Resolves the given URL against the current URL, if it isn't already absolute.
+    fn make_absolute(&self, value: &mut String) {
+        AbsoluteUrlRewriter::from_permalink(self.current_url).rewrite(value);
+    }
+
+    fn is_relative_url(value: &str) -> bool {
+        !value.is_empty()
+            && !value.starts_with('/')
+            && !value.starts_with('#')
+            && !value.starts_with("@/")
+            && url::Url::parse(value).is_err()
+    }
+
+    fn replace_relative_src(&self, bundle_path: &Path, value: &mut String) {
+        if !Self::is_relative_url(value) {
+            return;
+        }
+
+        let asset_path = value.split(['?', '#']).next().unwrap_or(value);
+        let source_path = bundle_path.join(asset_path);
+        let exists = source_path.exists();
+        if !exists || !self.site.passthrough_content {
+            self.site
+                .missing_assets
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .insert(MissingAsset {
+                    src: value.clone(),
+                    permalink: self.current_url.as_str().to_owned(),
+                    source_path,
+                    exists,
+                });
+        }
+
+        // Co-located assets are only copied to the output when content is
+        // passed through, so otherwise the `src` is left as it was written.
+        if !self.site.passthrough_content {
+            return;
+        }
+
+        if let Some(permalink) = self.current_url.join(value) {
+            *value = permalink.as_str().to_owned();
+        }
+    }
 }
 
 impl<'a> MutVisitor for LinkReplacer<'a> {
-    type Error = ();
+    type Error = RenderSiteError;
 
     fn visit(&mut self, element: &mut HtmlElement) -> Result<(), Self::Error> {
         if let Some(class) = element.attrs.get("class") {
@@ -130,6 +517,22 @@ impl<'a> MutVisitor for LinkReplacer<'a> {
             }
         }
 
+        // Stripping a link removes its `href`, which leaves just the text.
+        if self.site.broken_link_policy == BrokenLinkPolicy::Strip && element.tag_name == "a" {
+            if let Some(href) = element.attrs.get("href") {
+                if self.is_broken_link(href) {
+                    // Strict builds fail on broken anchors, whatever the policy.
+                    let link = href.split('#').next().unwrap_or(href);
+                    if self.site.strict && self.resolve_content_link(link).is_some() {
+                        return Err(self.broken_anchor_error(href));
+                    }
+
+                    self.record_broken_link(href);
+                    element.attrs.retain(|name, _value| name != "href");
+                }
+            }
+        }
+
         noop_visit_element(self, element)?;
 
         self.inside_footnote_reference = false;
@@ -138,30 +541,74 @@ impl<'a> MutVisitor for LinkReplacer<'a> {
     }
 
     fn visit_attr(&mut self, name: &str, value: &mut String) -> Result<(), Self::Error> {
+        if name == "src" {
+            if let Some(bundle_path) = self.bundle_path.as_deref() {
+                self.replace_relative_src(bundle_path, value);
+            }
+
+            if self.absolute_urls {
+                self.make_absolute(value);
+            }
+
+            return Ok(());
+        }
+
         if name == "href" {
             if value.starts_with("@/") {
-                let path = self.site.content_path.join(value.replacen("@/", "", 1));
+                let (link, fragment) = match value.split_once('#') {
+                    Some((link, fragment)) => (link, Some(fragment)),
+                    None => (value.as_str(), None),
+                };
 
-                let permalink = None
-                    .or_else(|| {
-                        self.site
-                            .pages
-                            .get(&path)
-                            .map(|page| page.permalink.clone())
-                    })
-                    .or_else(|| {
-                        self.site
-                            .sections
-                            .get(&path)
-                            .map(|section| section.permalink.clone())
-                    });
+                let Some((path, permalink)) = self.resolve_content_link(link) else {
+                    self.record_broken_link(value);
 
-                if let Some(permalink) = permalink {
+                    match &self.site.broken_link_policy {
+                        // Links within `<a>` elements have already been
+                        // stripped, so this is some other element.
+                        BrokenLinkPolicy::Leave | BrokenLinkPolicy::Strip => {}
+                        BrokenLinkPolicy::Replace(fallback_url) => {
+                            *value = fallback_url.clone();
+                        }
+                        BrokenLinkPolicy::Fail => {
+                            return Err(RenderSiteError::BrokenLink {
+                                link: value.clone(),
+                                permalink: self.current_url.as_str().to_owned(),
+                            });
+                        }
+                    }
+
+                    return Ok(());
+                };
+
+                let Some(fragment) = fragment else {
                     *value = permalink.as_str().to_owned();
-                } else {
-                    eprintln!("Invalid link: {value}");
+                    return Ok(());
+                };
+
+                if !self.anchor_exists(&path, fragment) {
+                    if self.site.strict {
+                        return Err(self.broken_anchor_error(value));
+                    }
+
+                    self.record_broken_link(value);
+
+                    match &self.site.broken_link_policy {
+                        // The link still points to the content, which exists.
+                        BrokenLinkPolicy::Leave | BrokenLinkPolicy::Strip => {}
+                        BrokenLinkPolicy::Replace(fallback_url) => {
+                            *value = fallback_url.clone();
+                            return Ok(());
+                        }
+                        BrokenLinkPolicy::Fail => {
+                            return Err(self.broken_anchor_error(value));
+                        }
+                    }
                 }
 
+                let new_value = format!("{}#{fragment}", permalink.as_str());
+                *value = new_value;
+
                 return Ok(());
             }
 
@@ -171,6 +618,10 @@ impl<'a> MutVisitor for LinkReplacer<'a> {
                     current_url = self.current_url.as_str()
                 )
             }
+
+            if self.absolute_urls {
+                self.make_absolute(value);
+            }
         }
 
         Ok(())
@@ -205,1027 +656,4771 @@ impl MutVisitor for LiveReloadInjector {
         noop_visit_element(self, element)?;
 
         if element.tag_name == "body" {
-            element.children.extend([script()
-                .src(format!("/livereload.js?port={}&amp;mindelay=10", self.port))
-                .into()])
+            element.children.extend([
+                script()
+                    .src(format!("/livereload.js?port={}&amp;mindelay=10", self.port))
+                    .into(),
+                script().src(BUILD_OVERLAY_JS_PATH).into(),
+            ])
         }
 
         Ok(())
     }
 }
 
-struct BuildSiteParams {
-    base_url: String,
-    title: Option<String>,
-    include_drafts: bool,
-    reading_speed: usize,
-    root_path: PathBuf,
-    sass_path: Option<PathBuf>,
-    sass_load_paths: Vec<PathBuf>,
-    templates: Templates,
-    markdown_components: Box<dyn MarkdownComponents>,
-    shortcodes: HashMap<String, Shortcode>,
-    taxonomies: Vec<Taxonomy>,
-}
-
-pub struct SiteConfig {
-    pub base_url: String,
-    pub title: Option<String>,
-    pub taxonomies: Vec<Taxonomy>,
-    /// The reading speed (in WPM) to use when determining reading time.
-    pub reading_speed: usize,
+/// Injects the assets declared for a template into the rendered output.
+///
+/// Stylesheets are added to the `<head>` and scripts are added to the end of
+/// the `<body>`.
+struct AssetInjector<'a> {
+    assets: &'a [String],
 }
 
-pub struct Site {
-    pub(crate) config: SiteConfig,
-    #[allow(unused)]
-    root_path: PathBuf,
-    content_path: PathBuf,
-    /// The path to the `static` directory that houses static assets.
-    static_path: PathBuf,
-    sass_path: Option<PathBuf>,
-    sass_load_paths: Vec<PathBuf>,
-    output_path: PathBuf,
-    templates: Templates,
-    markdown_components: Box<dyn MarkdownComponents>,
-    shortcodes: HashMap<String, Shortcode>,
-    pub(crate) sections: Sections,
-    pub(crate) pages: Pages,
-    pub(crate) taxonomies: HashMap<String, HashMap<String, Vec<PathBuf>>>,
-    include_drafts: bool,
-    is_serving: bool,
-    live_reload_port: Option<u16>,
-}
+impl<'a> AssetInjector<'a> {
+    pub fn inject(assets: Option<&'a Vec<String>>, element: &mut HtmlElement) {
+        let Some(assets) = assets else {
+            return;
+        };
 
-impl Site {
-    pub fn builder() -> SiteBuilder<()> {
-        SiteBuilder::new()
+        let mut injector = Self::new(assets);
+        injector.visit(element).unwrap();
     }
 
-    fn from_params(params: BuildSiteParams) -> Self {
-        let root_path = params.root_path;
-
-        Site {
-            config: SiteConfig {
-                base_url: params.base_url,
-                title: params.title,
-                taxonomies: params.taxonomies,
-                reading_speed: params.reading_speed,
-            },
-            root_path: root_path.to_owned(),
-            content_path: root_path.join("content"),
-            static_path: root_path.join("static"),
-            sass_path: params.sass_path.map(|sass_path| root_path.join(sass_path)),
-            sass_load_paths: params
-                .sass_load_paths
-                .into_iter()
-                .map(|load_path| root_path.join(load_path))
-                .collect(),
-            output_path: root_path.join("public"),
-            templates: params.templates,
-            markdown_components: params.markdown_components,
-            shortcodes: params.shortcodes,
-            sections: Sections::default(),
-            pages: Pages::default(),
-            taxonomies: HashMap::new(),
-            include_drafts: params.include_drafts,
-            is_serving: false,
-            live_reload_port: None,
-        }
+    pub fn new(assets: &'a [String]) -> Self {
+        Self { assets }
     }
 
-    pub fn base_url(&self) -> &str {
-        &self.config.base_url
-    }
+    fn is_script(asset: &str) -> bool {
+        let path = asset.split(['?', '#']).next().unwrap_or(asset);
 
-    pub fn set_output_path(&mut self, output_path: impl AsRef<Path>) {
-        self.output_path = output_path.as_ref().to_owned();
+        path.ends_with(".js") || path.ends_with(".mjs")
     }
+}
 
-    pub fn load(&mut self) -> Result<(), LoadSiteError> {
-        let walker = WalkDir::new(&self.content_path)
-            .follow_links(true)
-            .into_iter();
-
-        let mut pages = Vec::new();
-        let mut sections = Vec::new();
+impl<'a> MutVisitor for AssetInjector<'a> {
+    type Error = ();
 
-        for entry in walker {
-            let entry = entry?;
-            let path = entry.path();
+    fn visit(&mut self, element: &mut HtmlElement) -> Result<(), Self::Error> {
+        use auk::*;
 
-            let Some(filename) = entry.file_name().to_str() else {
-                continue;
-            };
+        noop_visit_element(self, element)?;
 
-            if !path.is_dir() {
-                if !filename.ends_with(".md")
-                    || filename.starts_with(".")
-                    || filename == "_index.md"
-                {
-                    continue;
-                }
+        if element.tag_name == "head" {
+            element.children.extend(
+                self.assets
+                    .iter()
+                    .filter(|asset| !Self::is_script(asset))
+                    .map(|asset| link().rel("stylesheet").href(asset).into()),
+            );
+        }
 
-                pages.push(Page::from_path(&self.config, &self.content_path, path)?);
-            } else {
-                if let Some(section) = Section::from_path(&self.config, &self.content_path, path)? {
-                    sections.push(section);
-                }
-            }
+        if element.tag_name == "body" {
+            element.children.extend(
+                self.assets
+                    .iter()
+                    .filter(|asset| Self::is_script(asset))
+                    .map(|asset| script().src(asset).into()),
+            );
         }
 
-        let mut aggregator =
-            ContentAggregator::new(self.content_path.clone(), self.config.taxonomies.clone());
+        Ok(())
+    }
+}
 
-        for section in sections {
-            if section.meta.draft && !self.include_drafts {
-                continue;
-            }
+/// Injects the snippets for the site's analytics providers into the rendered output.
+struct AnalyticsInjector<'a> {
+    analytics: &'a [Analytics],
+}
 
-            aggregator.add_section(section);
+impl<'a> AnalyticsInjector<'a> {
+    pub fn inject(analytics: &'a [Analytics], is_serving: bool, element: &mut HtmlElement) {
+        if analytics.is_empty() || is_serving {
+            return;
         }
 
-        for page in pages {
-            if page.meta.draft && !self.include_drafts {
-                continue;
-            }
-
-            aggregator.add_page(page);
-        }
-
-        let (sections, pages, taxonomies) = aggregator.aggregate();
-        self.sections = sections;
-        self.pages = pages;
-        self.taxonomies = taxonomies;
-
-        Ok(())
+        let mut injector = Self::new(analytics);
+        injector.visit(element).unwrap();
     }
 
-    pub fn render(&mut self) -> Result<(), RenderSiteError> {
-        if self.is_serving {
-            self.render_to(InMemoryStorage::new(SITE_CONTENT.clone()))
-        } else {
-            self.render_to(DiskStorage::new(self.output_path.clone()))
-        }
+    pub fn new(analytics: &'a [Analytics]) -> Self {
+        Self { analytics }
     }
+}
 
-    fn render_to(&mut self, storage: impl Store) -> Result<(), RenderSiteError> {
-        self.render_aliases(&storage);
+impl<'a> MutVisitor for AnalyticsInjector<'a> {
+    type Error = ();
 
-        let mut sections_to_update = HashMap::new();
+    fn visit(&mut self, element: &mut HtmlElement) -> Result<(), Self::Error> {
+        noop_visit_element(self, element)?;
 
-        for (section_path, section) in self.sections.iter() {
-            let (mut content, table_of_contents) = markdown_with_shortcodes(
-                &section.raw_content,
-                &self.markdown_components,
-                &self.shortcodes,
+        if element.tag_name == "head" {
+            element.children.extend(
+                self.analytics
+                    .iter()
+                    .flat_map(|analytics| analytics.head_elements()),
             );
-
-            let mut link_replacer = LinkReplacer::new(&self, &section.permalink);
-            link_replacer.visit_children(&mut content).unwrap();
-
-            sections_to_update.insert(section_path.clone(), (content, table_of_contents));
         }
 
-        for (section_path, (content, table_of_contents)) in sections_to_update {
-            let section = self.sections.get_mut(&section_path).unwrap();
-            section.content = content;
-            section.table_of_contents = table_of_contents;
+        if element.tag_name == "body" {
+            element.children.extend(
+                self.analytics
+                    .iter()
+                    .flat_map(|analytics| analytics.body_elements()),
+            );
         }
 
-        let mut pages_to_update = HashMap::new();
-
-        for (page_path, page) in self.pages.iter() {
-            let (mut content, table_of_contents) = markdown_with_shortcodes(
-                &page.raw_content,
-                &self.markdown_components,
-                &self.shortcodes,
-            );
+        Ok(())
+    }
+}
 
-            let mut link_replacer = LinkReplacer::new(&self, &page.permalink);
-            link_replacer.visit_children(&mut content).unwrap();
+/// Injects the assets for rendering math into the rendered output, if it
+/// contains any math.
+struct MathInjector<'a> {
+    math: &'a Math,
+}
 
-            pages_to_update.insert(page_path.clone(), (content, table_of_contents));
-        }
+impl<'a> MathInjector<'a> {
+    pub fn inject(math: Option<&'a Math>, element: &mut HtmlElement) {
+        let Some(math) = math else {
+            return;
+        };
 
-        for (page_path, (content, table_of_contents)) in pages_to_update {
-            let page = self.pages.get_mut(&page_path).unwrap();
-            page.content = content;
-            page.table_of_contents = table_of_contents;
+        if !contains_math(element) {
+            return;
         }
 
-        for section in self.sections.values() {
-            let section_template = if section.path == SectionPath("/_index".to_string()) {
-                &self.templates.index
-            } else {
-                let template_name = section
-                    .meta
-                    .template
-                    .clone()
-                    .map(TemplateKey::Custom)
-                    .unwrap_or(TemplateKey::Default);
-
-                let section_template = self
-                    .templates
-                    .section
-                    .get(&template_name)
-                    .ok_or_else(|| RenderSiteError::TemplateNotFound(template_name))?;
-
-                section_template
-            };
+        let mut injector = Self { math };
+        injector.visit(element).unwrap();
+    }
+}
 
-            let ctx = RenderSectionContext {
-                base: BaseRenderContext {
-                    base_url: self.base_url(),
-                    content_path: &self.content_path,
-                    markdown_components: &self.markdown_components,
-                    shortcodes: &self.shortcodes,
-                    sections: &self.sections,
-                    pages: &self.pages,
-                },
-                section: SectionToRender::from_section(section, &self.pages),
-            };
+impl<'a> MutVisitor for MathInjector<'a> {
+    type Error = ();
 
-            let mut rendered_section = section_template(&ctx);
+    fn visit(&mut self, element: &mut HtmlElement) -> Result<(), Self::Error> {
+        noop_visit_element(self, element)?;
 
-            let mut link_replacer = LinkReplacer::new(&self, &section.permalink);
-            link_replacer.visit(&mut rendered_section).unwrap();
+        if element.tag_name == "head" {
+            element.children.extend(self.math.head_elements());
+        }
 
-            LiveReloadInjector::inject(self.live_reload_port, &mut rendered_section);
+        Ok(())
+    }
+}
 
-            let rendered = HtmlElementRenderer::new().render_to_string(&rendered_section)?;
+/// Adds the attributes for loading local images efficiently: `width` and
+/// `height`, read from the image itself, to prevent layout shift, along with
+/// `loading="lazy"` and `decoding="async"`.
+///
+/// Only images that resolve to a file in `static/` or the content's bundle
+/// are affected, and attributes that are already set are left alone.
+struct ImageAttributeInjector<'a> {
+    static_path: &'a Path,
+    bundle_path: PathBuf,
+}
 
-            storage
-                .store_rendered_section(&section, rendered)
-                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+impl<'a> ImageAttributeInjector<'a> {
+    pub fn new(static_path: &'a Path, bundle_path: PathBuf) -> Self {
+        Self {
+            static_path,
+            bundle_path,
         }
+    }
 
-        for page in self.pages.values() {
-            let template_name = page
-                .meta
-                .template
-                .clone()
-                .map(TemplateKey::Custom)
-                .unwrap_or(TemplateKey::Default);
-
-            let page_template = self
-                .templates
-                .page
-                .get(&template_name)
-                .ok_or_else(|| RenderSiteError::TemplateNotFound(template_name))?;
+    /// Returns the path to the file that the given `src` refers to, if it is local.
+    fn local_path(&self, src: &str) -> Option<PathBuf> {
+        let path = src.split(['?', '#']).next().unwrap_or(src);
+        let path = decode_path(path);
 
-            let ctx = RenderPageContext {
-                base: BaseRenderContext {
-                    base_url: self.base_url(),
-                    content_path: &self.content_path,
-                    markdown_components: &self.markdown_components,
-                    shortcodes: &self.shortcodes,
-                    sections: &self.sections,
-                    pages: &self.pages,
-                },
-                page: PageToRender::from_page(page),
-            };
+        if path.starts_with("//") {
+            return None;
+        }
 
-            let mut rendered_page = page_template(&ctx);
+        let path = match path.strip_prefix('/') {
+            Some(path) => self.static_path.join(path),
+            None if LinkReplacer::is_relative_url(&path) => self.bundle_path.join(&*path),
+            None => return None,
+        };
 
-            let mut link_replacer = LinkReplacer::new(&self, &page.permalink);
-            link_replacer.visit(&mut rendered_page).unwrap();
+        path.is_file().then_some(path)
+    }
+}
 
-            LiveReloadInjector::inject(self.live_reload_port, &mut rendered_page);
+impl<'a> MutVisitor for ImageAttributeInjector<'a> {
+    type Error = ();
 
-            let rendered = HtmlElementRenderer::new().render_to_string(&rendered_page)?;
+    fn visit(&mut self, element: &mut HtmlElement) -> Result<(), Self::Error> {
+        noop_visit_element(self, element)?;
 
-            storage
-                .store_rendered_page(&page, rendered)
-                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        if element.tag_name != "img" {
+            return Ok(());
         }
 
-        render_sitemap(&self, &storage);
-        render_feed(
-            &self,
-            Permalink::from_path(&self.config, "atom.xml"),
-            None,
-            self.pages.values().collect(),
-            &storage,
-        );
-        self.render_404_page(&storage)?;
-        self.render_robots_txt(&storage)?;
-        self.render_taxonomies(&storage)?;
-
-        if let Some(sass_path) = self.sass_path.as_ref() {
-            fn is_sass(entry: &walkdir::DirEntry) -> bool {
-                entry
-                    .path()
-                    .extension()
-                    .and_then(|extension| extension.to_str())
-                    .map(|extension| extension == "sass" || extension == "scss")
-                    .unwrap_or(false)
-            }
+        let Some(path) = element
+            .attrs
+            .get("src")
+            .and_then(|src| self.local_path(src))
+        else {
+            return Ok(());
+        };
 
-            fn is_partial(entry: &walkdir::DirEntry) -> bool {
-                entry
-                    .file_name()
-                    .to_str()
-                    .map(|filename| filename.starts_with('_'))
-                    .unwrap_or(false)
+        let has_dimensions =
+            element.attrs.contains_key("width") || element.attrs.contains_key("height");
+        if !has_dimensions {
+            if let Some(dimensions) = image_dimensions(&path) {
+                element
+                    .attrs
+                    .insert("width".to_string(), dimensions.width.to_string());
+                element
+                    .attrs
+                    .insert("height".to_string(), dimensions.height.to_string());
             }
+        }
 
-            let sass_files = WalkDir::new(sass_path)
-                .into_iter()
-                .filter_entry(|entry| !is_partial(entry))
-                .filter_map(|entry| entry.ok())
-                .filter(is_sass)
-                .map(|entry| entry.into_path())
-                .collect::<Vec<_>>();
-
-            let options = grass::Options::default()
-                .style(grass::OutputStyle::Compressed)
-                .load_paths(&self.sass_load_paths);
-
-            for file in sass_files {
-                let css = grass::from_path(&file, &options).unwrap();
-                let path = file.strip_prefix(&sass_path).unwrap();
-
-                storage
-                    .store_static_file(&path.with_extension("css"), css)
-                    .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        for (name, value) in [("loading", "lazy"), ("decoding", "async")] {
+            if !element.attrs.contains_key(name) {
+                element.attrs.insert(name.to_string(), value.to_string());
             }
         }
 
-        self.copy_static_directory().unwrap();
-
         Ok(())
     }
+}
 
-    fn render_aliases(&self, storage: &impl Store) {
-        for section in self.sections.values() {
-            for alias in &section.meta.aliases {
-                self.render_alias(alias, &section.permalink, storage);
-            }
-        }
+/// A hook that is called once the site's content has been loaded.
+pub type OnContentLoaded = Arc<dyn Fn(&mut Pages, &mut Sections) + Send + Sync>;
 
-        for page in self.pages.values() {
-            for alias in &page.meta.aliases {
-                self.render_alias(alias, &page.permalink, storage);
-            }
-        }
-    }
+/// A hook that is called after the site has been rendered.
+pub type AfterRender = Arc<dyn Fn(&BuildReport) + Send + Sync>;
 
-    fn render_alias(&self, alias: &str, permalink: &Permalink, storage: &impl Store) {
-        use auk::*;
+/// A filter that selects the pages included in a custom feed.
+pub type FeedFilter = Arc<dyn Fn(&Page) -> bool + Send + Sync>;
 
-        let url = permalink.as_str();
-        let alias_template = vec![
-            meta().charset("utf-8").into(),
-            link().rel("canonical").href(url).into(),
-            meta()
-                .http_equiv("refresh")
-                .content(format!("0; url={url}"))
-                .into(),
-            title().child("Redirect").into(),
-            p().child(a().href(url).child("Click here"))
-                .child(" to be redirected.")
-                .into(),
-        ];
+/// A function that returns the entries of a custom feed.
+pub type FeedEntries = Arc<dyn Fn(&BaseRenderContext) -> Vec<FeedEntry> + Send + Sync>;
 
-        let mut html_renderer = HtmlElementRenderer::new();
-        html_renderer.visit_children(&alias_template).unwrap();
-        let mut alias_html = "<!DOCTYPE html>".to_string();
-        alias_html.push_str(html_renderer.html());
+/// The options for a custom feed.
+#[derive(Debug, Clone, Default)]
+pub struct FeedOptions {
+    title: Option<String>,
+    limit: Option<usize>,
+}
 
-        storage
-            .store_content(Permalink::from_path(&self.config, alias), alias_html)
-            .unwrap();
+impl FeedOptions {
+    /// Returns a new [`FeedOptions`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn render_404_page(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
-        let page_template = self.templates.not_found.clone().unwrap_or_else(|| {
-            Arc::new(|_ctx| {
-                use auk::*;
-
-                html()
-                    .child(head().child(title().child("404: Page Not Found")))
-                    .child(body().child(h1().child("404: Page Not Found")))
-            })
-        });
-
-        let ctx = BaseRenderContext {
-            base_url: self.base_url(),
-            content_path: &self.content_path,
-            markdown_components: &self.markdown_components,
-            shortcodes: &self.shortcodes,
-            sections: &self.sections,
-            pages: &self.pages,
-        };
+    /// Sets the title of the feed, which is appended to the site's title in the
+    /// same way as the titles of section feeds.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
 
-        let mut rendered_page = page_template(&ctx);
-        LiveReloadInjector::inject(self.live_reload_port, &mut rendered_page);
-        let rendered = HtmlElementRenderer::new().render_to_string(&rendered_page)?;
+    /// Sets the maximum number of entries in the feed.
+    ///
+    /// The most recent pages are kept.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
 
-        storage
-            .store_content(Permalink::from_path(&self.config, "404.html"), rendered)
-            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+/// A feed registered with [`SiteBuilder::add_feed`],
+/// [`SiteBuilder::add_feed_with_options`], or
+/// [`SiteBuilder::add_entries_feed`].
+#[derive(Clone)]
+struct CustomFeed {
+    /// The path to the feed, relative to the site root (e.g., `microblog.xml`).
+    path: String,
+    options: FeedOptions,
+    source: CustomFeedSource,
+}
 
-        Ok(())
-    }
+/// Where the entries of a [`CustomFeed`] come from.
+#[derive(Clone)]
+enum CustomFeedSource {
+    /// The site's pages that match the filter.
+    Pages(FeedFilter),
+    /// The entries returned by the function.
+    Entries(FeedEntries),
+}
 
-    fn render_robots_txt(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
-        let sitemap_url = Permalink::from_path(&self.config, "sitemap.xml");
+/// The hooks registered for the stages of the build pipeline.
+#[derive(Clone, Default)]
+struct Hooks {
+    on_content_loaded: Vec<OnContentLoaded>,
+    after_render: Vec<AfterRender>,
+}
 
-        let lines = vec![
-            "User-agent: *".to_string(),
-            "Disallow:".to_string(),
-            "Allow: /".to_string(),
-            format!("Sitemap: {}", sitemap_url.as_str()),
-            String::new(),
-        ];
+struct BuildSiteParams {
+    base_url: String,
+    title: Option<String>,
+    webmention_endpoint: Option<String>,
+    extra: toml::Table,
+    include_drafts: bool,
+    publish_future: Option<bool>,
+    strict: bool,
+    validate_html: bool,
+    accessibility_audit: Option<AccessibilityAudit>,
+    passthrough_content: bool,
+    static_files: StaticFiles,
+    implicit_sections: bool,
+    term_normalization: TermNormalization,
+    skip_empty_taxonomies: bool,
+    sitemap: bool,
+    robots_txt: bool,
+    feeds: bool,
+    not_found_page: bool,
+    custom_feeds: Vec<CustomFeed>,
+    feed_content: FeedContent,
+    broken_link_policy: BrokenLinkPolicy,
+    reading_speed: usize,
+    timezone: Tz,
+    date_format: String,
+    default_language: String,
+    versions: Option<Versions>,
+    menus: HashMap<String, Vec<MenuItem>>,
+    root_path: PathBuf,
+    sass_path: Option<PathBuf>,
+    sass_load_paths: Vec<PathBuf>,
+    templates: Templates,
+    markdown_components: Box<dyn MarkdownComponents>,
+    shortcodes: HashMap<String, Shortcode>,
+    taxonomies: Vec<Taxonomy>,
+    hooks: Hooks,
+    social_cards: Option<SocialCards>,
+    pdf_export: Option<PdfExport>,
+    analytics: Vec<Analytics>,
+    math: Option<Math>,
+    figures: Option<Figures>,
+    lazy_images: bool,
+    low_memory: bool,
+    content_rules: Vec<Arc<dyn ContentRule>>,
+    content_security_policy: Option<ContentSecurityPolicy>,
+    host_redirects: Option<HostRedirects>,
+    profiles: Vec<BuildProfile>,
+}
 
-        storage
-            .store_content(
-                Permalink::from_path(&self.config, "robots.txt"),
-                lines.join("\n"),
-            )
-            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+pub struct SiteConfig {
+    pub base_url: String,
+    pub title: Option<String>,
+    pub taxonomies: Vec<Taxonomy>,
+    /// The reading speed (in WPM) to use when determining reading time.
+    pub reading_speed: usize,
+    /// The timezone that dates are displayed in.
+    pub timezone: Tz,
+    /// The default format for dates.
+    pub date_format: String,
+}
 
-        Ok(())
+#[cfg(test)]
+impl SiteConfig {
+    /// Returns a [`SiteConfig`] for the given base URL, with the defaults for
+    /// everything else.
+    pub(crate) fn for_tests(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            title: None,
+            taxonomies: Vec::new(),
+            reading_speed: AVERAGE_ADULT_WPM,
+            timezone: Tz::UTC,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+        }
     }
+}
 
-    fn render_taxonomies(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
-        for (taxonomy, pages_by_term) in &self.taxonomies {
-            let taxonomy_template = self
-                .templates
-                .taxonomy
-                .get(taxonomy)
-                .expect("taxonomy template not found for {taxonomy:?}");
-
-            let mut terms = pages_by_term
-                .iter()
-                .map(|(term, pages)| TaxonomyTerm {
-                    name: term.clone(),
-                    permalink: Permalink::from_path(&self.config, &format!("/{taxonomy}/{term}")),
-                    pages: pages.clone(),
-                })
-                .collect::<Vec<_>>();
-
-            terms.sort_by(|a, b| a.name.cmp(&b.name));
+pub struct Site {
+    pub(crate) config: SiteConfig,
+    root_path: PathBuf,
+    pub(crate) content_path: PathBuf,
+    /// The path to the `static` directory that houses static assets.
+    static_path: PathBuf,
+    sass_path: Option<PathBuf>,
+    sass_load_paths: Vec<PathBuf>,
+    pub(crate) output_path: PathBuf,
+    /// The path to the directory where work is cached between builds (e.g.,
+    /// generated social card images).
+    cache_path: PathBuf,
+    templates: Templates,
+    markdown_components: Box<dyn MarkdownComponents>,
+    shortcodes: HashMap<String, Shortcode>,
+    markdown_cache: MarkdownCache,
+    pub(crate) sections: Sections,
+    pub(crate) pages: Pages,
+    pub(crate) taxonomies: HashMap<String, HashMap<String, Vec<PathBuf>>>,
+    /// The front matter for each taxonomy term, keyed by taxonomy and then by term.
+    taxonomy_term_meta: HashMap<String, HashMap<String, TaxonomyTermFrontMatter>>,
+    /// The pages in each series, in reading order.
+    series: HashMap<String, Vec<PathBuf>>,
+    stats: SiteStats,
+    /// The pages that have a date, from newest to oldest.
+    dated_pages: Vec<PathBuf>,
+    /// The endpoint that receives Webmentions for the site.
+    webmention_endpoint: Option<String>,
+    /// User-defined settings, made available to templates.
+    extra: toml::Table,
+    /// The language that content is written in, unless it specifies otherwise.
+    default_language: String,
+    /// The translations loaded from the `i18n` directory.
+    translations: Translations,
+    /// The versions of the site's documentation, if it is versioned.
+    versions: Option<Versions>,
+    /// The permalinks of the sections and pages in each version, keyed by
+    /// their path.
+    version_permalinks: HashMap<String, Permalink>,
+    /// The menus, keyed by name.
+    menus: HashMap<String, Vec<MenuItem>>,
+    social_cards: Option<SocialCards>,
+    pdf_export: Option<PdfExport>,
+    analytics: Vec<Analytics>,
+    math: Option<Math>,
+    figures: Option<Figures>,
+    lazy_images: bool,
+    /// The checks that are run against the content of each page.
+    pub(crate) content_rules: Vec<Arc<dyn ContentRule>>,
+    content_security_policy: Option<ContentSecurityPolicy>,
+    host_redirects: Option<HostRedirects>,
+    profiles: Vec<BuildProfile>,
+    /// The profile the site is being built with, if any.
+    profile: Option<BuildProfile>,
+    /// The IDs of the elements in each section and page, for checking links to
+    /// fragments.
+    anchors: HashMap<PathBuf, HashSet<String>>,
+    include_drafts: bool,
+    /// Whether pages dated in the future are published, overriding the default
+    /// for building or serving.
+    publish_future: Option<bool>,
+    /// Whether problems that would otherwise be warnings should fail the build.
+    strict: bool,
+    /// Whether rendered documents are checked for structural problems.
+    validate_html: bool,
+    accessibility_audit: Option<AccessibilityAudit>,
+    /// Whether files in the content directory that aren't content are copied
+    /// to the output.
+    passthrough_content: bool,
+    /// The files in the content directory that are copied to the output.
+    content_assets: Vec<ContentAsset>,
+    /// The files to copy from the static directory.
+    static_files: StaticFiles,
+    /// Whether the site is built using as little memory as possible, as set by
+    /// [`SiteBuilder::low_memory`] or [`BuildOptions::low_memory`].
+    low_memory: bool,
+    /// Whether the site is being built with [`BuildOptions::timings`].
+    collect_timings: bool,
+    /// Whether sections are synthesized for directories without an `_index.md`.
+    implicit_sections: bool,
+    pub(crate) term_normalization: TermNormalization,
+    /// Whether taxonomies without any terms are left out of the build.
+    skip_empty_taxonomies: bool,
+    /// Whether `sitemap.xml` is rendered.
+    sitemap: bool,
+    /// Whether `robots.txt` is rendered.
+    robots_txt: bool,
+    /// Whether the Atom feeds for the site, its sections, and its taxonomy
+    /// terms are rendered.
+    feeds: bool,
+    /// Whether `404.html` is rendered.
+    not_found_page: bool,
+    /// The feeds registered in addition to those for the site, its sections,
+    /// and its taxonomy terms.
+    custom_feeds: Vec<CustomFeed>,
+    /// How the content of feed entries is embedded.
+    pub(crate) feed_content: FeedContent,
+    /// What happens to `@/` links that don't point to any content.
+    broken_link_policy: BrokenLinkPolicy,
+    /// The broken `@/` links found during the current render.
+    broken_links: Mutex<BTreeSet<BrokenLink>>,
+    /// The co-located assets that are missing from the output, found during
+    /// the current render.
+    missing_assets: Mutex<BTreeSet<MissingAsset>>,
+    document_renderer: Mutex<DocumentRenderer>,
+    hooks: Hooks,
+    /// The usages of unregistered taxonomies found when the site was last loaded.
+    unknown_taxonomies: Vec<UnknownTaxonomy>,
+    /// The menu items linking to missing content found when the site was last
+    /// loaded.
+    unknown_menu_items: Vec<UnknownMenuItem>,
+    scheduled_pages: Vec<ScheduledPage>,
+    /// The report for the most recent render.
+    report: BuildReport,
+    /// The manifest of the files produced by the most recent render.
+    build_manifest: BuildManifest,
+    is_serving: bool,
+    /// Whether the site is being rendered for an export, in which case links
+    /// to the site are rewritten to relative file paths.
+    pub(crate) is_exporting: bool,
+    /// The rendered content of the site, when it is being served.
+    content: SiteContent,
+    /// The redirects for the site's aliases, when it is being served.
+    aliases: SiteAliases,
+    live_reload_port: Option<u16>,
+}
 
-            let ctx = RenderTaxonomyContext {
-                base: BaseRenderContext {
-                    base_url: self.base_url(),
-                    content_path: &self.content_path,
-                    markdown_components: &self.markdown_components,
-                    shortcodes: &self.shortcodes,
-                    sections: &self.sections,
-                    pages: &self.pages,
-                },
-                taxonomy: TaxonomyToRender {
-                    name: taxonomy.as_str(),
-                    terms: terms
-                        .iter()
-                        .map(|term| {
-                            let pages = term
-                                .pages
-                                .iter()
-                                .map(|page| self.pages.get(page).unwrap())
-                                .map(PageToRender::from_page)
-                                .collect::<Vec<_>>();
-
-                            TaxonomyTermToRender {
-                                name: term.name.as_str(),
-                                permalink: term.permalink.as_str(),
-                                pages,
-                            }
-                        })
-                        .collect(),
-                },
-            };
+impl Site {
+    pub fn builder() -> SiteBuilder<()> {
+        SiteBuilder::new()
+    }
 
-            let rendered_taxonomy_page = taxonomy_template(&ctx);
+    fn from_params(params: BuildSiteParams) -> Self {
+        let root_path = params.root_path;
 
-            storage
-                .store_content(
-                    Permalink::from_path(&self.config, &format!("/{taxonomy}")),
-                    HtmlElementRenderer::new().render_to_string(&rendered_taxonomy_page)?,
-                )
-                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        Site {
+            config: SiteConfig {
+                base_url: params.base_url,
+                title: params.title,
+                taxonomies: params.taxonomies,
+                reading_speed: params.reading_speed,
+                timezone: params.timezone,
+                date_format: params.date_format,
+            },
+            root_path: root_path.to_owned(),
+            content_path: root_path.join("content"),
+            static_path: root_path.join("static"),
+            sass_path: params.sass_path.map(|sass_path| root_path.join(sass_path)),
+            sass_load_paths: params
+                .sass_load_paths
+                .into_iter()
+                .map(|load_path| root_path.join(load_path))
+                .collect(),
+            output_path: root_path.join("public"),
+            cache_path: root_path.join(".cache"),
+            templates: params.templates,
+            markdown_components: params.markdown_components,
+            shortcodes: params.shortcodes,
+            markdown_cache: MarkdownCache::default(),
+            sections: Sections::default(),
+            pages: Pages::default(),
+            taxonomies: HashMap::new(),
+            taxonomy_term_meta: HashMap::new(),
+            series: HashMap::new(),
+            stats: SiteStats::default(),
+            dated_pages: Vec::new(),
+            webmention_endpoint: params.webmention_endpoint,
+            extra: params.extra,
+            default_language: params.default_language,
+            translations: Translations::default(),
+            versions: params.versions,
+            version_permalinks: HashMap::new(),
+            menus: params.menus,
+            social_cards: params.social_cards,
+            pdf_export: params.pdf_export,
+            analytics: params.analytics,
+            math: params.math,
+            figures: params.figures,
+            lazy_images: params.lazy_images,
+            content_rules: params.content_rules,
+            content_security_policy: params.content_security_policy,
+            host_redirects: params.host_redirects,
+            profiles: params.profiles,
+            profile: None,
+            anchors: HashMap::new(),
+            include_drafts: params.include_drafts,
+            publish_future: params.publish_future,
+            strict: params.strict,
+            validate_html: params.validate_html,
+            accessibility_audit: params.accessibility_audit,
+            passthrough_content: params.passthrough_content,
+            content_assets: Vec::new(),
+            static_files: params.static_files,
+            low_memory: params.low_memory,
+            collect_timings: false,
+            implicit_sections: params.implicit_sections,
+            term_normalization: params.term_normalization,
+            skip_empty_taxonomies: params.skip_empty_taxonomies,
+            sitemap: params.sitemap,
+            robots_txt: params.robots_txt,
+            feeds: params.feeds,
+            not_found_page: params.not_found_page,
+            custom_feeds: params.custom_feeds,
+            feed_content: params.feed_content,
+            broken_link_policy: params.broken_link_policy,
+            broken_links: Mutex::default(),
+            missing_assets: Mutex::default(),
+            document_renderer: Mutex::new(DocumentRenderer::new()),
+            hooks: params.hooks,
+            unknown_taxonomies: Vec::new(),
+            unknown_menu_items: Vec::new(),
+            scheduled_pages: Vec::new(),
+            report: BuildReport::default(),
+            build_manifest: BuildManifest::default(),
+            is_serving: false,
+            is_exporting: false,
+            content: SiteContent::default(),
+            aliases: SiteAliases::default(),
+            live_reload_port: None,
+        }
+    }
 
-            for (term, pages) in pages_by_term {
-                let term_template = self
-                    .templates
-                    .taxonomy_term
-                    .get(taxonomy)
-                    .expect("taxonomy term template not found for {taxonomy:?}");
+    pub fn base_url(&self) -> &str {
+        &self.config.base_url
+    }
 
-                let permalink = Permalink::from_path(&self.config, &format!("/{taxonomy}/{term}"));
-                let pages = pages
-                    .iter()
-                    .map(|page| self.pages.get(page).unwrap())
-                    .collect::<Vec<_>>();
-                let pages_to_render = pages
-                    .iter()
-                    .copied()
-                    .map(PageToRender::from_page)
-                    .collect::<Vec<_>>();
+    pub fn set_output_path(&mut self, output_path: impl AsRef<Path>) {
+        self.output_path = output_path.as_ref().to_owned();
+    }
 
-                let ctx = RenderTaxonomyTermContext {
-                    base: BaseRenderContext {
-                        base_url: self.base_url(),
-                        content_path: &self.content_path,
-                        markdown_components: &self.markdown_components,
-                        shortcodes: &self.shortcodes,
-                        sections: &self.sections,
-                        pages: &self.pages,
-                    },
-                    term: TaxonomyTermToRender {
-                        name: term.as_str(),
-                        permalink: permalink.as_str(),
-                        pages: pages_to_render,
-                    },
-                };
+    /// Returns the report for the most recent render of the site.
+    pub fn report(&self) -> &BuildReport {
+        &self.report
+    }
 
-                let rendered_term_page = term_template(&ctx);
+    /// Returns the shortcodes registered for the site, ordered by name.
+    pub fn shortcodes(&self) -> Vec<ShortcodeDescriptor> {
+        let mut shortcodes = self
+            .shortcodes
+            .iter()
+            .map(|(name, shortcode)| shortcode.describe(name))
+            .collect::<Vec<_>>();
+        shortcodes.sort_by(|a, b| a.name.cmp(&b.name));
+        shortcodes
+    }
 
-                storage
-                    .store_content(
-                        Permalink::from_path(&self.config, &format!("/{taxonomy}/{term}")),
-                        HtmlElementRenderer::new().render_to_string(&rendered_term_page)?,
-                    )
-                    .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+    /// Returns the manifest of the files produced by the most recent render of the site.
+    pub fn build_manifest(&self) -> &BuildManifest {
+        &self.build_manifest
+    }
 
-                render_feed(
-                    &self,
-                    Permalink::from_path(&self.config, &format!("{taxonomy}/{term}/atom.xml")),
-                    Some(term),
-                    pages,
-                    storage,
-                );
-            }
-        }
+    /// Renders the given Markdown text with the site's Markdown settings.
+    pub(crate) fn render_markdown(&self, text: &str) -> Result<Vec<Element>, ShortcodeError> {
+        let options = MarkdownOptions {
+            math: self.math.is_some(),
+            figures: self.figures.as_ref(),
+        };
+        let (elements, _table_of_contents) =
+            markdown_with_options(text, &self.markdown_components, &self.shortcodes, options)?;
 
-        Ok(())
+        Ok(elements)
     }
 
-    fn copy_static_directory(&self) -> Result<()> {
-        let source = self.static_path.clone();
-        if !source.exists() {
-            return Ok(());
+    pub(crate) fn base_render_context(&self) -> BaseRenderContext<'_> {
+        BaseRenderContext {
+            base_url: self.base_url(),
+            site_title: self.config.title.as_deref(),
+            content_path: &self.content_path,
+            markdown_components: &self.markdown_components,
+            shortcodes: &self.shortcodes,
+            markdown_options: MarkdownOptions {
+                math: self.math.is_some(),
+                figures: self.figures.as_ref(),
+            },
+            sections: &self.sections,
+            pages: &self.pages,
+            stats: &self.stats,
+            dated_pages: &self.dated_pages,
+            webmention_endpoint: self.webmention_endpoint.as_deref(),
+            extra: &self.extra,
+            taxonomies: &self.config.taxonomies,
+            term_normalization: self.term_normalization,
+            profile: self.profile.as_ref(),
+            timezone: self.config.timezone,
+            date_format: &self.config.date_format,
+            default_language: &self.default_language,
+            versions: self.versions.as_ref(),
+            version_permalinks: &self.version_permalinks,
+            menus: &self.menus,
+            feeds: self.feeds,
+            translations: &self.translations,
         }
+    }
 
-        let walker = WalkDir::new(&source).follow_links(true).into_iter();
-
-        for entry in walker {
-            let entry = entry?;
+    /// Returns the display names of the authors of the given page.
+    pub(crate) fn author_names<'a>(&'a self, page: &'a Page) -> Vec<&'a str> {
+        let profiles = self.taxonomy_term_meta.get(AUTHORS_TAXONOMY);
+
+        page.meta
+            .authors
+            .iter()
+            .map(|author| {
+                profiles
+                    .and_then(|profiles| profiles.get(author))
+                    .and_then(|profile| profile.title.as_deref())
+                    .unwrap_or(author)
+            })
+            .collect()
+    }
 
-            let relative_path = entry.path().strip_prefix(&source).unwrap();
-            let destination_path = self.output_path.join(relative_path);
+    /// Returns the series that the given page belongs to, if any.
+    fn series_to_render(&self, page: &Page) -> Option<SeriesToRender<'_>> {
+        let name = page.meta.series.as_ref()?;
+        let (name, paths) = self.series.get_key_value(name)?;
+
+        let position = paths.iter().position(|path| path == &page.file.path)?;
+        let pages = paths
+            .iter()
+            .filter_map(|path| self.pages.get(path))
+            .map(PageToRender::from_page)
+            .collect();
+
+        Some(SeriesToRender {
+            name,
+            pages,
+            position,
+        })
+    }
 
-            if entry.path().is_dir() {
-                if !destination_path.exists() {
-                    fs::create_dir_all(&destination_path)?;
-                }
-            } else {
-                fs::copy(entry.path(), &destination_path)?;
-            }
-        }
+    /// Returns the paths and templates of all of the generated pages,
+    /// including the home page.
+    fn generated_pages(&self) -> impl Iterator<Item = (&str, &RenderGeneratedPage)> {
+        self.templates
+            .home
+            .iter()
+            .map(|template| ("/", template))
+            .chain(
+                self.templates
+                    .generated
+                    .iter()
+                    .map(|(path, template)| (path.as_str(), template)),
+            )
+    }
 
-        Ok(())
+    /// Returns the permalinks of the 404 and error pages.
+    pub(crate) fn error_page_permalinks(&self) -> impl Iterator<Item = Permalink> + '_ {
+        std::iter::once(Permalink::from_path(&self.config, "404.html")).chain(
+            self.templates
+                .error
+                .keys()
+                .map(|status| Permalink::from_path(&self.config, &error_page_path(*status))),
+        )
     }
 
-    pub fn build(mut self) -> Result<()> {
-        self.load()?;
-        self.render()?;
+    pub fn load(&mut self) -> Result<(), LoadSiteError> {
+        self.translations = Translations::from_dir(self.root_path.join("i18n"))?;
 
-        Ok(())
-    }
+        let walker = WalkDir::new(&self.content_path)
+            .follow_links(true)
+            .into_iter();
 
-    pub async fn serve(mut self) -> Result<(), ServeSiteError> {
-        let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+        let mut content_files = Vec::new();
+        let mut asset_paths = Vec::new();
 
-        self.config.base_url = format!("http://{}", addr.to_string());
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path();
 
-        let listener = TcpListener::bind(addr).await?;
+            let Some(filename) = entry.file_name().to_str() else {
+                continue;
+            };
+
+            if !path.is_dir() {
+                if self.passthrough_content && is_passthrough_file(filename) {
+                    asset_paths.push(path.to_owned());
+                    continue;
+                }
 
-        /// [v4.0.2](https://github.com/livereload/livereload-js/blob/v4.0.2/dist/livereload.min.js)
-        const LIVE_RELOAD_JS: &'static str = include_str!("../assets/livereload.min.js");
+                if !filename.ends_with(".md")
+                    || filename.starts_with(".")
+                    || filename == "_index.md"
+                {
+                    continue;
+                }
 
-        let live_reload_server = WebSocket::new(|output: Sender| {
-            move |message: Message| {
-                if message.into_text().unwrap().contains("\"hello\"") {
-                    let handshake_response = json!({
-                        "command": "hello",
-                        "protocols": ["http://livereload.com/protocols/official-7"],
-                        "serverName": "Razorbill"
+                // Files directly within a taxonomy's directory hold the metadata
+                // for a term (e.g., `content/authors/jane-doe.md`).
+                if let Some(taxonomy) = self.taxonomy_for_term_path(path) {
+                    content_files.push(ContentFile::TermFile {
+                        taxonomy,
+                        term: filename.trim_end_matches(".md").to_owned(),
+                        path: path.to_owned(),
                     });
 
-                    return output.send(Message::text(
-                        serde_json::to_string(&handshake_response).unwrap(),
-                    ));
+                    continue;
                 }
 
-                Ok(())
-            }
-        })
-        .unwrap();
+                content_files.push(ContentFile::Page(path.to_owned()));
+            } else {
+                // Directories for taxonomy terms hold the term's metadata rather
+                // than a section.
+                if let Some(taxonomy) = self.taxonomy_for_term_path(path) {
+                    content_files.push(ContentFile::TermDirectory {
+                        taxonomy,
+                        term: filename.to_owned(),
+                        path: path.to_owned(),
+                    });
 
-        let live_reload_broadcaster = live_reload_server.broadcaster();
-        let live_reload_port = 35729;
-        let live_reload_address = SocketAddr::from(([127, 0, 0, 1], live_reload_port));
-        self.live_reload_port = Some(live_reload_port);
+                    continue;
+                }
 
-        let live_reload_server = live_reload_server
-            .bind(&live_reload_address)
-            .expect("failed to bind live reload server");
+                content_files.push(ContentFile::Section(path.to_owned()));
+            }
+        }
 
-        thread::spawn(move || {
-            live_reload_server.run().unwrap();
+        // Reading and parsing the content files is independent for each file,
+        // so we spread it across threads. Everything after this point stays
+        // single-threaded, and the results come back in the order the files
+        // were walked in, so the site is loaded the same way every time.
+        let config = &self.config;
+        let content_path = &self.content_path;
+        let loaded_files = par_map(&content_files, |content_file| {
+            content_file.load(config, content_path)
         });
 
-        fn empty() -> BoxBody<Bytes, hyper::Error> {
-            Empty::<Bytes>::new()
-                .map_err(|never| match never {})
-                .boxed()
-        }
-
-        fn full<T: Into<Bytes>>(chunk: T) -> BoxBody<Bytes, hyper::Error> {
-            Full::new(chunk.into())
-                .map_err(|never| match never {})
-                .boxed()
-        }
-
-        async fn handle_request(
-            req: Request<hyper::body::Incoming>,
-            static_path: Arc<Path>,
-        ) -> Result<Response<BoxBody<Bytes, hyper::Error>>, Infallible> {
-            match (req.method(), req.uri().path()) {
-                (&Method::GET, path) => {
-                    if path == "/livereload.js" {
-                        return Ok(Response::builder()
-                            .header(header::CONTENT_TYPE, "text/javascript")
-                            .status(StatusCode::OK)
-                            .body(full(LIVE_RELOAD_JS.to_owned()))
-                            .unwrap());
+        let mut pages = Vec::new();
+        let mut sections = Vec::new();
+        let mut term_meta = HashMap::new();
+        let mut errors: Vec<LoadSiteError> = Vec::new();
+
+        for loaded_file in loaded_files {
+            match loaded_file {
+                Ok(LoadedContent::Page(page)) => pages.push(page),
+                Ok(LoadedContent::Section(section)) => sections.extend(section),
+                Ok(LoadedContent::TermMeta {
+                    taxonomy,
+                    term,
+                    meta,
+                }) => {
+                    if let Some(meta) = meta {
+                        term_meta.insert((taxonomy, term), meta);
                     }
+                }
+                Err(err) => errors.push(err),
+            }
+        }
 
-                    let extension = path.rsplit_once('.').map(|(_, extension)| extension);
+        // We keep going after a content file fails to parse so that all of the
+        // problems can be reported at once.
+        if errors.len() == 1 {
+            return Err(errors.remove(0));
+        } else if !errors.is_empty() {
+            return Err(LoadSiteError::Multiple(errors));
+        }
 
-                    if let Some(content) = SITE_CONTENT.read().unwrap().get(path) {
-                        let content_type = match extension {
-                            Some("css") => "text/css",
-                            Some("xml") => "application/xml",
-                            _ => "text/html",
-                        };
+        let mut aggregator =
+            ContentAggregator::new(self.content_path.clone(), self.config.taxonomies.clone())
+                .term_normalization(self.term_normalization);
 
-                        return Ok(Response::builder()
-                            .header(header::CONTENT_TYPE, content_type)
-                            .status(StatusCode::OK)
-                            .body(full(content.to_owned()))
-                            .unwrap());
-                    }
+        for section in sections {
+            if section.meta.draft && !self.include_drafts {
+                continue;
+            }
 
-                    // Check if the user forgot to add a trailing `/`.
-                    if !path.ends_with('/') && extension.is_none() {
-                        let path = format!("{path}/");
-                        if SITE_CONTENT.read().unwrap().get(&path).is_some() {
-                            return Ok(Response::builder()
-                                .header(header::LOCATION, path)
-                                .status(StatusCode::PERMANENT_REDIRECT)
-                                .body(empty())
-                                .unwrap());
-                        }
-                    }
+            aggregator.add_section(section);
+        }
 
-                    let static_file_path = static_path.join(&path[1..]);
-                    if let Ok(contents) = tokio::fs::read(&static_file_path).await {
-                        return Ok(Response::builder()
-                            .status(StatusCode::OK)
-                            .header(
-                                header::CONTENT_TYPE,
-                                MimeGuess::from_path(static_file_path)
-                                    .first_or_octet_stream()
-                                    .essence_str(),
-                            )
-                            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
-                            .body(full(contents))
-                            .unwrap());
-                    }
+        // Pages dated in the future are only published when serving, so that
+        // they can be previewed, unless the site says otherwise.
+        let publish_future = self.publish_future.unwrap_or(self.is_serving);
+        let now = Utc::now();
+        let mut scheduled_pages = Vec::new();
 
-                    let mut not_found = Response::new(empty());
-                    *not_found.status_mut() = StatusCode::NOT_FOUND;
-                    Ok(not_found)
-                }
-                _ => {
-                    let mut not_found = Response::new(empty());
-                    *not_found.status_mut() = StatusCode::NOT_FOUND;
-                    Ok(not_found)
+        for page in pages {
+            if page.meta.draft && !self.include_drafts {
+                continue;
+            }
+
+            if let Some(scheduled_page) = scheduled_page(&page, self.config.timezone, now) {
+                scheduled_pages.push(scheduled_page);
+
+                if !publish_future {
+                    continue;
                 }
             }
+
+            aggregator.add_page(page);
         }
 
-        let static_path: Arc<Path> = self.static_path.clone().into();
-        let site = Arc::new(RwLock::new(self));
+        scheduled_pages.sort_by(|a, b| {
+            a.publish_at
+                .cmp(&b.publish_at)
+                .then_with(|| a.page.cmp(&b.page))
+        });
+        self.scheduled_pages = scheduled_pages;
 
-        {
-            let mut site = site.write().unwrap();
-            site.is_serving = true;
-            site.load().unwrap();
-            site.render().unwrap();
+        if self.implicit_sections {
+            aggregator.add_implicit_sections(&self.config);
         }
 
-        let (watcher_tx, mut watcher_rx) = unbounded_channel();
+        // The hooks run before the content is aggregated, so that any changes
+        // they make are reflected in the taxonomies and the sections' pages.
+        let (pages, sections) = aggregator.content_mut();
+        for hook in &self.hooks.on_content_loaded {
+            hook(pages, sections);
+        }
 
-        let mut watcher = RecommendedWatcher::new(
-            move |result: Result<Event, notify::Error>| {
-                let event = result.unwrap();
+        let (sections, pages, taxonomies) = aggregator.aggregate();
 
-                watcher_tx.send(event).unwrap();
-            },
-            notify::Config::default(),
-        )
-        .unwrap();
+        // Terms for unregistered taxonomies aren't indexed, so they're
+        // included in the build report to let the user know that they won't
+        // show up anywhere.
+        let unknown_taxonomies = find_unknown_taxonomies(&pages, &self.config.taxonomies);
+        if self.strict {
+            if let Some(unknown) = unknown_taxonomies.first() {
+                return Err(LoadSiteError::UnknownTaxonomy {
+                    taxonomy: unknown.taxonomy.clone(),
+                    filepath: unknown.page.clone(),
+                });
+            }
+        }
+        self.unknown_taxonomies = unknown_taxonomies;
 
-        watcher
-            .watch(&site.read().unwrap().content_path, RecursiveMode::Recursive)
-            .unwrap();
+        self.sections = sections;
+        self.pages = pages;
+        self.taxonomies = taxonomies;
+
+        self.taxonomy_term_meta = HashMap::new();
+        for (taxonomy, pages_by_term) in &self.taxonomies {
+            let meta_by_term = self.taxonomy_term_meta.entry(taxonomy.clone()).or_default();
 
-        if let Some(sass_path) = site.read().unwrap().sass_path.as_ref() {
-            watcher.watch(sass_path, RecursiveMode::Recursive).unwrap();
+            for term in pages_by_term.keys() {
+                let meta = term_meta
+                    .remove(&(taxonomy.clone(), term.clone()))
+                    .or_else(|| term_meta.remove(&(taxonomy.clone(), slug::slugify(term))))
+                    .unwrap_or_default();
+
+                meta_by_term.insert(term.clone(), meta);
+            }
+        }
+
+        self.series = group_series(&self.pages);
+        self.stats = SiteStats::compute(&self.pages, &self.sections, &self.taxonomies);
+        self.dated_pages = dated_pages(&self.pages);
+        self.version_permalinks = self.version_permalinks();
+
+        self.content_assets = Vec::new();
+        for asset_path in asset_paths {
+            let Some((asset, page_path)) = resolve_content_asset(
+                &self.config,
+                &self.content_path,
+                &self.sections,
+                &self.pages,
+                &asset_path,
+            ) else {
+                continue;
+            };
+
+            if let Some(page) = page_path.and_then(|page_path| self.pages.get_mut(&page_path)) {
+                page.assets.push(asset.clone());
+            }
+
+            self.content_assets.push(asset);
         }
 
-        tokio::task::spawn(async move {
-            use notify::EventKind;
+        let mut unknown_menu_items = Vec::new();
+        for (menu, items) in &self.menus {
+            for item in items {
+                if item
+                    .resolve(&self.content_path, &self.sections, &self.pages)
+                    .is_some()
+                {
+                    continue;
+                }
 
-            loop {
-                let Some(event) = watcher_rx.recv().await else {
+                let Some(path) = item.content_path(&self.content_path) else {
                     continue;
                 };
 
-                match event.kind {
-                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
-                        dbg!(&event.paths);
-
-                        let mut site = site.write().unwrap();
-                        site.load().unwrap();
-                        site.render().unwrap();
-
-                        let reload_message = json!({
-                            "command": "reload",
-                            "path": "/",
-                            "originalPath": "",
-                            "liveCSS": true,
-                            "liveImg": true,
-                            "protocol": ["http://livereload.com/protocols/official-7"]
-                        });
-
-                        live_reload_broadcaster
-                            .send(serde_json::to_string(&reload_message).unwrap())
-                            .unwrap();
-                    }
-                    _ => {}
+                if self.strict {
+                    return Err(LoadSiteError::UnknownMenuItem {
+                        menu: menu.clone(),
+                        path,
+                    });
                 }
+
+                unknown_menu_items.push(UnknownMenuItem {
+                    menu: menu.clone(),
+                    path: self.relative_content_path(&path),
+                });
             }
-        });
+        }
+        unknown_menu_items.sort_by(|a, b| (&a.menu, &a.path).cmp(&(&b.menu, &b.path)));
+        self.unknown_menu_items = unknown_menu_items;
 
-        loop {
-            let (stream, _) = listener.accept().await?;
+        Ok(())
+    }
 
-            let io = TokioIo::new(stream);
+    fn version_permalinks(&self) -> HashMap<String, Permalink> {
+        let Some(versions) = &self.versions else {
+            return HashMap::new();
+        };
 
-            tokio::task::spawn({
-                let static_path = static_path.clone();
-                async move {
-                    if let Err(err) = http1::Builder::new()
-                        .serve_connection(
-                            io,
-                            service_fn(move |req| handle_request(req, static_path.clone())),
-                        )
-                        .await
-                    {
-                        eprintln!("Error serving connection: {err:?}");
-                    }
+        let sections = self
+            .sections
+            .values()
+            .map(|section| (&section.path.0, &section.permalink));
+        let pages = self
+            .pages
+            .values()
+            .map(|page| (&page.path.0, &page.permalink));
+
+        sections
+            .chain(pages)
+            .filter(|(path, _)| versions.split_path(path).is_some())
+            .map(|(path, permalink)| (path.clone(), permalink.clone()))
+            .collect()
+    }
+
+    /// Returns the name of the taxonomy if the given path is the directory or
+    /// file for one of its terms (e.g., `content/tags/rust` or
+    /// `content/authors/jane-doe.md`).
+    fn taxonomy_for_term_path(&self, path: &Path) -> Option<String> {
+        let parent = path.parent()?;
+
+        self.config
+            .taxonomies
+            .iter()
+            .find(|taxonomy| parent == self.content_path.join(&taxonomy.name))
+            .map(|taxonomy| taxonomy.name.clone())
+    }
+
+    pub fn render(&mut self) -> Result<(), RenderSiteError> {
+        if self.is_serving {
+            self.render_to(InMemoryStorage::new(self.content.clone()))
+        } else {
+            self.render_to(DiskStorage::new(self.output_path.clone()))
+        }
+    }
+
+    /// Renders the site into memory, without writing anything to disk.
+    ///
+    /// This is only intended for use by the benchmarks.
+    #[cfg(feature = "bench")]
+    #[doc(hidden)]
+    pub fn render_in_memory(&mut self) -> Result<(), RenderSiteError> {
+        self.render_to(InMemoryStorage::new(SiteContent::default()))
+    }
+
+    pub(crate) fn render_to(&mut self, storage: impl Store) -> Result<(), RenderSiteError> {
+        let started_at = Instant::now();
+
+        // We check for missing templates up front, so that we don't write out
+        // half of a site that can't be rendered.
+        let missing_templates = self.missing_taxonomy_templates();
+        if !missing_templates.is_empty() {
+            return Err(RenderSiteError::TaxonomyTemplatesNotFound(
+                missing_templates,
+            ));
+        }
+
+        self.broken_links
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+        self.missing_assets
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+
+        let storage = RecordingStorage::new(storage);
+
+        // The policy would get in the way of live reloading, so it is only
+        // applied to builds.
+        let storage = CspStorage::new(
+            storage,
+            self.content_security_policy
+                .clone()
+                .filter(|_| !self.is_serving),
+        );
+
+        self.render_aliases(&storage);
+
+        let markdown_options = MarkdownOptions {
+            math: self.math.is_some(),
+            figures: self.figures.as_ref(),
+        };
+
+        // When collecting timings, the shortcodes are wrapped so that the time
+        // spent in them can be told apart from the rest of the Markdown.
+        let shortcode_elapsed = Arc::new(AtomicU64::new(0));
+        let timed_shortcodes = self.collect_timings.then(|| {
+            self.shortcodes
+                .iter()
+                .map(|(name, shortcode)| (name.clone(), shortcode.timed(shortcode_elapsed.clone())))
+                .collect::<HashMap<_, _>>()
+        });
+
+        let is_uncached = self.low_memory || self.collect_timings;
+        let markdown_cache = &mut self.markdown_cache;
+        let markdown_components = &self.markdown_components;
+        let shortcodes = timed_shortcodes.as_ref().unwrap_or(&self.shortcodes);
+        let mut render_markdown = |text: &str| {
+            if is_uncached {
+                render_uncached(text, markdown_components, shortcodes, markdown_options)
+            } else {
+                markdown_cache.render(text, markdown_components, shortcodes, markdown_options)
+            }
+        };
+
+        for section in self.sections.values_mut() {
+            let (content, table_of_contents, headings) = render_markdown(&section.raw_content)
+                .map_err(|err| RenderSiteError::Shortcode {
+                    filepath: section.file.path.clone(),
+                    err,
+                })?;
+
+            section.content = content;
+            section.table_of_contents = table_of_contents;
+            section.headings = headings;
+        }
+
+        let mut page_timings = HashMap::new();
+
+        for page in self.pages.values_mut() {
+            shortcode_elapsed.store(0, Ordering::Relaxed);
+            let started_at = Instant::now();
+
+            let (content, table_of_contents, headings) = render_markdown(&page.raw_content)
+                .map_err(|err| RenderSiteError::Shortcode {
+                    filepath: page.file.path.clone(),
+                    err,
+                })?;
+
+            if self.collect_timings {
+                let shortcodes = Duration::from_nanos(shortcode_elapsed.load(Ordering::Relaxed));
+
+                page_timings.insert(
+                    page.file.path.clone(),
+                    PageTiming {
+                        markdown: started_at.elapsed().saturating_sub(shortcodes),
+                        shortcodes,
+                        ..PageTiming::default()
+                    },
+                );
+            }
+
+            page.content = content;
+            page.table_of_contents = table_of_contents;
+            page.headings = headings;
+        }
+
+        self.markdown_cache.evict_unused();
+
+        let diagnostics = self.check_content_rules();
+
+        if self.strict {
+            let error = diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.severity == Severity::Error);
+            if let Some(error) = error {
+                return Err(RenderSiteError::ContentRule(error.clone()));
+            }
+        }
+
+        if self.low_memory {
+            // The raw content isn't needed again once it has been rendered.
+            for section in self.sections.values_mut() {
+                section.raw_content = String::new();
+            }
+
+            for page in self.pages.values_mut() {
+                page.raw_content = String::new();
+            }
+        }
+
+        // Collect the anchors for all of the content up front so that links
+        // to fragments in other pages can be checked against their tables of
+        // contents.
+        self.anchors = self
+            .sections
+            .iter()
+            .map(|(path, section)| {
+                let anchors = anchor_ids(&section.headings, &section.content);
+                (path.clone(), anchors)
+            })
+            .chain(self.pages.iter().map(|(path, page)| {
+                let anchors = anchor_ids(&page.headings, &page.content);
+                (path.clone(), anchors)
+            }))
+            .collect();
+
+        // Take the content out of the sections and pages while we replace the
+        // links in it, as the `LinkReplacer` needs to borrow the whole site.
+        let mut section_contents = self
+            .sections
+            .iter_mut()
+            .map(|(path, section)| (path.clone(), mem::take(&mut section.content)))
+            .collect::<Vec<_>>();
+
+        for (section_path, content) in section_contents.iter_mut() {
+            let section = &self.sections[section_path.as_path()];
+
+            let content = Arc::make_mut(content);
+
+            if self.lazy_images {
+                ImageAttributeInjector::new(&self.static_path, section.bundle_path())
+                    .visit_children(content)
+                    .unwrap();
+            }
+
+            let mut link_replacer = LinkReplacer::new(&self, &section.permalink)
+                .with_bundle_path(section.bundle_path());
+            link_replacer.visit_children(content)?;
+        }
+
+        for (section_path, content) in section_contents {
+            self.sections.get_mut(&section_path).unwrap().content = content;
+        }
+
+        let mut page_contents = self
+            .pages
+            .iter_mut()
+            .map(|(path, page)| (path.clone(), mem::take(&mut page.content)))
+            .collect::<Vec<_>>();
+
+        for (page_path, content) in page_contents.iter_mut() {
+            let page = &self.pages[page_path.as_path()];
+
+            let content = Arc::make_mut(content);
+
+            if self.lazy_images {
+                ImageAttributeInjector::new(&self.static_path, page.bundle_path())
+                    .visit_children(content)
+                    .unwrap();
+            }
+
+            let mut link_replacer =
+                LinkReplacer::new(&self, &page.permalink).with_bundle_path(page.bundle_path());
+            link_replacer.visit_children(content)?;
+        }
+
+        for (page_path, content) in page_contents {
+            self.pages.get_mut(&page_path).unwrap().content = content;
+        }
+
+        let mut document_diagnostics = DocumentDiagnostics::default();
+
+        for section in self.sections.values() {
+            let is_index = section.path == SectionPath("/_index".to_string());
+
+            // When there is a standalone home page it takes the place of the
+            // root section.
+            if is_index && self.templates.home.is_some() {
+                continue;
+            }
+
+            let (section_template, assets) = if is_index {
+                (&self.templates.index, None)
+            } else {
+                let template_name = section
+                    .meta
+                    .template
+                    .clone()
+                    .map(TemplateKey::Custom)
+                    .unwrap_or(TemplateKey::Default);
+
+                let assets = self.templates.section_assets.get(&template_name);
+
+                let section_template = self
+                    .templates
+                    .section
+                    .get(&template_name)
+                    .ok_or_else(|| RenderSiteError::TemplateNotFound(template_name))?;
+
+                (section_template, assets)
+            };
+
+            let ctx = RenderSectionContext {
+                base: self.base_render_context(),
+                section: SectionToRender::from_section(section, &self.pages),
+            };
+
+            let mut rendered_section = section_template(&ctx);
+
+            self.finish_document(&mut rendered_section, &section.permalink, assets)?;
+
+            self.check_document(
+                &rendered_section,
+                &section.file.path,
+                &mut document_diagnostics,
+            );
+
+            let rendered = self.document_renderer().render(&rendered_section)?;
+
+            storage
+                .store_rendered_section(&section, rendered)
+                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        }
+
+        self.render_social_cards(&storage)?;
+
+        if self.pdf_export.is_some() {
+            for page in self.pages.values_mut() {
+                if page.meta.pdf {
+                    page.pdf_url = page.permalink.join(PDF_FILE_NAME);
                 }
-            });
+            }
+        }
+
+        for page in self.pages.values() {
+            let template_name = page
+                .meta
+                .template
+                .clone()
+                .map(TemplateKey::Custom)
+                .unwrap_or(TemplateKey::Default);
+
+            let assets = self.templates.page_assets.get(&template_name);
+
+            let page_template = self
+                .templates
+                .page
+                .get(&template_name)
+                .ok_or_else(|| RenderSiteError::TemplateNotFound(template_name))?;
+
+            let ctx = RenderPageContext {
+                base: self.base_render_context(),
+                page: PageToRender::from_page(page),
+                ancestors: page
+                    .ancestors
+                    .iter()
+                    .filter_map(|path| self.sections.get(path))
+                    .map(|section| SectionToRender::from_section(section, &self.pages))
+                    .collect(),
+                parent_section: page
+                    .ancestors
+                    .last()
+                    .and_then(|path| self.sections.get(path))
+                    .map(|section| SectionToRender::from_section(section, &self.pages)),
+                series: self.series_to_render(page),
+            };
+
+            let template_started_at = Instant::now();
+            let mut rendered_page = page_template(&ctx);
+            let template_duration = template_started_at.elapsed();
+
+            self.finish_document(&mut rendered_page, &page.permalink, assets)?;
+
+            self.check_document(&rendered_page, &page.file.path, &mut document_diagnostics);
+
+            let serialization_started_at = Instant::now();
+            let rendered = self.document_renderer().render(&rendered_page)?;
+
+            if let Some(timing) = page_timings.get_mut(&page.file.path) {
+                timing.template = template_duration;
+                timing.serialization = serialization_started_at.elapsed();
+            }
+
+            if page.meta.pdf {
+                self.render_pdf(page, &rendered, &storage)?;
+            }
+
+            storage
+                .store_rendered_page(&page, rendered)
+                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        }
+
+        self.render_generated_pages(&storage, &mut document_diagnostics)?;
+
+        if self.feeds {
+            self.render_feeds(&storage);
+        }
+
+        if self.not_found_page {
+            self.render_404_page(&storage, &mut document_diagnostics)?;
+        }
+
+        self.render_error_pages(&storage, &mut document_diagnostics)?;
+
+        if self.robots_txt {
+            self.render_robots_txt(&storage)?;
+        }
+
+        self.render_host_redirects(&storage)?;
+        self.render_taxonomies(&storage, &mut document_diagnostics)?;
+
+        // The sitemap is rendered once all of the pages have been, so that it
+        // covers everything that was rendered.
+        if self.sitemap {
+            render_sitemap(&self, storage.inner().html_permalinks(), &storage);
+        }
+
+        if let Some(sass_path) = self.sass_path.as_ref() {
+            fn is_sass(entry: &walkdir::DirEntry) -> bool {
+                entry
+                    .path()
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(|extension| extension == "sass" || extension == "scss")
+                    .unwrap_or(false)
+            }
+
+            fn is_partial(entry: &walkdir::DirEntry) -> bool {
+                entry
+                    .file_name()
+                    .to_str()
+                    .map(|filename| filename.starts_with('_'))
+                    .unwrap_or(false)
+            }
+
+            let sass_files = WalkDir::new(sass_path)
+                .into_iter()
+                .filter_entry(|entry| !is_partial(entry))
+                .filter_map(|entry| entry.ok())
+                .filter(is_sass)
+                .map(|entry| entry.into_path())
+                .collect::<Vec<_>>();
+
+            let options = grass::Options::default()
+                .style(grass::OutputStyle::Compressed)
+                .load_paths(&self.sass_load_paths);
+
+            for file in sass_files {
+                let css = grass::from_path(&file, &options).unwrap();
+                let path = file.strip_prefix(&sass_path).unwrap();
+
+                storage
+                    .store_static_file(&path.with_extension("css"), css)
+                    .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+            }
+        }
+
+        self.render_permalink_manifest(&storage)?;
+        self.render_shortcode_manifest(&storage)?;
+
+        let page_timings = self.sorted_page_timings(page_timings);
+
+        let DocumentDiagnostics {
+            html: html_diagnostics,
+            accessibility: accessibility_diagnostics,
+        } = document_diagnostics;
+
+        if self.strict {
+            let is_error = |diagnostic: &&Diagnostic| diagnostic.severity == Severity::Error;
+
+            if let Some(error) = html_diagnostics.iter().find(is_error) {
+                return Err(RenderSiteError::InvalidHtml(error.clone()));
+            }
+
+            if let Some(error) = accessibility_diagnostics.iter().find(is_error) {
+                return Err(RenderSiteError::Accessibility(error.clone()));
+            }
+        }
+
+        let mut diagnostics = diagnostics;
+        diagnostics.extend(html_diagnostics);
+        diagnostics.extend(accessibility_diagnostics);
+        diagnostics.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+
+        self.render_content_assets(&storage)?;
+
+        let storage = storage
+            .finish()
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        let static_files = self.copy_static_directory(&storage)?;
+        self.render_build_manifest(storage)?;
+
+        self.report = BuildReport {
+            section_count: self.sections.len(),
+            page_count: self.pages.len(),
+            render_duration: started_at.elapsed(),
+            unknown_taxonomies: self.unknown_taxonomies.clone(),
+            unknown_menu_items: self.unknown_menu_items.clone(),
+            scheduled_pages: self.scheduled_pages.clone(),
+            diagnostics,
+            page_timings,
+            static_files,
+            broken_links: mem::take(
+                self.broken_links
+                    .get_mut()
+                    .unwrap_or_else(|err| err.into_inner()),
+            )
+            .into_iter()
+            .collect(),
+            missing_assets: mem::take(
+                self.missing_assets
+                    .get_mut()
+                    .unwrap_or_else(|err| err.into_inner()),
+            )
+            .into_iter()
+            .collect(),
+        };
+
+        for hook in &self.hooks.after_render {
+            hook(&self.report);
+        }
+
+        Ok(())
+    }
+
+    /// Applies the finishing touches that every rendered document gets:
+    /// resolving its links and injecting the template's assets, analytics,
+    /// math support, and (when serving) the live reload and build error
+    /// overlay scripts.
+    fn finish_document(
+        &self,
+        document: &mut HtmlElement,
+        permalink: &Permalink,
+        assets: Option<&Vec<String>>,
+    ) -> Result<(), RenderSiteError> {
+        LinkReplacer::new(self, permalink).visit(document)?;
+
+        AssetInjector::inject(assets, document);
+        AnalyticsInjector::inject(&self.analytics, self.is_serving, document);
+        MathInjector::inject(self.math.as_ref(), document);
+        LiveReloadInjector::inject(self.live_reload_port, document);
+
+        // This runs last, so that the links added above are rewritten too.
+        if self.is_exporting {
+            RelativeLinkRewriter::new(permalink, self.base_url())
+                .visit(document)
+                .unwrap();
+        }
+
+        Ok(())
+    }
+
+    /// Returns the renderer for turning documents into HTML.
+    fn document_renderer(&self) -> MutexGuard<'_, DocumentRenderer> {
+        self.document_renderer
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Checks a rendered section or page for the problems found by the HTML
+    /// validation and accessibility audit, if they are enabled.
+    fn check_document(
+        &self,
+        document: &HtmlElement,
+        file_path: &Path,
+        diagnostics: &mut DocumentDiagnostics,
+    ) {
+        if self.validate_html {
+            diagnostics.html.extend(
+                validate_html(document)
+                    .into_iter()
+                    .map(|problem| problem.into_diagnostic(self.relative_content_path(file_path))),
+            );
+        }
+
+        if let Some(accessibility_audit) = &self.accessibility_audit {
+            diagnostics.accessibility.extend(
+                accessibility_audit
+                    .audit(document)
+                    .into_iter()
+                    .map(|problem| problem.into_diagnostic(self.relative_content_path(file_path))),
+            );
+        }
+    }
+
+    /// Checks a rendered document that doesn't come from a content file (e.g.,
+    /// a taxonomy page), reporting its problems against its permalink's path.
+    fn check_generated_document(
+        &self,
+        document: &HtmlElement,
+        permalink: &Permalink,
+        diagnostics: &mut DocumentDiagnostics,
+    ) {
+        let path = PathBuf::from(permalink.decoded_path().as_ref());
+        self.check_document(document, &path, diagnostics);
+    }
+
+    fn render_permalink_manifest(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
+        let manifest = self
+            .permalink_manifest()
+            .to_json()
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        storage
+            .store_content(
+                Permalink::from_path(&self.config, "permalinks.json"),
+                manifest,
+            )
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    fn render_shortcode_manifest(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
+        let manifest = ShortcodeManifest {
+            shortcodes: self.shortcodes(),
+        }
+        .to_json()
+        .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        storage
+            .store_content(
+                Permalink::from_path(&self.config, "shortcodes.json"),
+                manifest,
+            )
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Returns the given page timings from slowest to fastest, with the paths
+    /// made relative to the content directory.
+    fn sorted_page_timings(&self, page_timings: HashMap<PathBuf, PageTiming>) -> Vec<PageTiming> {
+        let mut page_timings = page_timings
+            .into_iter()
+            .map(|(path, timing)| PageTiming {
+                path: self.relative_content_path(&path),
+                ..timing
+            })
+            .collect::<Vec<_>>();
+        page_timings.sort_by(|a, b| b.total().cmp(&a.total()).then_with(|| a.path.cmp(&b.path)));
+        page_timings
+    }
+
+    /// Writes the page timings from the most recent render to `timings.json`
+    /// in the site's root directory.
+    ///
+    /// The timings are kept out of the output directory, so that they aren't
+    /// deployed along with the site.
+    fn write_timings(&self) -> Result<()> {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+        let pages = self
+            .report
+            .page_timings
+            .iter()
+            .map(|timing| {
+                serde_json::json!({
+                    "path": timing.path,
+                    "total_ms": millis(timing.total()),
+                    "markdown_ms": millis(timing.markdown),
+                    "shortcodes_ms": millis(timing.shortcodes),
+                    "template_ms": millis(timing.template),
+                    "serialization_ms": millis(timing.serialization),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let timings = serde_json::to_string_pretty(&serde_json::json!({ "pages": pages }))?;
+        fs::write(self.root_path.join("timings.json"), timings)?;
+
+        Ok(())
+    }
+
+    /// Writes out the manifest of all of the files produced by the render.
+    ///
+    /// This must happen last, so that every other file is included.
+    fn render_build_manifest<S: Store>(
+        &mut self,
+        storage: RecordingStorage<S>,
+    ) -> Result<(), RenderSiteError> {
+        let (storage, files) = storage.finish();
+
+        self.build_manifest = BuildManifest {
+            files: files
+                .into_iter()
+                .map(|mut entry| {
+                    if let Some(page) = entry
+                        .source
+                        .as_ref()
+                        .and_then(|source| self.pages.get(source))
+                    {
+                        entry.links = external_links(&page.content, self.base_url());
+                    }
+
+                    entry.source = entry
+                        .source
+                        .map(|source| self.relative_content_path(&source));
+                    entry
+                })
+                .collect(),
+        };
+
+        let manifest = self
+            .build_manifest
+            .to_json()
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        storage
+            .store_content(
+                Permalink::from_path(&self.config, "manifest.json"),
+                manifest,
+            )
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Renders the social card image for each page, if social cards are enabled.
+    ///
+    /// Rendered cards are cached, so a card is only rendered again when what
+    /// is drawn on it changes.
+    fn render_social_cards(&mut self, storage: &impl Store) -> Result<(), RenderSiteError> {
+        let Some(social_cards) = self.social_cards.as_ref() else {
+            return Ok(());
+        };
+
+        let cache_path = self.cache_path.join("social-cards");
+
+        for page in self.pages.values_mut() {
+            let card = SocialCard {
+                title: page.meta.title.as_deref().unwrap_or(&page.slug),
+                site_name: self.config.title.as_deref(),
+                date: page.meta.date.as_deref(),
+            };
+
+            let cached_image_path =
+                cache_path.join(format!("{}.png", social_cards.cache_key(&card)));
+            if !cached_image_path.exists() {
+                let image =
+                    social_cards
+                        .render(&card)
+                        .map_err(|err| RenderSiteError::SocialCard {
+                            permalink: page.permalink.as_str().to_owned(),
+                            message: err.to_string(),
+                        })?;
+
+                fs::create_dir_all(&cache_path)?;
+                fs::write(&cached_image_path, image)?;
+            }
+
+            let image_path = format!(
+                "{}{SOCIAL_CARD_FILE_NAME}",
+                page.permalink.path().trim_start_matches('/')
+            );
+
+            storage
+                .copy_file(&cached_image_path, Path::new(&image_path), CopyMode::Copy)
+                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+            page.social_image = page.permalink.join(SOCIAL_CARD_FILE_NAME);
+        }
+
+        Ok(())
+    }
+
+    /// Renders the given page to a PDF, if PDF export is enabled.
+    fn render_pdf(
+        &self,
+        page: &Page,
+        rendered: &str,
+        storage: &impl Store,
+    ) -> Result<(), RenderSiteError> {
+        let Some(pdf_export) = self.pdf_export.as_ref() else {
+            return Ok(());
+        };
+
+        let pdf = pdf_export
+            .render(rendered, page.permalink.as_str())
+            .map_err(|err| RenderSiteError::Pdf {
+                permalink: page.permalink.as_str().to_owned(),
+                message: err.to_string(),
+            })?;
+
+        let pdf_path = format!(
+            "{}{PDF_FILE_NAME}",
+            page.permalink.path().trim_start_matches('/')
+        );
+
+        storage
+            .store_binary_file(Path::new(&pdf_path), pdf)
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Returns the aliases of the site's content, along with the permalink that
+    /// each one redirects to.
+    pub(crate) fn alias_redirects(&self) -> Vec<(Permalink, &Permalink)> {
+        let mut redirects = Vec::new();
+
+        for section in self.sections.values() {
+            for alias in &section.meta.aliases {
+                redirects.push((
+                    Permalink::from_path(&self.config, alias),
+                    &section.permalink,
+                ));
+            }
+        }
+
+        for page in self.pages.values() {
+            for alias in &page.meta.aliases {
+                redirects.push((Permalink::from_path(&self.config, alias), &page.permalink));
+            }
+        }
+
+        redirects.extend(self.latest_version_aliases());
+        redirects
+    }
+
+    /// Returns the redirects from `/latest/` to the latest version.
+    fn latest_version_aliases(&self) -> Vec<(Permalink, &Permalink)> {
+        let Some(versions) = &self.versions else {
+            return Vec::new();
+        };
+        let Some(latest) = versions.latest_name() else {
+            return Vec::new();
+        };
+
+        let mut redirects = Vec::new();
+        for (path, permalink) in &self.version_permalinks {
+            if let Some((version, subpath)) = versions.split_path(path) {
+                if version == latest {
+                    let alias = format!("/{LATEST_PATH}{subpath}");
+                    redirects.push((Permalink::from_path(&self.config, &alias), permalink));
+                }
+            }
+        }
+
+        redirects
+    }
+
+    fn render_aliases(&self, storage: &impl Store) {
+        let redirects = self.alias_redirects();
+
+        // The dev server answers requests for aliases with real redirects, so
+        // we don't need to render pages for them.
+        if self.is_serving {
+            *self.aliases.write().unwrap() = redirects
+                .into_iter()
+                .map(|(alias, permalink)| {
+                    (
+                        alias.decoded_path().into_owned(),
+                        permalink.as_str().to_owned(),
+                    )
+                })
+                .collect();
+
+            return;
+        }
+
+        for (alias, permalink) in redirects {
+            self.render_alias(alias, permalink, storage);
+        }
+    }
+
+    fn render_alias(&self, alias: Permalink, permalink: &Permalink, storage: &impl Store) {
+        use auk::*;
+
+        const DOCTYPE: &str = "<!DOCTYPE html>";
+
+        let url = permalink.as_str();
+        let mut alias_template = vec![
+            meta().charset("utf-8").into(),
+            link().rel("canonical").href(url).into(),
+            meta()
+                .http_equiv("refresh")
+                .content(format!("0; url={url}"))
+                .into(),
+            title().child("Redirect").into(),
+            p().child(a().href(url).child("Click here"))
+                .child(" to be redirected.")
+                .into(),
+        ];
+
+        if self.is_exporting {
+            RelativeLinkRewriter::new(&alias, self.base_url())
+                .visit_children(&mut alias_template)
+                .unwrap();
+        }
+
+        let alias_html = self
+            .document_renderer()
+            .render_children(DOCTYPE, &alias_template)
+            .unwrap();
+
+        storage.store_content(alias, alias_html).unwrap();
+    }
+
+    fn render_generated_pages(
+        &self,
+        storage: &impl Store,
+        diagnostics: &mut DocumentDiagnostics,
+    ) -> Result<(), RenderSiteError> {
+        for (path, template) in self.generated_pages() {
+            let permalink = Permalink::from_path(&self.config, path);
+
+            let ctx = RenderGeneratedPageContext {
+                base: self.base_render_context(),
+                path,
+                permalink: permalink.as_str(),
+            };
+
+            let mut rendered_page = template(&ctx);
+            self.finish_document(&mut rendered_page, &permalink, None)?;
+            self.check_generated_document(&rendered_page, &permalink, diagnostics);
+
+            let rendered = self.document_renderer().render(&rendered_page)?;
+
+            storage
+                .store_content(permalink, rendered)
+                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the Atom feeds for the site, for the sections that have one, and
+    /// the custom feeds.
+    fn render_feeds(&self, storage: &impl Store) {
+        render_feed(
+            &self,
+            Permalink::from_path(&self.config, "atom.xml"),
+            None,
+            self.pages.values().collect(),
+            None,
+            storage,
+        );
+
+        for section in self.sections.values() {
+            if !section.meta.generate_feed {
+                continue;
+            }
+
+            let Some(feed_permalink) = section.permalink.join("atom.xml") else {
+                continue;
+            };
+
+            render_feed(
+                &self,
+                feed_permalink,
+                Some(
+                    section
+                        .meta
+                        .title
+                        .as_deref()
+                        .unwrap_or(section.path.0.as_str()),
+                ),
+                // The feed has the same pages as the section's listing,
+                // including those in the sections nested beneath it.
+                section
+                    .all_pages
+                    .iter()
+                    .filter_map(|path| self.pages.get(path))
+                    .collect(),
+                None,
+                storage,
+            );
+        }
+
+        for feed in &self.custom_feeds {
+            let permalink = Permalink::from_path(&self.config, &feed.path);
+            let title = feed.options.title.as_deref();
+
+            match &feed.source {
+                CustomFeedSource::Pages(filter) => render_feed(
+                    &self,
+                    permalink,
+                    title,
+                    self.pages.values().filter(|page| filter(page)).collect(),
+                    feed.options.limit,
+                    storage,
+                ),
+                CustomFeedSource::Entries(entries) => render_entries_feed(
+                    &self,
+                    permalink,
+                    title,
+                    entries(&self.base_render_context()),
+                    feed.options.limit,
+                    storage,
+                ),
+            }
+        }
+    }
+
+    fn render_404_page(
+        &self,
+        storage: &impl Store,
+        diagnostics: &mut DocumentDiagnostics,
+    ) -> Result<(), RenderSiteError> {
+        // A 404 page registered with `add_error_template` is rendered along
+        // with the other error pages.
+        if self.templates.not_found.is_none() && self.templates.error.contains_key(&404) {
+            return Ok(());
+        }
+
+        let page_template = self.templates.not_found.clone().unwrap_or_else(|| {
+            Arc::new(|_ctx| {
+                use auk::*;
+
+                html()
+                    .child(head().child(title().child("404: Page Not Found")))
+                    .child(body().child(h1().child("404: Page Not Found")))
+            })
+        });
+
+        let ctx = self.base_render_context();
+        let permalink = Permalink::from_path(&self.config, "404.html");
+
+        let mut rendered_page = page_template(&ctx);
+        self.finish_document(&mut rendered_page, &permalink, None)?;
+        self.check_generated_document(&rendered_page, &permalink, diagnostics);
+        let rendered = self.document_renderer().render(&rendered_page)?;
+
+        storage
+            .store_content(permalink, rendered)
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    fn render_error_pages(
+        &self,
+        storage: &impl Store,
+        diagnostics: &mut DocumentDiagnostics,
+    ) -> Result<(), RenderSiteError> {
+        for (status, template) in &self.templates.error {
+            self.render_error_page(*status, template, None, storage, diagnostics)?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the page shown by the dev server while the site fails to build,
+    /// using the template for `500` errors, if there is one.
+    fn render_build_error_page(&self, error: &str) -> Result<(), RenderSiteError> {
+        let template = self.templates.error.get(&500).cloned().unwrap_or_else(|| {
+            Arc::new(|ctx| {
+                use auk::*;
+
+                let error = ctx
+                    .error
+                    .unwrap_or_default()
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;");
+
+                html()
+                    .child(head().child(title().child("500: Build Failed")))
+                    .child(
+                        body()
+                            .child(h1().child("500: Build Failed"))
+                            .child(pre().child(error)),
+                    )
+            })
+        });
+
+        let storage = InMemoryStorage::new(self.content.clone());
+
+        // The build failed, so there is no build report for the error page's
+        // own diagnostics to go in.
+        self.render_error_page(
+            500,
+            &template,
+            Some(error),
+            &storage,
+            &mut DocumentDiagnostics::default(),
+        )
+    }
+
+    fn render_error_page(
+        &self,
+        status: u16,
+        template: &RenderErrorPage,
+        error: Option<&str>,
+        storage: &impl Store,
+        diagnostics: &mut DocumentDiagnostics,
+    ) -> Result<(), RenderSiteError> {
+        let ctx = RenderErrorPageContext {
+            base: self.base_render_context(),
+            status,
+            error,
+        };
+
+        let permalink = Permalink::from_path(&self.config, &error_page_path(status));
+
+        let mut rendered_page = template(&ctx);
+        self.finish_document(&mut rendered_page, &permalink, None)?;
+        self.check_generated_document(&rendered_page, &permalink, diagnostics);
+        let rendered = self.document_renderer().render(&rendered_page)?;
+
+        storage
+            .store_content(permalink, rendered)
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    fn render_content_assets(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
+        for asset in &self.content_assets {
+            let contents = fs::read(&asset.file_path)?;
+            let output_path = asset.permalink.decoded_path();
+
+            storage
+                .store_binary_file(Path::new(output_path.trim_start_matches('/')), contents)
+                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    fn render_robots_txt(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
+        let mut lines = vec![
+            "User-agent: *".to_string(),
+            "Disallow:".to_string(),
+            "Allow: /".to_string(),
+        ];
+
+        // Only point crawlers at the sitemap if there is one.
+        if self.sitemap {
+            let sitemap_url = Permalink::from_path(&self.config, "sitemap.xml");
+            lines.push(format!("Sitemap: {}", sitemap_url.as_str()));
+        }
+
+        lines.push(String::new());
+
+        storage
+            .store_content(
+                Permalink::from_path(&self.config, "robots.txt"),
+                lines.join("\n"),
+            )
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    fn render_host_redirects(&self, storage: &impl Store) -> Result<(), RenderSiteError> {
+        let Some(host_redirects) = self.host_redirects.as_ref() else {
+            return Ok(());
+        };
+
+        if self.is_serving {
+            return Ok(());
+        }
+
+        let aliases = self.alias_redirects();
+        for (path, contents) in host_redirects.files(&self.config.base_url, &aliases) {
+            storage
+                .store_static_file(Path::new(path), contents)
+                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the taxonomy with the given name has been registered
+    /// with `add_taxonomy`, rather than only being declared in a config file.
+    fn is_registered_taxonomy(&self, name: &str) -> bool {
+        self.templates.taxonomy.contains_key(name)
+            || self.templates.taxonomy_term.contains_key(name)
+    }
+
+    /// Returns the registered taxonomies that are missing a template they need
+    /// to be rendered, along with the template that is missing.
+    ///
+    /// Taxonomies declared only in a config file are collected, but aren't
+    /// rendered until they are added with `add_taxonomy`, so they don't need
+    /// any templates.
+    fn missing_taxonomy_templates(&self) -> Vec<String> {
+        let mut missing_templates = self
+            .config
+            .taxonomies
+            .iter()
+            .filter(|taxonomy| self.is_registered_taxonomy(&taxonomy.name))
+            .flat_map(|taxonomy| {
+                let name = &taxonomy.name;
+
+                let list_template = (taxonomy.render_list
+                    && !self.templates.taxonomy.contains_key(name))
+                .then(|| format!("'{name}' (list template)"));
+                let term_template = (taxonomy.render_terms
+                    && !self.templates.taxonomy_term.contains_key(name))
+                .then(|| format!("'{name}' (term template)"));
+
+                list_template.into_iter().chain(term_template)
+            })
+            .collect::<Vec<_>>();
+
+        missing_templates.sort();
+        missing_templates
+    }
+
+    fn render_taxonomies(
+        &self,
+        storage: &impl Store,
+        diagnostics: &mut DocumentDiagnostics,
+    ) -> Result<(), RenderSiteError> {
+        for (taxonomy, pages_by_term) in &self.taxonomies {
+            // Taxonomies declared only in a config file don't have templates,
+            // so they aren't rendered. The templates for registered taxonomies
+            // were checked before rendering began.
+            let (Some(taxonomy_template), Some(term_template)) = (
+                self.templates.taxonomy.get(taxonomy),
+                self.templates.taxonomy_term.get(taxonomy),
+            ) else {
+                continue;
+            };
+
+            if pages_by_term.is_empty() && self.skip_empty_taxonomies {
+                continue;
+            }
+
+            let Some(settings) = self
+                .config
+                .taxonomies
+                .iter()
+                .find(|candidate| candidate.name == *taxonomy)
+            else {
+                continue;
+            };
+            let base_url = self.config.base_url.as_str();
+
+            if settings.render_list {
+                self.render_taxonomy_list(
+                    settings,
+                    taxonomy_template,
+                    pages_by_term,
+                    storage,
+                    diagnostics,
+                )?;
+            }
+
+            if !settings.render_terms {
+                continue;
+            }
+
+            for (term, pages) in pages_by_term {
+                let permalink = settings.term_permalink(base_url, term);
+                let meta = &self.taxonomy_term_meta[taxonomy][term];
+                let pages = pages
+                    .iter()
+                    .map(|page| self.pages.get(page).unwrap())
+                    .collect::<Vec<_>>();
+                let pages_to_render = pages
+                    .iter()
+                    .copied()
+                    .map(PageToRender::from_page)
+                    .collect::<Vec<_>>();
+
+                let ctx = RenderTaxonomyTermContext {
+                    base: self.base_render_context(),
+                    term: TaxonomyTermToRender {
+                        name: term.as_str(),
+                        title: &meta.title,
+                        permalink: permalink.as_str(),
+                        description: &meta.description,
+                        extra: &meta.extra,
+                        page_count: pages_to_render.len(),
+                        pages: pages_to_render,
+                    },
+                };
+
+                let mut rendered_term_page = term_template(&ctx);
+                self.finish_document(&mut rendered_term_page, &permalink, None)?;
+                self.check_generated_document(&rendered_term_page, &permalink, diagnostics);
+
+                storage
+                    .store_content(
+                        permalink.clone(),
+                        self.document_renderer().render(&rendered_term_page)?,
+                    )
+                    .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+                if !self.feeds {
+                    continue;
+                }
+
+                render_feed(
+                    &self,
+                    permalink.join("atom.xml").unwrap(),
+                    Some(meta.title.as_deref().unwrap_or(term)),
+                    pages,
+                    None,
+                    storage,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renders the page that lists all of the terms in the given taxonomy.
+    fn render_taxonomy_list(
+        &self,
+        taxonomy: &Taxonomy,
+        taxonomy_template: &RenderTaxonomy,
+        pages_by_term: &HashMap<String, Vec<PathBuf>>,
+        storage: &impl Store,
+        diagnostics: &mut DocumentDiagnostics,
+    ) -> Result<(), RenderSiteError> {
+        let base_url = self.config.base_url.as_str();
+
+        let mut terms = pages_by_term
+            .iter()
+            .map(|(term, pages)| TaxonomyTerm {
+                name: term.clone(),
+                permalink: taxonomy.term_permalink(base_url, term),
+                pages: pages.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        terms.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let permalink = taxonomy.permalink(base_url);
+        let ctx = RenderTaxonomyContext {
+            base: self.base_render_context(),
+            taxonomy: TaxonomyToRender {
+                name: taxonomy.name.as_str(),
+                permalink: permalink.as_str(),
+                terms: terms
+                    .iter()
+                    .map(|term| {
+                        let meta = &self.taxonomy_term_meta[&taxonomy.name][&term.name];
+                        let pages = term
+                            .pages
+                            .iter()
+                            .map(|page| self.pages.get(page).unwrap())
+                            .map(PageToRender::from_page)
+                            .collect::<Vec<_>>();
+
+                        TaxonomyTermToRender {
+                            name: term.name.as_str(),
+                            title: &meta.title,
+                            permalink: term.permalink.as_str(),
+                            description: &meta.description,
+                            extra: &meta.extra,
+                            page_count: pages.len(),
+                            pages,
+                        }
+                    })
+                    .collect(),
+            },
+        };
+
+        let mut rendered_taxonomy_page = taxonomy_template(&ctx);
+        self.finish_document(&mut rendered_taxonomy_page, &permalink, None)?;
+        self.check_generated_document(&rendered_taxonomy_page, &permalink, diagnostics);
+
+        storage
+            .store_content(
+                permalink.clone(),
+                self.document_renderer().render(&rendered_taxonomy_page)?,
+            )
+            .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+        Ok(())
+    }
+
+    fn copy_static_directory<S: Store>(
+        &self,
+        storage: &RecordingStorage<S>,
+    ) -> Result<StaticFilesReport, RenderSiteError> {
+        let mut report = StaticFilesReport::default();
+
+        let source = self.static_path.clone();
+        if !source.exists() {
+            return Ok(report);
+        }
+
+        let walker = WalkDir::new(&source).follow_links(true).into_iter();
+
+        for entry in walker {
+            let entry = entry.map_err(io::Error::from)?;
+            if entry.path().is_dir() {
+                continue;
+            }
+
+            let relative_path = entry.path().strip_prefix(&source).unwrap();
+            if !self.static_files.is_included(relative_path) {
+                report.excluded += 1;
+                continue;
+            }
+
+            let copied = storage
+                .copy_file(entry.path(), relative_path, self.static_files.copy_mode)
+                .map_err(|err| RenderSiteError::Storage(err.to_string()))?;
+
+            if copied {
+                report.copied += 1;
+            } else {
+                report.unchanged += 1;
+            }
+        }
+
+        Ok(report)
+    }
+
+    pub fn build(mut self) -> Result<()> {
+        self.load()?;
+        self.render()?;
+
+        Ok(())
+    }
+
+    pub fn build_with_options(mut self, options: BuildOptions) -> Result<()> {
+        if let Some(profile) = options.profile.as_deref() {
+            self.apply_profile(profile)?;
+        }
+
+        self.low_memory |= options.low_memory;
+        self.collect_timings = options.timings;
+
+        self.load()?;
+        self.render()?;
+
+        if self.collect_timings {
+            self.write_timings()?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the overrides from the [`BuildProfile`] with the given name.
+    ///
+    /// This must be called before the site is loaded.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("unknown build profile: {name:?}"))?;
+
+        if let Some(base_url) = profile.base_url.as_ref() {
+            self.config.base_url = base_url.clone();
+        }
+
+        if let Some(include_drafts) = profile.include_drafts {
+            self.include_drafts = include_drafts;
+        }
+
+        self.profile = Some(profile);
+
+        Ok(())
+    }
+
+    /// Reloads and re-renders the site, recording the outcome in the build status.
+    fn rebuild(&mut self, state: &ServerState) {
+        state.start_build();
+        let started_at = Instant::now();
+
+        let result = self
+            .load()
+            .map_err(|err| err.to_string())
+            .and_then(|()| self.render().map_err(|err| err.to_string()));
+
+        if let Err(err) = &result {
+            eprintln!("Failed to build site: {err}");
+
+            // Show the error in place of the stale content until the next
+            // successful build.
+            if let Err(err) = self.render_build_error_page(err) {
+                eprintln!("Failed to render build error page: {err}");
+            }
+        }
+
+        state.finish_build(started_at.elapsed(), result);
+    }
+
+    pub async fn serve(self) -> Result<(), ServeSiteError> {
+        self.serve_with_options(ServeOptions::default()).await
+    }
+
+    pub async fn serve_with_options(mut self, options: ServeOptions) -> Result<(), ServeSiteError> {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+
+        self.config.base_url = format!("http://{}", addr.to_string());
+
+        let listener = TcpListener::bind(addr).await?;
+
+        // Live reload clients connect to the same server, via a WebSocket upgrade.
+        self.live_reload_port = Some(addr.port());
+
+        let state = Arc::new(ServerState {
+            static_path: self.static_path.clone(),
+            options,
+            build_status: RwLock::new(BuildStatus::default()),
+            live_reload: LiveReloadBroadcaster::new(),
+            content: self.content.clone(),
+            aliases: self.aliases.clone(),
+            build_overlay: BuildStatusBroadcaster::new(),
+        });
+
+        let site = Arc::new(RwLock::new(self));
+
+        {
+            let mut site = site.write().unwrap();
+            site.is_serving = true;
+            site.rebuild(&state);
+        }
+
+        let url = format!("http://{}/", listener.local_addr()?);
+        println!(
+            "{}",
+            startup_banner(&url, &state.build_status.read().unwrap())
+        );
+
+        // The browser is opened once the site has built successfully, which
+        // may not be until after the first build.
+        let mut open_url = state.options.open.then_some(url);
+        let mut open_browser_if_built = move |state: &ServerState| {
+            if state.build_status.read().unwrap().has_errors() {
+                return;
+            }
+
+            if let Some(url) = open_url.take() {
+                if let Err(err) = open_browser(&url) {
+                    eprintln!("Failed to open {url} in the browser: {err}");
+                }
+            }
+        };
+        open_browser_if_built(&state);
+
+        let (watcher_tx, mut watcher_rx) = unbounded_channel();
+
+        let mut watcher = RecommendedWatcher::new(
+            move |result: Result<Event, notify::Error>| {
+                let event = result.unwrap();
+
+                watcher_tx.send(event).unwrap();
+            },
+            notify::Config::default(),
+        )
+        .unwrap();
+
+        let mut watched_directories = {
+            let site = site.read().unwrap();
+
+            WatchedDirectories::new(
+                site.root_path.clone(),
+                [
+                    Some(site.content_path.clone()),
+                    Some(site.static_path.clone()),
+                    site.sass_path.clone(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+            )
+        };
+
+        watched_directories.update(&mut watcher).unwrap();
+
+        tokio::task::spawn({
+            let state = state.clone();
+            async move {
+                while let Some(changed_paths) = next_changed_paths(&mut watcher_rx).await {
+                    // Directories may have been created (or removed) since we
+                    // last checked.
+                    if let Err(err) = watched_directories.update(&mut watcher) {
+                        eprintln!("Failed to watch for changes: {err}");
+                    }
+
+                    let changed_paths = changed_paths
+                        .into_iter()
+                        .filter(|path| watched_directories.is_relevant(path))
+                        .collect::<Vec<_>>();
+                    if changed_paths.is_empty() {
+                        continue;
+                    }
+
+                    let previous_content = content_etags(&state.content);
+                    let had_errors = state.build_status.read().unwrap().has_errors();
+
+                    let mut site = site.write().unwrap();
+                    site.rebuild(&state);
+                    open_browser_if_built(&state);
+
+                    let has_errors = state.build_status.read().unwrap().has_errors();
+                    let reload_paths = live_reload_paths(site.sass_path.as_deref(), &changed_paths);
+
+                    // Stylesheets are swapped in place, and the build error page
+                    // stands in for every page, so those go to every client.
+                    // Otherwise, only the clients viewing a page that changed
+                    // need to reload.
+                    if reload_paths != [FULL_RELOAD_PATH] || had_errors || has_errors {
+                        for path in reload_paths {
+                            state.live_reload.reload(&path);
+                        }
+                    } else {
+                        state.live_reload.reload_pages(changed_content(
+                            &previous_content,
+                            &content_etags(&state.content),
+                        ));
+                    }
+                }
+            }
+        });
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+
+            let io = TokioIo::new(stream);
+
+            tokio::task::spawn({
+                let state = state.clone();
+                async move {
+                    if let Err(err) = http1::Builder::new()
+                        .serve_connection(
+                            io,
+                            service_fn(move |req| handle_request(req, state.clone())),
+                        )
+                        .with_upgrades()
+                        .await
+                    {
+                        eprintln!("Error serving connection: {err:?}");
+                    }
+                }
+            });
+        }
+    }
+}
+
+pub struct SiteBuilder<State> {
+    state: PhantomData<State>,
+    root_path: PathBuf,
+    base_url: String,
+    title: Option<String>,
+    webmention_endpoint: Option<String>,
+    extra: toml::Table,
+    include_drafts: bool,
+    publish_future: Option<bool>,
+    strict: bool,
+    validate_html: bool,
+    accessibility_audit: Option<AccessibilityAudit>,
+    passthrough_content: bool,
+    static_files: StaticFiles,
+    implicit_sections: bool,
+    term_normalization: TermNormalization,
+    skip_empty_taxonomies: bool,
+    sitemap: bool,
+    robots_txt: bool,
+    feeds: bool,
+    not_found_page: bool,
+    custom_feeds: Vec<CustomFeed>,
+    feed_content: FeedContent,
+    broken_link_policy: BrokenLinkPolicy,
+    reading_speed: usize,
+    timezone: Tz,
+    date_format: String,
+    default_language: String,
+    versions: Option<Versions>,
+    menus: HashMap<String, Vec<MenuItem>>,
+    templates: Templates,
+    markdown_components: Box<dyn MarkdownComponents>,
+    shortcodes: HashMap<String, Shortcode>,
+    taxonomies: Vec<Taxonomy>,
+    sass_path: Option<PathBuf>,
+    sass_load_paths: Vec<PathBuf>,
+    hooks: Hooks,
+    social_cards: Option<SocialCards>,
+    pdf_export: Option<PdfExport>,
+    analytics: Vec<Analytics>,
+    math: Option<Math>,
+    figures: Option<Figures>,
+    lazy_images: bool,
+    low_memory: bool,
+    content_rules: Vec<Arc<dyn ContentRule>>,
+    content_security_policy: Option<ContentSecurityPolicy>,
+    host_redirects: Option<HostRedirects>,
+    profiles: Vec<BuildProfile>,
+}
+
+impl<State> SiteBuilder<State> {
+    fn coerce<NewState>(self) -> SiteBuilder<NewState> {
+        SiteBuilder {
+            state: PhantomData,
+            root_path: self.root_path,
+            base_url: self.base_url,
+            title: self.title,
+            webmention_endpoint: self.webmention_endpoint,
+            extra: self.extra,
+            include_drafts: self.include_drafts,
+            publish_future: self.publish_future,
+            strict: self.strict,
+            validate_html: self.validate_html,
+            accessibility_audit: self.accessibility_audit,
+            passthrough_content: self.passthrough_content,
+            static_files: self.static_files,
+            implicit_sections: self.implicit_sections,
+            term_normalization: self.term_normalization,
+            skip_empty_taxonomies: self.skip_empty_taxonomies,
+            sitemap: self.sitemap,
+            robots_txt: self.robots_txt,
+            feeds: self.feeds,
+            not_found_page: self.not_found_page,
+            custom_feeds: self.custom_feeds,
+            feed_content: self.feed_content,
+            broken_link_policy: self.broken_link_policy,
+            reading_speed: self.reading_speed,
+            timezone: self.timezone,
+            date_format: self.date_format,
+            default_language: self.default_language,
+            versions: self.versions,
+            menus: self.menus,
+            templates: self.templates,
+            markdown_components: self.markdown_components,
+            shortcodes: self.shortcodes,
+            taxonomies: self.taxonomies,
+            sass_path: self.sass_path,
+            sass_load_paths: self.sass_load_paths,
+            hooks: self.hooks,
+            social_cards: self.social_cards,
+            pdf_export: self.pdf_export,
+            analytics: self.analytics,
+            math: self.math,
+            figures: self.figures,
+            lazy_images: self.lazy_images,
+            low_memory: self.low_memory,
+            content_rules: self.content_rules,
+            content_security_policy: self.content_security_policy,
+            host_redirects: self.host_redirects,
+            profiles: self.profiles,
+        }
+    }
+
+    fn build_site(self) -> Site {
+        Site::from_params(BuildSiteParams {
+            base_url: self.base_url,
+            title: self.title,
+            webmention_endpoint: self.webmention_endpoint,
+            extra: self.extra,
+            include_drafts: self.include_drafts,
+            publish_future: self.publish_future,
+            strict: self.strict,
+            validate_html: self.validate_html,
+            accessibility_audit: self.accessibility_audit,
+            passthrough_content: self.passthrough_content,
+            static_files: self.static_files,
+            implicit_sections: self.implicit_sections,
+            term_normalization: self.term_normalization,
+            skip_empty_taxonomies: self.skip_empty_taxonomies,
+            sitemap: self.sitemap,
+            robots_txt: self.robots_txt,
+            feeds: self.feeds,
+            not_found_page: self.not_found_page,
+            custom_feeds: self.custom_feeds,
+            feed_content: self.feed_content,
+            broken_link_policy: self.broken_link_policy,
+            reading_speed: self.reading_speed,
+            timezone: self.timezone,
+            date_format: self.date_format,
+            default_language: self.default_language,
+            versions: self.versions,
+            menus: self.menus,
+            root_path: self.root_path,
+            sass_path: self.sass_path,
+            sass_load_paths: self.sass_load_paths,
+            templates: self.templates,
+            markdown_components: self.markdown_components,
+            shortcodes: self.shortcodes,
+            taxonomies: self.taxonomies,
+            hooks: self.hooks,
+            social_cards: self.social_cards,
+            pdf_export: self.pdf_export,
+            analytics: self.analytics,
+            math: self.math,
+            figures: self.figures,
+            lazy_images: self.lazy_images,
+            low_memory: self.low_memory,
+            content_rules: self.content_rules,
+            content_security_policy: self.content_security_policy,
+            host_redirects: self.host_redirects,
+            profiles: self.profiles,
+        })
+    }
+
+    pub fn include_drafts(mut self, include_drafts: bool) -> Self {
+        self.include_drafts = include_drafts;
+        self
+    }
+
+    /// Sets whether pages dated in the future are published.
+    ///
+    /// Unpublished pages are left out of the site entirely, including from
+    /// section listings, feeds, and the sitemap, until their date has passed.
+    /// They are listed in [`BuildReport::scheduled_pages`].
+    ///
+    /// Defaults to `false` when building the site and `true` when serving it.
+    pub fn publish_future(mut self, publish_future: bool) -> Self {
+        self.publish_future = Some(publish_future);
+        self
+    }
+
+    /// Sets whether the site should be built in strict mode.
+    ///
+    /// In strict mode, problems that would otherwise be reported as warnings
+    /// (such as links to missing anchors, or terms for unknown taxonomies) fail
+    /// the build.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets whether each rendered document should be checked for structural
+    /// problems, such as duplicate `id`s, `<li>`s outside of lists, images
+    /// without `alt` text, and empty `href`s.
+    ///
+    /// The problems are included in the [`BuildReport`]. In strict mode,
+    /// errors fail the build.
+    pub fn validate_html(mut self, validate_html: bool) -> Self {
+        self.validate_html = validate_html;
+        self
+    }
+
+    /// Enables the accessibility audit of each rendered document.
+    ///
+    /// The problems are included in the [`BuildReport`]. In strict mode,
+    /// rules configured as errors fail the build.
+    pub fn accessibility_audit(mut self, accessibility_audit: AccessibilityAudit) -> Self {
+        self.accessibility_audit = Some(accessibility_audit);
+        self
+    }
+
+    /// Sets whether files in the content directory that aren't Markdown (e.g.,
+    /// `posts/data.csv`) should be copied to the output.
+    ///
+    /// Files in a page's bundle (e.g., `blog/hello-world/cover.png` for
+    /// `blog/hello-world.md`) are placed beneath the page's URL, and are listed
+    /// in [`PageToRender::assets`]. Other files are placed beneath the URL of
+    /// the section containing them.
+    ///
+    /// Images in content that refer to files in the page's bundle (e.g.,
+    /// `![](cover.png)`) are only rewritten to point at the copied files when
+    /// this is enabled. Images whose files are missing, or aren't copied, are
+    /// listed in [`BuildReport::missing_assets`].
+    pub fn passthrough_content(mut self, passthrough_content: bool) -> Self {
+        self.passthrough_content = passthrough_content;
+        self
+    }
+
+    /// Sets which files are copied from the static directory.
+    ///
+    /// By default, everything except for the
+    /// [`DEFAULT_EXCLUDES`](crate::static_files::DEFAULT_EXCLUDES) is copied.
+    pub fn static_files(mut self, static_files: StaticFiles) -> Self {
+        self.static_files = static_files;
+        self
+    }
+
+    /// Sets whether sections should be synthesized for directories that
+    /// contain pages but don't have an `_index.md`.
+    ///
+    /// Implicit sections are titled after their directory, and inherit their
+    /// templates and sorting from the nearest ancestor section. Without them,
+    /// the pages in such directories aren't listed in any section.
+    pub fn implicit_sections(mut self, implicit_sections: bool) -> Self {
+        self.implicit_sections = implicit_sections;
+        self
+    }
+
+    /// Sets how taxonomy terms are normalized, so that differently-written
+    /// terms (e.g., "Rust" and "rust") can be merged into a single term.
+    pub fn term_normalization(mut self, term_normalization: TermNormalization) -> Self {
+        self.term_normalization = term_normalization;
+        self
+    }
+
+    /// Sets what happens to `@/` links that don't point to any content, or to
+    /// an anchor that doesn't exist.
+    ///
+    /// Broken links are included in the [`BuildReport`] regardless of the policy.
+    pub fn broken_link_policy(mut self, broken_link_policy: BrokenLinkPolicy) -> Self {
+        self.broken_link_policy = broken_link_policy;
+        self
+    }
+
+    /// Sets whether taxonomies without any terms should be skipped, rather
+    /// than rendering an empty listing page.
+    pub fn skip_empty_taxonomies(mut self, skip_empty_taxonomies: bool) -> Self {
+        self.skip_empty_taxonomies = skip_empty_taxonomies;
+        self
+    }
+
+    /// Sets whether `sitemap.xml` should be rendered.
+    ///
+    /// Turning off the auxiliary outputs (the sitemap, `robots.txt`, feeds,
+    /// and 404 page) is useful when the site is embedded within a larger one
+    /// that provides its own.
+    pub fn sitemap(mut self, sitemap: bool) -> Self {
+        self.sitemap = sitemap;
+        self
+    }
+
+    /// Sets whether `robots.txt` should be rendered.
+    ///
+    /// It only links to the sitemap when the sitemap is rendered.
+    pub fn robots_txt(mut self, robots_txt: bool) -> Self {
+        self.robots_txt = robots_txt;
+        self
+    }
+
+    /// Sets whether Atom feeds should be rendered, for the site as well as for
+    /// its sections and taxonomy terms.
+    ///
+    /// When feeds are turned off, the feed links in the render contexts are
+    /// omitted as well.
+    pub fn feeds(mut self, feeds: bool) -> Self {
+        self.feeds = feeds;
+        self
+    }
+
+    /// Sets how the content of feed entries is embedded.
+    ///
+    /// Defaults to [`FeedContent::EscapedHtml`].
+    pub fn feed_content(mut self, feed_content: FeedContent) -> Self {
+        self.feed_content = feed_content;
+        self
+    }
+
+    /// Sets whether `404.html` should be rendered.
+    pub fn not_found_page(mut self, not_found_page: bool) -> Self {
+        self.not_found_page = not_found_page;
+        self
+    }
+
+    /// Adds a feed at the given path (e.g., `microblog.xml`) containing the
+    /// pages for which `filter` returns `true`.
+    ///
+    /// ```
+    /// use razorbill::Site;
+    ///
+    /// let builder = Site::builder().add_feed("microblog.xml", |page| {
+    ///     page.meta.taxonomies.get("kind") == Some(&vec!["note".to_string()])
+    /// });
+    /// ```
+    pub fn add_feed(
+        self,
+        path: impl Into<String>,
+        filter: impl Fn(&Page) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.add_feed_with_options(path, FeedOptions::new(), filter)
+    }
+
+    /// Adds a feed at the given path containing the pages for which `filter`
+    /// returns `true`, using the given [`FeedOptions`].
+    pub fn add_feed_with_options(
+        self,
+        path: impl Into<String>,
+        options: FeedOptions,
+        filter: impl Fn(&Page) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.add_custom_feed(
+            path.into(),
+            options,
+            CustomFeedSource::Pages(Arc::new(filter)),
+        )
+    }
+
+    /// Adds a feed at the given path containing the entries returned by
+    /// `entries`, for data that isn't part of the site's content.
+    ///
+    /// ```
+    /// use razorbill::feed::FeedEntry;
+    /// use razorbill::{FeedOptions, Site};
+    ///
+    /// let builder = Site::builder().add_entries_feed(
+    ///     "changelog.xml",
+    ///     FeedOptions::new().title("Changelog"),
+    ///     |ctx| {
+    ///         vec![FeedEntry {
+    ///             title: "v1.0.0".to_string(),
+    ///             url: format!("{}/changelog/#v1.0.0", ctx.base_url()),
+    ///             published: "2024-01-31".to_string(),
+    ///             ..FeedEntry::default()
+    ///         }]
+    ///     },
+    /// );
+    /// ```
+    pub fn add_entries_feed(
+        self,
+        path: impl Into<String>,
+        options: FeedOptions,
+        entries: impl Fn(&BaseRenderContext) -> Vec<FeedEntry> + Send + Sync + 'static,
+    ) -> Self {
+        self.add_custom_feed(
+            path.into(),
+            options,
+            CustomFeedSource::Entries(Arc::new(entries)),
+        )
+    }
+
+    fn add_custom_feed(
+        mut self,
+        path: String,
+        options: FeedOptions,
+        source: CustomFeedSource,
+    ) -> Self {
+        self.custom_feeds.retain(|feed| feed.path != path);
+        self.custom_feeds.push(CustomFeed {
+            path,
+            options,
+            source,
+        });
+        self
+    }
+
+    pub fn reading_speed(mut self, wpm: usize) -> Self {
+        self.reading_speed = wpm;
+        self
+    }
+
+    /// Sets the timezone that dates are displayed in.
+    ///
+    /// Dates in front matter without a time are taken to be at midnight in
+    /// this timezone. Defaults to UTC.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Sets the default format for dates formatted with
+    /// [`BaseRenderContext::format_date`], using
+    /// [`chrono`'s syntax](https://docs.rs/chrono/latest/chrono/format/strftime/index.html).
+    pub fn date_format(mut self, date_format: impl Into<String>) -> Self {
+        self.date_format = date_format.into();
+        self
+    }
+
+    /// Sets the language that content is written in, unless it specifies
+    /// otherwise. Defaults to `en`.
+    pub fn default_language(mut self, default_language: impl Into<String>) -> Self {
+        self.default_language = default_language.into();
+        self
+    }
+
+    /// Builds the site as versioned documentation, with each version in its
+    /// own top-level section.
+    ///
+    /// The latest version is also available under `/latest/`, and templates
+    /// can build a version picker with [`RenderPageContext::versions`].
+    pub fn versions(mut self, versions: Versions) -> Self {
+        self.versions = Some(versions);
+        self
+    }
+
+    /// Adds a menu with the given name, for templates to render with
+    /// [`RenderPageContext::menu`].
+    ///
+    /// Adding a menu with the same name as an existing one replaces it.
+    pub fn add_menu(
+        mut self,
+        name: impl Into<String>,
+        items: impl IntoIterator<Item = MenuItem>,
+    ) -> Self {
+        self.menus.insert(name.into(), items.into_iter().collect());
+        self
+    }
+
+    pub fn with_markdown_components(
+        mut self,
+        markdown_components: impl MarkdownComponents + Send + Sync + 'static,
+    ) -> Self {
+        self.markdown_components = Box::new(markdown_components);
+        self
+    }
+
+    pub fn add_shortcode(mut self, name: impl Into<String>, shortcode: Shortcode) -> Self {
+        self.shortcodes.insert(name.into(), shortcode);
+        self
+    }
+
+    /// Registers the built-in shortcodes (`youtube`, `vimeo`, `audio`, `video`,
+    /// and `gist`).
+    ///
+    /// Shortcodes that have already been registered with the same name take
+    /// precedence over the built-in ones. See [`builtin_shortcodes`] for more.
+    pub fn with_builtin_shortcodes(mut self) -> Self {
+        for (name, shortcode) in builtin_shortcodes() {
+            self.shortcodes.entry(name).or_insert(shortcode);
+        }
+        self
+    }
+
+    pub fn with_sass(mut self, sass_path: impl AsRef<Path>) -> Self {
+        self.sass_path = Some(sass_path.as_ref().to_owned());
+        self
+    }
+
+    pub fn add_sass_load_path(mut self, load_path: impl AsRef<Path>) -> Self {
+        self.sass_load_paths.push(load_path.as_ref().to_owned());
+        self
+    }
+
+    /// Registers a hook that is called each time the site's content is loaded.
+    ///
+    /// The hook may mutate the pages and sections, for instance to compute
+    /// additional metadata. It runs before the pages are grouped into sections
+    /// and taxonomies, so the pages' ancestors and siblings and the sections'
+    /// pages aren't available yet.
+    pub fn on_content_loaded(
+        mut self,
+        hook: impl Fn(&mut Pages, &mut Sections) + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.on_content_loaded.push(Arc::new(hook));
+        self
+    }
+
+    /// Enables generating a social card image for each page.
+    ///
+    /// The image for each page is available to templates via
+    /// [`PageToRender::social_image`].
+    ///
+    /// Rendered images are cached in the `.cache` directory at the root of
+    /// the site, so a page's card is only rendered again when its title or
+    /// date, or the card's design, changes.
+    pub fn social_cards(mut self, social_cards: SocialCards) -> Self {
+        self.social_cards = Some(social_cards);
+        self
+    }
+
+    /// Enables exporting pages to PDF.
+    ///
+    /// Only pages with `pdf = true` in their front matter are exported. The
+    /// PDF for each page is available to templates via [`PageToRender::pdf_url`].
+    pub fn pdf_export(mut self, pdf_export: PdfExport) -> Self {
+        self.pdf_export = Some(pdf_export);
+        self
+    }
+
+    /// Enables rendering standalone images in Markdown content as figures.
+    ///
+    /// An image's title, if it has one, is used as the figure's caption.
+    pub fn figures(mut self, figures: Figures) -> Self {
+        self.figures = Some(figures);
+        self
+    }
+
+    /// Sets whether local images in the content should be lazy-loaded.
+    ///
+    /// Images that resolve to a file in `static/` or a page bundle are given
+    /// `loading="lazy"` and `decoding="async"`, along with `width` and
+    /// `height` attributes read from the image, which prevents layout shift
+    /// while they load.
+    pub fn lazy_images(mut self, lazy_images: bool) -> Self {
+        self.lazy_images = lazy_images;
+        self
+    }
+
+    /// Sets whether the site should be built using as little memory as possible.
+    ///
+    /// This applies to every build of the site, including the ones made while
+    /// serving it. See [`BuildOptions::low_memory`] for what it changes.
+    pub fn low_memory(mut self, low_memory: bool) -> Self {
+        self.low_memory = low_memory;
+        self
+    }
+
+    /// Registers a [`ContentRule`] that is run against the content of each page.
+    ///
+    /// The rule's findings are included in [`Site::lint`] and in the
+    /// [`BuildReport`] for each build. In strict mode, errors fail the build.
+    pub fn add_content_rule(mut self, rule: impl ContentRule + 'static) -> Self {
+        self.content_rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Enables rendering math in Markdown content.
+    ///
+    /// The assets for rendering math are only added to pages that contain math.
+    pub fn math(mut self, math: Math) -> Self {
+        self.math = Some(math);
+        self
+    }
+
+    /// Adds an analytics provider whose snippet is injected into every page.
+    ///
+    /// Analytics snippets are omitted when serving the site locally.
+    pub fn analytics(mut self, analytics: Analytics) -> Self {
+        self.analytics.push(analytics);
+        self
+    }
+
+    /// Enables generating a Content Security Policy for the rendered pages.
+    ///
+    /// The policy is not applied when serving the site locally.
+    pub fn content_security_policy(mut self, csp: ContentSecurityPolicy) -> Self {
+        self.content_security_policy = Some(csp);
+        self
+    }
+
+    /// Enables generating host-specific configuration that redirects alternate
+    /// hosts and plain HTTP requests to the `base_url`, as well as the aliases
+    /// of the site's content.
+    ///
+    /// The configuration is not generated when serving the site locally.
+    pub fn host_redirects(mut self, host_redirects: HostRedirects) -> Self {
+        self.host_redirects = Some(host_redirects);
+        self
+    }
+
+    /// Registers a [`BuildProfile`] that can be selected with [`BuildOptions::profile`].
+    pub fn profile(mut self, profile: BuildProfile) -> Self {
+        self.profiles
+            .retain(|existing| existing.name != profile.name);
+        self.profiles.push(profile);
+        self
+    }
+
+    /// Registers a hook that is called each time the site has been rendered.
+    pub fn after_render(mut self, hook: impl Fn(&BuildReport) + Send + Sync + 'static) -> Self {
+        self.hooks.after_render.push(Arc::new(hook));
+        self
+    }
+}
+
+impl SiteBuilder<()> {
+    pub fn new() -> Self {
+        Self {
+            state: PhantomData,
+            root_path: PathBuf::new(),
+            base_url: String::new(),
+            title: None,
+            webmention_endpoint: None,
+            extra: toml::Table::new(),
+            include_drafts: false,
+            publish_future: None,
+            strict: false,
+            validate_html: false,
+            accessibility_audit: None,
+            passthrough_content: false,
+            static_files: StaticFiles::default(),
+            implicit_sections: false,
+            term_normalization: TermNormalization::default(),
+            skip_empty_taxonomies: false,
+            sitemap: true,
+            robots_txt: true,
+            feeds: true,
+            not_found_page: true,
+            custom_feeds: Vec::new(),
+            feed_content: FeedContent::default(),
+            broken_link_policy: BrokenLinkPolicy::default(),
+            reading_speed: AVERAGE_ADULT_WPM,
+            timezone: Tz::UTC,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            default_language: DEFAULT_LANGUAGE.to_string(),
+            versions: None,
+            menus: HashMap::new(),
+            templates: Templates {
+                index: Arc::new(|_| auk::div()),
+                section: HashMap::new(),
+                page: HashMap::new(),
+                taxonomy: HashMap::new(),
+                taxonomy_term: HashMap::new(),
+                not_found: None,
+                error: HashMap::new(),
+                generated: HashMap::new(),
+                home: None,
+                section_assets: HashMap::new(),
+                page_assets: HashMap::new(),
+            },
+            markdown_components: Box::new(DefaultMarkdownComponents),
+            shortcodes: HashMap::new(),
+            taxonomies: Vec::new(),
+            sass_path: None,
+            sass_load_paths: Vec::new(),
+            hooks: Hooks::default(),
+            social_cards: None,
+            pdf_export: None,
+            analytics: Vec::new(),
+            math: None,
+            figures: None,
+            lazy_images: false,
+            low_memory: false,
+            content_rules: Vec::new(),
+            content_security_policy: None,
+            host_redirects: None,
+            profiles: Vec::new(),
+        }
+    }
+
+    pub fn root(self, root_path: impl AsRef<Path>) -> SiteBuilder<WithRootPath> {
+        SiteBuilder {
+            root_path: root_path.as_ref().to_owned(),
+            ..self.coerce()
+        }
+    }
+
+    /// Applies the settings from the [Zola](https://www.getzola.org)-style
+    /// `config.toml` at the given path.
+    ///
+    /// The directory containing the config file is used as the site root.
+    /// Any of the settings can still be overridden afterwards.
+    pub fn from_config_file(
+        self,
+        config_path: impl AsRef<Path>,
+    ) -> Result<SiteBuilder<WithBaseUrl>, LoadConfigError> {
+        let config_path = config_path.as_ref();
+        let config = ConfigFile::from_path(config_path)?;
+
+        let mut builder = self
+            .root(config_path.parent().unwrap_or(Path::new("")))
+            .base_url(config.base_url);
+        builder.title = config.title;
+        builder.taxonomies = config.taxonomies.into_iter().map(Taxonomy::from).collect();
+        builder.extra = config.extra;
+
+        if let Some(timezone) = config.timezone {
+            builder.timezone = timezone
+                .parse()
+                .map_err(|_| LoadConfigError::UnknownTimezone {
+                    timezone,
+                    filepath: config_path.to_owned(),
+                })?;
+        }
+
+        if let Some(date_format) = config.date_format {
+            builder.date_format = date_format;
+        }
+
+        if let Some(default_language) = config.default_language {
+            builder.default_language = default_language;
+        }
+
+        Ok(builder)
+    }
+}
+
+pub struct WithRootPath;
+
+impl SiteBuilder<WithRootPath> {
+    pub fn base_url(self, base_url: impl Into<String>) -> SiteBuilder<WithBaseUrl> {
+        SiteBuilder {
+            base_url: base_url.into(),
+            ..self.coerce()
+        }
+    }
+}
+
+pub struct WithBaseUrl;
+
+/// The builder states in which the base URL of the site has been set.
+pub trait HasBaseUrl {}
+
+impl HasBaseUrl for WithBaseUrl {}
+impl HasBaseUrl for WithTemplates {}
+
+impl<State: HasBaseUrl> SiteBuilder<State> {
+    /// Overrides the base URL of the site.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets a user-defined setting that is made available to templates via
+    /// [`BaseRenderContext::extra`].
+    pub fn extra(mut self, key: impl Into<String>, value: impl Into<toml::Value>) -> Self {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the endpoint that receives [Webmentions](https://www.w3.org/TR/webmention/)
+    /// for the site, which templates can advertise using
+    /// [`webmention_links`](BaseRenderContext::webmention_links).
+    pub fn webmention_endpoint(mut self, webmention_endpoint: impl Into<String>) -> Self {
+        self.webmention_endpoint = Some(webmention_endpoint.into());
+        self
+    }
+}
+
+impl SiteBuilder<WithBaseUrl> {
+    pub fn templates(
+        self,
+        index: impl Fn(&RenderSectionContext) -> HtmlElement + Send + Sync + 'static,
+        section: impl Fn(&RenderSectionContext) -> HtmlElement + Send + Sync + 'static,
+        page: impl Fn(&RenderPageContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> SiteBuilder<WithTemplates> {
+        SiteBuilder {
+            templates: Templates {
+                index: Arc::new(index),
+                section: HashMap::from_iter([(
+                    TemplateKey::Default,
+                    Arc::new(section) as RenderSection,
+                )]),
+                page: HashMap::from_iter([(TemplateKey::Default, Arc::new(page) as RenderPage)]),
+                taxonomy: HashMap::new(),
+                taxonomy_term: HashMap::new(),
+                not_found: None,
+                error: HashMap::new(),
+                generated: HashMap::new(),
+                home: None,
+                section_assets: HashMap::new(),
+                page_assets: HashMap::new(),
+            },
+            ..self.coerce()
         }
     }
 }
 
-pub struct SiteBuilder<State> {
-    state: PhantomData<State>,
-    root_path: PathBuf,
-    base_url: String,
-    title: Option<String>,
-    include_drafts: bool,
-    reading_speed: usize,
-    templates: Templates,
-    markdown_components: Box<dyn MarkdownComponents>,
-    shortcodes: HashMap<String, Shortcode>,
-    taxonomies: Vec<Taxonomy>,
-    sass_path: Option<PathBuf>,
-    sass_load_paths: Vec<PathBuf>,
+pub struct WithTemplates;
+
+impl SiteBuilder<WithTemplates> {
+    pub fn add_section_template(
+        mut self,
+        name: impl Into<String>,
+        template: impl Fn(&RenderSectionContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.templates
+            .section
+            .insert(TemplateKey::Custom(name.into()), Arc::new(template));
+        self
+    }
+
+    pub fn add_page_template(
+        mut self,
+        name: impl Into<String>,
+        template: impl Fn(&RenderPageContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.templates
+            .page
+            .insert(TemplateKey::Custom(name.into()), Arc::new(template));
+        self
+    }
+
+    /// Adds a section template along with the assets (stylesheets and scripts)
+    /// that should be included whenever it is rendered.
+    pub fn add_section_template_with_assets(
+        mut self,
+        name: impl Into<String>,
+        template: impl Fn(&RenderSectionContext) -> HtmlElement + Send + Sync + 'static,
+        assets: &[&str],
+    ) -> Self {
+        let key = TemplateKey::Custom(name.into());
+        self.templates.section_assets.insert(
+            key.clone(),
+            assets.iter().map(|asset| asset.to_string()).collect(),
+        );
+        self.templates.section.insert(key, Arc::new(template));
+        self
+    }
+
+    /// Adds a page template along with the assets (stylesheets and scripts)
+    /// that should be included whenever it is rendered.
+    pub fn add_page_template_with_assets(
+        mut self,
+        name: impl Into<String>,
+        template: impl Fn(&RenderPageContext) -> HtmlElement + Send + Sync + 'static,
+        assets: &[&str],
+    ) -> Self {
+        let key = TemplateKey::Custom(name.into());
+        self.templates.page_assets.insert(
+            key.clone(),
+            assets.iter().map(|asset| asset.to_string()).collect(),
+        );
+        self.templates.page.insert(key, Arc::new(template));
+        self
+    }
+
+    /// Adds a page at the given path that is rendered by `template` instead of
+    /// being backed by a content file.
+    pub fn add_generated_page(
+        mut self,
+        path: impl Into<String>,
+        template: impl Fn(&RenderGeneratedPageContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.templates
+            .generated
+            .insert(path.into(), Arc::new(template));
+        self
+    }
+
+    /// Renders the home page using the given template instead of the root
+    /// section's template.
+    ///
+    /// When this is set, `content/_index.md` is optional and will not be
+    /// rendered, even if it exists.
+    pub fn with_home_page(
+        mut self,
+        template: impl Fn(&RenderGeneratedPageContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.templates.home = Some(Arc::new(template));
+        self
+    }
+
+    pub fn add_404_template(
+        mut self,
+        template: impl Fn(&BaseRenderContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.templates.not_found = Some(Arc::new(template));
+        self
+    }
+
+    /// Adds a template for the page served with the given HTTP status code
+    /// (e.g., `403`).
+    ///
+    /// The page for `500` is rendered to `50x.html`, and the rest to
+    /// `{status}.html`. In serve mode, the `500` page is also shown in place
+    /// of the site's content while it fails to build.
+    pub fn add_error_template(
+        mut self,
+        status: u16,
+        template: impl Fn(&RenderErrorPageContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.templates.error.insert(status, Arc::new(template));
+        self
+    }
+
+    pub fn add_taxonomy(
+        mut self,
+        taxonomy: Taxonomy,
+        template: impl Fn(&RenderTaxonomyContext) -> HtmlElement + Send + Sync + 'static,
+        term_template: impl Fn(&RenderTaxonomyTermContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.templates
+            .taxonomy
+            .insert(taxonomy.name.clone(), Arc::new(template));
+        self.templates
+            .taxonomy_term
+            .insert(taxonomy.name.clone(), Arc::new(term_template));
+        self.taxonomies
+            .retain(|existing| existing.name != taxonomy.name);
+        self.taxonomies.push(taxonomy);
+        self
+    }
+
+    /// Adds support for authors, with an index of all of the authors and a
+    /// page (and feed) for each author.
+    ///
+    /// Pages list their authors by ID in the `authors` front matter. An
+    /// author's profile is read from `content/authors/{id}.md` (or
+    /// `content/authors/{id}/_index.md`), where the `title` is used as the
+    /// author's display name.
+    pub fn add_authors(
+        self,
+        template: impl Fn(&RenderTaxonomyContext) -> HtmlElement + Send + Sync + 'static,
+        author_template: impl Fn(&RenderTaxonomyTermContext) -> HtmlElement + Send + Sync + 'static,
+    ) -> Self {
+        self.add_taxonomy(Taxonomy::new(AUTHORS_TAXONOMY), template, author_template)
+    }
+
+    pub fn build(self) -> Site {
+        self.build_site()
+    }
 }
 
-impl<State> SiteBuilder<State> {
-    fn coerce<NewState>(self) -> SiteBuilder<NewState> {
-        SiteBuilder {
-            state: PhantomData,
-            root_path: self.root_path,
-            base_url: self.base_url,
-            title: self.title,
-            include_drafts: self.include_drafts,
-            reading_speed: self.reading_speed,
-            templates: self.templates,
-            markdown_components: self.markdown_components,
-            shortcodes: self.shortcodes,
-            taxonomies: self.taxonomies,
-            sass_path: self.sass_path,
-            sass_load_paths: self.sass_load_paths,
+#[cfg(test)]
+mod tests {
+    use auk::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::lint::{ContentRuleContext, RuleViolation};
+    use crate::social_card::SocialCardBackend;
+    use crate::test_site::{render, TestSite};
+
+    use super::*;
+
+    #[test]
+    fn test_document_renderer() {
+        let first = html().child(body().child(h1().child("First")));
+        let second = html().child(body().child(p().child("Second")));
+
+        let mut renderer = DocumentRenderer::new();
+
+        assert_eq!(
+            renderer.render(&first).unwrap(),
+            HtmlElementRenderer::new().render_to_string(&first).unwrap()
+        );
+        assert_eq!(
+            renderer.render(&second).unwrap(),
+            HtmlElementRenderer::new()
+                .render_to_string(&second)
+                .unwrap()
+        );
+        assert_eq!(
+            renderer
+                .render_children("<!DOCTYPE html>", &[title().child("Alias").into()])
+                .unwrap(),
+            "<!DOCTYPE html><title>Alias</title>"
+        );
+    }
+
+    #[cfg(feature = "bench")]
+    #[test]
+    fn test_render_in_memory() {
+        let test_site = TestSite::new("render_in_memory");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n");
+
+        let mut site = test_site.load();
+        site.render_in_memory().unwrap();
+
+        assert_eq!(site.report().page_count, 1);
+        assert!(!test_site.root_path().join("public").exists());
+    }
+
+    #[test]
+    fn test_on_content_loaded_runs_before_aggregation() {
+        let test_site = TestSite::new("on_content_loaded");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("blog/_index.md", "+++\nsort_by = \"weight\"\n+++\n")
+            .write("blog/first.md", "+++\ntitle = \"First\"\nweight = 1\n+++\n")
+            .write(
+                "blog/second.md",
+                "+++\ntitle = \"Second\"\nweight = 2\n+++\n",
+            );
+
+        let mut site = test_site
+            .builder()
+            .add_taxonomy(Taxonomy::new("tags"), |_| html(), |_| html())
+            .on_content_loaded(|pages, _sections| {
+                for page in pages.values_mut() {
+                    page.meta
+                        .taxonomies
+                        .insert("tags".to_string(), vec!["hooked".to_string()]);
+                    page.meta.weight = page.meta.weight.map(|weight| 10 - weight);
+                }
+            })
+            .build();
+        site.load().unwrap();
+
+        let first = test_site.content_path().join("blog/first.md");
+        let second = test_site.content_path().join("blog/second.md");
+
+        let mut tagged = site.taxonomies["tags"]["hooked"].clone();
+        tagged.sort();
+        assert_eq!(tagged, vec![first.clone(), second.clone()]);
+        assert!(site.taxonomy_term_meta["tags"].contains_key("hooked"));
+
+        let blog = &site.sections[&test_site.content_path().join("blog/_index.md")];
+        assert_eq!(blog.pages, vec![second.clone(), first.clone()]);
+        assert_eq!(site.pages[&second].next, Some(first));
+    }
+
+    #[test]
+    fn test_after_render_receives_each_report() {
+        let test_site = TestSite::new("after_render");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n");
+
+        let page_counts = Arc::new(Mutex::new(Vec::new()));
+        let mut site = test_site
+            .builder()
+            .after_render({
+                let page_counts = page_counts.clone();
+                move |report| page_counts.lock().unwrap().push(report.page_count)
+            })
+            .build();
+        site.load().unwrap();
+
+        render(&mut site).unwrap();
+        assert_eq!(*page_counts.lock().unwrap(), vec![1]);
+
+        test_site.write("goodbye.md", "+++\ntitle = \"Goodbye\"\n+++\n");
+        site.load().unwrap();
+        render(&mut site).unwrap();
+        assert_eq!(*page_counts.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_render_fails_for_registered_taxonomies_without_templates() {
+        let test_site = TestSite::new("taxonomies_without_templates");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n");
+
+        let mut builder = test_site
+            .builder()
+            .add_taxonomy(Taxonomy::new("tags"), |_| html(), |_| html())
+            .add_taxonomy(
+                Taxonomy {
+                    render_list: false,
+                    ..Taxonomy::new("topics")
+                },
+                |_| html(),
+                |_| html(),
+            );
+        builder.templates.taxonomy_term.remove("tags");
+        builder.templates.taxonomy.remove("topics");
+        builder.taxonomies.push(Taxonomy::new("categories"));
+        let mut site = builder.build();
+        site.load().unwrap();
+
+        let content = SiteContent::default();
+        let result = site.render_to(InMemoryStorage::new(content.clone()));
+
+        let missing_templates = match result {
+            Err(RenderSiteError::TaxonomyTemplatesNotFound(missing_templates)) => missing_templates,
+            result => panic!("expected missing taxonomy templates, got {result:?}"),
+        };
+        assert_eq!(
+            missing_templates,
+            vec!["'tags' (term template)".to_string()]
+        );
+        assert!(content.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_render_collects_config_only_taxonomies_without_rendering_them() {
+        let test_site = TestSite::new("config_only_taxonomies");
+        test_site.write("_index.md", "+++\n+++\n").write(
+            "hello.md",
+            "+++\ntitle = \"Hello\"\n\n[taxonomies]\ncategories = [\"news\"]\ntags = [\"rust\"]\n+++\n",
+        );
+
+        let mut site = test_site
+            .builder_from_config(indoc! {r#"
+                base_url = "https://example.com"
+
+                taxonomies = [
+                    { name = "categories" },
+                    { name = "tags" },
+                ]
+            "#})
+            .add_taxonomy(Taxonomy::new("tags"), |_| html(), |_| html())
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        assert_eq!(
+            site.taxonomies["categories"]["news"],
+            vec![test_site.content_path().join("hello.md")]
+        );
+        assert!(!rendered.contains_key("/categories/"));
+        assert!(!rendered.contains_key("/categories/news/"));
+        assert!(rendered.contains_key("/tags/"));
+        assert!(rendered.contains_key("/tags/rust/"));
+    }
+
+    #[test]
+    fn test_taxonomy_pages_include_the_build_overlay_when_serving() {
+        let test_site = TestSite::new("taxonomy_build_overlay");
+        test_site.write("_index.md", "+++\n+++\n").write(
+            "hello.md",
+            "+++\ntitle = \"Hello\"\n\n[taxonomies]\ntags = [\"rust\"]\n+++\n",
+        );
+
+        let mut site = test_site
+            .builder()
+            .add_taxonomy(
+                Taxonomy::new("tags"),
+                |_| html().child(body()),
+                |_| html().child(body()),
+            )
+            .build();
+        site.live_reload_port = Some(3000);
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        for path in ["/tags/", "/tags/rust/"] {
+            assert!(
+                rendered[path].contains(BUILD_OVERLAY_JS_PATH),
+                "expected the build overlay script in {path}"
+            );
+            assert!(rendered[path].contains("/livereload.js?port=3000"));
+        }
+    }
+
+    #[test]
+    fn test_taxonomy_pages_are_validated() {
+        let test_site = TestSite::new("taxonomy_validation");
+        test_site.write("_index.md", "+++\n+++\n").write(
+            "hello.md",
+            "+++\ntitle = \"Hello\"\n\n[taxonomies]\ntags = [\"rust\"]\n+++\n",
+        );
+
+        let mut site = test_site
+            .builder()
+            .add_taxonomy(
+                Taxonomy::new("tags"),
+                |_| html().child(body()),
+                |_| html().child(body().child(img().src("/cover.png"))),
+            )
+            .validate_html(true)
+            .build();
+        site.load().unwrap();
+
+        render(&mut site).unwrap();
+
+        assert_eq!(
+            site.report().diagnostics,
+            vec![Diagnostic {
+                severity: Severity::Error,
+                rule: "missing-alt".to_string(),
+                message: "<img src=\"/cover.png\"> has no alt attribute".to_string(),
+                path: PathBuf::from("/tags/rust/"),
+                line: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_timings_are_written_outside_the_output_directory() {
+        let test_site = TestSite::new("timings");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n\nHello, world!\n");
+
+        test_site
+            .builder()
+            .build()
+            .build_with_options(BuildOptions::new().timings(true))
+            .unwrap();
+
+        let timings: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(test_site.root_path().join("timings.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(timings["pages"][0]["path"], "hello.md");
+        assert!(!test_site.root_path().join("public/timings.json").exists());
+    }
+
+    #[test]
+    fn test_low_memory_drops_raw_content() {
+        let test_site = TestSite::new("low_memory");
+        test_site
+            .write("_index.md", "+++\n+++\n\nWelcome!\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n\nHello, world!\n");
+
+        let mut site = test_site.builder().low_memory(true).build();
+        site.load().unwrap();
+
+        let hello = test_site.content_path().join("hello.md");
+        assert_eq!(site.pages[&hello].raw_content, "Hello, world!\n");
+
+        let rendered = render(&mut site).unwrap();
+
+        assert!(rendered["/hello/"].contains("Hello, world!"));
+        assert!(site.pages.values().all(|page| page.raw_content.is_empty()));
+        assert!(site
+            .sections
+            .values()
+            .all(|section| section.raw_content.is_empty()));
+    }
+
+    /// A [`SocialCardBackend`] that counts the cards it renders.
+    struct CountingSocialCardBackend(Arc<AtomicU64>);
+
+    impl SocialCardBackend for CountingSocialCardBackend {
+        fn render_png(
+            &self,
+            svg: &str,
+            _fonts: &[PathBuf],
+        ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(svg.as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_social_cards_are_only_rendered_when_they_change() {
+        let test_site = TestSite::new("social_card_cache");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n")
+            .write("goodbye.md", "+++\ntitle = \"Goodbye\"\n+++\n");
+
+        let rendered_cards = Arc::new(AtomicU64::new(0));
+        let mut site = test_site
+            .builder()
+            .social_cards(SocialCards::new(CountingSocialCardBackend(
+                rendered_cards.clone(),
+            )))
+            .build();
+
+        site.load().unwrap();
+        let rendered = render(&mut site).unwrap();
+        assert_eq!(rendered_cards.load(Ordering::Relaxed), 2);
+        assert!(rendered["/hello/social.png"].contains(">Hello</text>"));
+
+        site.load().unwrap();
+        let rendered = render(&mut site).unwrap();
+        assert_eq!(rendered_cards.load(Ordering::Relaxed), 2);
+        assert!(rendered["/hello/social.png"].contains(">Hello</text>"));
+
+        test_site.write("hello.md", "+++\ntitle = \"Hello, again\"\n+++\n");
+        site.load().unwrap();
+        let rendered = render(&mut site).unwrap();
+        assert_eq!(rendered_cards.load(Ordering::Relaxed), 3);
+        assert!(rendered["/hello/social.png"].contains(">Hello, again</text>"));
+    }
+
+    fn broken_link_test_site(name: &str) -> TestSite {
+        let test_site = TestSite::new(name);
+        test_site.write("_index.md", "+++\n+++\n").write(
+            "hello.md",
+            "+++\ntitle = \"Hello\"\n+++\n\n[Missing](@/missing.md), [Gone](@/gone.md#intro), [Nowhere](@/hello.md#nowhere), and [Hello](@/hello.md).\n",
+        );
+        test_site
+    }
+
+    fn expected_broken_links() -> Vec<BrokenLink> {
+        vec![
+            BrokenLink {
+                link: "@/gone.md#intro".to_string(),
+                permalink: "https://example.com/hello/".to_string(),
+            },
+            BrokenLink {
+                link: "@/hello.md#nowhere".to_string(),
+                permalink: "https://example.com/hello/".to_string(),
+            },
+            BrokenLink {
+                link: "@/missing.md".to_string(),
+                permalink: "https://example.com/hello/".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_broken_link_policy_leave() {
+        let test_site = broken_link_test_site("broken_link_policy_leave");
+
+        let mut site = test_site.load();
+        let rendered = render(&mut site).unwrap();
+
+        let hello = &rendered["/hello/"];
+        assert!(hello.contains(r#"<a href="@/missing.md">Missing</a>"#));
+        assert!(hello.contains(r#"<a href="@/gone.md#intro">Gone</a>"#));
+        assert!(hello.contains(r#"<a href="https://example.com/hello/#nowhere">Nowhere</a>"#));
+        assert!(hello.contains(r#"<a href="https://example.com/hello/">Hello</a>"#));
+        assert_eq!(site.report().broken_links, expected_broken_links());
+    }
+
+    #[test]
+    fn test_broken_link_policy_strip() {
+        let test_site = broken_link_test_site("broken_link_policy_strip");
+
+        let mut site = test_site
+            .builder()
+            .broken_link_policy(BrokenLinkPolicy::Strip)
+            .build();
+        site.load().unwrap();
+        let rendered = render(&mut site).unwrap();
+
+        let hello = &rendered["/hello/"];
+        assert!(hello.contains("<a>Missing</a>, <a>Gone</a>, <a>Nowhere</a>"));
+        assert!(hello.contains(r#"<a href="https://example.com/hello/">Hello</a>"#));
+        assert_eq!(site.report().broken_links, expected_broken_links());
+    }
+
+    #[test]
+    fn test_broken_link_policy_replace() {
+        let test_site = broken_link_test_site("broken_link_policy_replace");
+
+        let mut site = test_site
+            .builder()
+            .broken_link_policy(BrokenLinkPolicy::Replace("/404.html".to_string()))
+            .build();
+        site.load().unwrap();
+        let rendered = render(&mut site).unwrap();
+
+        let hello = &rendered["/hello/"];
+        assert!(hello.contains(r#"<a href="/404.html">Missing</a>"#));
+        assert!(hello.contains(r#"<a href="/404.html">Gone</a>"#));
+        assert!(hello.contains(r#"<a href="/404.html">Nowhere</a>"#));
+        assert!(hello.contains(r#"<a href="https://example.com/hello/">Hello</a>"#));
+        assert_eq!(site.report().broken_links, expected_broken_links());
+    }
+
+    #[test]
+    fn test_broken_link_policy_fail() {
+        let test_site = broken_link_test_site("broken_link_policy_fail");
+
+        let mut site = test_site
+            .builder()
+            .broken_link_policy(BrokenLinkPolicy::Fail)
+            .build();
+        site.load().unwrap();
+
+        match render(&mut site) {
+            Err(RenderSiteError::BrokenLink { link, permalink }) => {
+                assert_eq!(link, "@/missing.md");
+                assert_eq!(permalink, "https://example.com/hello/");
+            }
+            result => panic!("expected a broken link, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_broken_anchors_fail_builds() {
+        for (name, policy, strict) in [
+            ("broken_anchor_policy_fail", BrokenLinkPolicy::Fail, false),
+            ("broken_anchor_strict", BrokenLinkPolicy::Leave, true),
+            ("broken_anchor_strict_strip", BrokenLinkPolicy::Strip, true),
+        ] {
+            let test_site = TestSite::new(name);
+            test_site.write("_index.md", "+++\n+++\n").write(
+                "hello.md",
+                "+++\ntitle = \"Hello\"\n+++\n\n## Intro\n\n[Intro](@/hello.md#intro) and [Nowhere](@/hello.md#nowhere).\n",
+            );
+
+            let mut site = test_site
+                .builder()
+                .broken_link_policy(policy)
+                .strict(strict)
+                .build();
+            site.load().unwrap();
+
+            match render(&mut site) {
+                Err(RenderSiteError::BrokenAnchor { link, permalink }) => {
+                    assert_eq!(link, "@/hello.md#nowhere", "{name}");
+                    assert_eq!(permalink, "https://example.com/hello/", "{name}");
+                }
+                result => panic!("{name}: expected a broken anchor, got {result:?}"),
+            }
+        }
+    }
+
+    /// Flags the lines in a page's content that contain a `TODO`, as errors
+    /// when they are urgent.
+    struct TodoRule;
+
+    impl ContentRule for TodoRule {
+        fn name(&self) -> &str {
+            "todo"
+        }
+
+        fn check(&self, ctx: &ContentRuleContext) -> Vec<RuleViolation> {
+            ctx.markdown
+                .lines()
+                .enumerate()
+                .filter_map(|(index, line)| {
+                    let violation = if line.contains("URGENT TODO") {
+                        RuleViolation::error("urgent TODO in content")
+                    } else if line.contains("TODO") {
+                        RuleViolation::warning("TODO in content")
+                    } else {
+                        return None;
+                    };
+
+                    Some(violation.at_line(index + 1))
+                })
+                .collect()
         }
     }
 
-    fn build_site(self) -> Site {
-        Site::from_params(BuildSiteParams {
-            base_url: self.base_url,
-            title: self.title,
-            include_drafts: self.include_drafts,
-            reading_speed: self.reading_speed,
-            root_path: self.root_path,
-            sass_path: self.sass_path,
-            sass_load_paths: self.sass_load_paths,
-            templates: self.templates,
-            markdown_components: self.markdown_components,
-            shortcodes: self.shortcodes,
-            taxonomies: self.taxonomies,
-        })
+    fn content_rule_test_site(name: &str) -> TestSite {
+        let test_site = TestSite::new(name);
+        test_site.write("_index.md", "+++\n+++\n").write(
+            "hello.md",
+            indoc! {"
+                +++
+                title = \"Hello\"
+                +++
+
+                Some text.
+                TODO: Tidy this up.
+                URGENT TODO: Fix this.
+            "},
+        );
+        test_site
     }
 
-    pub fn include_drafts(mut self, include_drafts: bool) -> Self {
-        self.include_drafts = include_drafts;
-        self
+    #[test]
+    fn test_content_rule_diagnostics_are_reported() {
+        let test_site = content_rule_test_site("content_rule_diagnostics");
+
+        let mut site = test_site.builder().add_content_rule(TodoRule).build();
+        site.load().unwrap();
+        render(&mut site).unwrap();
+
+        let diagnostics = site
+            .report()
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.rule == "todo")
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    severity: Severity::Warning,
+                    rule: "todo".to_string(),
+                    message: "TODO in content".to_string(),
+                    path: PathBuf::from("hello.md"),
+                    line: Some(6),
+                },
+                Diagnostic {
+                    severity: Severity::Error,
+                    rule: "todo".to_string(),
+                    message: "urgent TODO in content".to_string(),
+                    path: PathBuf::from("hello.md"),
+                    line: Some(7),
+                },
+            ]
+        );
     }
 
-    pub fn reading_speed(mut self, wpm: usize) -> Self {
-        self.reading_speed = wpm;
-        self
+    #[test]
+    fn test_content_rule_errors_fail_strict_builds() {
+        let test_site = content_rule_test_site("content_rule_strict");
+
+        let mut site = test_site
+            .builder()
+            .add_content_rule(TodoRule)
+            .strict(true)
+            .build();
+        site.load().unwrap();
+
+        match render(&mut site) {
+            Err(RenderSiteError::ContentRule(diagnostic)) => {
+                assert_eq!(diagnostic.rule, "todo");
+                assert_eq!(diagnostic.severity, Severity::Error);
+                assert_eq!(diagnostic.line, Some(7));
+            }
+            result => panic!("expected a content rule error, got {result:?}"),
+        }
     }
-}
 
-impl SiteBuilder<()> {
-    pub fn new() -> Self {
-        Self {
-            state: PhantomData,
-            root_path: PathBuf::new(),
-            base_url: String::new(),
-            title: None,
-            include_drafts: false,
-            reading_speed: AVERAGE_ADULT_WPM,
-            templates: Templates {
-                index: Arc::new(|_| auk::div()),
-                section: HashMap::new(),
-                page: HashMap::new(),
-                taxonomy: HashMap::new(),
-                taxonomy_term: HashMap::new(),
-                not_found: None,
-            },
-            markdown_components: Box::new(DefaultMarkdownComponents),
-            shortcodes: HashMap::new(),
-            taxonomies: Vec::new(),
-            sass_path: None,
-            sass_load_paths: Vec::new(),
-        }
+    fn bundle_test_site(name: &str) -> TestSite {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("blog/_index.md", "+++\n+++\n")
+            .write(
+                "blog/post.md",
+                "+++\ntitle = \"Post\"\n+++\n\n![Cat](cat.png)\n\n![Dog](dog.png)\n",
+            )
+            .write("blog/post/cat.png", "meow");
+        test_site
     }
 
-    pub fn root(self, root_path: impl AsRef<Path>) -> SiteBuilder<WithRootPath> {
-        SiteBuilder {
-            root_path: root_path.as_ref().to_owned(),
-            ..self.coerce()
+    fn missing_asset(test_site: &TestSite, src: &str, exists: bool) -> MissingAsset {
+        MissingAsset {
+            src: src.to_string(),
+            permalink: "https://example.com/blog/post/".to_string(),
+            source_path: test_site.content_path().join("blog/post").join(src),
+            exists,
         }
     }
-}
 
-pub struct WithRootPath;
+    #[test]
+    fn test_bundle_assets_with_passthrough_content() {
+        let test_site = bundle_test_site("bundle_assets_with_passthrough_content");
 
-impl SiteBuilder<WithRootPath> {
-    pub fn base_url(self, base_url: impl Into<String>) -> SiteBuilder<WithBaseUrl> {
-        SiteBuilder {
-            base_url: base_url.into(),
-            ..self.coerce()
+        let mut site = test_site.builder().passthrough_content(true).build();
+        site.load().unwrap();
+        let rendered = render(&mut site).unwrap();
+
+        assert!(rendered["/blog/post/"].contains(r#"src="https://example.com/blog/post/cat.png""#));
+        assert_eq!(rendered["/blog/post/cat.png"], "meow");
+        assert_eq!(
+            site.report().missing_assets,
+            vec![missing_asset(&test_site, "dog.png", false)]
+        );
+    }
+
+    #[test]
+    fn test_bundle_assets_without_passthrough_content() {
+        let test_site = bundle_test_site("bundle_assets_without_passthrough_content");
+
+        let mut site = test_site.load();
+        let rendered = render(&mut site).unwrap();
+
+        assert!(rendered["/blog/post/"].contains(r#"src="cat.png""#));
+        assert!(!rendered.contains_key("/blog/post/cat.png"));
+        assert_eq!(
+            site.report().missing_assets,
+            vec![
+                missing_asset(&test_site, "cat.png", true),
+                missing_asset(&test_site, "dog.png", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_served_sites_keep_their_own_content() {
+        let first_site = TestSite::new("served_content_first");
+        first_site
+            .write("_index.md", "+++\n+++\n")
+            .write("first.md", "+++\ntitle = \"First\"\n+++\n");
+        let second_site = TestSite::new("served_content_second");
+        second_site
+            .write("_index.md", "+++\n+++\n")
+            .write("second.md", "+++\ntitle = \"Second\"\n+++\n");
+
+        let mut first = first_site.builder().build();
+        let mut second = second_site.builder().build();
+        for site in [&mut first, &mut second] {
+            site.is_serving = true;
+            site.load().unwrap();
+            site.render().unwrap();
         }
+
+        let first_content = first.content.read().unwrap();
+        let second_content = second.content.read().unwrap();
+
+        assert!(first_content.contains_key("/first/"));
+        assert!(!first_content.contains_key("/second/"));
+        assert!(second_content.contains_key("/second/"));
+        assert!(!second_content.contains_key("/first/"));
+
+        // Nothing is written to the output directories while serving.
+        assert!(!first_site.root_path().join("public").exists());
+        assert!(!second_site.root_path().join("public").exists());
     }
-}
 
-pub struct WithBaseUrl;
+    #[test]
+    fn test_builder_settings_can_be_given_before_or_after_the_templates() {
+        let test_site = TestSite::new("builder_setting_order");
+
+        let before_templates = Site::builder()
+            .root(test_site.root_path())
+            .base_url("https://example.org")
+            .title("Before")
+            .extra("author", "Jane Doe")
+            .webmention_endpoint("https://webmention.io/example")
+            .with_sass("sass")
+            .templates(|_| html(), |_| html(), |_| html())
+            .base_url("https://example.com")
+            .build();
+
+        let after_templates = Site::builder()
+            .root(test_site.root_path())
+            .base_url("https://example.org")
+            .templates(|_| html(), |_| html(), |_| html())
+            .with_sass("sass")
+            .add_taxonomy(Taxonomy::new("tags"), |_| html(), |_| html())
+            .title("After")
+            .extra("author", "Jane Doe")
+            .webmention_endpoint("https://webmention.io/example")
+            .base_url("https://example.com")
+            .build();
+
+        for (site, title) in [(&before_templates, "Before"), (&after_templates, "After")] {
+            assert_eq!(site.base_url(), "https://example.com");
+            assert_eq!(site.config.title.as_deref(), Some(title));
+            assert_eq!(site.extra.get("author"), Some(&"Jane Doe".into()));
+            assert_eq!(
+                site.webmention_endpoint.as_deref(),
+                Some("https://webmention.io/example")
+            );
+            assert_eq!(site.sass_path, Some(test_site.root_path().join("sass")));
+        }
+        assert_eq!(
+            after_templates
+                .config
+                .taxonomies
+                .iter()
+                .map(|taxonomy| taxonomy.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["tags"]
+        );
+    }
 
-impl SiteBuilder<WithBaseUrl> {
-    pub fn title(mut self, title: impl Into<String>) -> Self {
-        self.title = Some(title.into());
-        self
+    fn profile_test_site(name: &str) -> (TestSite, Site) {
+        let test_site = TestSite::new(name);
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n")
+            .write("draft.md", "+++\ntitle = \"Draft\"\ndraft = true\n+++\n");
+
+        let site = Site::builder()
+            .root(test_site.root_path())
+            .base_url("https://example.com")
+            .templates(
+                |_| html(),
+                |_| html(),
+                |ctx| {
+                    html().child(body().child(format!(
+                        "{:?} {}",
+                        ctx.profile(),
+                        ctx.is_feature_enabled("comments")
+                    )))
+                },
+            )
+            .profile(BuildProfile::new("production").base_url("https://old.example.com"))
+            .profile(
+                BuildProfile::new("production")
+                    .base_url("https://www.example.com")
+                    .feature("comments", true),
+            )
+            .profile(BuildProfile::new("dev").include_drafts(true))
+            .build();
+
+        (test_site, site)
     }
 
-    pub fn templates(
-        self,
-        index: impl Fn(&RenderSectionContext) -> HtmlElement + Send + Sync + 'static,
-        section: impl Fn(&RenderSectionContext) -> HtmlElement + Send + Sync + 'static,
-        page: impl Fn(&RenderPageContext) -> HtmlElement + Send + Sync + 'static,
-    ) -> SiteBuilder<WithTemplates> {
-        SiteBuilder {
-            templates: Templates {
-                index: Arc::new(index),
-                section: HashMap::from_iter([(
-                    TemplateKey::Default,
-                    Arc::new(section) as RenderSection,
-                )]),
-                page: HashMap::from_iter([(TemplateKey::Default, Arc::new(page) as RenderPage)]),
-                taxonomy: HashMap::new(),
-                taxonomy_term: HashMap::new(),
-                not_found: None,
-            },
-            ..self.coerce()
+    #[test]
+    fn test_apply_profile() {
+        let (_test_site, mut site) = profile_test_site("apply_profile");
+
+        site.apply_profile("production").unwrap();
+
+        // Registering a profile again replaces the earlier one, and settings
+        // the profile doesn't override are left as they were.
+        assert_eq!(site.base_url(), "https://www.example.com");
+        assert!(!site.include_drafts);
+        assert_eq!(
+            site.profile.as_ref().map(BuildProfile::name),
+            Some("production")
+        );
+
+        let (_test_site, mut site) = profile_test_site("apply_profile_dev");
+
+        site.apply_profile("dev").unwrap();
+
+        assert_eq!(site.base_url(), "https://example.com");
+        assert!(site.include_drafts);
+
+        let result = site.apply_profile("staging");
+        assert!(
+            matches!(&result, Err(err) if err.to_string() == r#"unknown build profile: "staging""#),
+            "expected an unknown profile, got {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_profile_is_available_to_templates() {
+        let (_test_site, mut site) = profile_test_site("profile_templates");
+        site.apply_profile("dev").unwrap();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        assert!(rendered["/hello/"].contains(r#"Some("dev") false"#));
+        assert!(rendered.contains_key("/draft/"));
+
+        let (_test_site, mut site) = profile_test_site("profile_templates_production");
+        site.apply_profile("production").unwrap();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        assert!(rendered["/hello/"].contains(r#"Some("production") true"#));
+        assert!(!rendered.contains_key("/draft/"));
+    }
+
+    #[test]
+    fn test_template_assets_are_injected() {
+        let test_site = TestSite::new("template_assets");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("docs/_index.md", "+++\ntemplate = \"docs\"\n+++\n")
+            .write("essay.md", "+++\ntemplate = \"prose\"\n+++\n")
+            .write("plain.md", "+++\n+++\n");
+
+        let mut site = test_site
+            .builder()
+            .add_section_template_with_assets(
+                "docs",
+                |_| html().child(head()).child(body()),
+                &["/docs.css"],
+            )
+            .add_page_template_with_assets(
+                "prose",
+                |_| {
+                    html()
+                        .child(head().child(title().child("Essay")))
+                        .child(body().child(p()))
+                },
+                &["/prose.css", "/prose.js?v=1"],
+            )
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        // Stylesheets go at the end of the `<head>`, and scripts at the end of the `<body>`.
+        assert!(rendered["/essay/"].contains(
+            r#"<head><title>Essay</title><link rel="stylesheet" href="/prose.css"></head>"#
+        ));
+        assert!(
+            rendered["/essay/"].contains(r#"<p></p><script src="/prose.js?v=1"></script></body>"#)
+        );
+        assert!(
+            rendered["/docs/"].contains(r#"<head><link rel="stylesheet" href="/docs.css"></head>"#)
+        );
+
+        for path in ["/", "/plain/"] {
+            assert!(!rendered[path].contains("<link"), "{path}");
+            assert!(!rendered[path].contains("<script"), "{path}");
         }
     }
-}
 
-pub struct WithTemplates;
+    #[test]
+    fn test_generated_pages() {
+        let test_site = TestSite::new("generated_pages");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("hello.md", "+++\ntitle = \"Hello\"\n+++\n");
+
+        let mut site = test_site
+            .builder()
+            .add_generated_page("search", |ctx| {
+                html().child(
+                    body()
+                        .child(p().child(format!("{} {}", ctx.path, ctx.permalink)))
+                        .child(a().href("@/hello.md").child("Hello")),
+                )
+            })
+            .build();
+        site.load().unwrap();
 
-impl SiteBuilder<WithTemplates> {
-    pub fn add_section_template(
-        mut self,
-        name: impl Into<String>,
-        template: impl Fn(&RenderSectionContext) -> HtmlElement + Send + Sync + 'static,
-    ) -> Self {
-        self.templates
-            .section
-            .insert(TemplateKey::Custom(name.into()), Arc::new(template));
-        self
+        let rendered = render(&mut site).unwrap();
+
+        // Generated pages are finished like any other page, so `@/` links are resolved.
+        let search = &rendered["/search/"];
+        assert!(search.contains("<p>search https://example.com/search/</p>"));
+        assert!(search.contains(r#"<a href="https://example.com/hello/">Hello</a>"#));
     }
 
-    pub fn add_page_template(
-        mut self,
-        name: impl Into<String>,
-        template: impl Fn(&RenderPageContext) -> HtmlElement + Send + Sync + 'static,
-    ) -> Self {
-        self.templates
-            .page
-            .insert(TemplateKey::Custom(name.into()), Arc::new(template));
-        self
+    #[test]
+    fn test_home_page() {
+        for with_root_section in [false, true] {
+            let test_site = TestSite::new(&format!("home_page_{with_root_section}"));
+            test_site.write("blog/_index.md", "+++\ntitle = \"Blog\"\n+++\n");
+            if with_root_section {
+                test_site.write("_index.md", "+++\ntitle = \"Root\"\n+++\n");
+            }
+
+            let mut site = test_site
+                .builder()
+                .with_home_page(|ctx| html().child(body().child(h1().child(ctx.permalink))))
+                .build();
+            site.load().unwrap();
+
+            let rendered = render(&mut site).unwrap();
+
+            // The home page takes the place of the root section, even when it exists.
+            assert!(
+                rendered["/"].contains("<h1>https://example.com/</h1>"),
+                "{with_root_section}: {}",
+                rendered["/"]
+            );
+            assert!(!rendered["/"].contains("Root"), "{with_root_section}");
+            assert!(rendered.contains_key("/blog/"), "{with_root_section}");
+        }
     }
 
-    pub fn add_404_template(
-        mut self,
-        template: impl Fn(&BaseRenderContext) -> HtmlElement + Send + Sync + 'static,
-    ) -> Self {
-        self.templates.not_found = Some(Arc::new(template));
-        self
+    #[test]
+    fn test_taxonomy_term_metadata() {
+        let test_site = TestSite::new("taxonomy_term_metadata");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write(
+                "hello.md",
+                "+++\ntitle = \"Hello\"\n\n[taxonomies]\ntags = [\"Rust\", \"go\"]\n+++\n",
+            )
+            .write(
+                "tags/rust/_index.md",
+                indoc! {r#"
+                    +++
+                    title = "The Rust Programming Language"
+                    description = "Posts about Rust."
+
+                    [extra]
+                    icon = "crab"
+                    +++
+                "#},
+            );
+
+        let mut site = test_site
+            .builder()
+            .add_taxonomy(
+                Taxonomy::new("tags"),
+                |_| html(),
+                |ctx| {
+                    let term = &ctx.term;
+                    html().child(body().child(p().child(format!(
+                        "{} | {:?} | {:?} | {:?}",
+                        term.name,
+                        term.title,
+                        term.description,
+                        term.extra.get("icon").and_then(|icon| icon.as_str())
+                    ))))
+                },
+            )
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        // The metadata is matched to the term by its slug.
+        let expected = r#"<p>Rust | Some("The Rust Programming Language") | Some("Posts about Rust.") | Some("crab")</p>"#;
+        assert!(rendered["/tags/Rust/"].contains(expected));
+        assert!(rendered["/tags/go/"].contains("<p>go | None | None | None</p>"));
+        assert!(!site
+            .sections
+            .contains_key(&test_site.content_path().join("tags/rust/_index.md")));
     }
 
-    pub fn with_markdown_components(
-        mut self,
-        markdown_components: impl MarkdownComponents + Send + Sync + 'static,
-    ) -> Self {
-        self.markdown_components = Box::new(markdown_components);
-        self
+    #[test]
+    fn test_page_ancestors_and_parent_section() {
+        let test_site = TestSite::new("page_ancestors");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("about.md", "+++\n+++\n")
+            .write("docs/_index.md", "+++\n+++\n")
+            .write("docs/guides/_index.md", "+++\n+++\n")
+            .write("docs/guides/setup.md", "+++\n+++\n")
+            .write("docs/archive/_index.md", "+++\ntransparent = true\n+++\n")
+            .write("docs/archive/old.md", "+++\n+++\n");
+
+        let mut site = Site::builder()
+            .root(test_site.root_path())
+            .base_url("https://example.com")
+            .templates(
+                |_| html(),
+                |_| html(),
+                |ctx| {
+                    let ancestors = ctx
+                        .ancestors
+                        .iter()
+                        .map(|section| section.permalink)
+                        .collect::<Vec<_>>();
+                    let parent_section =
+                        ctx.parent_section.as_ref().map(|section| section.permalink);
+
+                    html().child(body().child(p().child(format!(
+                        "{} | {}",
+                        ancestors.join(" > "),
+                        parent_section.unwrap_or("none")
+                    ))))
+                },
+            )
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        let trail = |path: &str| {
+            let html = &rendered[path];
+            let start = html.find("<p>").unwrap() + "<p>".len();
+            let end = html.find("</p>").unwrap();
+            html[start..end].to_string()
+        };
+
+        assert_eq!(
+            trail("/about/"),
+            "https://example.com/ | https://example.com/"
+        );
+        assert_eq!(
+            trail("/docs/guides/setup/"),
+            "https://example.com/ > https://example.com/docs/ > https://example.com/docs/guides/ | https://example.com/docs/guides/"
+        );
+        // A page in a transparent section belongs to the nearest section that isn't transparent.
+        assert_eq!(
+            trail("/docs/archive/old/"),
+            "https://example.com/ > https://example.com/docs/ | https://example.com/docs/"
+        );
     }
 
-    pub fn add_shortcode(mut self, name: impl Into<String>, shortcode: Shortcode) -> Self {
-        self.shortcodes.insert(name.into(), shortcode);
-        self
+    #[test]
+    fn test_error_pages() {
+        let test_site = TestSite::new("error_pages");
+        test_site.write("_index.md", "+++\n+++\n");
+
+        let mut site = test_site
+            .builder()
+            .add_error_template(403, |ctx| {
+                html().child(body().child(h1().child(format!("{}: Forbidden", ctx.status))))
+            })
+            .add_error_template(500, |ctx| {
+                html().child(body().child(h1().child(format!(
+                    "{}: {}",
+                    ctx.status,
+                    ctx.error.unwrap_or("Server Error")
+                ))))
+            })
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        assert!(rendered["/403.html"].contains("<h1>403: Forbidden</h1>"));
+        assert!(rendered["/50x.html"].contains("<h1>500: Server Error</h1>"));
+        assert!(rendered["/404.html"].contains("<h1>404: Page Not Found</h1>"));
+        for path in ["403.html", "50x.html", "404.html"] {
+            assert!(!rendered["/sitemap.xml"].contains(path), "{path}");
+        }
     }
 
-    pub fn add_taxonomy(
-        mut self,
-        taxonomy: Taxonomy,
-        template: impl Fn(&RenderTaxonomyContext) -> HtmlElement + Send + Sync + 'static,
-        term_template: impl Fn(&RenderTaxonomyTermContext) -> HtmlElement + Send + Sync + 'static,
-    ) -> Self {
-        self.templates
-            .taxonomy
-            .insert(taxonomy.name.clone(), Arc::new(template));
-        self.templates
-            .taxonomy_term
-            .insert(taxonomy.name.clone(), Arc::new(term_template));
-        self.taxonomies.push(taxonomy);
-        self
+    #[test]
+    fn test_build_error_page() {
+        let test_site = TestSite::new("build_error_page");
+        test_site.write("_index.md", "+++\n+++\n");
+
+        let site = test_site.load();
+        site.render_build_error_page("expected `+++` in <hello.md>")
+            .unwrap();
+
+        let content = site.content.read().unwrap();
+        let build_error_page = String::from_utf8_lossy(&content["/50x.html"]);
+        assert!(build_error_page.contains("<h1>500: Build Failed</h1>"));
+        assert!(build_error_page.contains("<pre>expected `+++` in &lt;hello.md&gt;</pre>"));
     }
 
-    pub fn with_sass(self, sass_path: impl AsRef<Path>) -> SiteBuilder<WithSass> {
-        SiteBuilder {
-            sass_path: Some(sass_path.as_ref().to_owned()),
-            ..self.coerce()
+    #[test]
+    fn test_no_index() {
+        let test_site = TestSite::new("no_index");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write(
+                "public.md",
+                "+++\ntitle = \"Public\"\ndate = \"2024-01-01\"\n+++\n",
+            )
+            .write(
+                "hidden.md",
+                "+++\ntitle = \"Hidden\"\ndate = \"2024-01-02\"\nno_index = true\n+++\n",
+            )
+            .write("drafts/_index.md", "+++\nno_index = true\n+++\n")
+            .write("drafts/wip.md", "+++\ntitle = \"WIP\"\n+++\n");
+
+        let mut site = Site::builder()
+            .root(test_site.root_path())
+            .base_url("https://example.com")
+            .templates(
+                |_| html(),
+                |ctx| html().child(head().children(ctx.robots_meta())),
+                |ctx| html().child(head().children(ctx.robots_meta())),
+            )
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        let noindex = r#"<meta name="robots" content="noindex">"#;
+        assert!(rendered["/hidden/"].contains(noindex));
+        assert!(rendered["/drafts/"].contains(noindex));
+        assert!(!rendered["/public/"].contains(noindex));
+        assert!(!rendered["/drafts/wip/"].contains(noindex));
+
+        let sitemap = &rendered["/sitemap.xml"];
+        assert!(sitemap.contains("<loc>https://example.com/public/</loc>"));
+        assert!(!sitemap.contains("https://example.com/hidden/"));
+        assert!(!sitemap.contains("<loc>https://example.com/drafts/</loc>"));
+        // The pages in a `no_index` section are still indexed.
+        assert!(sitemap.contains("<loc>https://example.com/drafts/wip/</loc>"));
+
+        let feed = &rendered["/atom.xml"];
+        assert!(feed.contains("https://example.com/public/"));
+        assert!(!feed.contains("https://example.com/hidden/"));
+    }
+
+    #[test]
+    fn test_auxiliary_outputs_can_be_turned_off() {
+        let test_site = TestSite::new("auxiliary_outputs");
+        test_site
+            .write("_index.md", "+++\n+++\n")
+            .write("blog/_index.md", "+++\ngenerate_feed = true\n+++\n")
+            .write(
+                "blog/hello.md",
+                "+++\ntitle = \"Hello\"\ndate = \"2024-01-01\"\n\n[taxonomies]\ntags = [\"rust\"]\n+++\n",
+            );
+        let auxiliary_outputs = [
+            "/sitemap.xml",
+            "/robots.txt",
+            "/atom.xml",
+            "/blog/atom.xml",
+            "/tags/rust/atom.xml",
+            "/notes.xml",
+            "/404.html",
+        ];
+
+        let build = |enabled: bool| {
+            let mut site = test_site
+                .builder()
+                .add_taxonomy(Taxonomy::new("tags"), |_| html(), |_| html())
+                .add_feed("notes.xml", |_| true)
+                .sitemap(enabled)
+                .robots_txt(enabled)
+                .feeds(enabled)
+                .not_found_page(enabled)
+                .build();
+            site.load().unwrap();
+            render(&mut site).unwrap()
+        };
+
+        let rendered = build(true);
+        for path in auxiliary_outputs {
+            assert!(rendered.contains_key(path), "expected {path}");
+        }
+        assert!(rendered["/robots.txt"].contains("Sitemap: https://example.com/sitemap.xml"));
+
+        let rendered = build(false);
+        for path in auxiliary_outputs {
+            assert!(!rendered.contains_key(path), "unexpected {path}");
         }
+        assert!(rendered.contains_key("/blog/hello/"));
+        assert!(rendered.contains_key("/tags/rust/"));
     }
 
-    pub fn build(self) -> Site {
-        self.build_site()
+    #[test]
+    fn test_robots_txt_without_sitemap() {
+        let test_site = TestSite::new("robots_txt_without_sitemap");
+        test_site.write("_index.md", "+++\n+++\n");
+
+        let mut site = test_site.builder().sitemap(false).build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        assert_eq!(
+            rendered["/robots.txt"],
+            "User-agent: *\nDisallow:\nAllow: /\n"
+        );
     }
-}
 
-pub struct WithSass;
+    #[test]
+    fn test_custom_feeds() {
+        let test_site = TestSite::new("custom_feeds");
+        test_site.write("_index.md", "+++\n+++\n");
+        for (slug, date, kind) in [
+            ("first-note", "2024-01-01", "note"),
+            ("second-note", "2024-01-02", "note"),
+            ("third-note", "2024-01-03", "note"),
+            ("article", "2024-01-04", "article"),
+        ] {
+            test_site.write(
+                format!("{slug}.md"),
+                &format!(
+                    "+++\ntitle = \"{slug}\"\ndate = \"{date}\"\n\n[taxonomies]\nkind = [\"{kind}\"]\n+++\n"
+                ),
+            );
+        }
 
-impl SiteBuilder<WithSass> {
-    pub fn add_sass_load_path(mut self, load_path: impl AsRef<Path>) -> Self {
-        self.sass_load_paths.push(load_path.as_ref().to_owned());
-        self
+        let is_note =
+            |page: &Page| page.meta.taxonomies.get("kind") == Some(&vec!["note".to_string()]);
+        let mut site = test_site
+            .builder()
+            .title("My Blog")
+            .add_feed("notes.xml", is_note)
+            .add_feed_with_options(
+                "recent-notes.xml",
+                FeedOptions::new().title("Notes").limit(2),
+                is_note,
+            )
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        let notes = &rendered["/notes.xml"];
+        assert!(notes.contains("<title>My Blog</title>"));
+        for slug in ["first-note", "second-note", "third-note"] {
+            assert!(
+                notes.contains(&format!("https://example.com/{slug}/")),
+                "{slug}"
+            );
+        }
+        assert!(!notes.contains("https://example.com/article/"));
+
+        // Only the most recent pages are kept.
+        let recent_notes = &rendered["/recent-notes.xml"];
+        assert!(recent_notes.contains("<title>My Blog - Notes</title>"));
+        assert!(recent_notes.contains("https://example.com/third-note/"));
+        assert!(recent_notes.contains("https://example.com/second-note/"));
+        assert!(!recent_notes.contains("https://example.com/first-note/"));
+        assert!(!recent_notes.contains("https://example.com/article/"));
     }
 
-    pub fn build(self) -> Site {
-        self.build_site()
+    #[test]
+    fn test_sitemap_covers_rendered_pages() {
+        let test_site = TestSite::new("sitemap_rendered_pages");
+        test_site.write("_index.md", "+++\n+++\n").write(
+            "hello.md",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                date = "2024-01-01"
+                updated = "2024-02-01"
+                aliases = ["/hi/"]
+
+                [taxonomies]
+                tags = ["rust"]
+                +++
+            "#},
+        );
+
+        let mut site = test_site
+            .builder()
+            .add_taxonomy(Taxonomy::new("tags"), |_| html(), |_| html())
+            .add_generated_page("search", |_| html())
+            .build();
+        site.load().unwrap();
+
+        let rendered = render(&mut site).unwrap();
+
+        let sitemap = &rendered["/sitemap.xml"];
+        let entries = [
+            "<url><loc>https://example.com/</loc></url>",
+            "<url><loc>https://example.com/hello/</loc><lastmod>2024-02-01</lastmod></url>",
+            "<url><loc>https://example.com/search/</loc></url>",
+            "<url><loc>https://example.com/tags/</loc></url>",
+            "<url><loc>https://example.com/tags/rust/</loc></url>",
+        ];
+        for entry in entries {
+            assert!(sitemap.contains(entry), "expected {entry} in {sitemap}");
+        }
+        // Aliases, error pages, and feeds are left out.
+        assert_eq!(sitemap.matches("<url>").count(), entries.len(), "{sitemap}");
     }
 }
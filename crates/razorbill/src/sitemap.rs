@@ -1,8 +1,6 @@
-use std::collections::HashSet;
-use std::path::PathBuf;
-
-use auk::renderer::HtmlElementRenderer;
-use auk::*;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
 
 use crate::permalink::Permalink;
 use crate::storage::Store;
@@ -14,83 +12,188 @@ pub struct SitemapEntry {
     pub updated_at: Option<String>,
 }
 
-pub fn render_sitemap(site: &Site, storage: &impl Store) {
-    let mut entries = HashSet::new();
+/// Renders the sitemap for the site, with an entry for each of the given
+/// permalinks.
+///
+/// The permalinks are those of every HTML page that was rendered, so that
+/// taxonomy pages, generated pages, and anything else that is rendered are all
+/// included, not just the site's content.
+pub fn render_sitemap(site: &Site, rendered_permalinks: Vec<Permalink>, storage: &impl Store) {
+    let mut excluded = site
+        .sections
+        .values()
+        .filter(|section| section.meta.no_index)
+        .map(|section| section.permalink.clone())
+        .chain(
+            site.pages
+                .values()
+                .filter(|page| page.meta.no_index)
+                .map(|page| page.permalink.clone()),
+        )
+        .chain(site.error_page_permalinks())
+        .collect::<HashSet<_>>();
+
+    // Aliases only redirect to other pages, so they don't belong in the sitemap.
+    excluded.extend(
+        site.alias_redirects()
+            .into_iter()
+            .map(|(alias, _permalink)| alias),
+    );
+
+    let updated_at = site
+        .pages
+        .values()
+        .filter_map(|page| {
+            let updated_at = page.meta.updated.as_ref().or(page.meta.date.as_ref())?;
+            Some((&page.permalink, updated_at))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let entries = rendered_permalinks
+        .into_iter()
+        .filter(|permalink| !excluded.contains(permalink))
+        .map(|permalink| SitemapEntry {
+            updated_at: updated_at.get(&permalink).map(|&date| date.clone()),
+            permalink,
+        })
+        .collect::<Vec<_>>();
 
-    for section in site.sections.values() {
-        entries.insert(SitemapEntry {
-            permalink: section.permalink.clone(),
-            updated_at: None,
-        });
-    }
+    storage
+        .store_content_streaming(
+            Permalink::from_path(&site.config, "sitemap.xml"),
+            |writer| {
+                let mut sitemap_writer = SitemapWriter::new(writer)?;
 
-    for page in site.pages.values() {
-        entries.insert(SitemapEntry {
-            permalink: page.permalink.clone(),
-            updated_at: page
-                .meta
-                .updated
-                .as_ref()
-                .or(page.meta.date.as_ref())
-                .cloned(),
-        });
-    }
+                for entry in &entries {
+                    sitemap_writer.write_entry(entry)?;
+                }
 
-    for (taxonomy, terms_by_page) in &site.taxonomies {
-        entries.insert(SitemapEntry {
-            permalink: Permalink::from_path(&site.config, taxonomy.as_str()),
-            updated_at: None,
-        });
+                sitemap_writer.finish()?;
 
-        for (term, _pages) in terms_by_page {
-            entries.insert(SitemapEntry {
-                permalink: Permalink::from_path(&site.config, &format!("{taxonomy}/{term}")),
-                updated_at: None,
-            });
-        }
-    }
+                Ok(())
+            },
+        )
+        .unwrap();
+}
 
-    let mut entries = entries.into_iter().collect::<Vec<_>>();
-    entries.sort();
+/// A writer that serializes a sitemap incrementally.
+///
+/// Only a single entry is held in memory at a time, so sitemaps with a large
+/// number of URLs can be written without building the whole element tree.
+pub struct SitemapWriter<W: Write> {
+    writer: W,
+}
 
-    let rendered = HtmlElementRenderer::new()
-        .render_to_string(&sitemap_template(entries))
-        .unwrap();
+impl<W: Write> SitemapWriter<W> {
+    /// Returns a new [`SitemapWriter`] after writing the sitemap header to `writer`.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        const XML_PROLOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
 
-    const XML_PROLOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+        writeln!(writer, "{XML_PROLOG}")?;
+        write!(
+            writer,
+            r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#
+        )?;
 
-    let sitemap_xml = format!("{XML_PROLOG}\n{rendered}");
+        Ok(Self { writer })
+    }
 
-    storage
-        .store_static_file(&PathBuf::from("sitemap.xml"), sitemap_xml)
-        .unwrap();
-}
+    /// Writes the given [`SitemapEntry`] to the sitemap.
+    ///
+    /// Entries are written out directly, rather than by building and rendering
+    /// an element tree, as sitemaps can contain tens of thousands of them.
+    pub fn write_entry(&mut self, entry: &SitemapEntry) -> io::Result<()> {
+        write!(
+            self.writer,
+            "<url><loc>{}</loc>",
+            escape_text(entry.permalink.as_str())
+        )?;
+
+        if let Some(updated_at) = entry.updated_at.as_ref() {
+            write!(
+                self.writer,
+                "<lastmod>{}</lastmod>",
+                escape_text(updated_at)
+            )?;
+        }
 
-pub fn sitemap_template(entries: Vec<SitemapEntry>) -> HtmlElement {
-    urlset()
-        .attr("xmlns", "http://www.sitemaps.org/schemas/sitemap/0.9")
-        .children(entries.into_iter().map(|entry| {
-            url().child(loc().child(entry.permalink.as_str())).children(
-                entry
-                    .updated_at
-                    .as_ref()
-                    .map(|updated_at| lastmod().child(updated_at)),
-            )
-        }))
-}
+        write!(self.writer, "</url>")
+    }
 
-fn urlset() -> HtmlElement {
-    HtmlElement::new("urlset")
-}
+    /// Closes the sitemap and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        write!(self.writer, "</urlset>")?;
+        self.writer.flush()?;
 
-fn url() -> HtmlElement {
-    HtmlElement::new("url")
+        Ok(self.writer)
+    }
 }
 
-fn loc() -> HtmlElement {
-    HtmlElement::new("loc")
+/// Escapes the characters that are not allowed in XML text.
+fn escape_text(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>']) {
+        return Cow::Borrowed(text);
+    }
+
+    Cow::Owned(
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+    )
 }
 
-fn lastmod() -> HtmlElement {
-    HtmlElement::new("lastmod")
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::SiteConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_sitemap_writer() {
+        let config = SiteConfig::for_tests("https://example.com");
+
+        let entries = vec![
+            SitemapEntry {
+                permalink: Permalink::from_path(&config, "/"),
+                updated_at: None,
+            },
+            SitemapEntry {
+                permalink: Permalink::from_path(&config, "/blog/hello-world"),
+                updated_at: Some("2024-01-01".to_string()),
+            },
+        ];
+
+        let mut sitemap_writer = SitemapWriter::new(Vec::new()).unwrap();
+        for entry in &entries {
+            sitemap_writer.write_entry(entry).unwrap();
+        }
+        let sitemap_xml = String::from_utf8(sitemap_writer.finish().unwrap()).unwrap();
+
+        assert_eq!(
+            sitemap_xml,
+            [
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                "\n",
+                r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
+                "<url><loc>https://example.com/</loc></url>",
+                "<url><loc>https://example.com/blog/hello-world/</loc><lastmod>2024-01-01</lastmod></url>",
+                "</urlset>"
+            ]
+            .join("")
+        );
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            escape_text("https://example.com/"),
+            Cow::Borrowed("https://example.com/")
+        );
+        assert_eq!(
+            escape_text("https://example.com/?a=1&b=2"),
+            Cow::<str>::Owned("https://example.com/?a=1&amp;b=2".to_string())
+        );
+    }
 }
@@ -0,0 +1,300 @@
+//! Generating social card images for pages.
+//!
+//! Each card is laid out as an SVG document, which is then rasterized to a PNG
+//! by a [`SocialCardBackend`]. This keeps razorbill free of any particular
+//! drawing library, so sites can plug in whichever one they prefer (e.g.,
+//! [`resvg`](https://docs.rs/resvg)).
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::manifest::hex_digest;
+
+/// The name of the social card image within a page's output directory.
+pub(crate) const SOCIAL_CARD_FILE_NAME: &str = "social.png";
+
+/// The details shown on a social card.
+#[derive(Debug, Clone, Copy)]
+pub struct SocialCard<'a> {
+    pub title: &'a str,
+    pub site_name: Option<&'a str>,
+    pub date: Option<&'a str>,
+}
+
+/// A backend that rasterizes social cards.
+pub trait SocialCardBackend: Send + Sync {
+    /// Renders the given SVG document to a PNG, using the given font files
+    /// for the text.
+    fn render_png(
+        &self,
+        svg: &str,
+        fonts: &[PathBuf],
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+}
+
+/// The configuration for generating social cards.
+pub struct SocialCards {
+    backend: Box<dyn SocialCardBackend>,
+    fonts: Vec<PathBuf>,
+    font_family: String,
+    background: Option<PathBuf>,
+    width: u32,
+    height: u32,
+}
+
+impl SocialCards {
+    /// Returns a new [`SocialCards`] that renders cards using the given backend.
+    ///
+    /// Cards are 1200x630 by default, which is the size recommended by most
+    /// social networks.
+    pub fn new(backend: impl SocialCardBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+            fonts: Vec::new(),
+            font_family: "sans-serif".to_string(),
+            background: None,
+            width: 1200,
+            height: 630,
+        }
+    }
+
+    /// Adds a font file that is made available to the backend.
+    pub fn font(mut self, path: impl AsRef<Path>) -> Self {
+        self.fonts.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the font family used for the text on the card.
+    pub fn font_family(mut self, font_family: impl Into<String>) -> Self {
+        self.font_family = font_family.into();
+        self
+    }
+
+    /// Sets the image drawn behind the text on the card.
+    pub fn background(mut self, path: impl AsRef<Path>) -> Self {
+        self.background = Some(path.as_ref().to_owned());
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Renders the given card to a PNG.
+    pub fn render(&self, card: &SocialCard) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        self.backend.render_png(&self.to_svg(card), &self.fonts)
+    }
+
+    /// Returns the key that the rendered image for the given card is cached
+    /// under.
+    ///
+    /// The key covers everything that is drawn on the card (its title, site
+    /// name, and date, along with the layout, background, and fonts), so it
+    /// changes whenever the image would.
+    pub(crate) fn cache_key(&self, card: &SocialCard) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.to_svg(card));
+        for font in &self.fonts {
+            hasher.update([0]);
+            hasher.update(font.to_string_lossy().as_bytes());
+        }
+
+        hex_digest(hasher.finalize().as_slice())
+    }
+
+    /// Lays out the given card as an SVG document.
+    pub fn to_svg(&self, card: &SocialCard) -> String {
+        let (width, height) = (self.width, self.height);
+        let padding = width / 15;
+        // The font sizes are clamped so that tiny cards still have legible
+        // (and non-zero) text.
+        let title_size = (width / 19).max(2);
+        let detail_size = (width / 40).max(1);
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+
+        match self.background.as_ref() {
+            Some(background) => svg.push_str(&format!(
+                r#"<image x="0" y="0" width="{width}" height="{height}" preserveAspectRatio="xMidYMid slice" xlink:href="{}"/>"#,
+                escape_xml(&background.to_string_lossy())
+            )),
+            None => svg.push_str(&format!(
+                r##"<rect width="{width}" height="{height}" fill="#1e1e2e"/>"##
+            )),
+        }
+
+        svg.push_str(&format!(
+            r##"<g font-family="{}" fill="#ffffff">"##,
+            escape_xml(&self.font_family)
+        ));
+
+        // Roughly how many characters fit on a line, given the average width
+        // of a character is about half of the font size.
+        let max_line_len = (width.saturating_sub(padding * 2) / (title_size / 2)) as usize;
+
+        for (index, line) in wrap_text(card.title, max_line_len).iter().enumerate() {
+            let y = padding + title_size * (index as u32 + 1) * 6 / 5;
+
+            svg.push_str(&format!(
+                r#"<text x="{padding}" y="{y}" font-size="{title_size}" font-weight="bold">{}</text>"#,
+                escape_xml(line)
+            ));
+        }
+
+        let details = [card.site_name, card.date]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" · ");
+        if !details.is_empty() {
+            svg.push_str(&format!(
+                r#"<text x="{padding}" y="{}" font-size="{detail_size}">{}</text>"#,
+                height.saturating_sub(padding),
+                escape_xml(&details)
+            ));
+        }
+
+        svg.push_str("</g></svg>");
+        svg
+    }
+}
+
+/// Wraps the given text into lines of at most `max_line_len` characters,
+/// breaking on whitespace.
+fn wrap_text(text: &str, max_line_len: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_line_len {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct SvgBackend;
+
+    impl SocialCardBackend for SvgBackend {
+        fn render_png(
+            &self,
+            svg: &str,
+            _fonts: &[PathBuf],
+        ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+            Ok(svg.as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(
+            wrap_text("The quick brown fox jumps over the lazy dog", 16),
+            vec!["The quick brown", "fox jumps over", "the lazy dog"]
+        );
+        assert_eq!(
+            wrap_text("Supercalifragilistic", 5),
+            vec!["Supercalifragilistic"]
+        );
+    }
+
+    #[test]
+    fn test_social_card_svg() {
+        let social_cards = SocialCards::new(SvgBackend).font_family("Inter");
+
+        let svg = social_cards.to_svg(&SocialCard {
+            title: "Tips & Tricks",
+            site_name: Some("My Blog"),
+            date: Some("2024-01-01"),
+        });
+
+        assert!(svg.contains(r##"<g font-family="Inter" fill="#ffffff">"##));
+        assert!(svg.contains(">Tips &amp; Tricks</text>"));
+        assert!(svg.contains(">My Blog · 2024-01-01</text>"));
+    }
+
+    #[test]
+    fn test_social_card_svg_with_tiny_size() {
+        let card = SocialCard {
+            title: "Tips & Tricks",
+            site_name: Some("My Blog"),
+            date: None,
+        };
+
+        for (width, height) in [(0, 0), (1, 1), (37, 20), (1200, 10)] {
+            let svg = SocialCards::new(SvgBackend)
+                .size(width, height)
+                .to_svg(&card);
+
+            assert!(svg.contains("Tips"), "{width}x{height}: {svg}");
+            assert!(svg.contains(">My Blog</text>"), "{width}x{height}: {svg}");
+        }
+    }
+
+    #[test]
+    fn test_social_card_cache_key() {
+        let social_cards = SocialCards::new(SvgBackend);
+        let card = SocialCard {
+            title: "Hello",
+            site_name: Some("My Blog"),
+            date: Some("2024-01-01"),
+        };
+
+        let key = social_cards.cache_key(&card);
+        assert_eq!(social_cards.cache_key(&card), key);
+
+        for changed in [
+            SocialCard {
+                title: "Goodbye",
+                ..card
+            },
+            SocialCard {
+                date: Some("2024-01-02"),
+                ..card
+            },
+        ] {
+            assert_ne!(social_cards.cache_key(&changed), key);
+        }
+
+        assert_ne!(
+            SocialCards::new(SvgBackend)
+                .background("background.png")
+                .cache_key(&card),
+            key
+        );
+        assert_ne!(
+            SocialCards::new(SvgBackend)
+                .font("Inter.ttf")
+                .cache_key(&card),
+            key
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Filtering of the files copied from the static directory.
+//!
+//! Patterns are matched against the path of each file relative to the static
+//! directory, using `/` as the separator:
+//!
+//! - `*` matches any part of a path segment (e.g., `*.map`)
+//! - `?` matches a single character within a path segment
+//! - `**` matches any number of path segments (e.g., `drafts/**`)
+//!
+//! Patterns without a `/` are matched against the file name alone, so `*.map`
+//! matches source maps anywhere in the static directory.
+//!
+//! ```
+//! use razorbill::static_files::StaticFiles;
+//! use razorbill::Site;
+//!
+//! let builder = Site::builder().static_files(
+//!     StaticFiles::new()
+//!         .exclude("fonts/src/**")
+//!         .include("*.css")
+//!         .include("*.woff2"),
+//! );
+//! ```
+
+use std::path::Path;
+
+/// The patterns excluded from the static directory by default.
+pub const DEFAULT_EXCLUDES: &[&str] = &[".DS_Store", "Thumbs.db", "*.map"];
+
+/// How files from the static directory are placed in the output directory.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum CopyMode {
+    /// The files are copied.
+    ///
+    /// On filesystems that support it (e.g., APFS, Btrfs, and XFS), the
+    /// operating system may clone the file instead, sharing its contents with
+    /// the original until either is modified.
+    #[default]
+    Copy,
+    /// The files are hardlinked, so that they take up no extra space and
+    /// don't need to be copied on each build.
+    ///
+    /// Files are copied instead when they can't be hardlinked (e.g., when the
+    /// output directory is on a different filesystem).
+    ///
+    /// Since the output shares the files with the static directory, changes
+    /// to a file in the output directory also change the original.
+    Hardlink,
+}
+
+/// The files to copy from the static directory.
+#[derive(Debug, Clone)]
+pub struct StaticFiles {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    pub(crate) copy_mode: CopyMode,
+}
+
+impl Default for StaticFiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StaticFiles {
+    /// Returns a new [`StaticFiles`] that copies everything except for the
+    /// [`DEFAULT_EXCLUDES`].
+    pub fn new() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: DEFAULT_EXCLUDES
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+            copy_mode: CopyMode::default(),
+        }
+    }
+
+    /// Returns a new [`StaticFiles`] that copies everything.
+    pub fn all() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            copy_mode: CopyMode::default(),
+        }
+    }
+
+    /// Only copies files that match the given pattern (or any of the other
+    /// included patterns).
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Doesn't copy files that match the given pattern, even if they are
+    /// included.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Sets how the files are placed in the output directory.
+    pub fn copy_mode(mut self, copy_mode: CopyMode) -> Self {
+        self.copy_mode = copy_mode;
+        self
+    }
+
+    /// Returns whether the file at the given path, relative to the static
+    /// directory, should be copied.
+    pub fn is_included(&self, path: &Path) -> bool {
+        let path = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let is_included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| matches_path(pattern, &path));
+
+        is_included
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| matches_path(pattern, &path))
+    }
+}
+
+/// Returns whether the given `/`-separated path matches the pattern.
+fn matches_path(pattern: &str, path: &str) -> bool {
+    if !pattern.contains('/') {
+        let filename = path.rsplit('/').next().unwrap_or(path);
+        return matches_segment(pattern.as_bytes(), filename.as_bytes());
+    }
+
+    let pattern = pattern
+        .trim_start_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+    let path = path.split('/').collect::<Vec<_>>();
+
+    matches_segments(&pattern, &path)
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_segments(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path)) => {
+                matches_segment(segment.as_bytes(), name.as_bytes()) && matches_segments(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Returns whether a single path segment matches the pattern, which may
+/// contain `*` and `?` wildcards.
+fn matches_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| matches_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && matches_segment(rest, &name[1..]),
+        Some((char, rest)) => name
+            .split_first()
+            .is_some_and(|(name_char, name)| name_char == char && matches_segment(rest, name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_path() {
+        assert!(matches_path("*.map", "js/app.js.map"));
+        assert!(!matches_path("*.map", "js/app.js"));
+        assert!(matches_path(".DS_Store", "img/.DS_Store"));
+        assert!(matches_path("img/*.png", "img/cover.png"));
+        assert!(!matches_path("img/*.png", "img/posts/cover.png"));
+        assert!(matches_path("img/**/*.png", "img/cover.png"));
+        assert!(matches_path("img/**/*.png", "img/posts/2024/cover.png"));
+        assert!(matches_path("fonts/src/**", "fonts/src/inter/inter.glyphs"));
+        assert!(!matches_path("fonts/src/**", "fonts/inter.woff2"));
+        assert!(matches_path("icon-??.png", "icon-32.png"));
+        assert!(!matches_path("icon-??.png", "icon-128.png"));
+    }
+
+    #[test]
+    fn test_static_files() {
+        let static_files = StaticFiles::new();
+        assert!(static_files.is_included(Path::new("css/style.css")));
+        assert!(!static_files.is_included(Path::new("css/.DS_Store")));
+        assert!(!static_files.is_included(Path::new("js/app.js.map")));
+
+        let static_files = StaticFiles::all().include("*.css").exclude("vendor/**");
+        assert!(static_files.is_included(Path::new("css/style.css")));
+        assert!(!static_files.is_included(Path::new("js/app.js")));
+        assert!(!static_files.is_included(Path::new("vendor/reset.css")));
+    }
+}
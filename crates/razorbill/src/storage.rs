@@ -1,33 +1,75 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File};
-use std::io::{self, Write};
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 use crate::content::{Page, Section};
-use crate::permalink::Permalink;
+use crate::manifest::{content_hash, hex_digest, BuildManifestEntry};
+use crate::permalink::{encode_path, Permalink};
+use crate::static_files::CopyMode;
 
 pub trait Store {
-    type Error: std::error::Error;
+    type Error: std::error::Error + From<io::Error>;
 
     fn store_rendered_section(
         &self,
         section: &Section,
         rendered_html: String,
     ) -> Result<(), Self::Error> {
-        self.store_content(section.permalink.clone(), rendered_html.clone())
+        self.store_content(section.permalink.clone(), rendered_html)
     }
 
     fn store_rendered_page(&self, page: &Page, rendered_html: String) -> Result<(), Self::Error> {
-        self.store_content(page.permalink.clone(), rendered_html.clone())
+        self.store_content(page.permalink.clone(), rendered_html)
     }
 
     fn store_content(&self, permalink: Permalink, content: String) -> Result<(), Self::Error>;
 
+    /// Stores content that is produced incrementally by `write`.
+    ///
+    /// The default implementation buffers the content in memory before
+    /// storing it. Stores that are able to write the content out as it is
+    /// produced should override this.
+    fn store_content_streaming<F>(&self, permalink: Permalink, write: F) -> Result<(), Self::Error>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        let mut buffer = Vec::new();
+        write(&mut buffer)?;
+
+        let content = String::from_utf8(buffer)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        self.store_content(permalink, content)
+    }
+
     fn store_static_file(&self, path: &Path, content: String) -> Result<(), Self::Error>;
+
+    /// Stores a generated file with binary content, such as an image.
+    fn store_binary_file(&self, path: &Path, content: Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Copies the file at `source_path` (e.g., from the static directory) to
+    /// the given path.
+    ///
+    /// Returns `false` if the file was left as-is because it hasn't changed
+    /// since it was last copied.
+    ///
+    /// Stores that don't write to disk may ignore the [`CopyMode`].
+    fn copy_file(
+        &self,
+        source_path: &Path,
+        path: &Path,
+        _mode: CopyMode,
+    ) -> Result<bool, Self::Error> {
+        self.store_binary_file(path, fs::read(source_path)?)?;
+
+        Ok(true)
+    }
 }
 
 pub struct DiskStorage {
@@ -40,20 +82,26 @@ impl DiskStorage {
     }
 }
 
-impl Store for DiskStorage {
-    type Error = io::Error;
-
-    fn store_content(&self, permalink: Permalink, content: String) -> Result<(), Self::Error> {
+impl DiskStorage {
+    fn content_output_path(&self, permalink: &Permalink) -> io::Result<PathBuf> {
         let output_path = self
             .output_path
-            .join(PathBuf::from_str(permalink.path().trim_start_matches("/")).unwrap());
+            .join(PathBuf::from_str(permalink.decoded_path().trim_start_matches("/")).unwrap());
 
-        let output_path = if permalink.path().ends_with('/') {
+        if permalink.path().ends_with('/') {
             fs::create_dir_all(&output_path)?;
-            output_path.join("index.html")
+            Ok(output_path.join("index.html"))
         } else {
-            output_path
-        };
+            Ok(output_path)
+        }
+    }
+}
+
+impl Store for DiskStorage {
+    type Error = io::Error;
+
+    fn store_content(&self, permalink: Permalink, content: String) -> Result<(), Self::Error> {
+        let output_path = self.content_output_path(&permalink)?;
 
         let mut output_file = File::create(&output_path)?;
         output_file.write_all(content.as_bytes())?;
@@ -61,6 +109,19 @@ impl Store for DiskStorage {
         Ok(())
     }
 
+    fn store_content_streaming<F>(&self, permalink: Permalink, write: F) -> Result<(), Self::Error>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        let output_path = self.content_output_path(&permalink)?;
+
+        let mut output_file = BufWriter::new(File::create(&output_path)?);
+        write(&mut output_file)?;
+        output_file.flush()?;
+
+        Ok(())
+    }
+
     fn store_static_file(&self, path: &Path, content: String) -> Result<(), Self::Error> {
         let mut output_dir = self.output_path.to_owned();
 
@@ -77,14 +138,72 @@ impl Store for DiskStorage {
 
         Ok(())
     }
+
+    fn store_binary_file(&self, path: &Path, content: Vec<u8>) -> Result<(), Self::Error> {
+        let output_path = self.output_path.join(path);
+
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(output_path, content)
+    }
+
+    fn copy_file(
+        &self,
+        source_path: &Path,
+        path: &Path,
+        mode: CopyMode,
+    ) -> Result<bool, Self::Error> {
+        let output_path = self.output_path.join(path);
+
+        // A copy that is the same size and was written after the source was
+        // last modified is assumed to be up to date.
+        if let (Ok(source), Ok(output)) = (fs::metadata(source_path), fs::metadata(&output_path)) {
+            if source.len() == output.len() && output.modified()? >= source.modified()? {
+                return Ok(false);
+            }
+        }
+
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // The output may be a hardlink to the source, in which case writing
+        // to it would overwrite the source.
+        if output_path.exists() {
+            fs::remove_file(&output_path)?;
+        }
+
+        if mode == CopyMode::Hardlink {
+            // Link to the file itself, rather than to a symlink pointing at it.
+            let source_path = fs::canonicalize(source_path)?;
+
+            // Hardlinks can't cross filesystems, so we fall back to copying.
+            if fs::hard_link(&source_path, &output_path).is_ok() {
+                return Ok(true);
+            }
+        }
+
+        fs::copy(source_path, output_path)?;
+
+        Ok(true)
+    }
 }
 
+/// The rendered content of a site, keyed by its decoded URL path.
+pub type SiteContent = Arc<RwLock<HashMap<String, Vec<u8>>>>;
+
+/// The URLs that the aliases of a site redirect to, keyed by the decoded URL
+/// path of the alias.
+pub type SiteAliases = Arc<RwLock<HashMap<String, String>>>;
+
 pub struct InMemoryStorage {
-    storage: Arc<RwLock<HashMap<String, String>>>,
+    storage: SiteContent,
 }
 
 impl InMemoryStorage {
-    pub fn new(storage: Arc<RwLock<HashMap<String, String>>>) -> Self {
+    pub fn new(storage: SiteContent) -> Self {
         Self { storage }
     }
 }
@@ -93,6 +212,9 @@ impl InMemoryStorage {
 pub enum InMemoryStorageError {
     #[error("poisoned")]
     Poisoned,
+
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
 }
 
 impl Store for InMemoryStorage {
@@ -102,17 +224,313 @@ impl Store for InMemoryStorage {
         self.storage
             .write()
             .map_err(|_| InMemoryStorageError::Poisoned)?
-            .insert(permalink.path().to_owned(), content);
+            .insert(permalink.decoded_path().into_owned(), content.into_bytes());
 
         Ok(())
     }
 
     fn store_static_file(&self, path: &Path, css: String) -> Result<(), Self::Error> {
+        self.store_binary_file(path, css.into_bytes())
+    }
+
+    fn store_binary_file(&self, path: &Path, content: Vec<u8>) -> Result<(), Self::Error> {
         self.storage
             .write()
             .map_err(|_| InMemoryStorageError::Poisoned)?
-            .insert(format!("/{}", path.to_string_lossy().to_string()), css);
+            .insert(format!("/{}", path.to_string_lossy().to_string()), content);
+
+        Ok(())
+    }
+}
+
+/// A [`Store`] that records each file stored through it, so that a
+/// [`BuildManifest`](crate::manifest::BuildManifest) can be produced for the build.
+pub(crate) struct RecordingStorage<S> {
+    inner: S,
+    entries: Mutex<BTreeMap<String, BuildManifestEntry>>,
+    /// The permalinks of the HTML pages that were stored.
+    html_permalinks: Mutex<BTreeSet<Permalink>>,
+}
+
+impl<S: Store> RecordingStorage<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            entries: Mutex::new(BTreeMap::new()),
+            html_permalinks: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Records a file that was written to the output without going through the store.
+    pub fn record_file(&self, path: &Path, content: &[u8]) {
+        let path = url_path(path);
+
+        self.record(BuildManifestEntry {
+            permalink: format!("/{}", encode_path(&path)),
+            path,
+            size: content.len() as u64,
+            hash: content_hash(content),
+            source: None,
+            links: Vec::new(),
+        });
+    }
+
+    /// Returns the permalinks of the HTML pages stored so far, in order.
+    pub fn html_permalinks(&self) -> Vec<Permalink> {
+        self.html_permalinks
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the inner store along with the entries for all of the recorded files,
+    /// ordered by path.
+    pub fn finish(self) -> (S, Vec<BuildManifestEntry>) {
+        let entries = self
+            .entries
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+            .into_values()
+            .collect();
+
+        (self.inner, entries)
+    }
+
+    fn record(&self, entry: BuildManifestEntry) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(entry.path.clone(), entry);
+    }
+
+    fn record_html_permalink(&self, permalink: &Permalink) {
+        if is_html(permalink) {
+            self.html_permalinks
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .insert(permalink.clone());
+        }
+    }
+
+    fn record_content(&self, permalink: &Permalink, content: &str, source: Option<&Path>) {
+        self.record_html_permalink(permalink);
+        self.record(BuildManifestEntry {
+            path: content_output_path(permalink),
+            permalink: permalink.path().to_owned(),
+            size: content.len() as u64,
+            hash: content_hash(content.as_bytes()),
+            source: source.map(Path::to_owned),
+            links: Vec::new(),
+        });
+    }
+}
+
+impl<S: Store> Store for RecordingStorage<S> {
+    type Error = S::Error;
+
+    fn store_rendered_section(
+        &self,
+        section: &Section,
+        rendered_html: String,
+    ) -> Result<(), Self::Error> {
+        self.record_content(&section.permalink, &rendered_html, Some(&section.file.path));
+
+        self.inner.store_rendered_section(section, rendered_html)
+    }
+
+    fn store_rendered_page(&self, page: &Page, rendered_html: String) -> Result<(), Self::Error> {
+        self.record_content(&page.permalink, &rendered_html, Some(&page.file.path));
+
+        self.inner.store_rendered_page(page, rendered_html)
+    }
+
+    fn store_content(&self, permalink: Permalink, content: String) -> Result<(), Self::Error> {
+        self.record_content(&permalink, &content, None);
+
+        self.inner.store_content(permalink, content)
+    }
+
+    fn store_content_streaming<F>(&self, permalink: Permalink, write: F) -> Result<(), Self::Error>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+
+        self.inner
+            .store_content_streaming(permalink.clone(), |writer| {
+                write(&mut HashingWriter {
+                    inner: writer,
+                    hasher: &mut hasher,
+                    size: &mut size,
+                })
+            })?;
+
+        self.record_html_permalink(&permalink);
+        self.record(BuildManifestEntry {
+            path: content_output_path(&permalink),
+            permalink: permalink.path().to_owned(),
+            size,
+            hash: hex_digest(hasher.finalize().as_slice()),
+            source: None,
+            links: Vec::new(),
+        });
 
         Ok(())
     }
+
+    fn store_static_file(&self, path: &Path, content: String) -> Result<(), Self::Error> {
+        self.record_file(path, content.as_bytes());
+
+        self.inner.store_static_file(path, content)
+    }
+
+    fn store_binary_file(&self, path: &Path, content: Vec<u8>) -> Result<(), Self::Error> {
+        self.record_file(path, &content);
+
+        self.inner.store_binary_file(path, content)
+    }
+
+    fn copy_file(
+        &self,
+        source_path: &Path,
+        path: &Path,
+        mode: CopyMode,
+    ) -> Result<bool, Self::Error> {
+        self.record_file(path, &fs::read(source_path)?);
+
+        self.inner.copy_file(source_path, path, mode)
+    }
+}
+
+/// A writer that hashes everything written through it.
+struct HashingWriter<'a> {
+    inner: &'a mut dyn Write,
+    hasher: &'a mut Sha256,
+    size: &'a mut u64,
+}
+
+impl<'a> Write for HashingWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        *self.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns whether the content with the given permalink is an HTML page.
+pub(crate) fn is_html(permalink: &Permalink) -> bool {
+    let path = permalink.path();
+    path.ends_with('/') || path.ends_with(".html")
+}
+
+/// Returns the path of the file that content with the given permalink is
+/// written to, relative to the output directory.
+pub(crate) fn content_output_path(permalink: &Permalink) -> String {
+    let decoded_path = permalink.decoded_path();
+    let path = decoded_path.trim_start_matches('/');
+
+    if permalink.path().ends_with('/') {
+        format!("{path}index.html")
+    } else {
+        path.to_owned()
+    }
+}
+
+/// Returns the given relative path with `/` separators.
+fn url_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::SiteConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_recording_storage() {
+        let config = SiteConfig::for_tests("https://example.com");
+
+        let storage = RecordingStorage::new(InMemoryStorage::new(Arc::default()));
+
+        storage
+            .store_content(
+                Permalink::from_path(&config, "/blog/hello-world"),
+                "Hello, world!".to_string(),
+            )
+            .unwrap();
+        storage
+            .store_content_streaming(Permalink::from_path(&config, "atom.xml"), |writer| {
+                writer.write_all(b"Hello, ")?;
+                writer.write_all(b"world!")
+            })
+            .unwrap();
+        storage
+            .store_static_file(Path::new("css/style.css"), "body {}".to_string())
+            .unwrap();
+
+        assert_eq!(
+            storage.html_permalinks(),
+            vec![Permalink::from_path(&config, "/blog/hello-world")]
+        );
+
+        let (_storage, entries) = storage.finish();
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.path.as_str(), entry.permalink.as_str(), entry.size))
+                .collect::<Vec<_>>(),
+            vec![
+                ("atom.xml", "/atom.xml", 13),
+                ("blog/hello-world/index.html", "/blog/hello-world/", 13),
+                ("css/style.css", "/css/style.css", 7),
+            ]
+        );
+        assert_eq!(entries[0].hash, entries[1].hash);
+        assert_eq!(
+            entries[0].hash,
+            "315f5bdb76d078c43b8ac0064e4a0164612b1fce77c869345bfc94c75894edd3"
+        );
+    }
+
+    #[test]
+    fn test_unicode_permalinks() {
+        let config = SiteConfig::for_tests("https://example.com");
+
+        let content = Arc::default();
+        let storage = RecordingStorage::new(InMemoryStorage::new(Arc::clone(&content)));
+
+        storage
+            .store_content(
+                Permalink::from_path(&config, "/tags/café"),
+                "Café".to_string(),
+            )
+            .unwrap();
+
+        let (_storage, entries) = storage.finish();
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.path.as_str(), entry.permalink.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("tags/café/index.html", "/tags/caf%C3%A9/")]
+        );
+        assert!(content.read().unwrap().contains_key("/tags/café/"));
+    }
 }
@@ -0,0 +1,185 @@
+//! A site built from content in a temporary directory, for tests that need to
+//! run content through the whole pipeline.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use auk::*;
+use auk_markdown::TableOfContents;
+
+use crate::content::{
+    FileInfo, Page, PageFrontMatter, PagePath, ReadTime, Section, SectionFrontMatter, SectionPath,
+    WordCount,
+};
+use crate::permalink::Permalink;
+use crate::storage::{InMemoryStorage, SiteContent};
+use crate::{RenderSiteError, Site, SiteBuilder, SiteConfig, WithBaseUrl, WithTemplates};
+
+pub(crate) struct TestSite {
+    root_path: PathBuf,
+}
+
+impl TestSite {
+    /// Returns a new [`TestSite`] with no content.
+    ///
+    /// The `name` must be unique across all tests, as it determines the
+    /// temporary directory the site lives in.
+    pub fn new(name: &str) -> Self {
+        let root_path = std::env::temp_dir()
+            .join("razorbill-tests")
+            .join(format!("{name}-{}", std::process::id()));
+
+        if root_path.exists() {
+            fs::remove_dir_all(&root_path).unwrap();
+        }
+        fs::create_dir_all(root_path.join("content")).unwrap();
+
+        Self { root_path }
+    }
+
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+
+    pub fn content_path(&self) -> PathBuf {
+        self.root_path.join("content")
+    }
+
+    /// Writes a file to the given path, relative to the content directory.
+    pub fn write(&self, path: impl AsRef<Path>, contents: &str) -> &Self {
+        let path = self.content_path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+        self
+    }
+
+    /// Reads the file at the given path, relative to the content directory.
+    pub fn read(&self, path: impl AsRef<Path>) -> String {
+        fs::read_to_string(self.content_path().join(path)).unwrap()
+    }
+
+    /// Returns a [`SiteBuilder`] for the site, with templates that render the
+    /// title and content of each section and page.
+    pub fn builder(&self) -> SiteBuilder<WithTemplates> {
+        with_templates(
+            Site::builder()
+                .root(&self.root_path)
+                .base_url("https://example.com"),
+        )
+    }
+
+    /// Returns a [`SiteBuilder`] for the site with the given `config.toml`,
+    /// with the same templates as [`TestSite::builder`].
+    pub fn builder_from_config(&self, config: &str) -> SiteBuilder<WithTemplates> {
+        let config_path = self.root_path.join("config.toml");
+        fs::write(&config_path, config).unwrap();
+
+        with_templates(Site::builder().from_config_file(config_path).unwrap())
+    }
+
+    /// Builds and loads the site.
+    pub fn load(&self) -> Site {
+        let mut site = self.builder().build();
+        site.load().unwrap();
+        site
+    }
+}
+
+impl Drop for TestSite {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root_path);
+    }
+}
+
+/// Adds templates that render the title and content of each section and page.
+fn with_templates(builder: SiteBuilder<WithBaseUrl>) -> SiteBuilder<WithTemplates> {
+    builder.templates(
+        |ctx| html().child(body().child(h1().child(ctx.section.title.clone().unwrap_or_default()))),
+        |ctx| html().child(body().child(h1().child(ctx.section.title.clone().unwrap_or_default()))),
+        |ctx| {
+            html().child(
+                body()
+                    .child(h1().child(ctx.page.title.clone().unwrap_or_default()))
+                    .child(div().children(ctx.page.content.iter().cloned())),
+            )
+        },
+    )
+}
+
+/// Renders the given site into memory, returning the rendered files keyed by
+/// their path.
+pub(crate) fn render(site: &mut Site) -> Result<HashMap<String, String>, RenderSiteError> {
+    let content = SiteContent::default();
+    site.render_to(InMemoryStorage::new(content.clone()))?;
+
+    let content = content.read().unwrap();
+    Ok(content
+        .iter()
+        .map(|(path, contents)| (path.clone(), String::from_utf8_lossy(contents).into_owned()))
+        .collect())
+}
+
+/// Returns a [`Section`] for the file at the given path (e.g.,
+/// `content/blog/_index.md`), with the given front matter and no content.
+///
+/// This is for tests that exercise a single step of the pipeline without
+/// loading a whole site.
+pub(crate) fn make_section(filepath: &str, meta: SectionFrontMatter) -> Section {
+    let config = SiteConfig::for_tests("https://example.com");
+
+    let root_path = PathBuf::from("content");
+    let file = FileInfo::new(&root_path, filepath);
+    let path = SectionPath::from_file_path(root_path, &file.path).unwrap();
+
+    Section {
+        meta,
+        permalink: Permalink::from_path(&config, path.0.as_str()),
+        path,
+        file,
+        raw_content: String::new(),
+        content: Arc::default(),
+        table_of_contents: TableOfContents::default(),
+        headings: Vec::new(),
+        word_count: WordCount(0),
+        read_time: ReadTime(0),
+        pages: Vec::new(),
+        ancestors: Vec::new(),
+        subsections: Vec::new(),
+        all_pages: Vec::new(),
+    }
+}
+
+/// Returns a [`Page`] for the file at the given path (e.g.,
+/// `content/blog/hello.md`), with the given front matter and no content.
+///
+/// Like [`make_section`], this is for tests of a single step of the pipeline.
+pub(crate) fn make_page(filepath: &str, meta: PageFrontMatter) -> Page {
+    let config = SiteConfig::for_tests("https://example.com");
+
+    let root_path = PathBuf::from("content");
+    let file = FileInfo::new(&root_path, filepath);
+    let path = PagePath::from_file_path(root_path, &file.path).unwrap();
+
+    Page {
+        meta,
+        permalink: Permalink::from_path(&config, path.0.as_str()),
+        path,
+        file,
+        ancestors: Vec::new(),
+        previous: None,
+        next: None,
+        slug: String::new(),
+        raw_content: String::new(),
+        content_line_offset: 0,
+        content: Arc::default(),
+        table_of_contents: TableOfContents::default(),
+        headings: Vec::new(),
+        word_count: WordCount(0),
+        read_time: ReadTime(0),
+        social_image: None,
+        pdf_url: None,
+        assets: Vec::new(),
+    }
+}
@@ -0,0 +1,169 @@
+//! Utilities for testing shortcodes and transforms without constructing a
+//! [`Site`](crate::Site).
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use auk::renderer::HtmlElementRenderer;
+use auk::visitor::MutVisitor;
+use auk::Element;
+use auk_markdown::MarkdownComponents;
+
+use crate::markdown::{
+    markdown_with_options, resolve_headings, unwrap_paragraph, DefaultMarkdownComponents, Figures,
+    MarkdownOptions, Shortcode,
+};
+
+/// A harness for rendering Markdown through the same pipeline used when
+/// rendering a site.
+///
+/// The rendered HTML is returned as a string, which makes it suitable for use
+/// with snapshot testing tools like [`insta`](https://insta.rs).
+pub struct MarkdownHarness {
+    markdown_components: Box<dyn MarkdownComponents>,
+    shortcodes: HashMap<String, Shortcode>,
+    math: bool,
+    figures: Option<Figures>,
+}
+
+impl Default for MarkdownHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownHarness {
+    /// Returns a new [`MarkdownHarness`] with the default Markdown components
+    /// and no shortcodes.
+    pub fn new() -> Self {
+        Self {
+            markdown_components: Box::new(DefaultMarkdownComponents),
+            shortcodes: HashMap::new(),
+            math: false,
+            figures: None,
+        }
+    }
+
+    pub fn with_markdown_components(
+        mut self,
+        markdown_components: impl MarkdownComponents + Send + Sync + 'static,
+    ) -> Self {
+        self.markdown_components = Box::new(markdown_components);
+        self
+    }
+
+    pub fn add_shortcode(mut self, name: impl Into<String>, shortcode: Shortcode) -> Self {
+        self.shortcodes.insert(name.into(), shortcode);
+        self
+    }
+
+    /// Sets whether math is rendered, as when a site is built with
+    /// [`SiteBuilder::math`](crate::SiteBuilder::math).
+    pub fn with_math(mut self, math: bool) -> Self {
+        self.math = math;
+        self
+    }
+
+    /// Sets how standalone images are rendered as figures, as when a site is
+    /// built with [`SiteBuilder::figures`](crate::SiteBuilder::figures).
+    pub fn with_figures(mut self, figures: Figures) -> Self {
+        self.figures = Some(figures);
+        self
+    }
+
+    /// Renders the given Markdown text to a list of elements.
+    pub fn render_elements(&self, text: &str) -> Vec<Element> {
+        let options = MarkdownOptions {
+            math: self.math,
+            figures: self.figures.as_ref(),
+        };
+        let (mut elements, _table_of_contents) =
+            markdown_with_options(text, &self.markdown_components, &self.shortcodes, options)
+                .unwrap();
+        resolve_headings(&mut elements);
+
+        elements
+    }
+
+    /// Renders the given Markdown text to a string of HTML.
+    pub fn render(&self, text: &str) -> String {
+        render_elements_to_string(self.render_elements(text))
+    }
+
+    /// Renders the given Markdown text to a string of HTML after applying the
+    /// given transform to the rendered elements.
+    pub fn render_with_transform<V>(&self, text: &str, transform: &mut V) -> String
+    where
+        V: MutVisitor,
+        V::Error: Debug,
+    {
+        let mut elements = self.render_elements(text);
+        transform.visit_children(&mut elements).unwrap();
+
+        render_elements_to_string(elements)
+    }
+
+    /// Renders a single shortcode invocation (e.g., `{{ youtube(id="8o3i10OuMFQ") }}`)
+    /// to a string of HTML.
+    ///
+    /// The invocation is rendered on its own, so the result is not wrapped
+    /// in a paragraph.
+    pub fn render_shortcode(&self, invocation: &str) -> String {
+        render_elements_to_string(unwrap_paragraph(self.render_elements(invocation.trim())))
+    }
+
+    /// Asserts that the given Markdown text renders to the expected HTML.
+    #[track_caller]
+    pub fn assert_renders(&self, text: &str, expected_html: &str) {
+        let rendered = self.render(text);
+
+        assert!(
+            rendered == expected_html,
+            "rendered HTML did not match\n\nexpected:\n{expected_html}\n\nrendered:\n{rendered}\n"
+        );
+    }
+}
+
+pub(crate) fn render_elements_to_string(elements: Vec<Element>) -> String {
+    elements
+        .into_iter()
+        .map(|element| match element {
+            Element::Text(element) => element.text,
+            Element::Html(element) => HtmlElementRenderer::new()
+                .render_to_string(&element)
+                .unwrap(),
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+#[cfg(test)]
+mod tests {
+    use auk::*;
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[test]
+    fn test_render_shortcode() {
+        #[derive(Deserialize)]
+        struct GreetArgs {
+            name: String,
+        }
+
+        let harness = MarkdownHarness::new().add_shortcode(
+            "greet",
+            Shortcode::new(|args: GreetArgs| span().child(format!("Hello, {}!", args.name)).into()),
+        );
+
+        assert_eq!(
+            harness.render_shortcode(r#"{{ greet(name="Razorbill") }}"#),
+            "<span>Hello, Razorbill!</span>"
+        );
+        harness.assert_renders(
+            r#"Say {{ greet(name="Razorbill") }}"#,
+            "<p>Say <span>Hello, Razorbill!</span></p>",
+        );
+    }
+}
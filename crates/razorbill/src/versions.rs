@@ -0,0 +1,199 @@
+//! Support for sites with multiple versions of their documentation.
+//!
+//! Each version lives in its own top-level section (e.g., `content/v1/` and
+//! `content/v2/`), and is rendered under a URL with its name (e.g., `/v1/`).
+//! The latest version is also made available under `/latest/`, which
+//! redirects to the corresponding page in that version.
+
+use std::collections::HashMap;
+
+use crate::permalink::Permalink;
+
+/// The path that redirects to the latest version.
+pub(crate) const LATEST_PATH: &str = "latest";
+
+/// The versions of a site's documentation.
+#[derive(Debug, Clone)]
+pub struct Versions {
+    names: Vec<String>,
+    latest: Option<String>,
+}
+
+impl Versions {
+    /// Returns a new [`Versions`] with the given version names, which are also
+    /// the names of the sections that contain each version.
+    ///
+    /// The versions should be ordered from oldest to newest, with the last one
+    /// being the latest unless set otherwise with [`latest`](Self::latest).
+    pub fn new(names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            names: names.into_iter().map(Into::into).collect(),
+            latest: None,
+        }
+    }
+
+    /// Sets the name of the latest version.
+    ///
+    /// This is useful when newer versions (e.g., prereleases) have been
+    /// published but aren't considered to be the latest yet.
+    pub fn latest(mut self, name: impl Into<String>) -> Self {
+        self.latest = Some(name.into());
+        self
+    }
+
+    /// Returns the name of the latest version.
+    pub fn latest_name(&self) -> Option<&str> {
+        self.latest
+            .as_deref()
+            .or(self.names.last().map(String::as_str))
+    }
+
+    /// Splits the given content path (e.g., `/v1/guide/intro`) into the name of
+    /// its version and the path within that version (e.g., `/guide/intro`).
+    ///
+    /// Returns `None` if the path isn't within a version.
+    pub(crate) fn split_path<'p>(&self, path: &'p str) -> Option<(&str, &'p str)> {
+        let (name, _) = path.strip_prefix('/')?.split_once('/')?;
+        let name = self.names.iter().find(|candidate| *candidate == name)?;
+
+        Some((name.as_str(), &path[name.len() + 1..]))
+    }
+
+    /// Returns the versions of the content at the given path, for use in a
+    /// version picker.
+    ///
+    /// The given permalinks are keyed by content path.
+    pub(crate) fn versions_of<'a>(
+        &'a self,
+        path: &str,
+        permalinks: &'a HashMap<String, Permalink>,
+    ) -> Vec<VersionToRender<'a>> {
+        let Some((current, subpath)) = self.split_path(path) else {
+            return Vec::new();
+        };
+
+        let latest = self.latest_name();
+
+        self.names
+            .iter()
+            .filter_map(|name| {
+                // Fall back to the root of the version when it doesn't have
+                // the content.
+                let permalink = permalinks
+                    .get(&format!("/{name}{subpath}"))
+                    .or_else(|| permalinks.get(&format!("/{name}/_index")))?;
+
+                Some(VersionToRender {
+                    name,
+                    permalink: permalink.as_str(),
+                    is_current: name == current,
+                    is_latest: Some(name.as_str()) == latest,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the permalink of the content at the given path in the latest
+    /// version, if it exists there.
+    pub(crate) fn latest_permalink<'a>(
+        &self,
+        path: &str,
+        permalinks: &'a HashMap<String, Permalink>,
+    ) -> Option<&'a Permalink> {
+        let (_, subpath) = self.split_path(path)?;
+
+        permalinks.get(&format!("/{}{subpath}", self.latest_name()?))
+    }
+}
+
+/// A version of the content being rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionToRender<'a> {
+    pub name: &'a str,
+    /// The URL of the content in this version, or of the root of this version
+    /// if the content doesn't exist in it.
+    pub permalink: &'a str,
+    pub is_current: bool,
+    pub is_latest: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::SiteConfig;
+
+    use super::*;
+
+    fn permalinks(paths: &[&str]) -> HashMap<String, Permalink> {
+        let config = SiteConfig::for_tests("https://docs.example.com");
+
+        paths
+            .iter()
+            .map(|path| (path.to_string(), Permalink::from_path(&config, path)))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_path() {
+        let versions = Versions::new(["v1", "v2"]);
+
+        assert_eq!(
+            versions.split_path("/v1/guide/intro"),
+            Some(("v1", "/guide/intro"))
+        );
+        assert_eq!(versions.split_path("/v2/_index"), Some(("v2", "/_index")));
+        assert_eq!(versions.split_path("/v3/guide/intro"), None);
+        assert_eq!(versions.split_path("/v1"), None);
+    }
+
+    #[test]
+    fn test_versions_of() {
+        let versions = Versions::new(["v1", "v2", "v3"]).latest("v2");
+        let permalinks = permalinks(&[
+            "/v1/_index",
+            "/v1/guide/intro",
+            "/v2/_index",
+            "/v2/guide/intro",
+            "/v3/_index",
+        ]);
+
+        assert_eq!(
+            versions.versions_of("/v1/guide/intro", &permalinks),
+            vec![
+                VersionToRender {
+                    name: "v1",
+                    permalink: "https://docs.example.com/v1/guide/intro/",
+                    is_current: true,
+                    is_latest: false,
+                },
+                VersionToRender {
+                    name: "v2",
+                    permalink: "https://docs.example.com/v2/guide/intro/",
+                    is_current: false,
+                    is_latest: true,
+                },
+                VersionToRender {
+                    name: "v3",
+                    permalink: "https://docs.example.com/v3/",
+                    is_current: false,
+                    is_latest: false,
+                },
+            ]
+        );
+        assert_eq!(versions.versions_of("/about", &permalinks), Vec::new());
+
+        assert_eq!(
+            versions
+                .latest_permalink("/v1/guide/intro", &permalinks)
+                .map(Permalink::as_str),
+            Some("https://docs.example.com/v2/guide/intro/")
+        );
+        assert_eq!(
+            versions
+                .latest_permalink("/v3/_index", &permalinks)
+                .map(Permalink::as_str),
+            Some("https://docs.example.com/v2/")
+        );
+    }
+}
@@ -27,22 +27,27 @@ async fn main() -> Result<()> {
     let site = Site::builder()
         .root("examples/blog")
         .base_url("https://blog.example.com")
+        .title("Razorbill Blog")
         .templates(
             |ctx| index(IndexProps { ctx }),
             |ctx| section(SectionProps { ctx }),
             |ctx| {
                 page(PageProps {
                     ctx,
-                    children: vec![post(ctx.page.content.clone())],
+                    children: vec![post(&ctx.page.content)],
                 })
             },
         )
-        .add_page_template("prose", |ctx| {
-            prose(ProseProps {
-                ctx,
-                children: vec![post(ctx.page.content.clone())],
-            })
-        })
+        .add_page_template_with_assets(
+            "prose",
+            |ctx| {
+                prose(ProseProps {
+                    ctx,
+                    children: vec![post(&ctx.page.content)],
+                })
+            },
+            &["/prose.css"],
+        )
         .with_markdown_components(SiteMarkdownComponents)
         .add_shortcode(
             "say_hello",
@@ -58,9 +63,7 @@ async fn main() -> Result<()> {
             }),
         )
         .add_taxonomy(
-            Taxonomy {
-                name: "tags".into(),
-            },
+            Taxonomy::new("tags"),
             |ctx| {
                 html().child(
                     body().child(h1().child(ctx.taxonomy.name)).child(
@@ -132,8 +135,7 @@ fn base_page(props: BasePageProps) -> HtmlElement {
                         .stylesheets
                         .into_iter()
                         .map(|stylesheet| link().rel("stylesheet").href(stylesheet)),
-                )
-                .child(script().src("/livereload.js?port=35729")),
+                ),
         )
         .children(props.children)
 }
@@ -229,7 +231,7 @@ fn page(PageProps { ctx, children }: PageProps) -> HtmlElement {
         children: vec![body()
             .child(
                 h1().class(plumage().class("heading").text_center())
-                    .child("Razorbill Blog"),
+                    .child(ctx.site().title.unwrap_or_default()),
             )
             .children(page.date.as_ref().map(|date| {
                 h3().class(plumage().text_center())
@@ -261,11 +263,11 @@ fn prose(ProseProps { ctx, children }: ProseProps) -> HtmlElement {
             .as_ref()
             .map(|title| title.as_str())
             .unwrap_or(page.slug),
-        stylesheets: vec!["/prose.css"],
+        stylesheets: vec![],
         children: vec![body()
             .child(
                 h1().class(plumage().class("heading").text_center())
-                    .child("Razorbill Blog"),
+                    .child(ctx.site().title.unwrap_or_default()),
             )
             .child(div().class("content").children(children))],
     })
@@ -276,8 +278,8 @@ struct SayArgs {
     pub greeting: String,
 }
 
-fn post(content: Vec<Element>) -> HtmlElement {
-    div().children(content)
+fn post(content: &[Element]) -> HtmlElement {
+    div().children(content.iter().cloned())
 }
 
 fn post_paragraph() -> HtmlElement {